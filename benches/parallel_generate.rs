@@ -0,0 +1,33 @@
+//! Compares generating shares for 16 groups of 16 members on one thread and
+//! on all available threads.
+//!
+//! Run with `cargo bench --features rayon`.
+
+use std::time::{Duration, Instant};
+
+use sskr::{GroupSpec, Secret, Spec, sskr_generate_using};
+
+const ITERATIONS: u32 = 200;
+
+fn time_generate(spec: &Spec, secret: &Secret) -> Duration {
+    let mut rng = bc_rand::make_fake_random_number_generator();
+    let start = Instant::now();
+    for _ in 0..ITERATIONS {
+        std::hint::black_box(sskr_generate_using(spec, secret, &mut rng).unwrap());
+    }
+    start.elapsed() / ITERATIONS
+}
+
+fn main() {
+    let secret = Secret::new([0x5a; 32]).unwrap();
+    let spec = Spec::new(8, vec![GroupSpec::new(8, 16).unwrap(); 16]).unwrap();
+
+    let single = rayon::ThreadPoolBuilder::new().num_threads(1).build().unwrap();
+    let serial = single.install(|| time_generate(&spec, &secret));
+    let parallel = time_generate(&spec, &secret);
+
+    println!("16x16 split of a 32-byte secret, {} threads", rayon::current_num_threads());
+    println!("  one thread:  {serial:?}");
+    println!("  all threads: {parallel:?}");
+    println!("  speedup:     {:.2}x", serial.as_secs_f64() / parallel.as_secs_f64());
+}
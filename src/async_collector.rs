@@ -0,0 +1,156 @@
+use tokio::sync::watch;
+
+use crate::{CollectorStatus, SSKRError, Secret, ShareCollector, SyncShareCollector};
+
+/// A `ShareCollector` that can be shared between tasks, with a future that
+/// resolves once the collected shares reach a quorum.
+///
+/// The collector is `Send` and `Sync`, so it can be wrapped in an `Arc` and
+/// handed to every task that receives shares. Adding or removing a share
+/// takes a lock only for as long as the share takes to check, and never
+/// awaits. As with `SyncShareCollector`, which it is built on, a task that
+/// panics while using the collector does not poison it for the others.
+#[derive(Debug)]
+pub struct AsyncShareCollector {
+    collector: SyncShareCollector,
+    status: watch::Sender<CollectorStatus>,
+}
+
+impl AsyncShareCollector {
+    /// Creates a new, empty `AsyncShareCollector`.
+    pub fn new() -> Self {
+        Self::from(ShareCollector::new())
+    }
+
+    /// Adds a serialized share, returning the updated status.
+    ///
+    /// # Errors
+    ///
+    /// Returns any error `ShareCollector::add_share` returns, leaving the
+    /// collector unchanged.
+    pub fn add_share(&self, share: &[u8]) -> Result<CollectorStatus, SSKRError> {
+        let mut collector = self.collector.lock();
+        let status = collector.add_share(share)?;
+        self.status.send_replace(status.clone());
+        Ok(status)
+    }
+
+    /// Removes the share of the given member, returning its bytes, or `None`
+    /// if the collector doesn't hold it.
+    pub fn remove_share(&self, group_index: usize, member_index: usize) -> Option<Vec<u8>> {
+        let mut collector = self.collector.lock();
+        let share = collector.remove_share(group_index, member_index)?;
+        self.status.send_replace(collector.status());
+        Some(share)
+    }
+
+    /// Returns the progress of the recovery.
+    pub fn status(&self) -> CollectorStatus {
+        self.status.borrow().clone()
+    }
+
+    /// Waits until the collected shares are enough to recover the secret,
+    /// returning the status at that point.
+    ///
+    /// Resolves immediately if they already are. Dropping the future before
+    /// it resolves has no effect on the collector, so the wait can be
+    /// abandoned or raced against a timeout.
+    pub async fn wait_for_quorum(&self) -> CollectorStatus {
+        let mut receiver = self.status.subscribe();
+        // The sender lives as long as `self`, so the channel can't close.
+        let status = receiver.wait_for(CollectorStatus::can_combine).await.unwrap();
+        status.clone()
+    }
+
+    /// Consumes this collector, returning the `ShareCollector` it wraps.
+    pub fn into_inner(self) -> ShareCollector {
+        self.collector.into_inner()
+    }
+
+    /// Combines the collected shares into a `Secret`.
+    ///
+    /// # Errors
+    ///
+    /// Returns any error `ShareCollector::combine` returns.
+    pub fn combine(self) -> Result<Secret, SSKRError> {
+        self.into_inner().combine()
+    }
+}
+
+impl Default for AsyncShareCollector {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl From<ShareCollector> for AsyncShareCollector {
+    fn from(collector: ShareCollector) -> Self {
+        let (status, _) = watch::channel(collector.status());
+        Self { collector: SyncShareCollector::from(collector), status }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{sync::Arc, time::Duration};
+
+    use super::*;
+    use crate::{sskr_generate_using, test_support};
+    
+
+    fn is_send_sync<T: Send + Sync>() {}
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn test_wait_for_quorum() {
+        is_send_sync::<AsyncShareCollector>();
+
+        let mut rng = bc_rand::make_fake_random_number_generator();
+        let secret = test_support::secret();
+        let spec = test_support::spec();
+        let shares = sskr_generate_using(&spec, &secret, &mut rng).unwrap();
+        let collector = Arc::new(AsyncShareCollector::new());
+
+        // An abandoned wait leaves the collector usable.
+        let timed_out = tokio::time::timeout(Duration::from_millis(10), collector.wait_for_quorum()).await;
+        assert!(timed_out.is_err());
+
+        let waiter = tokio::spawn({
+            let collector = collector.clone();
+            async move { collector.wait_for_quorum().await }
+        });
+
+        let submitters: Vec<_> = [&shares[0][0], &shares[1][1], &shares[0][2], &shares[1][3], &shares[1][4]]
+            .into_iter()
+            .map(|share| {
+                let collector = collector.clone();
+                let share = share.clone();
+                tokio::spawn(async move { collector.add_share(&share).unwrap() })
+            })
+            .collect();
+        for submitter in submitters {
+            submitter.await.unwrap();
+        }
+
+        let status = waiter.await.unwrap();
+        assert!(status.can_combine());
+        assert_eq!(status, collector.status());
+        assert!(collector.wait_for_quorum().await.can_combine());
+
+        assert!(collector.remove_share(0, 2).is_some());
+        assert!(!collector.status().can_combine());
+        collector.add_share(&shares[2][0]).unwrap();
+        assert!(matches!(collector.add_share(&shares[2][0]), Err(SSKRError::DuplicateMemberIndex)));
+
+        // A panic while the lock is held doesn't poison the collector.
+        let panicking = collector.clone();
+        assert!(std::thread::spawn(move || {
+            let _guard = panicking.collector.lock();
+            panic!("holding the lock");
+        }).join().is_err());
+        assert!(collector.remove_share(2, 0).is_some());
+        collector.add_share(&shares[2][0]).unwrap();
+
+        let collector = Arc::into_inner(collector).unwrap();
+        assert_eq!(collector.combine().unwrap(), secret);
+    }
+}
@@ -0,0 +1,97 @@
+use std::future::Future;
+
+use zeroize::Zeroizing;
+
+use crate::{SSKRError, Secret, ShareVersion, Spec, sskr_generate_using, seeded_rng::{SeededRandomNumberGenerator, SEED_SIZE_BYTES}};
+
+/// A source of random data that is awaited, such as a slow hardware entropy
+/// source.
+///
+/// Implementations may write `async fn fill_random_data`, provided the
+/// future is `Send`.
+pub trait AsyncRandomNumberGenerator {
+    /// Fills `data` with random bytes.
+    fn fill_random_data(&mut self, data: &mut [u8]) -> impl Future<Output = ()> + Send;
+}
+
+/// Generates SSKR shares for the given `Spec` and `Secret`, awaiting the
+/// random data from `random_generator`.
+///
+/// A 32-byte seed is awaited in a single request, so that the executor is
+/// free while the generator gathers it. The shares are then generated as
+/// `sskr_generate_using` generates them, from the stream of blocks
+/// `HMAC-SHA256(seed, i)` for `i = 0, 1, 2, ...`, with `i` encoded as a
+/// 64-bit big-endian integer. The seed is zeroized once the shares are
+/// generated.
+///
+/// # Errors
+///
+/// Returns the same errors as `sskr_generate`, before any random data is
+/// requested.
+pub async fn sskr_generate_async<R>(
+    spec: &Spec,
+    master_secret: &Secret,
+    random_generator: &mut R
+) -> Result<Vec<Vec<Vec<u8>>>, SSKRError>
+where
+    R: AsyncRandomNumberGenerator
+{
+    spec.validate_for_version(ShareVersion::V0)?;
+
+    let mut seed = Zeroizing::new([0u8; SEED_SIZE_BYTES]);
+    random_generator.fill_random_data(&mut *seed).await;
+    sskr_generate_using(spec, master_secret, &mut SeededRandomNumberGenerator::new(seed))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::{GroupSpec, sskr_combine, test_support};
+    use bc_rand::RandomNumberGenerator;
+    
+
+    /// Yields the bytes of the fake random number generator after a delay,
+    /// recording the size of each request.
+    struct DelayedRandomNumberGenerator<R> {
+        inner: R,
+        requests: Vec<usize>,
+    }
+
+    impl<R> AsyncRandomNumberGenerator for DelayedRandomNumberGenerator<R>
+    where
+        R: RandomNumberGenerator + Send
+    {
+        async fn fill_random_data(&mut self, data: &mut [u8]) {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+            self.inner.fill_random_data(data);
+            self.requests.push(data.len());
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_generate_async() {
+        for secret in [test_support::secret(), Secret::new([0x5a; 32]).unwrap()] {
+            let spec = Spec::new(2, vec![GroupSpec::new(2, 3).unwrap(), GroupSpec::new(1, 1).unwrap(), GroupSpec::new(4, 5).unwrap()]).unwrap();
+            let mut seed = Zeroizing::new([0u8; SEED_SIZE_BYTES]);
+            bc_rand::make_fake_random_number_generator().fill_random_data(&mut *seed);
+            let expected = sskr_generate_using(&spec, &secret, &mut SeededRandomNumberGenerator::new(seed)).unwrap();
+
+            let mut rng = DelayedRandomNumberGenerator { inner: bc_rand::make_fake_random_number_generator(), requests: Vec::new() };
+            let shares = tokio::spawn(async move {
+                let shares = sskr_generate_async(&spec, &secret, &mut rng).await.unwrap();
+                (shares, rng.requests, secret)
+            });
+            let (shares, requests, secret) = shares.await.unwrap();
+            assert_eq!(shares, expected);
+            assert_eq!(requests, [SEED_SIZE_BYTES]);
+            assert_eq!(sskr_combine(&[&shares[1][0], &shares[0][2], &shares[0][0]]).unwrap(), secret);
+        }
+
+        let spec = Spec::new_with_version(1, vec![GroupSpec::new(1, 1).unwrap(); 17], ShareVersion::V1).unwrap();
+        let mut rng = DelayedRandomNumberGenerator { inner: bc_rand::make_fake_random_number_generator(), requests: Vec::new() };
+        assert!(matches!(sskr_generate_async(&spec, &Secret::new([0; 16]).unwrap(), &mut rng).await, Err(SSKRError::GroupCountInvalid)));
+        assert!(rng.requests.is_empty());
+    }
+}
@@ -0,0 +1,113 @@
+use bc_crypto::hash::sha256;
+
+use crate::{SSKRError, encoding::validate_share};
+
+/// Returns the check code of a serialized SSKR share: two Bytewords that let
+/// people confirm, by reading them aloud, that they hold the same share.
+///
+/// The code is stable across versions of this crate so that other
+/// implementations can reproduce it. It is computed by taking the SHA-256
+/// digest of the entire serialized share, header and payload, and rendering
+/// its first two bytes as [Bytewords], lowercase and joined by a hyphen. For
+/// example, a share whose digest begins `00 ff` has the check code
+/// `able-zoom`.
+///
+/// A check code identifies a share well enough to catch transcription errors
+/// and mix-ups, but with only 65,536 possible codes it is not a security
+/// measure.
+///
+/// [Bytewords]: https://github.com/BlockchainCommons/Research/blob/master/papers/bcr-2020-012-bytewords.md
+///
+/// # Errors
+///
+/// Returns an error if `bytes` is not a well-formed SSKR share.
+pub fn share_checkwords(bytes: &[u8]) -> Result<String, SSKRError> {
+    validate_share(bytes)?;
+    let digest = sha256(bytes);
+    Ok(format!("{}-{}", BYTEWORDS[digest[0] as usize], BYTEWORDS[digest[1] as usize]))
+}
+
+/// Returns `true` if `words` is the check code of the serialized share
+/// `bytes`, as computed by [`share_checkwords`].
+///
+/// The comparison ignores case and surrounding whitespace, and the two words
+/// may be separated by a hyphen or by whitespace. Returns `false` if `bytes`
+/// is not a well-formed SSKR share.
+pub fn verify_checkwords(bytes: &[u8], words: &str) -> bool {
+    let Ok(expected) = share_checkwords(bytes) else {
+        return false;
+    };
+    let words = words.trim().to_lowercase();
+    let given: Vec<&str> = words.split(|c: char| c == '-' || c.is_whitespace()).filter(|w| !w.is_empty()).collect();
+    let expected: Vec<&str> = expected.split('-').collect();
+    given == expected
+}
+
+/// The Bytewords word list, one word for each byte value.
+pub(crate) const BYTEWORDS: [&str; 256] = [
+    "able", "acid", "also", "apex", "aqua", "arch", "atom", "aunt",
+    "away", "axis", "back", "bald", "barn", "belt", "beta", "bias",
+    "blue", "body", "brag", "brew", "bulb", "buzz", "calm", "cash",
+    "cats", "chef", "city", "claw", "code", "cola", "cook", "cost",
+    "crux", "curl", "cusp", "cyan", "dark", "data", "days", "deli",
+    "dice", "diet", "door", "down", "draw", "drop", "drum", "dull",
+    "duty", "each", "easy", "echo", "edge", "epic", "even", "exam",
+    "exit", "eyes", "fact", "fair", "fern", "figs", "film", "fish",
+    "fizz", "flap", "flew", "flux", "foxy", "free", "frog", "fuel",
+    "fund", "gala", "game", "gear", "gems", "gift", "girl", "glow",
+    "good", "gray", "grim", "guru", "gush", "gyro", "half", "hang",
+    "hard", "hawk", "heat", "help", "high", "hill", "holy", "hope",
+    "horn", "huts", "iced", "idea", "idle", "inch", "inky", "into",
+    "iris", "iron", "item", "jade", "jazz", "join", "jolt", "jowl",
+    "judo", "jugs", "jump", "junk", "jury", "keep", "keno", "kept",
+    "keys", "kick", "kiln", "king", "kite", "kiwi", "knob", "lamb",
+    "lava", "lazy", "leaf", "legs", "liar", "limp", "lion", "list",
+    "logo", "loud", "love", "luau", "luck", "lung", "main", "many",
+    "math", "maze", "memo", "menu", "meow", "mild", "mint", "miss",
+    "monk", "nail", "navy", "need", "news", "next", "noon", "note",
+    "numb", "obey", "oboe", "omit", "onyx", "open", "oval", "owls",
+    "paid", "part", "peck", "play", "plus", "poem", "pool", "pose",
+    "puff", "puma", "purr", "quad", "quiz", "race", "ramp", "real",
+    "redo", "rich", "road", "rock", "roof", "ruby", "ruin", "runs",
+    "rust", "safe", "saga", "scar", "sets", "silk", "skew", "slot",
+    "soap", "solo", "song", "stub", "surf", "swan", "taco", "task",
+    "taxi", "tent", "tied", "time", "tiny", "toil", "tomb", "toys",
+    "trip", "tuna", "twin", "ugly", "undo", "unit", "urge", "user",
+    "vast", "very", "veto", "vial", "vibe", "view", "visa", "void",
+    "vows", "wall", "wand", "warm", "wasp", "wave", "waxy", "webs",
+    "what", "when", "whiz", "wolf", "work", "yank", "yawn", "yell",
+    "yoga", "yurt", "zaps", "zero", "zest", "zinc", "zone", "zoom",
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hex_literal::hex;
+
+    const SHARE: [u8; 21] = hex!("0011010000112233445566778899aabbccddeeff00");
+
+    #[test]
+    fn test_checkwords_vectors() {
+        assert_eq!(BYTEWORDS[0], "able");
+        assert_eq!(BYTEWORDS[0xff], "zoom");
+        assert_eq!(share_checkwords(&SHARE).unwrap(), "good-veto");
+        assert_eq!(share_checkwords(&hex!("a3f1120102fa5b08b0abd1f4e61b2c7b3d62e81c04b917a1d23e55b67d10d8c2e39c6a8f42")).unwrap(), "flux-brag");
+    }
+
+    #[test]
+    fn test_verify_checkwords() {
+        let words = share_checkwords(&SHARE).unwrap();
+        assert!(verify_checkwords(&SHARE, &words));
+        assert!(verify_checkwords(&SHARE, &format!("  {}  ", words.to_uppercase())));
+        assert!(verify_checkwords(&SHARE, &words.replace('-', " ")));
+        assert!(!verify_checkwords(&SHARE, "able-able"));
+        assert!(!verify_checkwords(&SHARE, words.split('-').next().unwrap()));
+        assert!(!verify_checkwords(&SHARE, &format!("{}-able", words)));
+
+        let mut other = SHARE;
+        other[20] ^= 1;
+        assert!(!verify_checkwords(&other, &words));
+        assert!(!verify_checkwords(&SHARE[..4], &words));
+        assert!(share_checkwords(&SHARE[..4]).is_err());
+    }
+}
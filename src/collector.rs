@@ -0,0 +1,1111 @@
+use std::fmt;
+
+use crate::{
+    SSKRError,
+    Secret,
+    ShareBytes,
+    sskr_combine,
+    encoding::{check_share_matches, deserialize_share},
+    share::SSKRShareRef,
+};
+
+/// Collects the shares of a single split one at a time, as they turn up
+/// during a recovery, and reports progress towards a quorum.
+///
+/// The first share added establishes the split; every later share must agree
+/// with it. A share that doesn't is rejected with an error naming the
+/// disagreement, and the collector is left as it was.
+///
+/// An observer set with [`set_observer`](Self::set_observer) is told of each
+/// share added or rejected, and of each group and the quorum being reached.
+#[derive(Default)]
+pub struct ShareCollector {
+    shares: Vec<ShareBytes>,
+    observer: Option<Observer>,
+}
+
+type Observer = Box<dyn FnMut(CollectorEvent<'_>) + Send>;
+
+/// Cloning a collector clones its shares, but not its observer.
+impl Clone for ShareCollector {
+    fn clone(&self) -> Self {
+        Self { shares: self.shares.clone(), observer: None }
+    }
+}
+
+impl fmt::Debug for ShareCollector {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ShareCollector")
+            .field("shares", &self.shares)
+            .field("observer", &self.observer.is_some())
+            .finish()
+    }
+}
+
+impl ShareCollector {
+    /// Creates a new, empty `ShareCollector`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the shares collected so far, in the order they were added.
+    pub fn shares(&self) -> &[ShareBytes] {
+        &self.shares
+    }
+
+    /// Returns the number of shares collected so far.
+    pub fn len(&self) -> usize {
+        self.shares.len()
+    }
+
+    /// Returns `true` if no shares have been collected.
+    pub fn is_empty(&self) -> bool {
+        self.shares.is_empty()
+    }
+
+    /// Adds a serialized share to the collector, returning the updated
+    /// status.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error, leaving the collector unchanged, if the share is
+    /// malformed, belongs to a different split than the shares already
+    /// collected, or repeats a member already collected.
+    pub fn add_share(&mut self, share: &[u8]) -> Result<CollectorStatus, SSKRError> {
+        if let Err(error) = self.validate_new_share(share) {
+            self.notify(CollectorEvent::ShareRejected { reason: &error });
+            return Err(error);
+        }
+        let previous = self.status();
+        self.shares.push(ShareBytes::from_validated(share.to_vec()));
+        Ok(self.notify_accepted(share, &previous))
+    }
+
+    /// Sets a function to be called as the collector changes, replacing any
+    /// observer already set.
+    ///
+    /// The observer is called from `add_share` and `replace_share`, once for
+    /// each event, in the order: `ShareAccepted` or `ShareRejected`, then
+    /// `GroupCompleted` if the share completed its group, then
+    /// `QuorumReachable` if that made the secret recoverable. A group or
+    /// quorum that was already reached is not reported again unless a share
+    /// is removed and it is then reached anew.
+    pub fn set_observer(&mut self, observer: impl FnMut(CollectorEvent<'_>) + Send + 'static) {
+        self.observer = Some(Box::new(observer));
+    }
+
+    /// Removes the observer, if one is set.
+    pub fn clear_observer(&mut self) {
+        self.observer = None;
+    }
+
+    fn notify(&mut self, event: CollectorEvent<'_>) {
+        if let Some(observer) = &mut self.observer {
+            observer(event);
+        }
+    }
+
+    /// Reports that `share` has been accepted, along with any group or quorum
+    /// it reached since `previous`, returning the new status.
+    fn notify_accepted(&mut self, share: &[u8], previous: &CollectorStatus) -> CollectorStatus {
+        let status = self.status();
+        if self.observer.is_none() {
+            return status;
+        }
+        let share = deserialize_share(share, 0).unwrap();
+        let group_index = share.group_index();
+        self.notify(CollectorEvent::ShareAccepted { group_index, member_index: share.member_index() });
+        let was_complete = previous.groups.iter().any(|group| group.group_index == group_index && group.is_complete());
+        let is_complete = status.groups.iter().any(|group| group.group_index == group_index && group.is_complete());
+        if is_complete && !was_complete {
+            self.notify(CollectorEvent::GroupCompleted { group_index });
+        }
+        if status.can_combine() && !previous.can_combine() {
+            self.notify(CollectorEvent::QuorumReachable);
+        }
+        status
+    }
+
+    /// Removes the share of the given member from the collector, returning
+    /// its bytes, or `None` if the collector doesn't hold it.
+    ///
+    /// Removing the last share leaves an empty collector, which will accept
+    /// a share from any split.
+    pub fn remove_share(&mut self, group_index: usize, member_index: usize) -> Option<Vec<u8>> {
+        let position = self.share_refs().position(|share| {
+            share.group_index() == group_index && share.member_index() == member_index
+        })?;
+        Some(self.shares.remove(position).into_bytes())
+    }
+
+    /// Replaces the collected share `old_share` with `new_share`, returning
+    /// the updated status.
+    ///
+    /// The new share is checked as if `old_share` had never been added.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error, leaving the collector unchanged, if `old_share` is
+    /// not in the collector or `new_share` could not be added in its place.
+    pub fn replace_share(&mut self, old_share: &[u8], new_share: &[u8]) -> Result<CollectorStatus, SSKRError> {
+        let position = self.shares
+            .iter()
+            .position(|share| share.data() == old_share)
+            .ok_or(SSKRError::ShareNotCollected)?;
+        let previous = self.status();
+        let removed = self.shares.remove(position);
+        if let Err(error) = self.validate_new_share(new_share) {
+            self.shares.insert(position, removed);
+            self.notify(CollectorEvent::ShareRejected { reason: &error });
+            return Err(error);
+        }
+        self.shares.insert(position, ShareBytes::from_validated(new_share.to_vec()));
+        Ok(self.notify_accepted(new_share, &previous))
+    }
+
+    /// Pools the shares of `other` into this collector, returning the merged
+    /// collector.
+    ///
+    /// A share held by both collectors is kept once. Shares from `other` are
+    /// added after those of this collector, in the order `other` holds them.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SSKRError::ConflictingDuplicateShare` if the collectors hold
+    /// different shares for the same member, or any error `add_share` would
+    /// return for a share of `other`, such as `SSKRError::IdentifierMismatch`
+    /// if the collectors hold shares of different splits.
+    pub fn merge(mut self, other: ShareCollector) -> Result<ShareCollector, SSKRError> {
+        for share in other.shares {
+            if self.shares.contains(&share) {
+                continue;
+            }
+            match self.validate_new_share(share.data()) {
+                Ok(()) => self.shares.push(share),
+                Err(SSKRError::DuplicateMemberIndex) => {
+                    let share = deserialize_share(share.data(), 0).unwrap();
+                    return Err(SSKRError::ConflictingDuplicateShare {
+                        group_index: share.group_index(),
+                        member_index: share.member_index(),
+                    });
+                }
+                Err(error) => return Err(error),
+            }
+        }
+        Ok(self)
+    }
+
+    /// Checks that `share` could be added to the collector.
+    fn validate_new_share(&self, share: &[u8]) -> Result<(), SSKRError> {
+        let share_index = self.shares.len();
+        let new_share = deserialize_share(share, share_index)?;
+        for existing in self.share_refs() {
+            check_share_matches(&existing, &new_share, share_index)?;
+            if existing.group_index() != new_share.group_index() {
+                continue;
+            }
+            if existing.member_threshold() != new_share.member_threshold() {
+                return Err(SSKRError::MemberThresholdMismatch {
+                    group_index: new_share.group_index(),
+                    expected: existing.member_threshold(),
+                    found: new_share.member_threshold(),
+                    share_index,
+                });
+            }
+            if existing.member_index() == new_share.member_index() {
+                return Err(SSKRError::DuplicateMemberIndex);
+            }
+        }
+        Ok(())
+    }
+
+    /// Explains what would happen if `share` were added, without adding it.
+    ///
+    /// This lets an interactive tool warn about a share before it is
+    /// consumed: a share that [`add_share`](Self::add_share) would accept is
+    /// reported as `Acceptable` or `Redundant`, and one it would reject as
+    /// one of the other variants, carrying the values in conflict.
+    pub fn check_share(&self, share: &[u8]) -> ShareCheck {
+        match self.validate_new_share(share) {
+            Ok(()) => {
+                // Validation succeeded, so the share parses.
+                let new_share = deserialize_share(share, 0).unwrap();
+                let group_index = new_share.group_index();
+                let status = self.status();
+                match status.groups().iter().find(|group| group.group_index() == group_index) {
+                    Some(group) if group.is_complete() => ShareCheck::Redundant {
+                        group_index,
+                        member_threshold: group.member_threshold(),
+                    },
+                    _ => ShareCheck::Acceptable,
+                }
+            }
+            Err(SSKRError::DuplicateMemberIndex) => {
+                let new_share = deserialize_share(share, 0).unwrap();
+                ShareCheck::Duplicate {
+                    group_index: new_share.group_index(),
+                    member_index: new_share.member_index(),
+                }
+            }
+            Err(SSKRError::IdentifierMismatch { expected, found, .. }) => ShareCheck::DifferentSplit { expected, found },
+            Err(SSKRError::ShareLengthMismatch { expected, found, .. }) => ShareCheck::LengthMismatch { expected, found },
+            Err(error) => ShareCheck::Rejected(error),
+        }
+    }
+
+    /// Returns the collected shares, parsed.
+    fn share_refs(&self) -> impl Iterator<Item = SSKRShareRef<'_>> {
+        // Every share was validated when it was added.
+        self.shares.iter().map(|share| deserialize_share(share.data(), 0).unwrap())
+    }
+
+    /// Returns the progress of the recovery.
+    pub fn status(&self) -> CollectorStatus {
+        let mut status = CollectorStatus::default();
+        for share in self.share_refs() {
+            if status.identifier.is_none() {
+                status.identifier = Some(share.identifier());
+                status.group_threshold = Some(share.group_threshold());
+                status.group_count = Some(share.group_count());
+            }
+            let position = status.groups.binary_search_by_key(&share.group_index(), |group| group.group_index);
+            let group = match position {
+                Ok(position) => &mut status.groups[position],
+                Err(position) => {
+                    status.groups.insert(position, GroupStatus {
+                        group_index: share.group_index(),
+                        member_threshold: share.member_threshold(),
+                        member_indexes: Vec::new(),
+                    });
+                    &mut status.groups[position]
+                }
+            };
+            let member_position = group.member_indexes.binary_search(&share.member_index()).unwrap_err();
+            group.member_indexes.insert(member_position, share.member_index());
+        }
+        status.share_count = self.shares.len();
+        status
+    }
+
+    /// Captures the collector for persisting, so that a recovery can be
+    /// resumed later with [`from_state`](Self::from_state).
+    pub fn to_state(&self) -> CollectorState {
+        let status = self.status();
+        CollectorState {
+            identifier: status.identifier,
+            group_threshold: status.group_threshold,
+            group_count: status.group_count,
+            secret_len: self.share_refs().next().map(|share| share.value().len()),
+            shares: self.shares.clone(),
+        }
+    }
+
+    /// Restores a collector captured by [`to_state`](Self::to_state).
+    ///
+    /// The shares are added again one at a time, so a state that has been
+    /// edited or corrupted can't produce a collector that `add_share` would
+    /// not.
+    ///
+    /// # Errors
+    ///
+    /// Returns any error `add_share` would for the shares in the state, or
+    /// `SSKRError::CollectorStateInvalid` if the metadata recorded in the
+    /// state disagrees with the shares.
+    pub fn from_state(state: CollectorState) -> Result<Self, SSKRError> {
+        let mut collector = Self::new();
+        for share in &state.shares {
+            collector.add_share(share.data())?;
+        }
+        if collector.to_state() != state {
+            return Err(SSKRError::CollectorStateInvalid);
+        }
+        Ok(collector)
+    }
+
+    /// Combines the collected shares into a `Secret`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the collected shares do not meet the quorum, or if
+    /// any of them is corrupt.
+    pub fn combine(self) -> Result<Secret, SSKRError> {
+        sskr_combine(&self.shares)
+    }
+}
+
+/// The contents of a `ShareCollector`, captured by
+/// [`ShareCollector::to_state`] so that a recovery can span restarts.
+///
+/// Along with the shares themselves, the state records the identifier and
+/// metadata of the split, which are checked against the shares when the
+/// state is restored. With the `serde` feature the state is serializable,
+/// with each share written as hex. With the `encrypted-state` feature it can
+/// be encrypted under a passphrase with `seal`.
+///
+/// **The state holds real share material**, and should be stored with the
+/// same care as the shares.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CollectorState {
+    identifier: Option<u32>,
+    group_threshold: Option<usize>,
+    group_count: Option<usize>,
+    secret_len: Option<usize>,
+    shares: Vec<ShareBytes>,
+}
+
+impl CollectorState {
+    /// Returns the identifier of the split, or `None` if no shares were
+    /// collected.
+    pub fn identifier(&self) -> Option<u32> {
+        self.identifier
+    }
+
+    /// Returns the length of the secret being recovered, or `None` if no
+    /// shares were collected.
+    pub fn secret_len(&self) -> Option<usize> {
+        self.secret_len
+    }
+
+    /// Returns the collected shares, in the order they were added.
+    pub fn shares(&self) -> &[ShareBytes] {
+        &self.shares
+    }
+}
+
+/// A change to a `ShareCollector`, reported to its observer.
+#[derive(Debug)]
+pub enum CollectorEvent<'a> {
+    /// A share was added.
+    ShareAccepted { group_index: usize, member_index: usize },
+    /// A share could not be added, for the given reason.
+    ShareRejected { reason: &'a SSKRError },
+    /// A group reached its member threshold.
+    GroupCompleted { group_index: usize },
+    /// Enough groups reached their member threshold to recover the secret.
+    QuorumReachable,
+}
+
+/// What would happen if a share were added to a `ShareCollector`, as reported
+/// by [`ShareCollector::check_share`].
+#[derive(Debug)]
+pub enum ShareCheck {
+    /// The share would be accepted and brings its group closer to its
+    /// member threshold.
+    Acceptable,
+    /// The share would be accepted, but its group already has the
+    /// `member_threshold` shares it needs.
+    Redundant { group_index: usize, member_threshold: usize },
+    /// The share would be rejected because the collector already holds the
+    /// share of this member.
+    Duplicate { group_index: usize, member_index: usize },
+    /// The share would be rejected because it belongs to the split with
+    /// identifier `found` rather than `expected`.
+    DifferentSplit { expected: u32, found: u32 },
+    /// The share would be rejected because its payload is `found` bytes, but
+    /// the shares collected so far have `expected`.
+    LengthMismatch { expected: usize, found: usize },
+    /// The share would be rejected for the given reason, such as being
+    /// malformed or disagreeing with the collected shares about the shape of
+    /// the split.
+    Rejected(SSKRError),
+}
+
+impl ShareCheck {
+    /// Returns `true` if the share would be accepted.
+    pub fn is_acceptable(&self) -> bool {
+        matches!(self, ShareCheck::Acceptable | ShareCheck::Redundant { .. })
+    }
+}
+
+/// The progress of a recovery in a `ShareCollector`.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct CollectorStatus {
+    identifier: Option<u32>,
+    group_threshold: Option<usize>,
+    group_count: Option<usize>,
+    share_count: usize,
+    groups: Vec<GroupStatus>,
+}
+
+impl CollectorStatus {
+    /// Returns the identifier of the split, or `None` if no shares have been
+    /// collected.
+    pub fn identifier(&self) -> Option<u32> {
+        self.identifier
+    }
+
+    /// Returns the number of groups needed to recover the secret, or `None`
+    /// if no shares have been collected.
+    pub fn group_threshold(&self) -> Option<usize> {
+        self.group_threshold
+    }
+
+    /// Returns the number of groups in the split, or `None` if no shares have
+    /// been collected.
+    pub fn group_count(&self) -> Option<usize> {
+        self.group_count
+    }
+
+    /// Returns the number of shares collected.
+    pub fn share_count(&self) -> usize {
+        self.share_count
+    }
+
+    /// Returns the groups that shares have been collected from, in order of
+    /// group index.
+    pub fn groups(&self) -> &[GroupStatus] {
+        &self.groups
+    }
+
+    /// Returns the number of groups that have reached their member threshold.
+    pub fn complete_group_count(&self) -> usize {
+        self.groups.iter().filter(|group| group.is_complete()).count()
+    }
+
+    /// Returns the number of additional groups that must reach their member
+    /// threshold before the secret can be recovered, or `None` if no shares
+    /// have been collected.
+    pub fn groups_needed(&self) -> Option<usize> {
+        self.group_threshold.map(|threshold| threshold.saturating_sub(self.complete_group_count()))
+    }
+
+    /// Returns the groups worth collecting more shares from, the closest to
+    /// completion first.
+    ///
+    /// Only the metadata of the shares collected so far is used. A group that
+    /// shares have been collected from is ranked by how many more it needs.
+    /// A group that no shares have been seen from has an unknown member
+    /// threshold, so it needs at least one share and is ranked after every
+    /// group seen. Complete groups are never suggested, and once enough
+    /// groups are complete there are no suggestions at all.
+    ///
+    /// Any [`groups_needed`](Self::groups_needed) of the suggested groups
+    /// will complete the quorum.
+    pub fn suggestions(&self) -> Vec<GroupSuggestion> {
+        let (Some(group_count), Some(groups_needed)) = (self.group_count, self.groups_needed()) else {
+            return Vec::new();
+        };
+        if groups_needed == 0 {
+            return Vec::new();
+        }
+
+        let mut suggestions: Vec<GroupSuggestion> = (0..group_count)
+            .filter_map(|group_index| {
+                match self.groups.iter().find(|group| group.group_index() == group_index) {
+                    Some(group) if group.is_complete() => None,
+                    Some(group) => Some(GroupSuggestion {
+                        group_index,
+                        shares_held: group.shares_held(),
+                        shares_needed: Some(group.member_threshold() - group.shares_held()),
+                    }),
+                    None => Some(GroupSuggestion { group_index, shares_held: 0, shares_needed: None }),
+                }
+            })
+            .collect();
+        suggestions.sort_by_key(|suggestion| (suggestion.shares_needed.unwrap_or(usize::MAX), suggestion.group_index));
+        suggestions
+    }
+
+    /// Returns `true` if enough groups have reached their member threshold
+    /// to recover the secret.
+    ///
+    /// This reflects only the share metadata; recovery can still fail if a
+    /// share is corrupt.
+    pub fn can_combine(&self) -> bool {
+        self.group_threshold.is_some_and(|threshold| self.complete_group_count() >= threshold)
+    }
+
+    /// Renders the status as a plain-text table for display in a terminal.
+    ///
+    /// There is a row for every group of the split, showing its member
+    /// threshold, the members collected from it, and whether it is complete,
+    /// followed by a line counting the complete groups against the group
+    /// threshold. Groups no shares have been seen from show an unknown
+    /// threshold. Long member lists are cut short with an ellipsis, so no
+    /// line is wider than 80 columns. No share contents are shown.
+    pub fn render_table(&self) -> String {
+        const MAX_MEMBERS_WIDTH: usize = 40;
+
+        let (Some(group_count), Some(group_threshold)) = (self.group_count, self.group_threshold) else {
+            return "No shares collected.\n".to_string();
+        };
+
+        let rows: Vec<(usize, String, usize, String, bool)> = (0..group_count)
+            .map(|group_index| {
+                match self.groups.iter().find(|group| group.group_index == group_index) {
+                    Some(group) => (
+                        group_index,
+                        group.member_threshold.to_string(),
+                        group.shares_held(),
+                        render_member_indexes(&group.member_indexes, MAX_MEMBERS_WIDTH),
+                        group.is_complete(),
+                    ),
+                    None => (group_index, "?".to_string(), 0, "-".to_string(), false),
+                }
+            })
+            .collect();
+        let members_width = rows
+            .iter()
+            .map(|row| row.3.chars().count())
+            .max()
+            .unwrap_or(0)
+            .max("Members".len());
+
+        let mut result = format!("Group  Threshold  Held  {:<members_width$}  Complete\n", "Members");
+        for (group_index, member_threshold, shares_held, members, is_complete) in rows {
+            let mark = if is_complete { "\u{2713}" } else { "\u{2717}" };
+            result += &format!("{group_index:>5}  {member_threshold:>9}  {shares_held:>4}  {members:<members_width$}  {mark}\n");
+        }
+        result += &format!("{} of {} groups satisfied\n", self.complete_group_count(), group_threshold);
+        result
+    }
+}
+
+/// Lists member indexes separated by commas, ending the list with an
+/// ellipsis where it would otherwise be wider than `max_width`.
+fn render_member_indexes(member_indexes: &[usize], max_width: usize) -> String {
+    const ELLIPSIS: &str = "\u{2026}";
+
+    let mut result = String::new();
+    for (i, member_index) in member_indexes.iter().enumerate() {
+        let item = if i == 0 { member_index.to_string() } else { format!(", {member_index}") };
+        let is_last = i + 1 == member_indexes.len();
+        // Leave room for ", …" unless this is the final item.
+        let reserved = if is_last { 0 } else { 3 };
+        if result.len() + item.len() + reserved > max_width {
+            if !result.is_empty() {
+                result += ", ";
+            }
+            result += ELLIPSIS;
+            break;
+        }
+        result += &item;
+    }
+    result
+}
+
+/// A group that more shares should be collected from, as suggested by
+/// [`CollectorStatus::suggestions`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct GroupSuggestion {
+    group_index: usize,
+    shares_held: usize,
+    shares_needed: Option<usize>,
+}
+
+impl GroupSuggestion {
+    /// Returns the index of the group.
+    pub fn group_index(&self) -> usize {
+        self.group_index
+    }
+
+    /// Returns the number of shares already collected from the group.
+    pub fn shares_held(&self) -> usize {
+        self.shares_held
+    }
+
+    /// Returns the number of additional shares the group needs, or `None` if
+    /// no shares have been seen from the group, so its member threshold is
+    /// unknown.
+    pub fn shares_needed(&self) -> Option<usize> {
+        self.shares_needed
+    }
+}
+
+/// The shares collected from one group of a split.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct GroupStatus {
+    group_index: usize,
+    member_threshold: usize,
+    member_indexes: Vec<usize>,
+}
+
+impl GroupStatus {
+    /// Returns the index of the group.
+    pub fn group_index(&self) -> usize {
+        self.group_index
+    }
+
+    /// Returns the number of member shares needed to recover the group.
+    pub fn member_threshold(&self) -> usize {
+        self.member_threshold
+    }
+
+    /// Returns the member indexes of the shares collected, in order.
+    pub fn member_indexes(&self) -> &[usize] {
+        &self.member_indexes
+    }
+
+    /// Returns the number of shares collected from the group.
+    pub fn shares_held(&self) -> usize {
+        self.member_indexes.len()
+    }
+
+    /// Returns `true` if the group has reached its member threshold.
+    pub fn is_complete(&self) -> bool {
+        self.shares_held() >= self.member_threshold
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{GroupSpec, Spec, ShareVersion, sskr_generate_using, sskr_generate_with_version_using, test_support};
+    use hex_literal::hex;
+
+    #[test]
+    fn test_collector_progress() {
+        let secret = test_support::secret();
+        let shares = test_support::generate(&test_support::spec());
+        let mut collector = ShareCollector::new();
+
+        let status = collector.status();
+        assert_eq!(status.identifier(), None);
+        assert_eq!(status.share_count(), 0);
+        assert!(status.groups().is_empty());
+        assert!(!status.can_combine());
+
+        let status = collector.add_share(&shares[1][4]).unwrap();
+        let identifier = u16::from_be_bytes([shares[1][4][0], shares[1][4][1]]) as u32;
+        assert_eq!(status.identifier(), Some(identifier));
+        assert_eq!(status.group_threshold(), Some(2));
+        assert_eq!(status.group_count(), Some(3));
+        assert_eq!(status.groups().len(), 1);
+        assert_eq!(status.groups()[0].group_index(), 1);
+        assert_eq!(status.groups()[0].member_threshold(), 3);
+        assert_eq!(status.groups()[0].shares_held(), 1);
+
+        collector.add_share(&shares[0][2]).unwrap();
+        let status = collector.add_share(&shares[0][0]).unwrap();
+        assert_eq!(status.groups().len(), 2);
+        assert_eq!(status.groups()[0].member_indexes(), &[0, 2]);
+        assert!(status.groups()[0].is_complete());
+        assert!(!status.groups()[1].is_complete());
+        assert_eq!(status.complete_group_count(), 1);
+        assert!(!status.can_combine());
+
+        collector.add_share(&shares[1][1]).unwrap();
+        assert!(!collector.status().can_combine());
+        let status = collector.add_share(&shares[1][0]).unwrap();
+        assert_eq!(status.groups()[1].member_indexes(), &[0, 1, 4]);
+        assert_eq!(status.complete_group_count(), 2);
+        assert!(status.can_combine());
+        assert_eq!(status.share_count(), 5);
+        assert_eq!(collector.len(), 5);
+
+        assert_eq!(collector.combine().unwrap(), secret);
+    }
+
+    #[test]
+    fn test_remove_and_replace_shares() {
+        let secret = test_support::secret();
+        let shares = test_support::generate(&test_support::spec());
+        let mut collector = ShareCollector::new();
+        for share in [&shares[0][0], &shares[0][1], &shares[1][0], &shares[1][1], &shares[1][2]] {
+            collector.add_share(share).unwrap();
+        }
+        assert!(collector.status().can_combine());
+
+        // Removing a share drops its group back below its threshold.
+        assert_eq!(collector.remove_share(1, 1).as_ref(), Some(&shares[1][1]));
+        let status = collector.status();
+        assert_eq!(status.groups()[1].member_indexes(), &[0, 2]);
+        assert!(!status.groups()[1].is_complete());
+        assert_eq!(status.complete_group_count(), 1);
+        assert!(!status.can_combine());
+        assert_eq!(collector.remove_share(1, 1), None);
+        assert_eq!(collector.remove_share(0, 0).as_ref(), Some(&shares[0][0]));
+        assert_eq!(collector.len(), 3);
+        assert_eq!(collector.status().complete_group_count(), 0);
+
+        // Different shares complete the groups again.
+        collector.add_share(&shares[1][4]).unwrap();
+        let status = collector.add_share(&shares[0][2]).unwrap();
+        assert!(status.can_combine());
+        assert_eq!(collector.clone().combine().unwrap(), secret);
+
+        // A mistaken share can be swapped out in place.
+        let mut wrong = shares[2][0].clone();
+        wrong[1] ^= 0x01;
+        assert!(matches!(collector.replace_share(&wrong, &shares[2][0]), Err(SSKRError::ShareNotCollected)));
+        let before = collector.status();
+        assert!(matches!(collector.replace_share(&shares[0][2], &wrong), Err(SSKRError::IdentifierMismatch { .. })));
+        assert!(matches!(collector.replace_share(&shares[0][2], &shares[0][1]), Err(SSKRError::DuplicateMemberIndex)));
+        assert_eq!(collector.status(), before);
+        let status = collector.replace_share(&shares[0][2], &shares[0][0]).unwrap();
+        assert_eq!(status.groups()[0].member_indexes(), &[0, 1]);
+        let status = collector.replace_share(&shares[0][0], &shares[2][0]).unwrap();
+        assert_eq!(status.groups().len(), 3);
+        assert_eq!(status.complete_group_count(), 2);
+        assert_eq!(collector.combine().unwrap(), secret);
+
+        // Emptying the collector lets it start over with another split.
+        let mut collector = ShareCollector::new();
+        collector.add_share(&shares[0][0]).unwrap();
+        collector.remove_share(0, 0).unwrap();
+        assert!(collector.is_empty());
+        collector.add_share(&wrong).unwrap();
+    }
+
+    #[test]
+    fn test_suggestions() {
+        let secret = test_support::secret();
+        let spec = Spec::new(3, vec![
+            GroupSpec::new(2, 3).unwrap(),
+            GroupSpec::new(5, 7).unwrap(),
+            GroupSpec::new(1, 1).unwrap(),
+            GroupSpec::new(4, 6).unwrap(),
+            GroupSpec::new(3, 3).unwrap(),
+        ]).unwrap();
+        let shares = sskr_generate_using(&spec, &secret, &mut bc_rand::make_fake_random_number_generator()).unwrap();
+        let summary = |status: &CollectorStatus| -> Vec<(usize, usize, Option<usize>)> {
+            status.suggestions().iter().map(|s| (s.group_index(), s.shares_held(), s.shares_needed())).collect()
+        };
+
+        let mut collector = ShareCollector::new();
+        assert!(collector.status().suggestions().is_empty());
+        assert_eq!(collector.status().groups_needed(), None);
+
+        let status = collector.add_share(&shares[1][0]).unwrap();
+        assert_eq!(status.groups_needed(), Some(3));
+        assert_eq!(summary(&status), vec![(1, 1, Some(4)), (0, 0, None), (2, 0, None), (3, 0, None), (4, 0, None)]);
+
+        collector.add_share(&shares[3][0]).unwrap();
+        collector.add_share(&shares[3][1]).unwrap();
+        collector.add_share(&shares[3][2]).unwrap();
+        let status = collector.add_share(&shares[0][0]).unwrap();
+        assert_eq!(summary(&status), vec![(0, 1, Some(1)), (3, 3, Some(1)), (1, 1, Some(4)), (2, 0, None), (4, 0, None)]);
+
+        // Complete groups drop out of the suggestions.
+        collector.add_share(&shares[2][0]).unwrap();
+        let status = collector.add_share(&shares[3][3]).unwrap();
+        assert_eq!(status.groups_needed(), Some(1));
+        assert_eq!(summary(&status), vec![(0, 1, Some(1)), (1, 1, Some(4)), (4, 0, None)]);
+
+        // Once the quorum is reachable there is nothing left to suggest.
+        let status = collector.add_share(&shares[0][2]).unwrap();
+        assert_eq!(status.groups_needed(), Some(0));
+        assert!(status.can_combine());
+        assert!(status.suggestions().is_empty());
+        assert_eq!(collector.combine().unwrap(), secret);
+    }
+
+    #[test]
+    fn test_check_share() {
+        let shares = test_support::generate(&test_support::spec());
+        let mut collector = ShareCollector::new();
+        assert!(matches!(collector.check_share(&shares[0][0]), ShareCheck::Acceptable));
+
+        collector.add_share(&shares[0][0]).unwrap();
+        let before = collector.status();
+        assert!(matches!(collector.check_share(&shares[0][1]), ShareCheck::Acceptable));
+        assert!(matches!(collector.check_share(&shares[1][3]), ShareCheck::Acceptable));
+        assert!(matches!(collector.check_share(&shares[0][0]), ShareCheck::Duplicate { group_index: 0, member_index: 0 }));
+
+        let mut other = shares[0][1].clone();
+        other[1] ^= 0x01;
+        let identifier = u16::from_be_bytes([shares[0][0][0], shares[0][0][1]]) as u32;
+        assert!(matches!(collector.check_share(&other), ShareCheck::DifferentSplit { expected, found } if expected == identifier && found == identifier ^ 0x01));
+
+        let mut rng = bc_rand::make_fake_random_number_generator();
+        let secret = Secret::new([7u8; 32]).unwrap();
+        let spec = Spec::new(2, vec![GroupSpec::new(2, 3).unwrap(); 3]).unwrap();
+        let mut longer = sskr_generate_using(&spec, &secret, &mut rng).unwrap()[0][1].clone();
+        longer[..2].copy_from_slice(&shares[0][0][..2]);
+        assert!(matches!(collector.check_share(&longer), ShareCheck::LengthMismatch { expected: 16, found: 32 }));
+
+        let mut threshold = shares[0][1].clone();
+        threshold[3] = 0x02;
+        assert!(matches!(collector.check_share(&threshold), ShareCheck::Rejected(SSKRError::MemberThresholdMismatch { group_index: 0, expected: 2, found: 3, .. })));
+        assert!(matches!(collector.check_share(&shares[0][1][..3]), ShareCheck::Rejected(SSKRError::ShareLengthInvalid { .. })));
+        assert!(!collector.check_share(&shares[0][1][..3]).is_acceptable());
+
+        // Checking never changes the collector.
+        assert_eq!(collector.status(), before);
+
+        collector.add_share(&shares[0][1]).unwrap();
+        let check = collector.check_share(&shares[0][2]);
+        assert!(matches!(check, ShareCheck::Redundant { group_index: 0, member_threshold: 2 }));
+        assert!(check.is_acceptable());
+    }
+
+    #[test]
+    fn test_collector_rejects_mismatched_shares() {
+        let shares = test_support::generate(&test_support::spec());
+        let mut collector = ShareCollector::new();
+        collector.add_share(&shares[0][0]).unwrap();
+        let before = collector.status();
+
+        let with_byte = |i: usize, b: u8| {
+            let mut share = shares[0][1].clone();
+            share[i] = b;
+            share
+        };
+        let identifier = u16::from_be_bytes([shares[0][0][0], shares[0][0][1]]) as u32;
+
+        let error = collector.add_share(&with_byte(0, shares[0][1][0] ^ 0xff)).unwrap_err();
+        assert!(matches!(error, SSKRError::IdentifierMismatch { expected, found, share_index: 1 } if expected == identifier && found == identifier ^ 0xff00));
+        assert!(matches!(collector.add_share(&with_byte(2, 0x13)), Err(SSKRError::GroupCountMismatch { expected: 3, found: 4, share_index: 1 })));
+        assert!(matches!(collector.add_share(&with_byte(2, 0x02)), Err(SSKRError::GroupThresholdMismatch { expected: 2, found: 1, share_index: 1 })));
+        assert!(matches!(collector.add_share(&with_byte(3, 0x02)), Err(SSKRError::MemberThresholdMismatch { group_index: 0, expected: 2, found: 3, share_index: 1 })));
+        assert!(matches!(collector.add_share(&shares[0][0]), Err(SSKRError::DuplicateMemberIndex)));
+        assert!(matches!(collector.add_share(&shares[0][1][..3]), Err(SSKRError::ShareLengthInvalid { share_index: Some(1), len: 3 })));
+
+        let mut rng = bc_rand::make_fake_random_number_generator();
+        let secret = Secret::new(hex!("0ff784df000c4380a5ed683f7e6e3dcf0ff784df000c4380a5ed683f7e6e3dcf")).unwrap();
+        let spec = Spec::new(2, vec![GroupSpec::new(2, 3).unwrap(); 3]).unwrap();
+        let longer = sskr_generate_using(&spec, &secret, &mut rng).unwrap();
+        let mut longer_share = longer[1][0].clone();
+        longer_share[..2].copy_from_slice(&shares[0][0][..2]);
+        assert!(matches!(collector.add_share(&longer_share), Err(SSKRError::ShareLengthMismatch { expected: 16, found: 32, share_index: 1 })));
+        let wide = sskr_generate_with_version_using(&spec, &secret, ShareVersion::V2, &mut rng).unwrap();
+        assert!(matches!(collector.add_share(&wide[1][0]), Err(SSKRError::ShareVersionMismatch { expected: ShareVersion::V0, found: ShareVersion::V2, share_index: 1 })));
+
+        assert_eq!(collector.status(), before);
+        assert_eq!(collector.len(), 1);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_state_round_trip() {
+        let secret = test_support::secret();
+        let shares = test_support::generate(&test_support::spec());
+        let mut collector = ShareCollector::new();
+
+        let json = serde_json::to_string(&collector.to_state()).unwrap();
+        let restored = ShareCollector::from_state(serde_json::from_str(&json).unwrap()).unwrap();
+        assert!(restored.is_empty());
+
+        collector.add_share(&shares[0][2]).unwrap();
+        collector.add_share(&shares[1][0]).unwrap();
+        collector.add_share(&shares[0][1]).unwrap();
+        let state = collector.to_state();
+        assert_eq!(state.identifier(), collector.status().identifier());
+        assert_eq!(state.secret_len(), Some(16));
+        assert_eq!(state.shares(), collector.shares());
+
+        // Shares are written as hex.
+        let json = serde_json::to_string(&state).unwrap();
+        assert!(json.contains(&hex::encode(&shares[1][0])));
+
+        let mut restored = ShareCollector::from_state(serde_json::from_str(&json).unwrap()).unwrap();
+        assert_eq!(restored.status(), collector.status());
+        assert_eq!(restored.shares(), collector.shares());
+        restored.add_share(&shares[2][0]).unwrap();
+        assert_eq!(restored.combine().unwrap(), secret);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_state_rejects_tampering() {
+        let shares = test_support::generate(&test_support::spec());
+        let mut collector = ShareCollector::new();
+        collector.add_share(&shares[1][0]).unwrap();
+        collector.add_share(&shares[1][3]).unwrap();
+        let json: serde_json::Value = serde_json::to_value(collector.to_state()).unwrap();
+
+        let restore = |edit: &dyn Fn(&mut serde_json::Value)| {
+            let mut json = json.clone();
+            edit(&mut json);
+            match serde_json::from_value::<CollectorState>(json) {
+                Ok(state) => ShareCollector::from_state(state).map(|_| ()).map_err(Some),
+                Err(_) => Err(None),
+            }
+        };
+        assert!(restore(&|_| {}).is_ok());
+
+        // The metadata must agree with the shares.
+        assert!(matches!(restore(&|json| json["identifier"] = 7.into()), Err(Some(SSKRError::CollectorStateInvalid))));
+        assert!(matches!(restore(&|json| json["group_threshold"] = 3.into()), Err(Some(SSKRError::CollectorStateInvalid))));
+        assert!(matches!(restore(&|json| json["group_count"] = serde_json::Value::Null), Err(Some(SSKRError::CollectorStateInvalid))));
+        assert!(matches!(restore(&|json| json["secret_len"] = 32.into()), Err(Some(SSKRError::CollectorStateInvalid))));
+        assert!(matches!(restore(&|json| json["shares"] = serde_json::json!([])), Err(Some(SSKRError::CollectorStateInvalid))));
+
+        // The shares must be well formed and could all have been added.
+        assert!(matches!(
+            restore(&|json| json["shares"][1] = json["shares"][0].clone()),
+            Err(Some(SSKRError::DuplicateMemberIndex))
+        ));
+        let mut long = shares[1][3].clone();
+        long.extend_from_slice(&[0, 0]);
+        assert!(matches!(
+            restore(&|json| json["shares"][1] = hex::encode(&long).into()),
+            Err(Some(SSKRError::ShareLengthMismatch { .. }))
+        ));
+        assert!(matches!(restore(&|json| json["shares"][1] = "00112233".into()), Err(None)));
+        assert!(restore(&|json| json["shares"][1] = hex::encode(&shares[1][4]).into()).is_ok());
+    }
+
+    #[test]
+    fn test_merge() {
+        let secret = test_support::secret();
+        let shares = test_support::generate(&test_support::spec());
+        let collector_with = |indexes: &[(usize, usize)]| {
+            let mut collector = ShareCollector::new();
+            for &(group_index, member_index) in indexes {
+                collector.add_share(&shares[group_index][member_index]).unwrap();
+            }
+            collector
+        };
+
+        // Disjoint shares.
+        let merged = collector_with(&[(0, 0), (1, 1)]).merge(collector_with(&[(1, 2), (1, 4)])).unwrap();
+        assert_eq!(merged.len(), 4);
+        let status = merged.status();
+        assert_eq!(status.groups()[1].member_indexes(), &[1, 2, 4]);
+        assert_eq!(status.complete_group_count(), 1);
+
+        // Shares held by both are kept once.
+        let merged = merged.merge(collector_with(&[(1, 4), (0, 1), (1, 1)])).unwrap();
+        assert_eq!(merged.len(), 5);
+        assert!(merged.status().can_combine());
+        assert_eq!(merged.combine().unwrap(), secret);
+
+        // Either collector may be empty.
+        assert_eq!(ShareCollector::new().merge(collector_with(&[(2, 0)])).unwrap().len(), 1);
+        assert_eq!(collector_with(&[(2, 0)]).merge(ShareCollector::new()).unwrap().len(), 1);
+
+        // A different share for the same member is a conflict.
+        let mut altered = shares[1][4].clone();
+        let last = altered.len() - 1;
+        altered[last] ^= 0x01;
+        let mut conflicting = ShareCollector::new();
+        conflicting.add_share(&altered).unwrap();
+        assert!(matches!(
+            collector_with(&[(0, 0), (1, 4)]).merge(conflicting),
+            Err(SSKRError::ConflictingDuplicateShare { group_index: 1, member_index: 4 })
+        ));
+
+        // So are shares of another split.
+        let mut rng = bc_rand::make_fake_random_number_generator();
+        bc_rand::RandomNumberGenerator::random_data(&mut rng, 5);
+        let other_shares = test_support::generate_using(&test_support::spec(), &mut rng);
+        let mut other = ShareCollector::new();
+        other.add_share(&other_shares[0][1]).unwrap();
+        assert!(matches!(
+            collector_with(&[(0, 0)]).merge(other),
+            Err(SSKRError::IdentifierMismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn test_render_table() {
+        let shares = test_support::generate(&test_support::spec());
+        let mut collector = ShareCollector::new();
+        assert_eq!(collector.status().render_table(), "No shares collected.\n");
+
+        collector.add_share(&shares[1][4]).unwrap();
+        assert_eq!(collector.status().render_table(), concat!(
+            "Group  Threshold  Held  Members  Complete\n",
+            "    0          ?     0  -        \u{2717}\n",
+            "    1          3     1  4        \u{2717}\n",
+            "    2          ?     0  -        \u{2717}\n",
+            "0 of 2 groups satisfied\n",
+        ));
+
+        collector.add_share(&shares[1][0]).unwrap();
+        collector.add_share(&shares[1][2]).unwrap();
+        collector.add_share(&shares[0][1]).unwrap();
+        collector.add_share(&shares[2][0]).unwrap();
+        assert_eq!(collector.status().render_table(), concat!(
+            "Group  Threshold  Held  Members  Complete\n",
+            "    0          2     1  1        \u{2717}\n",
+            "    1          3     3  0, 2, 4  \u{2713}\n",
+            "    2          1     1  0        \u{2713}\n",
+            "2 of 2 groups satisfied\n",
+        ));
+    }
+
+    #[test]
+    fn test_render_table_width() {
+        let secret = test_support::secret();
+        let spec = Spec::new(16, vec![GroupSpec::new(16, 16).unwrap(); 16]).unwrap();
+        let shares = sskr_generate_using(&spec, &secret, &mut bc_rand::make_fake_random_number_generator()).unwrap();
+        let mut collector = ShareCollector::new();
+        for group in &shares {
+            for share in group {
+                collector.add_share(share).unwrap();
+            }
+        }
+
+        let table = collector.status().render_table();
+        assert_eq!(table.lines().count(), 18);
+        assert!(table.lines().all(|line| line.chars().count() <= 80));
+        assert_eq!(
+            table.lines().nth(1).unwrap(),
+            "    0         16    16  0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, \u{2026}  \u{2713}"
+        );
+        assert_eq!(table.lines().last().unwrap(), "16 of 16 groups satisfied");
+        assert!(!table.contains(&hex::encode(&shares[0][0][5..])[..8]));
+
+        assert_eq!(render_member_indexes(&[1, 2, 3], 7), "1, 2, 3");
+        assert_eq!(render_member_indexes(&[1, 2, 3], 6), "1, \u{2026}");
+        assert_eq!(render_member_indexes(&[100, 200], 4), "\u{2026}");
+        assert_eq!(render_member_indexes(&[], 4), "");
+    }
+
+    #[test]
+    fn test_observer() {
+        use std::sync::{Arc, Mutex};
+
+        let shares = test_support::generate(&test_support::spec());
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let mut collector = ShareCollector::new();
+        collector.set_observer({
+            let events = events.clone();
+            move |event| events.lock().unwrap().push(format!("{event:?}"))
+        });
+        let take_events = || std::mem::take(&mut *events.lock().unwrap());
+
+        collector.add_share(&shares[0][0]).unwrap();
+        assert_eq!(take_events(), ["ShareAccepted { group_index: 0, member_index: 0 }"]);
+
+        collector.add_share(&shares[0][2]).unwrap();
+        assert_eq!(take_events(), [
+            "ShareAccepted { group_index: 0, member_index: 2 }",
+            "GroupCompleted { group_index: 0 }",
+        ]);
+
+        // A complete group is not reported again.
+        collector.add_share(&shares[0][1]).unwrap();
+        assert_eq!(take_events(), ["ShareAccepted { group_index: 0, member_index: 1 }"]);
+
+        collector.add_share(&shares[0][1]).unwrap_err();
+        assert_eq!(take_events(), ["ShareRejected { reason: DuplicateMemberIndex }"]);
+
+        collector.add_share(&shares[2][0]).unwrap();
+        assert_eq!(take_events(), [
+            "ShareAccepted { group_index: 2, member_index: 0 }",
+            "GroupCompleted { group_index: 2 }",
+            "QuorumReachable",
+        ]);
+
+        collector.add_share(&shares[1][0]).unwrap();
+        assert_eq!(take_events(), ["ShareAccepted { group_index: 1, member_index: 0 }"]);
+
+        // Losing and regaining a group is a new transition.
+        collector.remove_share(2, 0).unwrap();
+        assert!(take_events().is_empty());
+        collector.replace_share(&shares[1][0], &shares[2][0]).unwrap();
+        assert_eq!(take_events(), [
+            "ShareAccepted { group_index: 2, member_index: 0 }",
+            "GroupCompleted { group_index: 2 }",
+            "QuorumReachable",
+        ]);
+        collector.replace_share(&shares[0][1], &shares[0][0]).unwrap_err();
+        assert_eq!(take_events(), ["ShareRejected { reason: DuplicateMemberIndex }"]);
+
+        // Clones don't share the observer.
+        let mut clone = collector.clone();
+        clone.add_share(&shares[1][1]).unwrap();
+        assert!(take_events().is_empty());
+
+        collector.clear_observer();
+        collector.add_share(&shares[1][1]).unwrap();
+        assert!(take_events().is_empty());
+    }
+}
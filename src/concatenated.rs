@@ -0,0 +1,121 @@
+use crate::{
+    MIN_SERIALIZE_SIZE_BYTES,
+    SSKRError,
+    Secret,
+    encoding::{check_share_matches, deserialize_share},
+    sskr_combine,
+};
+
+/// Combines SSKR shares stored back to back as fixed-length records in `buf`.
+///
+/// `buf` is split into records of `share_len` bytes, which are read in place.
+/// A record whose bytes are all `0x00` or all `0xFF`, as erased flash memory
+/// reads, is an empty slot and is skipped. The other records are combined as
+/// `sskr_combine` combines them; a share index in an error counts only those
+/// records.
+///
+/// # Errors
+///
+/// Returns `SSKRError::RecordLengthInvalid` if `share_len` is too short to
+/// hold a share, `SSKRError::ConcatenatedLengthInvalid` if `buf` is not a
+/// whole number of records, and otherwise the same errors as `sskr_combine`.
+pub fn sskr_combine_concatenated(buf: &[u8], share_len: usize) -> Result<Secret, SSKRError> {
+    if share_len < MIN_SERIALIZE_SIZE_BYTES {
+        return Err(SSKRError::RecordLengthInvalid { share_len, min: MIN_SERIALIZE_SIZE_BYTES });
+    }
+    if !buf.len().is_multiple_of(share_len) {
+        return Err(SSKRError::ConcatenatedLengthInvalid { len: buf.len(), share_len });
+    }
+    let shares: Vec<&[u8]> = buf
+        .chunks_exact(share_len)
+        .filter(|record| !is_erased(record))
+        .collect();
+    sskr_combine(&shares)
+}
+
+/// Serializes `shares` back to back, as read by [`sskr_combine_concatenated`].
+///
+/// Every share has the same length, which is the record length to read them
+/// back with.
+///
+/// # Errors
+///
+/// Returns an error if a share is malformed, or does not belong to the same
+/// split as the first.
+pub fn sskr_serialize_concatenated<T>(shares: &[T]) -> Result<Vec<u8>, SSKRError>
+where
+    T: AsRef<[u8]>
+{
+    let mut buf = Vec::with_capacity(shares.iter().map(|share| share.as_ref().len()).sum());
+    let mut first = None;
+    for (share_index, share) in shares.iter().enumerate() {
+        let share = share.as_ref();
+        let share_ref = deserialize_share(share, share_index)?;
+        check_share_matches(first.get_or_insert(share_ref), &share_ref, share_index)?;
+        buf.extend_from_slice(share);
+    }
+    Ok(buf)
+}
+
+/// Returns `true` if `record` reads as erased flash memory.
+fn is_erased(record: &[u8]) -> bool {
+    record.iter().all(|&byte| byte == 0x00) || record.iter().all(|&byte| byte == 0xff)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{GroupSpec, Spec, sskr_generate_using, test_support};
+    
+
+    #[test]
+    fn test_concatenated_round_trip() {
+        let secret = test_support::secret();
+        let spec = Spec::new(2, vec![GroupSpec::new(2, 3).unwrap(), GroupSpec::new(1, 1).unwrap()]).unwrap();
+        let shares = sskr_generate_using(&spec, &secret, &mut bc_rand::make_fake_random_number_generator()).unwrap();
+        let share_len = shares[0][0].len();
+
+        let buf = sskr_serialize_concatenated(&[&shares[0][0], &shares[1][0], &shares[0][2]]).unwrap();
+        assert_eq!(buf.len(), 3 * share_len);
+        assert_eq!(&buf[share_len..2 * share_len], &shares[1][0][..]);
+        assert_eq!(sskr_combine_concatenated(&buf, share_len).unwrap(), secret);
+
+        // Erased slots are skipped.
+        let mut page = vec![0xff; share_len];
+        page.extend_from_slice(&buf[..share_len]);
+        page.extend(std::iter::repeat_n(0x00, share_len));
+        page.extend_from_slice(&buf[share_len..]);
+        assert_eq!(sskr_combine_concatenated(&page, share_len).unwrap(), secret);
+
+        // A partially erased slot is a corrupt share.
+        page[..4].fill(0x00);
+        assert!(sskr_combine_concatenated(&page, share_len).is_err());
+    }
+
+    #[test]
+    fn test_concatenated_errors() {
+        let secret = test_support::secret();
+        let spec = Spec::new(1, vec![GroupSpec::new(2, 3).unwrap()]).unwrap();
+        let shares = sskr_generate_using(&spec, &secret, &mut bc_rand::make_fake_random_number_generator()).unwrap();
+        let buf = sskr_serialize_concatenated(&shares[0]).unwrap();
+
+        assert!(matches!(
+            sskr_combine_concatenated(&buf, 20),
+            Err(SSKRError::RecordLengthInvalid { share_len: 20, min: 21 })
+        ));
+        assert!(matches!(
+            sskr_combine_concatenated(&buf[1..], 21),
+            Err(SSKRError::ConcatenatedLengthInvalid { len: 62, share_len: 21 })
+        ));
+        assert!(matches!(sskr_combine_concatenated(&[0x00; 42], 21), Err(SSKRError::SharesEmpty)));
+
+        let mut rng = bc_rand::make_fake_random_number_generator();
+        bc_rand::RandomNumberGenerator::random_data(&mut rng, 5);
+        let other = sskr_generate_using(&spec, &secret, &mut rng).unwrap();
+        assert!(matches!(
+            sskr_serialize_concatenated(&[&shares[0][0], &other[0][1]]),
+            Err(SSKRError::IdentifierMismatch { share_index: 1, .. })
+        ));
+        assert!(sskr_serialize_concatenated(&[&shares[0][0][..8]]).is_err());
+    }
+}
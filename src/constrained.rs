@@ -0,0 +1,198 @@
+use bc_rand::RandomNumberGenerator;
+
+use crate::{
+    SSKRError,
+    SSKRShare,
+    Secret,
+    ShareValue,
+    ShareVersion,
+    Spec,
+    encoding::split_secret,
+    seeded_rng::SeededRandomNumberGenerator,
+    shamir,
+};
+
+/// A constraint on the shares of one group, as taken by
+/// [`sskr_generate_constrained`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GroupConstraint {
+    /// The value of the share of member `member_index` of group
+    /// `group_index` is `value`, such as a value that has already been
+    /// recorded somewhere it cannot be changed. The other shares of the group
+    /// are derived to suit it.
+    FixedShare { group_index: usize, member_index: usize, value: ShareValue },
+}
+
+/// Generates SSKR shares for the given `Spec` and `Secret`, subject to the
+/// given constraints.
+///
+/// This is `sskr_generate` with some share values chosen by the caller; see
+/// [`sskr_generate_constrained_using`].
+#[cfg(feature = "default-rng")]
+pub fn sskr_generate_constrained(
+    spec: &Spec,
+    master_secret: &Secret,
+    constraints: &[GroupConstraint]
+) -> Result<Vec<Vec<Vec<u8>>>, SSKRError> {
+    let mut rng = bc_rand::SecureRandomNumberGenerator;
+    sskr_generate_constrained_using(spec, master_secret, constraints, &mut rng)
+}
+
+/// Generates SSKR shares for the given `Spec` and `Secret` using the provided
+/// random number generator, subject to the given constraints.
+///
+/// For each `GroupConstraint::FixedShare`, the value of the given member's
+/// share is the given value, and the group's other shares are derived so
+/// that any member-threshold of the group's shares, fixed or not, recover
+/// it as usual. The shares are standard shares in every other respect, and
+/// with no constraints they are those `sskr_generate_using` returns with the
+/// same random number generator.
+///
+/// The shares of a group lie on a polynomial determined by its
+/// member-threshold points, two of which are fixed by the group secret and a
+/// random digest of it that recovery checks. So at most the member threshold
+/// less two shares of a group can be fixed, and none of a group with a
+/// member threshold of one or two.
+///
+/// A fixed value must be as long as the secret, and should be as random as
+/// the share it replaces: the fixed shares of a group are points chosen by
+/// the caller rather than at random, and the secret is as safe as they are.
+///
+/// # Errors
+///
+/// Returns the same errors as `sskr_generate`, and
+/// `SSKRError::FixedShareIndexInvalid` for a fixed share of a group or member
+/// not in `spec` or fixed more than once,
+/// `SSKRError::FixedShareLengthMismatch` for a fixed value not as long as the
+/// secret, and `SSKRError::TooManyFixedShares` for a group with more fixed
+/// shares than allowed.
+pub fn sskr_generate_constrained_using(
+    spec: &Spec,
+    master_secret: &Secret,
+    constraints: &[GroupConstraint],
+    random_generator: &mut impl RandomNumberGenerator
+) -> Result<Vec<Vec<Vec<u8>>>, SSKRError> {
+    let version = ShareVersion::V0;
+    spec.validate_for_version(version)?;
+
+    let mut fixed: Vec<Vec<(usize, &[u8])>> = vec![Vec::new(); spec.group_count()];
+    for constraint in constraints {
+        let GroupConstraint::FixedShare { group_index, member_index, value } = constraint;
+        let (group_index, member_index) = (*group_index, *member_index);
+        let Some(group) = spec.groups().get(group_index) else {
+            return Err(SSKRError::FixedShareIndexInvalid { group_index, member_index });
+        };
+        let group_fixed = &mut fixed[group_index];
+        if member_index >= group.member_count() || group_fixed.iter().any(|&(index, _)| index == member_index) {
+            return Err(SSKRError::FixedShareIndexInvalid { group_index, member_index });
+        }
+        if value.len() != master_secret.len() {
+            return Err(SSKRError::FixedShareLengthMismatch { group_index, member_index, expected: master_secret.len(), found: value.len() });
+        }
+        group_fixed.push((member_index, value.data()));
+    }
+    for (group_index, (group, group_fixed)) in spec.groups().iter().zip(&fixed).enumerate() {
+        let max = group.member_threshold().saturating_sub(2);
+        if group_fixed.len() > max {
+            return Err(SSKRError::TooManyFixedShares { group_index, count: group_fixed.len(), max });
+        }
+    }
+
+    // assign a random identifier
+    let mut identifier = [0u8; 2];
+    random_generator.fill_random_data(&mut identifier);
+    let identifier = u16::from_be_bytes(identifier) as u32;
+
+    let group_secrets = split_secret(version, spec.group_threshold(), spec.group_count(), master_secret.data(), random_generator)?;
+
+    let mut groups_shares = Vec::with_capacity(spec.group_count());
+    for (group_index, group) in spec.groups().iter().enumerate() {
+        // Only a group with fixed shares needs the crate's own Shamir
+        // implementation; the others are split by `bc-shamir`.
+        let mut group_rng = SeededRandomNumberGenerator::from_rng(random_generator);
+        let member_secrets = if fixed[group_index].is_empty() {
+            split_secret(version, group.member_threshold(), group.member_count(), &group_secrets[group_index], &mut group_rng)?
+        } else {
+            shamir::split_secret_with_fixed(
+                group.member_threshold(),
+                group.member_count(),
+                &group_secrets[group_index],
+                &fixed[group_index],
+                &mut group_rng,
+            )?
+        };
+        let member_shares: Vec<Vec<u8>> = member_secrets.into_iter().enumerate().map(|(member_index, member_secret)| {
+            SSKRShare::new(
+                identifier,
+                group_index,
+                spec.group_threshold(),
+                spec.group_count(),
+                member_index,
+                group.member_threshold(),
+                ShareValue::new(member_secret),
+            ).with_version(version).serialize()
+        }).collect();
+        groups_shares.push(member_shares);
+    }
+
+    Ok(groups_shares)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{GroupSpec, METADATA_SIZE_BYTES, sskr_combine, sskr_generate_using, test_support};
+    use hex_literal::hex;
+
+    fn fixed(group_index: usize, member_index: usize, value: &[u8]) -> GroupConstraint {
+        GroupConstraint::FixedShare { group_index, member_index, value: ShareValue::new(value.to_vec()) }
+    }
+
+    #[test]
+    fn test_generate_constrained() {
+        let secret = test_support::secret();
+        let spec = Spec::new(2, vec![GroupSpec::new(3, 5).unwrap(), GroupSpec::new(4, 4).unwrap(), GroupSpec::new(1, 1).unwrap()]).unwrap();
+
+        let unconstrained = sskr_generate_constrained_using(&spec, &secret, &[], &mut bc_rand::make_fake_random_number_generator()).unwrap();
+        assert_eq!(unconstrained, sskr_generate_using(&spec, &secret, &mut bc_rand::make_fake_random_number_generator()).unwrap());
+
+        let plate = hex!("00112233445566778899aabbccddeeff");
+        let other = hex!("ffeeddccbbaa99887766554433221100");
+        let constraints = [fixed(0, 3, &plate), fixed(1, 0, &other), fixed(1, 2, &plate)];
+        let shares = sskr_generate_constrained_using(&spec, &secret, &constraints, &mut bc_rand::SecureRandomNumberGenerator).unwrap();
+        assert_eq!(shares[0][3][METADATA_SIZE_BYTES..], plate);
+        assert_eq!(shares[1][0][METADATA_SIZE_BYTES..], other);
+        assert_eq!(shares[1][2][METADATA_SIZE_BYTES..], plate);
+
+        // Any quorum recovers the secret, whether or not it holds fixed shares.
+        assert_eq!(sskr_combine(&[&shares[0][3], &shares[0][0], &shares[0][4], &shares[2][0]]).unwrap(), secret);
+        assert_eq!(sskr_combine(&[&shares[0][1], &shares[0][2], &shares[0][0], &shares[2][0]]).unwrap(), secret);
+        assert_eq!(sskr_combine(&[&shares[1][3], &shares[1][2], &shares[1][1], &shares[1][0], &shares[0][3], &shares[0][1], &shares[0][2]]).unwrap(), secret);
+    }
+
+    #[test]
+    fn test_generate_constrained_errors() {
+        let secret = test_support::secret();
+        let spec = Spec::new(1, vec![GroupSpec::new(3, 5).unwrap(), GroupSpec::new(2, 3).unwrap()]).unwrap();
+        let value = [0x5a; 16];
+
+        assert!(matches!(
+            sskr_generate_constrained_using(&spec, &secret, &[fixed(0, 1, &value), fixed(0, 2, &value)], &mut bc_rand::SecureRandomNumberGenerator),
+            Err(SSKRError::TooManyFixedShares { group_index: 0, count: 2, max: 1 })
+        ));
+        assert!(matches!(
+            sskr_generate_constrained_using(&spec, &secret, &[fixed(1, 0, &value)], &mut bc_rand::SecureRandomNumberGenerator),
+            Err(SSKRError::TooManyFixedShares { group_index: 1, count: 1, max: 0 })
+        ));
+        assert!(matches!(
+            sskr_generate_constrained_using(&spec, &secret, &[fixed(0, 1, &[0x5a; 18])], &mut bc_rand::SecureRandomNumberGenerator),
+            Err(SSKRError::FixedShareLengthMismatch { group_index: 0, member_index: 1, expected: 16, found: 18 })
+        ));
+        for constraints in [[fixed(0, 5, &value), fixed(0, 0, &value)], [fixed(0, 1, &value), fixed(0, 1, &value)], [fixed(2, 0, &value), fixed(0, 0, &value)]] {
+            assert!(matches!(
+                sskr_generate_constrained_using(&spec, &secret, &constraints, &mut bc_rand::SecureRandomNumberGenerator),
+                Err(SSKRError::FixedShareIndexInvalid { .. })
+            ));
+        }
+    }
+}
@@ -0,0 +1,125 @@
+use bc_crypto::hash::hkdf_hmac_sha256;
+use zeroize::Zeroizing;
+
+use crate::{SSKRError, Secret, Spec, sskr_generate_using, seeded_rng::{SeededRandomNumberGenerator, SEED_SIZE_BYTES}};
+
+/// The salt that separates the key derived by [`sskr_generate_deterministic`]
+/// from any other use of the same seed.
+const DETERMINISTIC_SALT: &[u8] = b"sskr-deterministic-v1";
+
+/// Generates SSKR shares for the given `Spec` and `Secret`, deriving every
+/// random choice from `seed`.
+///
+/// The shares, identifier included, are a function of `spec`, `secret` and
+/// `seed` alone, so the same three always give the same shares. Reusing a
+/// seed with a different secret or a different spec is safe: all three are
+/// hashed together, so the splits are unrelated. Reusing a seed with the
+/// same secret and spec reproduces the same split, which is the point, but
+/// also means its shares are not independent of those of any other split
+/// made the same way.
+///
+/// Because the randomness depends on the secret, anyone who knows the seed
+/// can check a guess at the secret against a single share. Keep the seed as
+/// secret as the shares, or use a secret with enough entropy that it cannot
+/// be guessed, as any key should have.
+///
+/// The random bytes are drawn, in the order `sskr_generate_using` draws
+/// them, from this generator, so that other implementations can reproduce
+/// the shares:
+///
+/// - the key is HKDF-SHA256 of the input keying material
+///   `len(seed) || seed || len(spec) || spec || secret`, where `spec` is the
+///   group threshold followed by the member threshold and member count of
+///   each group in turn, each a single byte, so that `2 of [2-of-3, 1-of-1]`
+///   is `02 02 03 01 01`, and each `len` is a 64-bit big-endian byte count,
+///   with the salt `sskr-deterministic-v1` and empty info, expanded to 32
+///   bytes;
+/// - the stream is the concatenation of the blocks
+///   `HMAC-SHA256(key, i)` for `i = 0, 1, 2, ...`, with `i` encoded as a
+///   64-bit big-endian integer.
+///
+/// # Errors
+///
+/// Returns the same errors as `sskr_generate`.
+pub fn sskr_generate_deterministic(
+    spec: &Spec,
+    secret: &Secret,
+    seed: &[u8]
+) -> Result<Vec<Vec<Vec<u8>>>, SSKRError> {
+    let mut rng = deterministic_rng(seed, spec, secret);
+    sskr_generate_using(spec, secret, &mut rng)
+}
+
+/// Returns the generator behind [`sskr_generate_deterministic`], seeded with
+/// the key derived from `seed`, `spec` and `secret`.
+fn deterministic_rng(seed: &[u8], spec: &Spec, secret: &Secret) -> SeededRandomNumberGenerator {
+    let spec = encode_spec(spec);
+    let mut key_material = Zeroizing::new(Vec::with_capacity(16 + seed.len() + spec.len() + secret.len()));
+    key_material.extend_from_slice(&(seed.len() as u64).to_be_bytes());
+    key_material.extend_from_slice(seed);
+    key_material.extend_from_slice(&(spec.len() as u64).to_be_bytes());
+    key_material.extend_from_slice(&spec);
+    key_material.extend_from_slice(secret.data());
+    let key = Zeroizing::new(hkdf_hmac_sha256(&*key_material, DETERMINISTIC_SALT, SEED_SIZE_BYTES));
+    let mut seed = Zeroizing::new([0u8; SEED_SIZE_BYTES]);
+    seed.copy_from_slice(&key);
+    SeededRandomNumberGenerator::new(seed)
+}
+
+/// Encodes `spec` as it is bound into the key: the group threshold, then the
+/// member threshold and member count of each group, a byte each. Every count
+/// of a spec `sskr_generate_using` accepts fits in a byte.
+fn encode_spec(spec: &Spec) -> Vec<u8> {
+    let mut encoded = Vec::with_capacity(1 + 2 * spec.group_count());
+    encoded.push(spec.group_threshold() as u8);
+    for group in spec.groups() {
+        encoded.push(group.member_threshold() as u8);
+        encoded.push(group.member_count() as u8);
+    }
+    encoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{GroupSpec, sskr_combine, test_support};
+    use hex_literal::hex;
+
+    #[test]
+    fn test_generate_deterministic() {
+        let secret = test_support::secret();
+        let spec = Spec::new(2, vec![GroupSpec::new(2, 3).unwrap(), GroupSpec::new(1, 1).unwrap()]).unwrap();
+        let shares = sskr_generate_deterministic(&spec, &secret, b"seed").unwrap();
+        assert_eq!(shares, vec![
+            vec![
+                hex!("e324110100d8d864c9fe3fa86d3195953c04b23e8c").to_vec(),
+                hex!("e32411010111911276cd641dedad8285009fd11757").to_vec(),
+                hex!("e324110102514a88ac9889d97612bbb54429746c21").to_vec(),
+            ],
+            vec![hex!("e324111000c1714c497143476ae0f7d5f7df2ea722").to_vec()],
+        ]);
+        assert_eq!(sskr_combine(&[&shares[0][2], &shares[1][0], &shares[0][0]]).unwrap(), secret);
+        assert_eq!(sskr_generate_deterministic(&spec, &secret, b"seed").unwrap(), shares);
+
+        // Another seed, or another secret with the same seed, gives an
+        // unrelated split.
+        assert_ne!(sskr_generate_deterministic(&spec, &secret, b"seed2").unwrap()[0][0], shares[0][0]);
+        let other_secret = Secret::new(hex!("0ff784df000c4380a5ed683f7e6e3dce")).unwrap();
+        let other = sskr_generate_deterministic(&spec, &other_secret, b"seed").unwrap();
+        assert_ne!(other[0][0][..2], shares[0][0][..2]);
+        assert_eq!(sskr_combine(&[&other[0][1], &other[0][0], &other[1][0]]).unwrap(), other_secret);
+
+        // Another spec gives an unrelated split, even where its first group
+        // is the same.
+        let other_spec = Spec::new(1, vec![GroupSpec::new(2, 3).unwrap(), GroupSpec::new(1, 1).unwrap()]).unwrap();
+        let other = sskr_generate_deterministic(&other_spec, &secret, b"seed").unwrap();
+        assert_ne!(other[0][0][..2], shares[0][0][..2]);
+        assert_ne!(other[0][0][5..], shares[0][0][5..]);
+        assert_eq!(sskr_combine(&[&other[1][0]]).unwrap(), secret);
+
+        // The seed and the secret are not simply concatenated.
+        let shares = sskr_generate_deterministic(&spec, &secret, b"").unwrap();
+        assert_ne!(sskr_generate_deterministic(&spec, &secret, &[0]).unwrap(), shares);
+        assert_eq!(sskr_combine(&[&shares[0][0], &shares[0][1], &shares[1][0]]).unwrap(), secret);
+    }
+}
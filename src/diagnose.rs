@@ -0,0 +1,276 @@
+use crate::{
+    GroupSecret,
+    MAX_SHARE_COUNT,
+    SSKRError,
+    ShareVersion,
+    encoding::{Group, ReservedBits, deserialize_shares, group_shares, recover_secret},
+};
+
+/// The number of members of a group, and of groups of a split, that
+/// [`sskr_diagnose`] searches.
+///
+/// When more are provided, only the first `MAX_DIAGNOSED_MEMBERS`, or the
+/// first threshold-many if the threshold is larger, are searched.
+pub const MAX_DIAGNOSED_MEMBERS: usize = MAX_SHARE_COUNT;
+
+/// Looks for corrupt shares among `shares` by recovering each group from
+/// every combination of its member shares.
+///
+/// When a group holds more shares than its member threshold, each
+/// threshold-sized combination of them is recovered in turn. A share that is
+/// left out of every combination that recovers is a suspect. A group with
+/// exactly as many shares as its threshold has only one combination, so a
+/// corrupt share in it makes the group unrecoverable but cannot be singled
+/// out. The shares of a group with a member threshold of 1 are copies of the
+/// group secret with no digest, so they can never be suspected.
+///
+/// The search stops as soon as every share of a group has been part of a
+/// combination that recovers. In the worst case, where one share is corrupt
+/// and listed last, it recovers each of the C(n, t) combinations of the `n`
+/// shares searched with threshold `t`: at most C(16, 8) = 12,870 recoveries
+/// for a group, and as many again to check that the recoverable groups
+/// combine into the secret.
+///
+/// # Errors
+///
+/// Returns an error if a share is malformed or does not belong to the same
+/// split as the first, or if two shares claim the same member but differ.
+pub fn sskr_diagnose<T>(shares: &[T]) -> Result<Diagnosis, SSKRError>
+where
+    T: AsRef<[u8]>
+{
+    let sskr_shares = deserialize_shares(shares, ReservedBits::Version)?;
+    let grouped = group_shares(&sskr_shares)?;
+
+    let mut groups = Vec::with_capacity(grouped.groups.len());
+    let mut group_secrets: Vec<(usize, GroupSecret)> = Vec::new();
+    for group in &grouped.groups {
+        let (diagnosis, group_secret) = diagnose_group(grouped.version, group);
+        groups.push(diagnosis);
+        if let Some(group_secret) = group_secret {
+            group_secrets.push((group.group_index, group_secret));
+        }
+    }
+    groups.sort_by_key(|group| group.group_index);
+
+    let group_threshold = grouped.group_threshold;
+    let searched = group_secrets.len().min(MAX_DIAGNOSED_MEMBERS.max(group_threshold));
+    let recoverable = for_each_combination(searched, group_threshold, |subset| {
+        let indexes: Vec<usize> = subset.iter().map(|&i| group_secrets[i].0).collect();
+        let values: Vec<&[u8]> = subset.iter().map(|&i| group_secrets[i].1.data()).collect();
+        recover_secret(grouped.version, &indexes, &values).map(GroupSecret::new).is_ok()
+    });
+
+    Ok(Diagnosis { groups, recoverable })
+}
+
+/// Recovers `group` from every threshold-sized combination of its shares,
+/// returning its diagnosis and the group secret, if any combination
+/// recovered.
+fn diagnose_group(version: ShareVersion, group: &Group<'_>) -> (GroupDiagnosis, Option<GroupSecret>) {
+    let threshold = group.member_threshold;
+    let searched = group.member_indexes.len().min(MAX_DIAGNOSED_MEMBERS.max(threshold));
+    let mut recovered_with = vec![false; searched];
+    let mut group_secret = None;
+
+    for_each_combination(searched, threshold, |subset| {
+        let indexes: Vec<usize> = subset.iter().map(|&i| group.member_indexes[i]).collect();
+        let values: Vec<&[u8]> = subset.iter().map(|&i| group.member_shares[i]).collect();
+        if let Ok(secret) = recover_secret(version, &indexes, &values).map(GroupSecret::new) {
+            group_secret.get_or_insert(secret);
+            subset.iter().for_each(|&i| recovered_with[i] = true);
+        }
+        recovered_with.iter().all(|&recovered| recovered)
+    });
+
+    let suspect_members = match group_secret {
+        Some(_) => (0..searched)
+            .filter(|&i| !recovered_with[i])
+            .map(|i| group.member_indexes[i])
+            .collect(),
+        None => Vec::new(),
+    };
+    let diagnosis = GroupDiagnosis {
+        group_index: group.group_index,
+        member_threshold: threshold,
+        member_indexes: group.member_indexes.clone(),
+        suspect_members,
+        recoverable: group_secret.is_some(),
+    };
+    (diagnosis, group_secret)
+}
+
+/// Calls `f` with each `k`-element subset of `0..n`, as indexes in increasing
+/// order, until it returns `true`. Returns whether it did.
+fn for_each_combination(n: usize, k: usize, mut f: impl FnMut(&[usize]) -> bool) -> bool {
+    if k == 0 || k > n {
+        return false;
+    }
+    let mut subset: Vec<usize> = (0..k).collect();
+    loop {
+        if f(&subset) {
+            return true;
+        }
+        let Some(i) = (0..k).rev().find(|&i| subset[i] < n - k + i) else {
+            return false;
+        };
+        subset[i] += 1;
+        for j in i + 1..k {
+            subset[j] = subset[j - 1] + 1;
+        }
+    }
+}
+
+/// The result of [`sskr_diagnose`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Diagnosis {
+    groups: Vec<GroupDiagnosis>,
+    recoverable: bool,
+}
+
+impl Diagnosis {
+    /// Returns the diagnosis of each group that shares were provided for, in
+    /// order of group index.
+    pub fn groups(&self) -> &[GroupDiagnosis] {
+        &self.groups
+    }
+
+    /// Returns the `(group_index, member_index)` of every suspect share, in
+    /// order of group index.
+    pub fn suspects(&self) -> Vec<(usize, usize)> {
+        self.groups
+            .iter()
+            .flat_map(|group| group.suspect_members.iter().map(|&member_index| (group.group_index, member_index)))
+            .collect()
+    }
+
+    /// Returns `true` if the secret can be recovered without the suspect
+    /// shares.
+    pub fn is_recoverable(&self) -> bool {
+        self.recoverable
+    }
+}
+
+/// The diagnosis of one group, as reported by [`sskr_diagnose`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct GroupDiagnosis {
+    group_index: usize,
+    member_threshold: usize,
+    member_indexes: Vec<usize>,
+    suspect_members: Vec<usize>,
+    recoverable: bool,
+}
+
+impl GroupDiagnosis {
+    /// Returns the index of the group.
+    pub fn group_index(&self) -> usize {
+        self.group_index
+    }
+
+    /// Returns the member threshold of the group.
+    pub fn member_threshold(&self) -> usize {
+        self.member_threshold
+    }
+
+    /// Returns the member indexes of the distinct shares provided for the
+    /// group, in the order they were provided.
+    pub fn member_indexes(&self) -> &[usize] {
+        &self.member_indexes
+    }
+
+    /// Returns the member indexes of the group's suspect shares.
+    pub fn suspect_members(&self) -> &[usize] {
+        &self.suspect_members
+    }
+
+    /// Returns `true` if some combination of the group's shares recovered its
+    /// group secret.
+    pub fn is_recoverable(&self) -> bool {
+        self.recoverable
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{GroupSpec, Spec, sskr_combine, test_support};
+    
+
+    fn corrupt(share: &[u8]) -> Vec<u8> {
+        let mut share = share.to_vec();
+        let last = share.len() - 1;
+        share[last] ^= 0x01;
+        share
+    }
+
+    #[test]
+    fn test_combinations() {
+        let mut subsets = Vec::new();
+        assert!(!for_each_combination(4, 2, |subset| {
+            subsets.push(subset.to_vec());
+            false
+        }));
+        assert_eq!(subsets, [[0, 1], [0, 2], [0, 3], [1, 2], [1, 3], [2, 3]]);
+
+        let mut count = 0;
+        assert!(!for_each_combination(16, 8, |_| {
+            count += 1;
+            false
+        }));
+        assert_eq!(count, 12_870);
+        assert!(!for_each_combination(2, 3, |_| true));
+    }
+
+    #[test]
+    fn test_diagnose_corrupt_share() {
+        let spec = Spec::new(1, vec![GroupSpec::new(2, 3).unwrap()]).unwrap();
+        for version in [ShareVersion::V0, ShareVersion::V1, ShareVersion::V2, ShareVersion::V3] {
+            let shares = test_support::generate_with_version_using(&spec, version, &mut bc_rand::make_fake_random_number_generator());
+            let diagnosis = sskr_diagnose(&shares[0]).unwrap();
+            assert!(diagnosis.suspects().is_empty());
+            assert!(diagnosis.is_recoverable());
+
+            let input = [corrupt(&shares[0][0]), shares[0][1].clone(), shares[0][2].clone()];
+            assert!(sskr_combine(&input).is_err());
+            let diagnosis = sskr_diagnose(&input).unwrap();
+            assert_eq!(diagnosis.suspects(), [(0, 0)]);
+            assert!(diagnosis.is_recoverable());
+            assert_eq!(diagnosis.groups()[0].member_indexes(), [0, 1, 2]);
+        }
+    }
+
+    #[test]
+    fn test_diagnose_multiple_groups() {
+        let spec = Spec::new(2, vec![
+            GroupSpec::new(3, 5).unwrap(),
+            GroupSpec::new(2, 2).unwrap(),
+            GroupSpec::new(2, 4).unwrap(),
+        ]).unwrap();
+        let shares = test_support::generate(&spec);
+
+        // Two corrupt shares in the first group are both found, and the
+        // second group can't be recovered, but the third group stands in.
+        let input = [
+            shares[0][0].clone(), corrupt(&shares[0][1]), shares[0][2].clone(), corrupt(&shares[0][3]), shares[0][4].clone(),
+            shares[1][0].clone(), corrupt(&shares[1][1]),
+            shares[2][3].clone(), shares[2][1].clone(),
+        ];
+        let diagnosis = sskr_diagnose(&input).unwrap();
+        assert_eq!(diagnosis.suspects(), [(0, 1), (0, 3)]);
+        let recoverable: Vec<bool> = diagnosis.groups().iter().map(GroupDiagnosis::is_recoverable).collect();
+        assert_eq!(recoverable, [true, false, true]);
+        assert!(diagnosis.is_recoverable());
+
+        // With too few good shares, the secret can't be recovered.
+        let diagnosis = sskr_diagnose(&input[..7]).unwrap();
+        assert_eq!(diagnosis.suspects(), [(0, 1), (0, 3)]);
+        assert!(!diagnosis.is_recoverable());
+        let diagnosis = sskr_diagnose(&[corrupt(&shares[0][0]), shares[0][1].clone(), shares[0][2].clone()]).unwrap();
+        assert!(diagnosis.suspects().is_empty());
+        assert!(!diagnosis.groups()[0].is_recoverable());
+
+        assert!(matches!(sskr_diagnose::<Vec<u8>>(&[]), Err(SSKRError::SharesEmpty)));
+    }
+}
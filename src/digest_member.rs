@@ -0,0 +1,178 @@
+use bc_crypto::hmac_sha256;
+
+use crate::{
+    GroupSecret,
+    SSKRError,
+    SSKRShare,
+    Secret,
+    ShareValue,
+    sskr_combine,
+    sskr_recover_group,
+    encoding::{ReservedBits, deserialize_share, deserialize_shares},
+    transcript::GroupedShareBytes,
+};
+
+/// The domain separation prefix of the message a digest member authenticates.
+const DIGEST_MEMBER_DOMAIN: &[u8] = b"SSKR digest member";
+
+/// Returns the member index of the digest member in shares of a format with
+/// `max_share_count` members: the highest the format allows.
+pub(crate) fn digest_member_index(max_share_count: usize) -> usize {
+    max_share_count - 1
+}
+
+/// Appends a digest member share to each group of `shares`, as
+/// [`GenerateOptions::with_digest_member`](crate::GenerateOptions::with_digest_member)
+/// describes.
+pub(crate) fn append_digest_members(shares: &mut GroupedShareBytes) -> Result<(), SSKRError> {
+    for (group_index, group) in shares.iter_mut().enumerate() {
+        let first = deserialize_share(&group[0], 0)?;
+        let member_index = digest_member_index(first.version().max_share_count());
+        if group.len() > member_index {
+            return Err(SSKRError::DigestMemberUnavailable { group_index });
+        }
+        let group_secret = sskr_recover_group(group)?;
+        let header = SSKRShare::new(
+            first.identifier(),
+            group_index,
+            first.group_threshold(),
+            first.group_count(),
+            member_index,
+            first.member_threshold(),
+            ShareValue::new(Vec::new()),
+        ).with_version(first.version()).with_fingerprint(first.fingerprint()).serialize();
+        let digest = member_digest(&group_secret, &header);
+        group.push([header, digest].concat());
+    }
+    Ok(())
+}
+
+/// Combines SSKR shares generated with a digest member in each group into a
+/// `Secret`, checking each group secret against its digest member.
+///
+/// The digest member of a group is its share with the highest member index
+/// the shares' format allows. It is set aside, and the group secret is
+/// recovered from the other shares of its group and checked against it,
+/// before the remaining shares are combined as `sskr_combine` combines
+/// them. A group whose digest member is not among `shares`, or that has too
+/// few other shares to be recovered, is not checked.
+///
+/// Only combine shares this way that were generated with a digest member: in
+/// a split without one, a share with the highest member index is an
+/// ordinary share, and would be mistaken for a digest.
+///
+/// # Errors
+///
+/// Returns `SSKRError::DigestMismatch` if a group secret does not match its
+/// digest member, as when one of the group's shares is corrupt, and
+/// otherwise the same errors as `sskr_combine`.
+pub fn sskr_combine_with_digest_member<T>(shares: &[T]) -> Result<Secret, SSKRError>
+where
+    T: AsRef<[u8]>
+{
+    let sskr_shares = deserialize_shares(shares, ReservedBits::Version)?;
+    let Some(first) = sskr_shares.first() else {
+        return sskr_combine(shares);
+    };
+    let member_index = digest_member_index(first.version().max_share_count());
+    let (digest_members, data_shares): (Vec<_>, Vec<_>) = sskr_shares
+        .iter()
+        .zip(shares)
+        .partition(|(share, _)| share.member_index() == member_index);
+
+    for (digest_member, bytes) in &digest_members {
+        let group_index = digest_member.group_index();
+        let group: Vec<&[u8]> = data_shares
+            .iter()
+            .filter(|(share, _)| share.group_index() == group_index)
+            .map(|(_, bytes)| bytes.as_ref())
+            .collect();
+        let group_secret = match sskr_recover_group(&group) {
+            Ok(group_secret) => group_secret,
+            Err(SSKRError::SharesEmpty | SSKRError::NotEnoughMemberShares { .. }) => continue,
+            Err(error) => return Err(error),
+        };
+        let bytes = bytes.as_ref();
+        let (header, digest) = bytes.split_at(bytes.len() - digest_member.value().len());
+        if member_digest(&group_secret, header) != digest {
+            return Err(SSKRError::DigestMismatch { group_index });
+        }
+    }
+
+    let data_shares: Vec<&[u8]> = data_shares.iter().map(|(_, bytes)| bytes.as_ref()).collect();
+    sskr_combine(&data_shares)
+}
+
+/// The digest of `group_secret` a digest member with the metadata `header`
+/// holds: HMAC-SHA256 keyed with the group secret, truncated to its length.
+fn member_digest(group_secret: &GroupSecret, header: &[u8]) -> Vec<u8> {
+    let digest = hmac_sha256(group_secret.data(), [DIGEST_MEMBER_DOMAIN, header].concat());
+    digest[..group_secret.len()].to_vec()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{GenerateOptions, GroupSpec, Spec, sskr_generate_with_options_using, test_support};
+    
+
+    #[test]
+    fn test_digest_member() {
+        let secret = test_support::secret();
+        let spec = Spec::new(2, vec![GroupSpec::new(2, 3).unwrap(), GroupSpec::new(1, 2).unwrap()]).unwrap();
+        let options = GenerateOptions::new().with_digest_member(true);
+        let copies = sskr_generate_with_options_using(&spec, &secret, &options, &mut bc_rand::make_fake_random_number_generator()).unwrap();
+        for copy in copies.iter().flatten() {
+            assert_eq!(deserialize_share(copy.share(), 0).unwrap().member_index(), copy.member_index());
+        }
+        let shares = test_support::generate_with_options(&spec, &options);
+        assert_eq!(shares.iter().map(Vec::len).collect::<Vec<_>>(), [4, 3]);
+        for (group_index, group) in shares.iter().enumerate() {
+            let digest_member = deserialize_share(&group[group.len() - 1], 0).unwrap();
+            assert_eq!((digest_member.group_index(), digest_member.member_index()), (group_index, 15));
+            assert_eq!(digest_member.value().len(), secret.len());
+        }
+
+        assert_eq!(sskr_combine_with_digest_member(&[&shares[0][0], &shares[0][3], &shares[0][2], &shares[1][1], &shares[1][2]]).unwrap(), secret);
+        // Without the digest members, the shares combine as any others.
+        assert_eq!(sskr_combine_with_digest_member(&[&shares[0][0], &shares[0][2], &shares[1][1]]).unwrap(), secret);
+        assert_eq!(sskr_combine(&[&shares[0][0], &shares[0][2], &shares[1][1]]).unwrap(), secret);
+        // A digest member is not a member of the group's quorum.
+        assert!(matches!(
+            sskr_combine_with_digest_member(&[&shares[0][0], &shares[0][3], &shares[1][1]]),
+            Err(SSKRError::NotEnoughMemberShares { group_index: 0, have: 1, need: 2 })
+        ));
+
+        let full = Spec::new(1, vec![GroupSpec::new(2, 16).unwrap()]).unwrap();
+        assert!(matches!(
+            sskr_generate_with_options_using(&full, &secret, &GenerateOptions::new().with_digest_member(true), &mut bc_rand::SecureRandomNumberGenerator),
+            Err(SSKRError::DigestMemberUnavailable { group_index: 0 })
+        ));
+    }
+
+    #[test]
+    fn test_digest_member_detects_corruption() {
+        let secret = test_support::secret();
+        let spec = Spec::new(1, vec![GroupSpec::new(1, 2).unwrap()]).unwrap();
+        let shares = test_support::generate_with_options(&spec, &GenerateOptions::new().with_digest_member(true));
+        let mut corrupt = shares[0][0].clone();
+        *corrupt.last_mut().unwrap() ^= 0x01;
+
+        // A share of a group with a member threshold of one is its group
+        // secret, so nothing catches the corruption but the digest member.
+        assert_ne!(sskr_combine(&[&corrupt]).unwrap(), secret);
+        assert!(matches!(
+            sskr_combine_with_digest_member(&[&corrupt, &shares[0][2]]),
+            Err(SSKRError::DigestMismatch { group_index: 0 })
+        ));
+        assert_eq!(sskr_combine_with_digest_member(&[&shares[0][2], &shares[0][1]]).unwrap(), secret);
+
+        // A corrupt digest member is caught too.
+        let mut corrupt_digest = shares[0][2].clone();
+        corrupt_digest[5] ^= 0x01;
+        assert!(matches!(
+            sskr_combine_with_digest_member(&[&shares[0][0], &corrupt_digest]),
+            Err(SSKRError::DigestMismatch { group_index: 0 })
+        ));
+    }
+}
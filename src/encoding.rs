@@ -1,6 +1,13 @@
+use std::collections::{HashMap, HashSet};
+
 use bc_rand::RandomNumberGenerator;
-use bc_shamir::{split_secret, recover_secret};
-use crate::{SSKRError, METADATA_SIZE_BYTES, Secret, Spec, share::SSKRShare};
+use zeroize::Zeroizing;
+use crate::{SSKRError, METADATA_SIZE_BYTES, MIN_SECRET_LEN, MAX_SECRET_LEN, EXTENDED_METADATA_SIZE_BYTES, WIDE_IDENTIFIER_METADATA_SIZE_BYTES, FINGERPRINTED_METADATA_SIZE_BYTES, FINGERPRINT_SIZE_BYTES, MAX_EXTENDED_SHARE_COUNT, MAX_EXTENDED_GROUPS_COUNT, MAX_TOTAL_SHARE_COUNT, Secret, Spec, share::{SSKRShare, SSKRShareRef, ShareValue},
+    ShareVersion, ShareBytes, GroupSecret, PartialPolicy, PartialRecovery, SpecField, SplitMetadata, GeneratedShares, RecoveredSecret, RecoveryInfo, PASSPHRASE_SHARE_MAGIC, SHARE_ENVELOPE_MAGIC, SHARE_VERSION_ESCAPE, shamir,
+    fingerprint::{FingerprintAlg, fingerprints_match},
+    error::count_of,
+    transcript::GroupedShareBytes};
+use crate::seeded_rng::SeededRandomNumberGenerator;
 
 /// Generates SSKR shares for the given `Spec` and `Secret`.
 ///
@@ -8,6 +15,11 @@ use crate::{SSKRError, METADATA_SIZE_BYTES, Secret, Spec, share::SSKRShare};
 ///
 /// * `spec` - The `Spec` instance that defines the group and member thresholds.
 /// * `master_secret` - The `Secret` instance to be split into shares.
+///
+/// Shares are generated with `bc_rand::SecureRandomNumberGenerator`. This
+/// function requires the `default-rng` feature; use `sskr_generate_using` to
+/// pass a generator explicitly.
+#[cfg(feature = "default-rng")]
 pub fn sskr_generate(
     spec: &Spec,
     master_secret: &Secret
@@ -25,22 +37,414 @@ pub fn sskr_generate(
 /// * `master_secret` - The `Secret` instance to be split into shares.
 /// * `random_generator` - The random number generator to use for generating
 ///   shares.
+///
+/// The generator is drawn from for the identifier, then for the split of the
+/// secret among the groups, then for a 32-byte seed for each group in turn.
+/// Each group's members are split with the stream of blocks
+/// `HMAC-SHA256(seed, i)` for `i = 0, 1, 2, ...`, with `i` encoded as a
+/// 64-bit big-endian integer, so that the groups can be split independently.
 pub fn sskr_generate_using(
     spec: &Spec,
     master_secret: &Secret,
     random_generator: &mut impl RandomNumberGenerator
 ) -> Result<Vec<Vec<Vec<u8>>>, SSKRError> {
-    let groups_shares = generate_shares(spec, master_secret, random_generator)?;
+    generate_serialized(spec, master_secret, ShareVersion::V0, 0, random_generator).map(|(_, shares)| shares)
+}
+
+/// Generates SSKR shares for the given `Spec` and `Secret`, returning each
+/// share as an `SSKRShare`.
+///
+/// Each share serializes, with [`SSKRShare::serialize`], to the bytes that
+/// `sskr_generate` returns for it.
+///
+/// # Arguments
+///
+/// * `spec` - The `Spec` instance that defines the group and member thresholds.
+/// * `master_secret` - The `Secret` instance to be split into shares.
+#[cfg(feature = "default-rng")]
+pub fn sskr_generate_shares(
+    spec: &Spec,
+    master_secret: &Secret
+) -> Result<Vec<Vec<SSKRShare>>, SSKRError> {
+    let mut rng = bc_rand::SecureRandomNumberGenerator;
+    sskr_generate_shares_using(spec, master_secret, &mut rng)
+}
+
+/// Generates SSKR shares for the given `Spec` and `Secret` using the provided
+/// random number generator, returning each share as an `SSKRShare`.
+///
+/// Each share serializes, with [`SSKRShare::serialize`], to the bytes that
+/// `sskr_generate_using` returns for it with the same random number
+/// generator.
+///
+/// # Arguments
+///
+/// * `spec` - The `Spec` instance that defines the group and member thresholds.
+/// * `master_secret` - The `Secret` instance to be split into shares.
+/// * `random_generator` - The random number generator to use for generating
+///   shares.
+pub fn sskr_generate_shares_using(
+    spec: &Spec,
+    master_secret: &Secret,
+    random_generator: &mut impl RandomNumberGenerator
+) -> Result<Vec<Vec<SSKRShare>>, SSKRError> {
+    generate_shares(spec, master_secret, ShareVersion::V0, random_generator)
+}
+
+/// Generates SSKR shares for the given `Spec` and `Secret`, returning them
+/// with the identifier they were given.
+///
+/// The shares are identical to those returned by `sskr_generate`.
+///
+/// # Arguments
+///
+/// * `spec` - The `Spec` instance that defines the group and member thresholds.
+/// * `master_secret` - The `Secret` instance to be split into shares.
+#[cfg(feature = "default-rng")]
+pub fn sskr_generate_detailed(
+    spec: &Spec,
+    master_secret: &Secret
+) -> Result<GeneratedShares, SSKRError> {
+    let mut rng = bc_rand::SecureRandomNumberGenerator;
+    sskr_generate_detailed_using(spec, master_secret, &mut rng)
+}
+
+/// Generates SSKR shares for the given `Spec` and `Secret` using the provided
+/// random number generator, returning them with the identifier they were
+/// given.
+///
+/// The shares are identical to those returned by `sskr_generate_using` with
+/// the same random number generator.
+///
+/// # Arguments
+///
+/// * `spec` - The `Spec` instance that defines the group and member thresholds.
+/// * `master_secret` - The `Secret` instance to be split into shares.
+/// * `random_generator` - The random number generator to use for generating
+///   shares.
+pub fn sskr_generate_detailed_using(
+    spec: &Spec,
+    master_secret: &Secret,
+    random_generator: &mut impl RandomNumberGenerator
+) -> Result<GeneratedShares, SSKRError> {
+    let (identifier, shares) = generate_serialized(spec, master_secret, ShareVersion::V0, 0, random_generator)?;
+    Ok(GeneratedShares::new(identifier, spec.clone(), shares))
+}
+
+/// Generates SSKR shares for the given `Spec` and `Secret` in the given share
+/// format.
+///
+/// With `ShareVersion::V0` this is `sskr_generate`. With `ShareVersion::V1`
+/// the shares use the extended format, which allows splits of more than 16
+/// groups or members built with `Spec::new_with_version` and
+/// `GroupSpec::new_with_version`. **Extended-format shares can only be
+/// combined by implementations that support that format;** older versions of
+/// this crate reject them with `SSKRError::UnsupportedShareVersion`, and
+/// implementations predating share versions report their reserved bits as
+/// invalid.
+///
+/// # Arguments
+///
+/// * `spec` - The `Spec` instance that defines the group and member thresholds.
+/// * `master_secret` - The `Secret` instance to be split into shares.
+/// * `version` - The share format to generate.
+///
+/// # Errors
+///
+/// Returns an error if `spec` has more groups or members than `version`
+/// allows.
+#[cfg(feature = "default-rng")]
+pub fn sskr_generate_with_version(
+    spec: &Spec,
+    master_secret: &Secret,
+    version: ShareVersion
+) -> Result<Vec<Vec<Vec<u8>>>, SSKRError> {
+    let mut rng = bc_rand::SecureRandomNumberGenerator;
+    sskr_generate_with_version_using(spec, master_secret, version, &mut rng)
+}
+
+/// Generates SSKR shares for the given `Spec` and `Secret` in the given share
+/// format using the provided random number generator.
+///
+/// See [`sskr_generate_with_version`] for the interoperability caveats.
+///
+/// # Arguments
+///
+/// * `spec` - The `Spec` instance that defines the group and member thresholds.
+/// * `master_secret` - The `Secret` instance to be split into shares.
+/// * `version` - The share format to generate.
+/// * `random_generator` - The random number generator to use for generating
+///   shares.
+pub fn sskr_generate_with_version_using(
+    spec: &Spec,
+    master_secret: &Secret,
+    version: ShareVersion,
+    random_generator: &mut impl RandomNumberGenerator
+) -> Result<Vec<Vec<Vec<u8>>>, SSKRError> {
+    generate_serialized(spec, master_secret, version, 0, random_generator).map(|(_, shares)| shares)
+}
+
+/// Generates SSKR shares for the given `Spec` and `Secret`, returning each
+/// share as `ShareBytes`.
+///
+/// The shares are identical to those returned by `sskr_generate`.
+///
+/// # Arguments
+///
+/// * `spec` - The `Spec` instance that defines the group and member thresholds.
+/// * `master_secret` - The `Secret` instance to be split into shares.
+#[cfg(feature = "default-rng")]
+pub fn sskr_generate_share_bytes(
+    spec: &Spec,
+    master_secret: &Secret
+) -> Result<Vec<Vec<ShareBytes>>, SSKRError> {
+    let mut rng = bc_rand::SecureRandomNumberGenerator;
+    sskr_generate_share_bytes_using(spec, master_secret, &mut rng)
+}
 
-    let result: Vec<Vec<Vec<u8>>> = groups_shares.iter().map (|group| {
-        group.iter().map(serialize_share).collect()
+/// Generates SSKR shares for the given `Spec` and `Secret` using the provided
+/// random number generator, returning each share as `ShareBytes`.
+///
+/// The shares are identical to those returned by `sskr_generate_using` with
+/// the same random number generator.
+///
+/// # Arguments
+///
+/// * `spec` - The `Spec` instance that defines the group and member thresholds.
+/// * `master_secret` - The `Secret` instance to be split into shares.
+/// * `random_generator` - The random number generator to use for generating
+///   shares.
+pub fn sskr_generate_share_bytes_using(
+    spec: &Spec,
+    master_secret: &Secret,
+    random_generator: &mut impl RandomNumberGenerator
+) -> Result<Vec<Vec<ShareBytes>>, SSKRError> {
+    let groups_shares = sskr_generate_using(spec, master_secret, random_generator)?;
+
+    let result = groups_shares.into_iter().map(|group| {
+        group.into_iter().map(ShareBytes::from_validated).collect()
     }).collect();
 
     Ok(result)
 }
 
+/// Generates SSKR shares for the given `Spec` and `Secret` with the given
+/// identifier, rather than a random one.
+///
+/// The shares are otherwise generated as `sskr_generate` generates them.
+/// This lets an application that coordinates several splits choose their
+/// identifiers, for instance to number successive backups.
+///
+/// The identifier is what tells the shares of different splits apart, and a
+/// random one makes it unlikely that two splits share it. Shares of two
+/// splits with the same identifier and spec cannot be told apart by their
+/// metadata: mixed up, they fail to combine with
+/// `SSKRError::GroupRecoveryFailed` or `SSKRError::MasterRecoveryFailed`,
+/// or with `SSKRError::ConflictingDuplicateShare` when both splits provide
+/// the same member, rather than with `SSKRError::IdentifierMismatch`. Give
+/// each split its own identifier.
+///
+/// The identifier is a `u32`, as identifiers are everywhere in this crate,
+/// but the shares are in the standard format, whose identifier is 16 bits.
+///
+/// # Arguments
+///
+/// * `spec` - The `Spec` instance that defines the group and member thresholds.
+/// * `master_secret` - The `Secret` instance to be split into shares.
+/// * `identifier` - The identifier of the split, which must be at most
+///   `0xffff`.
+///
+/// # Errors
+///
+/// Returns `SSKRError::IdentifierInvalid` if `identifier` does not fit in 16
+/// bits, and otherwise the same errors as `sskr_generate`.
+#[cfg(feature = "default-rng")]
+pub fn sskr_generate_with_identifier(
+    spec: &Spec,
+    master_secret: &Secret,
+    identifier: u32
+) -> Result<Vec<Vec<Vec<u8>>>, SSKRError> {
+    let mut rng = bc_rand::SecureRandomNumberGenerator;
+    sskr_generate_with_identifier_using(spec, master_secret, identifier, &mut rng)
+}
+
+/// Generates SSKR shares for the given `Spec` and `Secret` with the given
+/// identifier, using the provided random number generator.
+///
+/// See [`sskr_generate_with_identifier`] for the risk of reusing an
+/// identifier. The random number generator is used only to split the
+/// secret, so its output differs from that of `sskr_generate_using` with
+/// the same generator.
+///
+/// # Arguments
+///
+/// * `spec` - The `Spec` instance that defines the group and member thresholds.
+/// * `master_secret` - The `Secret` instance to be split into shares.
+/// * `identifier` - The identifier of the split, which must be at most
+///   `0xffff`.
+/// * `random_generator` - The random number generator to use for generating
+///   shares.
+///
+/// # Errors
+///
+/// Returns the same errors as [`sskr_generate_with_identifier`].
+pub fn sskr_generate_with_identifier_using(
+    spec: &Spec,
+    master_secret: &Secret,
+    identifier: u32,
+    random_generator: &mut impl RandomNumberGenerator
+) -> Result<Vec<Vec<Vec<u8>>>, SSKRError> {
+    generate_serialized_with_identifier(spec, master_secret, ShareVersion::V0, identifier, 0, random_generator)
+}
+
+/// Generates SSKR shares for the given `Spec` and `Secret`, storing
+/// application data in the reserved bits of every share.
+///
+/// **Shares generated with a nonzero `app_nibble` are not standard SSKR
+/// shares.** The SSKR specification requires the reserved bits to be zero, so
+/// other implementations will reject them, and `sskr_combine` fails with
+/// `SSKRError::AppNibblePresent`. They can only be combined with
+/// [`sskr_combine_lenient`], and the nibble can be read back with
+/// [`sskr_app_nibble`]. With an `app_nibble` of zero the shares are identical
+/// to those of `sskr_generate`.
+///
+/// The nibble is also where a share is marked as being in another format or
+/// protected, so the values that do so — `SHARE_VERSION_ESCAPE`, 14 for a
+/// passphrase and 15 for an envelope — are refused. Share formats are only
+/// ever added behind the escape, so every other value will always be read
+/// as application data.
+///
+/// # Arguments
+///
+/// * `spec` - The `Spec` instance that defines the group and member thresholds.
+/// * `master_secret` - The `Secret` instance to be split into shares.
+/// * `app_nibble` - The application data: zero, or in the range `2..=13`.
+///
+/// # Errors
+///
+/// Returns `SSKRError::AppNibbleInvalid` if `app_nibble` does not fit in four
+/// bits, and `SSKRError::AppNibbleReserved` if it marks a share format or a
+/// protected share.
+#[cfg(feature = "default-rng")]
+pub fn sskr_generate_with_app_nibble(
+    spec: &Spec,
+    master_secret: &Secret,
+    app_nibble: u8
+) -> Result<Vec<Vec<Vec<u8>>>, SSKRError> {
+    let mut rng = bc_rand::SecureRandomNumberGenerator;
+    sskr_generate_with_app_nibble_using(spec, master_secret, app_nibble, &mut rng)
+}
+
+/// Generates SSKR shares for the given `Spec` and `Secret` using the provided
+/// random number generator, storing application data in the reserved bits of
+/// every share.
+///
+/// See [`sskr_generate_with_app_nibble`] for the interoperability caveats.
+///
+/// # Arguments
+///
+/// * `spec` - The `Spec` instance that defines the group and member thresholds.
+/// * `master_secret` - The `Secret` instance to be split into shares.
+/// * `app_nibble` - The application data: zero, or in the range `2..=13`.
+/// * `random_generator` - The random number generator to use for generating
+///   shares.
+pub fn sskr_generate_with_app_nibble_using(
+    spec: &Spec,
+    master_secret: &Secret,
+    app_nibble: u8,
+    random_generator: &mut impl RandomNumberGenerator
+) -> Result<Vec<Vec<Vec<u8>>>, SSKRError> {
+    if app_nibble > 0xf {
+        return Err(SSKRError::AppNibbleInvalid);
+    }
+    if ShareVersion::is_assigned_nibble(app_nibble) {
+        return Err(SSKRError::AppNibbleReserved { app_nibble });
+    }
+
+    generate_serialized(spec, master_secret, ShareVersion::V0, app_nibble, random_generator).map(|(_, shares)| shares)
+}
+
+/// Returns the application data stored in the reserved bits of a serialized
+/// SSKR share.
+///
+/// The share is parsed leniently, so this succeeds for shares generated by
+/// [`sskr_generate_with_app_nibble`] as well as for standard shares, which
+/// always return zero.
+///
+/// # Errors
+///
+/// Returns an error if the share is otherwise malformed.
+pub fn sskr_app_nibble(share: &[u8]) -> Result<u8, SSKRError> {
+    Ok(deserialize_share_with(share, None, ReservedBits::AppData)?.app_nibble())
+}
+
+/// Combines the given SSKR shares into a `Secret`, accepting application data
+/// in their reserved bits.
+///
+/// This is the counterpart of [`sskr_generate_with_app_nibble`]. All the
+/// shares must carry the same application data. Standard shares, whose
+/// reserved bits are zero, combine exactly as they do with `sskr_combine`.
+///
+/// # Arguments
+///
+/// * `shares` - A slice of SSKR shares to be combined.
+///
+/// # Errors
+///
+/// Returns the same errors as `sskr_combine`, except that application data in
+/// the reserved bits is accepted. Shares whose reserved bits mark another
+/// format or a protected share are read as `sskr_combine` reads them.
+pub fn sskr_combine_lenient<T>(shares: &[T]) -> Result<Secret, SSKRError>
+where
+    T: AsRef<[u8]>
+{
+    let sskr_shares = deserialize_shares(shares, ReservedBits::AppData)?;
+    combine_shares(&sskr_shares).map(RecoveredSecret::into_secret)
+}
+
+/// Combines the SSKR shares yielded by an iterator into a `Secret`.
+///
+/// This is `sskr_combine` for shares that are not already in a slice. The
+/// shares are moved, not copied, into a buffer so that their values can be
+/// read in place, and a share index in an error is the position at which the
+/// iterator yielded the share.
+///
+/// # Errors
+///
+/// Returns the same errors as `sskr_combine`.
+pub fn sskr_combine_iter<I, T>(shares: I) -> Result<Secret, SSKRError>
+where
+    I: IntoIterator<Item = T>,
+    T: AsRef<[u8]>
+{
+    let shares: Vec<T> = shares.into_iter().collect();
+    sskr_combine(&shares)
+}
+
+/// Combines the given SSKR shares into a `Secret`, with a record of how it
+/// was recovered.
+///
+/// This is `sskr_combine`, returning a [`RecoveredSecret`] that also tells
+/// which split the secret came from and which groups and how many shares
+/// were used to recover it.
+///
+/// # Errors
+///
+/// Returns the same errors as `sskr_combine`.
+pub fn sskr_combine_detailed<T>(shares: &[T]) -> Result<RecoveredSecret, SSKRError>
+where
+    T: AsRef<[u8]>
+{
+    let sskr_shares = deserialize_shares(shares, ReservedBits::Version)?;
+    combine_shares(&sskr_shares)
+}
+
 /// Combines the given SSKR shares into a `Secret`.
 ///
+/// The share values are read in place from `shares`; they are not copied
+/// while the shares are being sorted into groups. A share that appears more
+/// than once is used once.
+///
 /// # Arguments
 ///
 /// * `shares` - A slice of SSKR shares to be combined.
@@ -48,22 +452,504 @@ pub fn sskr_generate_using(
 /// # Errors
 ///
 /// Returns an error if the shares do not meet the necessary quorum of groups
-/// and member shares within each group.
+/// and member shares within each group, if more than `MAX_TOTAL_SHARE_COUNT`
+/// distinct shares are provided (repeats of a share count once), or if a
+/// share uses a format version this crate doesn't support. Returns `SSKRError::ConflictingDuplicateShare` if two
+/// shares claim the same member of a group but differ in value.
 pub fn sskr_combine<T>(shares: &[T]) -> Result<Secret, SSKRError>
 where
     T: AsRef<[u8]>
 {
-    let mut sskr_shares = Vec::with_capacity(shares.len());
+    sskr_combine_detailed(shares).map(RecoveredSecret::into_secret)
+}
 
-    for share in shares {
-        let sskr_share = deserialize_share(share.as_ref())?;
-        sskr_shares.push(sskr_share);
+/// Combines the given SSKR shares into a `Secret` without checking that
+/// their identifiers agree.
+///
+/// This is for shares generated in the privacy mode of
+/// [`GenerateOptions::with_identifier`](crate::GenerateOptions::with_identifier),
+/// whose identifier is the same for every split and so does not tell their
+/// shares apart. It is otherwise `sskr_combine`: the shares must still agree
+/// on every other field of their metadata, and the recovered group secrets
+/// and master secret are still checked against the checksums of the split.
+///
+/// Without the identifier to go by, shares of two splits with the same spec
+/// that are mixed up are caught only by those checksums, after recovery has
+/// been attempted, with `SSKRError::GroupRecoveryFailed` or
+/// `SSKRError::MasterRecoveryFailed`, or with
+/// `SSKRError::ConflictingDuplicateShare` when both splits provide the same
+/// member. Use `sskr_combine` for shares with random identifiers.
+///
+/// # Errors
+///
+/// Returns the same errors as `sskr_combine`, except
+/// `SSKRError::IdentifierMismatch`.
+pub fn sskr_combine_ignoring_identifier<T>(shares: &[T]) -> Result<Secret, SSKRError>
+where
+    T: AsRef<[u8]>
+{
+    let sskr_shares = deserialize_shares(shares, ReservedBits::Version)?;
+    let Some(identifier) = sskr_shares.first().map(SSKRShareRef::identifier) else {
+        return Err(SSKRError::SharesEmpty);
+    };
+    let sskr_shares: Vec<SSKRShareRef<'_>> = sskr_shares
+        .iter()
+        .map(|share| {
+            SSKRShareRef::new(
+                identifier,
+                share.group_index(),
+                share.group_threshold(),
+                share.group_count(),
+                share.member_index(),
+                share.member_threshold(),
+                share.value(),
+            ).with_app_nibble(share.app_nibble()).with_version(share.version()).with_fingerprint(share.fingerprint())
+        })
+        .collect();
+    combine_shares(&sskr_shares).map(RecoveredSecret::into_secret)
+}
+
+/// Combines the given SSKR shares, writing the secret into `out` rather than
+/// returning it in a `Secret`.
+///
+/// The length of the secret is that of the share values, and is checked
+/// against `out` before any recovery is attempted. The secret is written to
+/// the start of `out`, and the rest of `out` is left untouched. Every
+/// intermediate buffer holding the secret or a group secret is zeroized
+/// before returning.
+///
+/// # Errors
+///
+/// Returns `SSKRError::OutputBufferTooSmall` if `out` is shorter than the
+/// secret, and otherwise the same errors as `sskr_combine`.
+pub fn sskr_combine_into<T>(shares: &[T], out: &mut [u8]) -> Result<usize, SSKRError>
+where
+    T: AsRef<[u8]>
+{
+    let sskr_shares = deserialize_shares(shares, ReservedBits::Version)?;
+    if let Some(first) = sskr_shares.first() {
+        let required = first.value().len();
+        if out.len() < required {
+            return Err(SSKRError::OutputBufferTooSmall { len: out.len(), required });
+        }
     }
+    let secret = combine_shares(&sskr_shares)?.into_secret().into_zeroizing();
+    out[..secret.len()].copy_from_slice(&secret);
+    Ok(secret.len())
+}
 
-    combine_shares(&sskr_shares)
+/// Recovers the secret of a single group from its member shares.
+///
+/// This lets the holders of one group check that their shares are intact
+/// without involving any other group. All of `shares` must be of the same
+/// split and the same group, and there must be at least as many distinct
+/// members as the group's member threshold. The group is recovered from the
+/// first member-threshold of them, as `sskr_combine` recovers it.
+///
+/// The result is a [`GroupSecret`], not the master secret, but it is just as
+/// sensitive: together with the group secrets of enough other groups to meet
+/// the group threshold it reconstructs the master secret, and a split with a
+/// group threshold of one gives it away on its own. Treat it as the master
+/// secret, and drop it as soon as it is no longer needed.
+///
+/// # Errors
+///
+/// Returns `SSKRError::GroupIndexMismatch` if the shares are not all of one
+/// group, `SSKRError::NotEnoughMemberShares` if the group is short of its
+/// member threshold, `SSKRError::GroupRecoveryFailed` if the shares do not
+/// decode to a group secret, and otherwise the same errors as `sskr_combine`
+/// for malformed or inconsistent shares.
+pub fn sskr_recover_group<T>(shares: &[T]) -> Result<GroupSecret, SSKRError>
+where
+    T: AsRef<[u8]>
+{
+    let sskr_shares = deserialize_shares(shares, ReservedBits::Version)?;
+    let grouped = group_quorum(&sskr_shares)?;
+    let group = &grouped.groups[0];
+    let need = group.member_threshold;
+    recover_secret(grouped.version, &group.member_indexes[..need], &group.member_shares[..need])
+        .map(GroupSecret::new)
+        .map_err(|source| SSKRError::GroupRecoveryFailed { group_index: group.group_index, source })
+}
+
+/// Issues shares for further members of a group from a quorum of its
+/// existing shares.
+///
+/// The member shares of a group are points on a polynomial that any
+/// member-threshold of them determine, so new points on it can be given to
+/// members added after the split without regenerating it. All of `shares`
+/// must be of the same split and the same group, with at least as many
+/// distinct members as the group's member threshold. A share is returned for
+/// each of `new_member_indexes`, in order, with the same metadata as the
+/// existing shares; it combines with them, and with the shares of the other
+/// groups, as though it had been issued with them.
+///
+/// The group's member threshold is unchanged: the new shares make the group
+/// larger, not harder to recover. Indexes are zero-based, as elsewhere.
+///
+/// # Errors
+///
+/// Returns `SSKRError::MemberIndexInvalid` for an index beyond the largest
+/// member index of the shares' format, `SSKRError::MemberIndexInUse` for an
+/// index that is among `shares` or is requested twice, and otherwise the
+/// same errors as [`sskr_recover_group`].
+pub fn sskr_extend_group<T>(shares: &[T], new_member_indexes: &[usize]) -> Result<Vec<Vec<u8>>, SSKRError>
+where
+    T: AsRef<[u8]>
+{
+    let sskr_shares = deserialize_shares(shares, ReservedBits::Version)?;
+    let grouped = group_quorum(&sskr_shares)?;
+    let group = &grouped.groups[0];
+    let max = grouped.version.max_share_count();
+    for (i, &member_index) in new_member_indexes.iter().enumerate() {
+        if member_index >= max {
+            return Err(SSKRError::MemberIndexInvalid { member_index, max });
+        }
+        if group.has_member(member_index) || new_member_indexes[..i].contains(&member_index) {
+            return Err(SSKRError::MemberIndexInUse { member_index });
+        }
+    }
+
+    let need = group.member_threshold;
+    let values = shamir::extend_shares(&group.member_indexes[..need], &group.member_shares[..need], new_member_indexes)
+        .map_err(|source| SSKRError::GroupRecoveryFailed { group_index: group.group_index, source })?;
+    let first = &sskr_shares[0];
+    Ok(new_member_indexes
+        .iter()
+        .zip(values)
+        .map(|(&member_index, value)| {
+            SSKRShare::new(
+                grouped.identifier,
+                group.group_index,
+                grouped.group_threshold,
+                grouped.group_count,
+                member_index,
+                group.member_threshold,
+                ShareValue::new(value),
+            ).with_version(first.version()).with_fingerprint(first.fingerprint()).serialize()
+        })
+        .collect())
+}
+
+/// Groups `sskr_shares`, checking that they are all of one group and reach
+/// its member threshold.
+fn group_quorum<'a>(sskr_shares: &[SSKRShareRef<'a>]) -> Result<GroupedShares<'a>, SSKRError> {
+    let grouped = group_shares(sskr_shares)?;
+    let expected = sskr_shares[0].group_index();
+    if let Some((share_index, share)) = sskr_shares.iter().enumerate().find(|(_, share)| share.group_index() != expected) {
+        return Err(SSKRError::GroupIndexMismatch { expected, found: share.group_index(), share_index });
+    }
+    let group = &grouped.groups[0];
+    let (have, need) = (group.member_indexes.len(), group.member_threshold);
+    if have < need {
+        return Err(SSKRError::NotEnoughMemberShares { group_index: group.group_index, have, need });
+    }
+    Ok(grouped)
+}
+
+/// Recovers the master secret of a split from the secrets of its groups.
+///
+/// This is the second half of [`sskr_recover_group`]: each group can recover
+/// its group secret on its own, and only the group secrets need be brought
+/// together to recover the master secret. `entries` pairs each group secret
+/// with the index of its group, and `metadata` describes the split, as read
+/// from any of its shares with [`SplitMetadata::from_share`]. The master
+/// secret is recovered from the first group-threshold entries.
+///
+/// # Errors
+///
+/// Returns `SSKRError::NotEnoughGroups` if there are fewer entries than the
+/// group threshold, `SSKRError::GroupSecretIndexInvalid` for a group index
+/// out of range, `SSKRError::DuplicateGroupSecret` for a group given more
+/// than once, `SSKRError::GroupSecretLengthMismatch` if the group secrets
+/// differ in length, and `SSKRError::MasterRecoveryFailed` if they do not
+/// decode to a secret, as when one is of another split.
+pub fn sskr_combine_from_group_secrets(
+    entries: &[(usize, GroupSecret)],
+    metadata: &SplitMetadata
+) -> Result<Secret, SSKRError> {
+    let group_threshold = metadata.group_threshold();
+    if entries.len() < group_threshold {
+        return Err(SSKRError::NotEnoughGroups);
+    }
+    let mut seen = [0u64; 4];
+    let expected = entries[0].1.len();
+    for (group_index, group_secret) in entries {
+        let group_index = *group_index;
+        if group_index >= metadata.group_count() {
+            return Err(SSKRError::GroupSecretIndexInvalid { group_index, group_count: metadata.group_count() });
+        }
+        if seen[group_index / 64] & (1 << (group_index % 64)) != 0 {
+            return Err(SSKRError::DuplicateGroupSecret { group_index });
+        }
+        seen[group_index / 64] |= 1 << (group_index % 64);
+        if group_secret.len() != expected {
+            return Err(SSKRError::GroupSecretLengthMismatch { group_index, expected, found: group_secret.len() });
+        }
+    }
+    recover_master_secret(metadata.version(), &entries[..group_threshold], entries.len())
+}
+
+/// Combines the given SSKR shares into a `Secret`, checking it against a
+/// digest of the secret recorded when it was split.
+///
+/// The fingerprint is the SHA-256 digest of the secret's bytes, with nothing
+/// else hashed, as `bc_crypto::hash::sha256(secret.data())` computes it. It
+/// catches shares that combine cleanly but to the wrong secret, as shares of
+/// an older split of a key that has since been replaced do. The comparison
+/// takes the same time wherever the digests differ.
+///
+/// # Errors
+///
+/// Returns `SSKRError::FingerprintMismatch` if the recovered secret does not
+/// match `expected_digest`, in which case the secret is zeroized rather than
+/// returned, and otherwise the same errors as `sskr_combine`.
+pub fn sskr_combine_expecting<T>(shares: &[T], expected_digest: &[u8; 32]) -> Result<Secret, SSKRError>
+where
+    T: AsRef<[u8]>
+{
+    let secret = sskr_combine(shares)?;
+    let digest = bc_crypto::hash::sha256(secret.data());
+    let difference = digest.iter().zip(expected_digest).fold(0u8, |acc, (a, b)| acc | (a ^ b));
+    if difference != 0 {
+        drop(secret.into_zeroizing());
+        return Err(SSKRError::FingerprintMismatch);
+    }
+    Ok(secret)
+}
+
+/// Combines the given SSKR shares into a `Secret`, checking that every share
+/// provided agrees with it.
+///
+/// `sskr_combine` recovers each group from its first member-threshold shares
+/// and ignores the rest. This function also checks each extra share of a
+/// group: the group secret is recovered again with the extra share in place
+/// of one of the others, and must come out the same. An extra share that is
+/// corrupt, or that belongs to a different split, is reported rather than
+/// ignored.
+///
+/// # Arguments
+///
+/// * `shares` - A slice of SSKR shares to be combined.
+///
+/// # Errors
+///
+/// Returns `SSKRError::InconsistentShare` naming an extra share that does not
+/// agree with the others of its group, and otherwise the same errors as
+/// `sskr_combine`.
+pub fn sskr_combine_verified<T>(shares: &[T]) -> Result<Secret, SSKRError>
+where
+    T: AsRef<[u8]>
+{
+    let sskr_shares = deserialize_shares(shares, ReservedBits::Version)?;
+    let grouped = group_shares(&sskr_shares)?;
+    for group in &grouped.groups {
+        verify_group(grouped.version, group)?;
+    }
+    combine_shares(&sskr_shares).map(RecoveredSecret::into_secret)
+}
+
+/// Combines the given SSKR shares into a `Secret`, refusing shares that were
+/// not made according to `spec`.
+///
+/// Before any recovery is attempted, the group count and group threshold of
+/// every share, and the member threshold of its group, are checked against
+/// `spec`. As a share's group index is always less than its group count, this
+/// also checks that it names a group of `spec`. This catches shares of a
+/// different split that happens to share an identifier, such as an earlier
+/// backup of the same secret made with another spec.
+///
+/// The member counts of `spec` cannot be checked: a share records how many
+/// shares of its group are needed, but not how many were made.
+///
+/// # Errors
+///
+/// Returns `SSKRError::SpecMismatch` for the first share that doesn't agree
+/// with `spec`, and otherwise the same errors as `sskr_combine`.
+pub fn sskr_combine_with_spec<T>(spec: &Spec, shares: &[T]) -> Result<Secret, SSKRError>
+where
+    T: AsRef<[u8]>
+{
+    let sskr_shares = deserialize_shares(shares, ReservedBits::Version)?;
+    for (share_index, share) in sskr_shares.iter().enumerate() {
+        check_share_against_spec(spec, share, share_index)?;
+    }
+    combine_shares(&sskr_shares).map(RecoveredSecret::into_secret)
+}
+
+/// Checks the metadata of `share` against `spec`.
+fn check_share_against_spec(spec: &Spec, share: &SSKRShareRef<'_>, share_index: usize) -> Result<(), SSKRError> {
+    let mismatch = |field, expected, found| SSKRError::SpecMismatch { field, expected, found, share_index };
+    if share.group_count() != spec.group_count() {
+        return Err(mismatch(SpecField::GroupCount, spec.group_count(), share.group_count()));
+    }
+    if share.group_threshold() != spec.group_threshold() {
+        return Err(mismatch(SpecField::GroupThreshold, spec.group_threshold(), share.group_threshold()));
+    }
+    let group_index = share.group_index();
+    let member_threshold = spec.groups()[group_index].member_threshold();
+    if share.member_threshold() != member_threshold {
+        return Err(mismatch(SpecField::MemberThreshold { group_index }, member_threshold, share.member_threshold()));
+    }
+    Ok(())
 }
 
-fn serialize_share(share: &SSKRShare) -> Vec<u8> {
+/// Combines the given `SSKRShare` values into a `Secret`.
+///
+/// This is `sskr_combine` for shares that have already been deserialized, or
+/// that were never serialized. Each share is checked as deserializing it
+/// would check it, and the shares are then combined exactly as
+/// `sskr_combine` combines their serialized forms. Shares carrying
+/// application data combine as they do with `sskr_combine_lenient`.
+///
+/// # Errors
+///
+/// Returns the same errors as `sskr_combine` would for the serialized shares.
+pub fn sskr_combine_shares(shares: &[SSKRShare]) -> Result<Secret, SSKRError> {
+    let mut slots = ShareSlots::new(shares.len());
+    let share_refs = shares
+        .iter()
+        .enumerate()
+        .map(|(share_index, share)| {
+            let share = share.as_share_ref();
+            check_share_fields(&share, share_index)?;
+            slots.insert(&share)?;
+            Ok(share)
+        })
+        .collect::<Result<Vec<_>, SSKRError>>()?;
+    combine_shares(&share_refs).map(RecoveredSecret::into_secret)
+}
+
+/// Combines the given SSKR shares, releasing the recovered group secrets when
+/// the master secret cannot be reconstructed.
+///
+/// **Using `PartialPolicy::AllowGroupSecrets` increases the exposure of the
+/// split.** A group secret is not the master secret, but any
+/// `group_threshold` group secrets from the same split reconstruct it, so
+/// every group secret returned by this function must be protected exactly as
+/// carefully as the master secret. This is intended for incident response,
+/// where learning which groups are still intact is worth that risk. Ordinary
+/// recovery should use [`sskr_combine`], which never releases group secrets.
+///
+/// # Arguments
+///
+/// * `shares` - A slice of SSKR shares to be combined.
+/// * `policy` - Whether the group secrets of satisfied groups are returned if
+///   the master secret cannot be recovered.
+///
+/// # Returns
+///
+/// `PartialRecovery::Complete` with the master secret if the shares meet the
+/// quorum, otherwise `PartialRecovery::Incomplete` with the reason for the
+/// failure and, if the policy allows it, the group secrets of every group
+/// that could be recovered.
+pub fn sskr_combine_partial<T>(shares: &[T], policy: PartialPolicy) -> PartialRecovery
+where
+    T: AsRef<[u8]>
+{
+    match deserialize_shares(shares, ReservedBits::Version) {
+        Ok(sskr_shares) => combine_shares_partial(&sskr_shares, policy),
+        Err(error) => PartialRecovery::Incomplete { group_secrets: Vec::new(), error },
+    }
+}
+
+/// Sorts serialized SSKR shares into canonical order.
+///
+/// Shares are ordered by identifier, then group index, then member index, the
+/// same order as `SSKRShare`. Shares from different splits therefore cluster
+/// together by identifier. Only the share metadata is parsed; share values
+/// are not copied.
+///
+/// # Errors
+///
+/// Returns an error, leaving `shares` unchanged, if any share cannot be
+/// parsed.
+pub fn sort_shares<T>(shares: &mut [T]) -> Result<(), SSKRError>
+where
+    T: AsRef<[u8]>
+{
+    let mut keys = Vec::with_capacity(shares.len());
+    for (share_index, share) in shares.iter().enumerate() {
+        let sskr_share = deserialize_share(share.as_ref(), share_index)?;
+        keys.push((sskr_share.identifier(), sskr_share.group_index(), sskr_share.member_index()));
+    }
+
+    // Apply the permutation that sorts the keys, breaking ties by the bytes.
+    let mut order: Vec<usize> = (0..shares.len()).collect();
+    order.sort_by(|&a, &b| {
+        keys[a].cmp(&keys[b]).then_with(|| shares[a].as_ref().cmp(shares[b].as_ref()))
+    });
+    for i in 0..order.len() {
+        let mut j = order[i];
+        while j < i {
+            j = order[j];
+        }
+        shares.swap(i, j);
+    }
+
+    Ok(())
+}
+
+/// Returns a plain English description of a serialized SSKR share, suitable
+/// for showing to a user before they start a recovery.
+///
+/// Only the share metadata is interpreted; the share value is never included.
+/// Group and share numbers are one-based. The wording is stable, for example:
+///
+/// ```text
+/// SSKR share for split 0x3fa1: this is share 3 of group 1; the split has 2
+/// groups, 2 groups required, and this group needs 2 of its shares. The share
+/// carries a 32-byte payload.
+/// ```
+///
+/// # Errors
+///
+/// Returns an error if the share is malformed. The share is passed on its
+/// own, so the error's `share_index` is `None` and its message names no
+/// position.
+pub fn describe_share(bytes: &[u8]) -> Result<String, SSKRError> {
+    let share = deserialize_lone_share(bytes)?;
+    Ok(format!(
+        "SSKR share for split 0x{:0width$x}: this is share {} of group {}; the split has {}, {} required, and this group needs {} of its shares. The share carries a {}-byte payload.",
+        share.identifier(),
+        share.member_index() + 1,
+        share.group_index() + 1,
+        count_of(share.group_count(), "group"),
+        count_of(share.group_threshold(), "group"),
+        share.member_threshold(),
+        share.value().len(),
+        width = share.version().identifier_size_bytes() * 2,
+    ))
+}
+
+pub(crate) fn serialize_share(share: &SSKRShare) -> Vec<u8> {
+    let mut result = Vec::with_capacity(share.value().len() + share.version().metadata_size_bytes());
+    serialize_share_into(share, &mut result);
+    result
+}
+
+/// Appends the serialization of `share` to `out`.
+pub(crate) fn serialize_share_into(share: &SSKRShare, out: &mut Vec<u8>) {
+    match share.version() {
+        ShareVersion::V0 => out.extend_from_slice(&serialize_standard_metadata(share)),
+        ShareVersion::V1 => out.extend_from_slice(&serialize_extended_metadata(share)),
+        ShareVersion::V2 => out.extend_from_slice(&serialize_wide_identifier_metadata(share)),
+        ShareVersion::V3 => out.extend_from_slice(&serialize_fingerprinted_metadata(share)),
+    }
+    out.extend_from_slice(share.value().data());
+}
+
+/// Returns the metadata header that `serialize_share` writes for `share`.
+pub(crate) fn serialize_metadata(share: &SSKRShare) -> Vec<u8> {
+    match share.version() {
+        ShareVersion::V0 => serialize_standard_metadata(share).to_vec(),
+        ShareVersion::V1 => serialize_extended_metadata(share).to_vec(),
+        ShareVersion::V2 => serialize_wide_identifier_metadata(share).to_vec(),
+        ShareVersion::V3 => serialize_fingerprinted_metadata(share).to_vec(),
+    }
+}
+
+fn serialize_standard_metadata(share: &SSKRShare) -> [u8; METADATA_SIZE_BYTES] {
     // pack the id, group and member data into 5 bytes:
     // 76543210        76543210        76543210
     //         76543210        76543210
@@ -73,214 +959,1554 @@ fn serialize_share(share: &SSKRShare) -> Vec<u8> {
     //                     group-count: 4
     //                         group-index: 4
     //                             member-threshold: 4
-    //                                 reserved (MUST be zero): 4
+    //                                 version (zero unless application
+    //                                 data was requested): 4
     //                                     member-index: 4
 
-    let mut result = Vec::with_capacity(share.value().len() + METADATA_SIZE_BYTES);
     let id = share.identifier();
     let gt = (share.group_threshold() - 1) & 0xf;
     let gc = (share.group_count() - 1) & 0xf;
     let gi = share.group_index() & 0xf;
     let mt = (share.member_threshold() - 1) & 0xf;
     let mi = share.member_index() & 0xf;
+    let rs = (share.app_nibble() & 0xf) as usize;
+
+    let id1 = id >> 8;
+    let id2 = id & 0xff;
+
+    [
+        id1 as u8,
+        id2 as u8,
+        ((gt << 4) | gc) as u8,
+        ((gi << 4) | mt) as u8,
+        ((rs << 4) | mi) as u8,
+    ]
+}
+
+fn serialize_extended_metadata(share: &SSKRShare) -> [u8; EXTENDED_METADATA_SIZE_BYTES] {
+    // The escape stays where the standard format keeps its reserved bits, so
+    // that older decoders reject the share rather than misread it, and the
+    // version follows it:
+    // 76543210        76543210        76543210        76543210        76543210
+    //         76543210        76543210        76543210        76543210
+    // ----------------========--------====----========--------========--------
+    // identifier: 16
+    //                 group-threshold: 8
+    //                         group-count: 8
+    //                                 escape: 4
+    //                                     reserved (MUST be zero): 4
+    //                                         version: 8
+    //                                                 group-index: 8
+    //                                                         member-threshold: 8
+    //                                                                 member-index: 8
+
+    let id = share.identifier();
+
+    [
+        (id >> 8) as u8,
+        (id & 0xff) as u8,
+        (share.group_threshold() - 1) as u8,
+        (share.group_count() - 1) as u8,
+        SHARE_VERSION_ESCAPE << 4,
+        ShareVersion::V1.value(),
+        share.group_index() as u8,
+        (share.member_threshold() - 1) as u8,
+        share.member_index() as u8,
+    ]
+}
+
+fn serialize_wide_identifier_metadata(share: &SSKRShare) -> [u8; WIDE_IDENTIFIER_METADATA_SIZE_BYTES] {
+    // The first five bytes are laid out as in the standard format, carrying
+    // the high half of the identifier, and the version and the low half
+    // follow them:
+    // 76543210        76543210        76543210        76543210
+    //         76543210        76543210        76543210        76543210
+    // ----------------====----====----====----========================
+    // identifier (high): 16
+    //                 group-threshold: 4
+    //                     group-count: 4
+    //                         group-index: 4
+    //                             member-threshold: 4
+    //                                 escape: 4
+    //                                     member-index: 4
+    //                                         version: 8
+    //                                                 identifier (low): 16
+
+    let id = share.identifier();
+    let gt = (share.group_threshold() - 1) & 0xf;
+    let gc = (share.group_count() - 1) & 0xf;
+    let gi = share.group_index() & 0xf;
+    let mt = (share.member_threshold() - 1) & 0xf;
+    let mi = share.member_index() & 0xf;
+    let escape = SHARE_VERSION_ESCAPE as usize;
+
+    [
+        (id >> 24) as u8,
+        (id >> 16) as u8,
+        ((gt << 4) | gc) as u8,
+        ((gi << 4) | mt) as u8,
+        ((escape << 4) | mi) as u8,
+        ShareVersion::V2.value(),
+        (id >> 8) as u8,
+        id as u8,
+    ]
+}
+
+fn serialize_fingerprinted_metadata(share: &SSKRShare) -> [u8; FINGERPRINTED_METADATA_SIZE_BYTES] {
+    // The first five bytes are laid out as in the standard format, and the
+    // version and the fingerprint of the master secret follow them:
+    // 76543210        76543210        76543210        76543210
+    //         76543210        76543210        76543210
+    // ----------------====----====----====----========--------------------------------
+    // identifier: 16
+    //                 group-threshold: 4
+    //                     group-count: 4
+    //                         group-index: 4
+    //                             member-threshold: 4
+    //                                 escape: 4
+    //                                     member-index: 4
+    //                                         version: 8
+    //                                                 fingerprint: 32
+
+    let standard = serialize_standard_metadata(share);
+    let mut metadata = [0u8; FINGERPRINTED_METADATA_SIZE_BYTES];
+    metadata[..METADATA_SIZE_BYTES].copy_from_slice(&standard);
+    metadata[4] = (SHARE_VERSION_ESCAPE << 4) | (standard[4] & 0xf);
+    metadata[METADATA_SIZE_BYTES] = ShareVersion::V3.value();
+    metadata[METADATA_SIZE_BYTES + 1..].copy_from_slice(&share.fingerprint());
+    metadata
+}
+
+/// Checks that `source` is a well-formed serialized share.
+pub(crate) fn validate_share(source: &[u8]) -> Result<(), SSKRError> {
+    deserialize_lone_share(source).map(|_| ())
+}
+
+/// How the four reserved bits of a share's metadata are treated when it is
+/// deserialized, when they are not zero and do not mark another format or a
+/// protected share.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ReservedBits {
+    /// The bits must be zero, as the SSKR specification requires.
+    Version,
+    /// The bits carry application data.
+    AppData,
+}
+
+/// Deserializes the shares passed to a combine operation, refusing piles with
+/// more distinct shares than any split can hold.
+///
+/// Shares are counted as they are parsed, so a pile is refused as soon as it
+/// is known to be too large. See `ShareSlots` for how the limit is chosen.
+pub(crate) fn deserialize_shares<T>(shares: &[T], reserved_bits: ReservedBits) -> Result<Vec<SSKRShareRef<'_>>, SSKRError>
+where
+    T: AsRef<[u8]>
+{
+    let mut slots = ShareSlots::new(shares.len());
+    shares
+        .iter()
+        .enumerate()
+        .map(|(share_index, share)| {
+            let share = deserialize_share_with(share.as_ref(), Some(share_index), reserved_bits)?;
+            slots.insert(&share)?;
+            Ok(share)
+        })
+        .collect()
+}
+
+/// The distinct shares of a pile being combined, counted to refuse piles too
+/// large to belong to a single split.
+///
+/// A share counts once however many times it appears, so exact duplicates are
+/// never refused. The limit is `MAX_TOTAL_SHARE_COUNT` unless every share
+/// counted so far is in a format that allows larger splits: the format of a
+/// share is only known once it has been parsed, and a single share claiming
+/// the extended format does not lift the limit for the rest of the pile.
+struct ShareSlots {
+    slots: HashSet<(u32, usize, usize)>,
+    max: usize,
+}
+
+impl ShareSlots {
+    fn new(share_count: usize) -> Self {
+        Self { slots: HashSet::with_capacity(share_count.min(MAX_TOTAL_SHARE_COUNT)), max: usize::MAX }
+    }
+
+    /// Counts `share`, failing with `SSKRError::TooManyShares` if the pile
+    /// now holds more distinct shares than the formats of its shares allow.
+    fn insert(&mut self, share: &SSKRShareRef<'_>) -> Result<(), SSKRError> {
+        self.max = self.max.min(share.version().max_total_share_count());
+        self.slots.insert((share.identifier(), share.group_index(), share.member_index()));
+        if self.slots.len() > self.max {
+            return Err(SSKRError::TooManyShares { count: self.slots.len(), max: self.max });
+        }
+        Ok(())
+    }
+}
+
+/// Deserializes the share at position `share_index` of the caller's input,
+/// which is used only to report errors.
+pub(crate) fn deserialize_share(source: &[u8], share_index: usize) -> Result<SSKRShareRef<'_>, SSKRError> {
+    deserialize_share_with(source, Some(share_index), ReservedBits::Version)
+}
+
+/// Deserializes a share passed on its own, so that its errors name no
+/// position.
+pub(crate) fn deserialize_lone_share(source: &[u8]) -> Result<SSKRShareRef<'_>, SSKRError> {
+    deserialize_share_with(source, None, ReservedBits::Version)
+}
+
+fn deserialize_share_with(
+    source: &[u8],
+    share_index: Option<usize>,
+    reserved_bits: ReservedBits
+) -> Result<SSKRShareRef<'_>, SSKRError> {
+    if source.starts_with(&PASSPHRASE_SHARE_MAGIC) {
+        return Err(SSKRError::PassphraseRequired { share_index });
+    }
+    if source.starts_with(&SHARE_ENVELOPE_MAGIC) {
+        return Err(SSKRError::EnvelopeRequired { share_index });
+    }
+    if source.len() < METADATA_SIZE_BYTES {
+        return Err(SSKRError::ShareLengthInvalid { share_index, len: source.len() });
+    }
+
+    // The reserved bits decide how the rest of the share is laid out, so they
+    // are checked before anything else is read.
+    let reserved = source[4] >> 4;
+    let share = match reserved {
+        0 => deserialize_standard_share(source, share_index, METADATA_SIZE_BYTES)?,
+        SHARE_VERSION_ESCAPE => deserialize_versioned_share(source, share_index)?,
+        _ if ShareVersion::is_assigned_nibble(reserved) => return Err(SSKRError::ShareReservedBitsInvalid { share_index }),
+        _ => match reserved_bits {
+            ReservedBits::Version => return Err(SSKRError::AppNibblePresent { share_index, app_nibble: reserved }),
+            ReservedBits::AppData => deserialize_standard_share(source, share_index, METADATA_SIZE_BYTES)?.with_app_nibble(reserved),
+        },
+    };
+
+    check_payload_len(share.value().len(), share_index)?;
+
+    Ok(share)
+}
+
+/// Checks that a share value of `len` bytes could have come from splitting a
+/// valid secret.
+fn check_payload_len(len: usize, share_index: Option<usize>) -> Result<(), SSKRError> {
+    if len < MIN_SECRET_LEN {
+        return Err(SSKRError::SharePayloadTooShort { share_index, len });
+    }
+    if len > MAX_SECRET_LEN {
+        return Err(SSKRError::SharePayloadTooLong { share_index, len });
+    }
+    if len & 1 != 0 {
+        return Err(SSKRError::SharePayloadLengthNotEven { share_index, len });
+    }
+    Ok(())
+}
+
+/// Checks the fields of a share that was not deserialized, with the checks
+/// deserializing makes of a share in its format, so that it can be combined
+/// exactly as if it had been.
+fn check_share_fields(share: &SSKRShareRef<'_>, share_index: usize) -> Result<(), SSKRError> {
+    let share_index = Some(share_index);
+    let version = share.version();
+    let (group_threshold, group_count) = (share.group_threshold(), share.group_count());
+    if group_count == 0 || group_count > version.max_groups_count() {
+        return Err(SSKRError::ShareGroupCountInvalid { share_index, group_count });
+    }
+    if group_threshold == 0 || group_threshold > group_count {
+        return Err(SSKRError::ShareGroupThresholdInvalid { share_index, group_threshold, group_count });
+    }
+    let group_index = share.group_index();
+    if group_index >= group_count {
+        return Err(SSKRError::GroupIndexInvalid { share_index, group_index, group_count });
+    }
+    let member_threshold = share.member_threshold();
+    if member_threshold == 0 || member_threshold > version.max_share_count() {
+        return Err(SSKRError::ShareMemberThresholdInvalid { share_index, member_threshold });
+    }
+    let member_index = share.member_index();
+    if member_index >= version.max_share_count() {
+        return Err(SSKRError::ShareMemberIndexInvalid { share_index, member_index });
+    }
+    check_payload_len(share.value().len(), share_index)
+}
+
+/// Reads the metadata of a standard-format share, whose reserved bits the
+/// caller has already interpreted. The value follows `metadata_size` bytes of
+/// metadata.
+fn deserialize_standard_share(
+    source: &[u8],
+    share_index: Option<usize>,
+    metadata_size: usize
+) -> Result<SSKRShareRef<'_>, SSKRError> {
+    let group_threshold = ((source[2] >> 4) + 1) as usize;
+    let group_count = ((source[2] & 0xf) + 1) as usize;
+
+    if group_threshold > group_count {
+        return Err(SSKRError::ShareGroupThresholdInvalid { share_index, group_threshold, group_count });
+    }
+
+    let identifier = ((source[0] as u32) << 8) | source[1] as u32;
+    let group_index = (source[3] >> 4) as usize;
+    if group_index >= group_count {
+        return Err(SSKRError::GroupIndexInvalid { share_index, group_index, group_count });
+    }
+    let member_threshold = ((source[3] & 0xf) + 1) as usize;
+    let member_index = (source[4] & 0xf) as usize;
+
+    Ok(SSKRShareRef::new(
+        identifier,
+        group_index,
+        group_threshold,
+        group_count,
+        member_index,
+        member_threshold,
+        &source[metadata_size..],
+    ))
+}
+
+/// Reads a share whose reserved bits are `SHARE_VERSION_ESCAPE`, in the format
+/// named by the version byte that follows the standard metadata.
+fn deserialize_versioned_share(source: &[u8], share_index: Option<usize>) -> Result<SSKRShareRef<'_>, SSKRError> {
+    let Some(&version) = source.get(METADATA_SIZE_BYTES) else {
+        return Err(SSKRError::ShareLengthInvalid { share_index, len: source.len() });
+    };
+    match ShareVersion::try_from(version) {
+        Ok(ShareVersion::V1) => deserialize_extended_share(source, share_index),
+        Ok(ShareVersion::V2) => deserialize_wide_identifier_share(source, share_index),
+        Ok(ShareVersion::V3) => deserialize_fingerprinted_share(source, share_index),
+        Ok(ShareVersion::V0) | Err(_) => Err(SSKRError::UnsupportedShareVersion { share_index, version }),
+    }
+}
+
+fn deserialize_extended_share(source: &[u8], share_index: Option<usize>) -> Result<SSKRShareRef<'_>, SSKRError> {
+    if source.len() < EXTENDED_METADATA_SIZE_BYTES {
+        return Err(SSKRError::ShareLengthInvalid { share_index, len: source.len() });
+    }
+    if source[4] & 0xf != 0 {
+        return Err(SSKRError::ShareReservedBitsInvalid { share_index });
+    }
+
+    let group_threshold = source[2] as usize + 1;
+    let group_count = source[3] as usize + 1;
+
+    if group_count > MAX_EXTENDED_GROUPS_COUNT {
+        return Err(SSKRError::ShareGroupCountInvalid { share_index, group_count });
+    }
+    if group_threshold > group_count {
+        return Err(SSKRError::ShareGroupThresholdInvalid { share_index, group_threshold, group_count });
+    }
+
+    let identifier = ((source[0] as u32) << 8) | source[1] as u32;
+    let group_index = source[6] as usize;
+    if group_index >= group_count {
+        return Err(SSKRError::GroupIndexInvalid { share_index, group_index, group_count });
+    }
+    let member_threshold = source[7] as usize + 1;
+    let member_index = source[8] as usize;
+    if member_threshold > MAX_EXTENDED_SHARE_COUNT {
+        return Err(SSKRError::ShareMemberThresholdInvalid { share_index, member_threshold });
+    }
+    if member_index >= MAX_EXTENDED_SHARE_COUNT {
+        return Err(SSKRError::ShareMemberIndexInvalid { share_index, member_index });
+    }
+
+    Ok(SSKRShareRef::new(
+        identifier,
+        group_index,
+        group_threshold,
+        group_count,
+        member_index,
+        member_threshold,
+        &source[EXTENDED_METADATA_SIZE_BYTES..],
+    ).with_version(ShareVersion::V1))
+}
+
+fn deserialize_wide_identifier_share(source: &[u8], share_index: Option<usize>) -> Result<SSKRShareRef<'_>, SSKRError> {
+    if source.len() < WIDE_IDENTIFIER_METADATA_SIZE_BYTES {
+        return Err(SSKRError::ShareLengthInvalid { share_index, len: source.len() });
+    }
+
+    let share = deserialize_standard_share(source, share_index, WIDE_IDENTIFIER_METADATA_SIZE_BYTES)?;
+    let identifier = (share.identifier() << 16) | ((source[6] as u32) << 8) | source[7] as u32;
+
+    Ok(SSKRShareRef::new(
+        identifier,
+        share.group_index(),
+        share.group_threshold(),
+        share.group_count(),
+        share.member_index(),
+        share.member_threshold(),
+        share.value(),
+    ).with_version(ShareVersion::V2))
+}
+
+fn deserialize_fingerprinted_share(source: &[u8], share_index: Option<usize>) -> Result<SSKRShareRef<'_>, SSKRError> {
+    if source.len() < FINGERPRINTED_METADATA_SIZE_BYTES {
+        return Err(SSKRError::ShareLengthInvalid { share_index, len: source.len() });
+    }
+
+    let mut fingerprint = [0u8; FINGERPRINT_SIZE_BYTES];
+    fingerprint.copy_from_slice(&source[METADATA_SIZE_BYTES + 1..FINGERPRINTED_METADATA_SIZE_BYTES]);
+
+    Ok(deserialize_standard_share(source, share_index, FINGERPRINTED_METADATA_SIZE_BYTES)?
+        .with_version(ShareVersion::V3)
+        .with_fingerprint(fingerprint))
+}
+
+/// Checks that `share`, at position `share_index` of the caller's input,
+/// belongs to the same split as `expected`, naming the first field on which
+/// they disagree.
+pub(crate) fn check_share_matches(
+    expected: &SSKRShareRef<'_>,
+    share: &SSKRShareRef<'_>,
+    share_index: usize
+) -> Result<(), SSKRError> {
+    if share.version() != expected.version() {
+        return Err(SSKRError::ShareVersionMismatch { expected: expected.version(), found: share.version(), share_index });
+    }
+    if share.identifier() != expected.identifier() {
+        return Err(SSKRError::IdentifierMismatch { expected: expected.identifier(), found: share.identifier(), share_index });
+    }
+    if share.group_threshold() != expected.group_threshold() {
+        return Err(SSKRError::GroupThresholdMismatch { expected: expected.group_threshold(), found: share.group_threshold(), share_index });
+    }
+    if share.group_count() != expected.group_count() {
+        return Err(SSKRError::GroupCountMismatch { expected: expected.group_count(), found: share.group_count(), share_index });
+    }
+    if share.value().len() != expected.value().len() {
+        return Err(SSKRError::ShareLengthMismatch { expected: expected.value().len(), found: share.value().len(), share_index });
+    }
+    Ok(())
+}
 
-    let id1 = id >> 8;
-    let id2 = id & 0xff;
+/// Splits `secret` with the Shamir implementation for `version`. The standard
+/// formats are split by `bc-shamir`, as they always have been, and only the
+/// extended format, which it cannot split, by the crate's own
+/// implementation.
+pub(crate) fn split_secret(
+    version: ShareVersion,
+    threshold: usize,
+    share_count: usize,
+    secret: &[u8],
+    random_generator: &mut impl RandomNumberGenerator
+) -> Result<Vec<Vec<u8>>, SSKRError> {
+    match version {
+        ShareVersion::V0 | ShareVersion::V2 | ShareVersion::V3 => bc_shamir::split_secret(threshold, share_count, secret, random_generator),
+        ShareVersion::V1 => shamir::split_secret(threshold, share_count, secret, random_generator),
+    }.map_err(SSKRError::ShamirError)
+}
 
-    result.push(id1 as u8);
-    result.push(id2 as u8);
-    result.push(((gt << 4) | gc) as u8);
-    result.push(((gi << 4) | mt) as u8);
-    result.push(mi as u8);
-    result.extend_from_slice(share.value().data());
+/// Recovers a secret split by `split_secret` for `version`.
+///
+/// The standard formats are recovered by `bc-shamir`, as they always have
+/// been, and only the extended format, which it cannot recover, by the
+/// crate's own implementation. `bc-shamir` zeroizes the copies of the
+/// shares it interpolates, and every caller wraps the secret returned here
+/// so that it is zeroized when dropped.
+pub(crate) fn recover_secret<T>(version: ShareVersion, indexes: &[usize], shares: &[T]) -> Result<Vec<u8>, bc_shamir::Error>
+where
+    T: AsRef<[u8]>
+{
+    match version {
+        ShareVersion::V0 | ShareVersion::V2 | ShareVersion::V3 => bc_shamir::recover_secret(indexes, shares),
+        ShareVersion::V1 => shamir::recover_secret(indexes, shares),
+    }
+}
 
-    result
+/// Draws a random identifier that fits the identifier field of `version`.
+pub(crate) fn random_identifier(version: ShareVersion, random_generator: &mut impl RandomNumberGenerator) -> u32 {
+    let mut identifier = [0u8; 4];
+    let identifier_size = version.identifier_size_bytes();
+    random_generator.fill_random_data(&mut identifier[4 - identifier_size..]);
+    u32::from_be_bytes(identifier)
 }
 
-fn deserialize_share(source: &[u8]) -> Result<SSKRShare, SSKRError> {
-    if source.len() < METADATA_SIZE_BYTES {
-        return Err(SSKRError::ShareLengthInvalid);
-    }
+fn generate_shares(
+    spec: &Spec,
+    master_secret: &Secret,
+    version: ShareVersion,
+    random_generator: &mut impl RandomNumberGenerator
+) -> Result<Vec<Vec<SSKRShare>>, SSKRError> {
+    spec.validate_for_version(version)?;
+    let identifier = random_identifier(version, random_generator);
+    generate_shares_with_identifier(spec, master_secret, version, identifier, random_generator)
+}
 
-    let group_threshold = ((source[2] >> 4) + 1) as usize;
-    let group_count = ((source[2] & 0xf) + 1) as usize;
+/// Generates the shares of a split with the given identifier, which must fit
+/// the identifier field of `version`.
+pub(crate) fn generate_shares_with_identifier(
+    spec: &Spec,
+    master_secret: &Secret,
+    version: ShareVersion,
+    identifier: u32,
+    random_generator: &mut impl RandomNumberGenerator
+) -> Result<Vec<Vec<SSKRShare>>, SSKRError> {
+    spec.validate_for_version(version)?;
 
-    if group_threshold > group_count {
-        return Err(SSKRError::GroupThresholdInvalid);
-    }
+    let group_secrets = split_secret(version, spec.group_threshold(), spec.group_count(), master_secret.data(), random_generator)?;
+    let fingerprint = embedded_fingerprint(version, master_secret);
 
-    let identifier = ((source[0] as u16) << 8) | source[1] as u16;
-    let group_index = (source[3] >> 4) as usize;
-    let member_threshold = ((source[3] & 0xf) + 1) as usize;
-    let reserved = source[4] >> 4;
-    if reserved != 0 {
-        return Err(SSKRError::ShareReservedBitsInvalid);
+    let mut member_values: GroupedShareBytes = spec.groups().iter()
+        .map(|group| (0..group.member_count()).map(|_| Vec::with_capacity(master_secret.len())).collect())
+        .collect();
+    split_group_members(spec, version, &group_secrets, &mut member_values, random_generator)?;
+
+    let groups_shares = spec.groups().iter().zip(member_values).enumerate().map(|(group_index, (group, values))| {
+        values.into_iter().enumerate().map(|(member_index, value)| {
+            SSKRShare::new(
+                identifier,
+                group_index,
+                spec.group_threshold(),
+                spec.group_count(),
+                member_index,
+                group.member_threshold(),
+                ShareValue::new(value),
+            ).with_version(version).with_fingerprint(fingerprint)
+        }).collect()
+    }).collect();
+
+    Ok(groups_shares)
+}
+
+/// Returns the fingerprint of `master_secret` that shares of `version` carry,
+/// which is zero for the formats that carry none.
+fn embedded_fingerprint(version: ShareVersion, master_secret: &Secret) -> [u8; FINGERPRINT_SIZE_BYTES] {
+    match version {
+        ShareVersion::V3 => FingerprintAlg::Sha256Trunc4.fingerprint(master_secret.data()),
+        _ => [0; FINGERPRINT_SIZE_BYTES],
     }
-    let member_index = (source[4] & 0xf) as usize;
-    let value = Secret::new(&source[METADATA_SIZE_BYTES..])?;
+}
 
-    Ok(SSKRShare::new(
-        identifier,
-        group_index,
-        group_threshold,
-        group_count,
-        member_index,
-        member_threshold,
-        value,
-    ))
+/// Generates the serialized shares of a split with a random identifier,
+/// returning the identifier with them.
+pub(crate) fn generate_serialized(
+    spec: &Spec,
+    master_secret: &Secret,
+    version: ShareVersion,
+    app_nibble: u8,
+    random_generator: &mut impl RandomNumberGenerator
+) -> Result<(u32, GroupedShareBytes), SSKRError> {
+    spec.validate_for_version(version)?;
+    let identifier = random_identifier(version, random_generator);
+    let shares = generate_serialized_with_identifier(spec, master_secret, version, identifier, app_nibble, random_generator)?;
+    Ok((identifier, shares))
 }
 
-fn generate_shares(
+/// Generates the serialized shares of a split with the given identifier,
+/// failing with `SSKRError::IdentifierInvalid` if it does not fit the
+/// identifier field of `version`.
+///
+/// The result is what serializing the shares of
+/// `generate_shares_with_identifier` gives, but each share is built in a
+/// single buffer of its final size: its header is written first and its
+/// value is appended by the Shamir split.
+pub(crate) fn generate_serialized_with_identifier(
     spec: &Spec,
     master_secret: &Secret,
+    version: ShareVersion,
+    identifier: u32,
+    app_nibble: u8,
     random_generator: &mut impl RandomNumberGenerator
-) -> Result<Vec<Vec<SSKRShare>>, SSKRError> {
-    // assign a random identifier
-    let mut identifier = [0u8; 2];
-    random_generator.fill_random_data(&mut identifier);
-    let identifier: u16 = ((identifier[0] as u16) << 8) | identifier[1] as u16;
-
-    let mut groups_shares: Vec<Vec<SSKRShare>> = Vec::with_capacity(spec.group_count());
-
-    let group_secrets = split_secret(spec.group_threshold(), spec.group_count(), master_secret.data(), random_generator).map_err(SSKRError::ShamirError)?;
-
-    for (group_index, group) in spec.groups().iter().enumerate() {
-        let group_secret = &group_secrets[group_index];
-        let member_secrets = split_secret(group.member_threshold(), group.member_count(), group_secret, random_generator)
-            .map_err(SSKRError::ShamirError)?
-            .into_iter().map(Secret::new)
-            .collect::<Result<Vec<Secret>, _>>()?;
-        let member_sskr_shares: Vec<SSKRShare> = member_secrets.into_iter().enumerate().map(|(member_index, member_secret)| {
-            SSKRShare::new(
+) -> Result<GroupedShareBytes, SSKRError> {
+    spec.validate_for_version(version)?;
+    version.check_identifier(identifier)?;
+
+    let group_secrets = split_secret(version, spec.group_threshold(), spec.group_count(), master_secret.data(), random_generator)?;
+    let fingerprint = embedded_fingerprint(version, master_secret);
+
+    let share_len = version.metadata_size_bytes() + master_secret.len();
+    let mut shares: GroupedShareBytes = spec.groups().iter().enumerate().map(|(group_index, group)| {
+        (0..group.member_count()).map(|member_index| {
+            // A share with no value serializes to just its header.
+            let header = SSKRShare::new(
                 identifier,
                 group_index,
                 spec.group_threshold(),
                 spec.group_count(),
                 member_index,
                 group.member_threshold(),
-                member_secret,
-            )
-        }).collect();
-        groups_shares.push(member_sskr_shares);
+                ShareValue::new(Vec::new()),
+            ).with_version(version).with_app_nibble(app_nibble).with_fingerprint(fingerprint);
+            let mut share = Vec::with_capacity(share_len);
+            serialize_share_into(&header, &mut share);
+            share
+        }).collect()
+    }).collect();
+    split_group_members(spec, version, &group_secrets, &mut shares, random_generator)?;
+
+    Ok(shares)
+}
+
+/// Splits the secret of each group of `spec` into its members' shares,
+/// appending the value of each member's share to its buffer in `outputs`.
+///
+/// A seed is drawn from `random_generator` for each group in turn, and each
+/// group's members are split by `split_members_into` with a generator seeded
+/// with it. With the `rayon` feature the groups are then split in parallel,
+/// giving the same shares as splitting them in turn.
+#[cfg(not(feature = "rayon"))]
+fn split_group_members(
+    spec: &Spec,
+    version: ShareVersion,
+    group_secrets: &[Vec<u8>],
+    outputs: &mut [Vec<Vec<u8>>],
+    random_generator: &mut impl RandomNumberGenerator
+) -> Result<(), SSKRError> {
+    split_group_members_serial(spec, version, group_secrets, outputs, random_generator)
+}
+
+#[cfg(feature = "rayon")]
+fn split_group_members(
+    spec: &Spec,
+    version: ShareVersion,
+    group_secrets: &[Vec<u8>],
+    outputs: &mut [Vec<Vec<u8>>],
+    random_generator: &mut impl RandomNumberGenerator
+) -> Result<(), SSKRError> {
+    split_group_members_parallel(spec, version, group_secrets, outputs, random_generator)
+}
+
+#[cfg(any(not(feature = "rayon"), test))]
+fn split_group_members_serial(
+    spec: &Spec,
+    version: ShareVersion,
+    group_secrets: &[Vec<u8>],
+    outputs: &mut [Vec<Vec<u8>>],
+    random_generator: &mut impl RandomNumberGenerator
+) -> Result<(), SSKRError> {
+    for ((group, group_secret), members) in spec.groups().iter().zip(group_secrets).zip(outputs) {
+        let mut group_rng = SeededRandomNumberGenerator::from_rng(random_generator);
+        split_members_into(version, group.member_threshold(), group_secret, members, &mut group_rng)?;
     }
+    Ok(())
+}
 
-    Ok(groups_shares)
+#[cfg(feature = "rayon")]
+fn split_group_members_parallel(
+    spec: &Spec,
+    version: ShareVersion,
+    group_secrets: &[Vec<u8>],
+    outputs: &mut [Vec<Vec<u8>>],
+    random_generator: &mut impl RandomNumberGenerator
+) -> Result<(), SSKRError> {
+    use rayon::prelude::*;
+
+    let group_rngs: Vec<_> = spec.groups().iter()
+        .map(|_| SeededRandomNumberGenerator::from_rng(random_generator))
+        .collect();
+    outputs.par_iter_mut().zip(spec.groups()).zip(group_secrets).zip(group_rngs)
+        .try_for_each(|(((members, group), group_secret), mut group_rng)| {
+            split_members_into(version, group.member_threshold(), group_secret, members, &mut group_rng)
+        })
+}
+
+/// Splits a group secret into as many member shares as there are `outputs`,
+/// appending share `i` to `outputs[i]`, with the Shamir implementation for
+/// `version`. The extended format is split in place; the shares of the
+/// standard formats are split by `bc-shamir` and copied, and its output is
+/// zeroized once they are.
+fn split_members_into(
+    version: ShareVersion,
+    threshold: usize,
+    group_secret: &[u8],
+    outputs: &mut [Vec<u8>],
+    random_generator: &mut impl RandomNumberGenerator
+) -> Result<(), SSKRError> {
+    if version == ShareVersion::V1 {
+        return shamir::split_secret_into(threshold, group_secret, &[], outputs, random_generator).map_err(SSKRError::ShamirError);
+    }
+    let values = Zeroizing::new(split_secret(version, threshold, outputs.len(), group_secret, random_generator)?);
+    for (output, value) in outputs.iter_mut().zip(values.iter()) {
+        output.extend_from_slice(value);
+    }
+    Ok(())
 }
 
 #[derive(Debug)]
-struct Group {
-    group_index: usize,
-    member_threshold: usize,
-    member_indexes: Vec<usize>,
-    member_shares: Vec<Secret>,
+pub(crate) struct Group<'a> {
+    pub(crate) group_index: usize,
+    pub(crate) member_threshold: usize,
+    pub(crate) member_indexes: Vec<usize>,
+    pub(crate) member_shares: Vec<&'a [u8]>,
+    /// The members in `member_indexes`, as a bitmap over every index a share
+    /// can hold.
+    members: [u64; 4],
 }
 
-impl Group {
+impl<'a> Group<'a> {
     fn new(group_index: usize, member_threshold: usize) -> Self {
         Self {
             group_index,
             member_threshold,
-            member_indexes: Vec::with_capacity(16),
-            member_shares: Vec::with_capacity(16),
+            member_indexes: Vec::with_capacity(member_threshold),
+            member_shares: Vec::with_capacity(member_threshold),
+            members: [0; 4],
         }
     }
+
+    fn has_member(&self, member_index: usize) -> bool {
+        self.members[member_index / 64] & (1 << (member_index % 64)) != 0
+    }
+
+    fn push_member(&mut self, member_index: usize, share: &'a [u8]) {
+        self.members[member_index / 64] |= 1 << (member_index % 64);
+        self.member_indexes.push(member_index);
+        self.member_shares.push(share);
+    }
 }
 
-fn combine_shares(shares: &[SSKRShare]) -> Result<Secret, SSKRError> {
-    let mut identifier = 0;
-    let mut group_threshold = 0;
-    let mut group_count = 0;
+/// The shares passed to a combine operation, validated for consistent
+/// metadata and sorted into their member groups.
+pub(crate) struct GroupedShares<'a> {
+    pub(crate) identifier: u32,
+    pub(crate) version: ShareVersion,
+    pub(crate) group_threshold: usize,
+    pub(crate) group_count: usize,
+    pub(crate) groups: Vec<Group<'a>>,
+}
 
-    if shares.is_empty() {
+pub(crate) fn group_shares<'a>(shares: &[SSKRShareRef<'a>]) -> Result<GroupedShares<'a>, SSKRError> {
+    // The first share establishes the expected values for common metadata.
+    let Some(first) = shares.first() else {
         return Err(SSKRError::SharesEmpty);
-    }
+    };
 
-    let mut next_group = 0;
-    let mut groups: Vec<Group> = Vec::with_capacity(16);
-    let mut secret_len = 0;
+    // The groups in the order they first appear, which is the order they are
+    // recovered in, and the position of each in `groups` by group index.
+    let group_count = first.group_count();
+    let mut groups: Vec<Group<'a>> = Vec::with_capacity(group_count);
+    let mut positions: HashMap<usize, usize> = HashMap::with_capacity(group_count);
 
     for (i, share) in shares.iter().enumerate() {
-        if i == 0 {
-            // on the first one, establish expected values for common metadata
-            identifier = share.identifier();
-            group_count = share.group_count();
-            group_threshold = share.group_threshold();
-            secret_len = share.value().len();
-        } else {
-            // on subsequent shares, check that common metadata matches
-            if share.identifier() != identifier ||
-                share.group_threshold() != group_threshold ||
-                share.group_count() != group_count ||
-                share.value().len() != secret_len
-            {
-                return Err(SSKRError::ShareSetInvalid);
-            }
+        // Check that common metadata matches
+        check_share_matches(first, share, i)?;
+        if share.app_nibble() != first.app_nibble() {
+            return Err(SSKRError::AppNibbleMismatch { expected: first.app_nibble(), found: share.app_nibble(), share_index: i });
         }
 
         // sort shares into member groups
-        let mut group_found = false;
-        for group in groups.iter_mut() {
-            if share.group_index() == group.group_index {
-                group_found = true;
-                if share.member_threshold() != group.member_threshold {
-                    return Err(SSKRError::MemberThresholdInvalid);
-                }
-                for k in 0..group.member_indexes.len() {
-                    if share.member_index() == group.member_indexes[k] {
-                        return Err(SSKRError::DuplicateMemberIndex);
-                    }
-                }
-                if group.member_indexes.len() < group.member_threshold {
-                    group.member_indexes.push(share.member_index());
-                    group.member_shares.push(share.value().clone());
-                }
-            }
+        let position = *positions.entry(share.group_index()).or_insert_with(|| {
+            groups.push(Group::new(share.group_index(), share.member_threshold()));
+            groups.len() - 1
+        });
+        let group = &mut groups[position];
+        if share.member_threshold() != group.member_threshold {
+            return Err(SSKRError::MemberThresholdMismatch {
+                group_index: group.group_index,
+                expected: group.member_threshold,
+                found: share.member_threshold(),
+                share_index: i,
+            });
         }
-
-        if !group_found {
-            let mut g = Group::new(share.group_index(), share.member_threshold());
-            g.member_indexes.push(share.member_index());
-            g.member_shares.push(share.value().clone());
-            groups.push(g);
-            next_group += 1;
+        if !group.has_member(share.member_index()) {
+            group.push_member(share.member_index(), share.value());
+            continue;
+        }
+        // A share pasted twice is harmless, but two different values for one
+        // member mean a share is corrupt or from another split
+        let k = group.member_indexes.iter().position(|&index| index == share.member_index()).unwrap();
+        if group.member_shares[k] != share.value() {
+            return Err(SSKRError::ConflictingDuplicateShare {
+                group_index: group.group_index,
+                member_index: share.member_index(),
+            });
         }
     }
 
-    // Check that we have enough groups to recover the master secret
-    if next_group < group_threshold {
-        return Err(SSKRError::NotEnoughGroups);
-    }
+    Ok(GroupedShares {
+        identifier: first.identifier(),
+        version: first.version(),
+        group_threshold: first.group_threshold(),
+        group_count,
+        groups,
+    })
+}
 
-    // Here, all of the shares are unpacked into member groups. Now we go through each
-    // group and recover the group secret, and then use the result to recover the
-    // master secret
-    let mut master_indexes = Vec::with_capacity(16);
-    let mut master_shares = Vec::with_capacity(16);
+/// Recovers the group secret of every group that holds enough member shares,
+/// stopping once `limit` group secrets have been recovered. Groups that cannot
+/// be decoded are skipped, and the first of them is returned as
+/// `SSKRError::GroupRecoveryFailed`.
+///
+/// The group secrets are wrapped as soon as they are recovered, so that they
+/// are zeroized however the caller returns.
+pub(crate) fn recover_group_secrets(
+    version: ShareVersion,
+    groups: Vec<Group<'_>>,
+    limit: usize
+) -> (Vec<(usize, GroupSecret)>, Option<SSKRError>) {
+    let mut group_secrets = Vec::with_capacity(groups.len().min(limit));
+    let mut failure = None;
 
     for group in groups {
         // Only attempt to recover the group secret if we have enough shares
         if group.member_indexes.len() < group.member_threshold {
             continue;
         }
-        // Recover the group secret
-        if let Ok(group_secret) = recover_secret(&group.member_indexes, &group.member_shares) {
-            master_indexes.push(group.group_index);
-            master_shares.push(group_secret);
+        // Recover the group secret from the first shares that meet the threshold
+        let threshold = group.member_threshold;
+        match recover_secret(version, &group.member_indexes[..threshold], &group.member_shares[..threshold]) {
+            Ok(group_secret) => group_secrets.push((group.group_index, GroupSecret::new(group_secret))),
+            Err(source) => {
+                failure.get_or_insert(SSKRError::GroupRecoveryFailed { group_index: group.group_index, source });
+            }
         }
         // Stop if we have enough groups to recover the master secret
-        if master_indexes.len() == group_threshold {
+        if group_secrets.len() == limit {
             break;
         }
     }
 
-    // If we don't have enough groups to recover the master secret, return an error
-    if master_indexes.len() < group_threshold {
+    (group_secrets, failure)
+}
+
+/// Checks that every member share of `group` beyond its threshold agrees with
+/// the group secret recovered from the first threshold shares, by recovering
+/// the group secret again with each extra share in place of the last of them.
+///
+/// A group that is short of its threshold, or whose first threshold shares
+/// cannot be decoded, is left for the recovery itself to report.
+fn verify_group(version: ShareVersion, group: &Group<'_>) -> Result<(), SSKRError> {
+    let threshold = group.member_threshold;
+    if group.member_indexes.len() <= threshold {
+        return Ok(());
+    }
+    let mut member_indexes = group.member_indexes[..threshold].to_vec();
+    let mut member_shares = group.member_shares[..threshold].to_vec();
+    let Ok(group_secret) = recover_secret(version, &member_indexes, &member_shares).map(GroupSecret::new) else {
+        return Ok(());
+    };
+
+    for (&member_index, &member_share) in group.member_indexes[threshold..].iter().zip(&group.member_shares[threshold..]) {
+        member_indexes[threshold - 1] = member_index;
+        member_shares[threshold - 1] = member_share;
+        let consistent = recover_secret(version, &member_indexes, &member_shares)
+            .map(GroupSecret::new)
+            .is_ok_and(|secret| secret.data() == group_secret.data());
+        if !consistent {
+            return Err(SSKRError::InconsistentShare { group_index: group.group_index, member_index });
+        }
+    }
+    Ok(())
+}
+
+/// Returns the number of groups that hold enough member shares to be
+/// recovered.
+fn complete_group_count(groups: &[Group<'_>]) -> usize {
+    groups.iter().filter(|group| group.member_indexes.len() >= group.member_threshold).count()
+}
+
+/// Returns `SSKRError::NotEnoughMemberShares` for the group that is closest
+/// to its member threshold without reaching it, or `None` if every group
+/// reached its threshold.
+fn not_enough_member_shares(groups: &[Group<'_>]) -> Option<SSKRError> {
+    groups
+        .iter()
+        .filter(|group| group.member_indexes.len() < group.member_threshold)
+        .min_by_key(|group| group.member_threshold - group.member_indexes.len())
+        .map(|group| SSKRError::NotEnoughMemberShares {
+            group_index: group.group_index,
+            have: group.member_indexes.len(),
+            need: group.member_threshold,
+        })
+}
+
+/// Recovers the master secret from exactly `group_threshold` group secrets,
+/// out of `complete_groups` groups that held enough member shares.
+fn recover_master_secret<T>(
+    version: ShareVersion,
+    group_secrets: &[(usize, T)],
+    complete_groups: usize
+) -> Result<Secret, SSKRError>
+where
+    T: AsRef<[u8]>
+{
+    let (master_indexes, master_shares): (Vec<usize>, Vec<&[u8]>) = group_secrets
+        .iter()
+        .map(|(index, secret)| (*index, secret.as_ref()))
+        .unzip();
+    let master_secret = recover_secret(version, &master_indexes, &master_shares)
+        .map_err(|source| SSKRError::MasterRecoveryFailed {
+            unused_complete_groups: complete_groups.saturating_sub(group_secrets.len()),
+            source,
+        })?;
+    Secret::from_vec(master_secret)
+}
+
+fn combine_shares(shares: &[SSKRShareRef<'_>]) -> Result<RecoveredSecret, SSKRError> {
+    let grouped = group_shares(shares)?;
+    let group_threshold = grouped.group_threshold;
+
+    // Check that we have enough groups to recover the master secret
+    if grouped.groups.len() < group_threshold {
         return Err(SSKRError::NotEnoughGroups);
     }
 
+    // Here, all of the shares are unpacked into member groups. Now we go through each
+    // group and recover the group secret, and then use the result to recover the
+    // master secret
+    let shortfall = not_enough_member_shares(&grouped.groups);
+    let complete_groups = complete_group_count(&grouped.groups);
+    let member_thresholds: Vec<(usize, usize)> = grouped.groups
+        .iter()
+        .map(|group| (group.group_index, group.member_threshold))
+        .collect();
+    let (group_secrets, failure) = recover_group_secrets(grouped.version, grouped.groups, group_threshold);
+
+    // If we don't have enough groups to recover the master secret, return an
+    // error, blaming a corrupt group before a short one
+    if group_secrets.len() < group_threshold {
+        return Err(failure.or(shortfall).unwrap_or(SSKRError::NotEnoughGroups));
+    }
+
     // Recover the master secret
-    let master_secret = recover_secret(&master_indexes, &master_shares)?;
-    let master_secret = Secret::new(master_secret)?;
+    let secret = recover_master_secret(grouped.version, &group_secrets, complete_groups)?;
+    let secret = check_embedded_fingerprints(shares, secret)?;
+
+    let groups_used: Vec<usize> = group_secrets.iter().map(|(group_index, _)| *group_index).collect();
+    let shares_used = member_thresholds
+        .iter()
+        .filter(|(group_index, _)| groups_used.contains(group_index))
+        .map(|(_, member_threshold)| member_threshold)
+        .sum();
+    let info = RecoveryInfo::new(
+        grouped.identifier,
+        grouped.version,
+        group_threshold,
+        grouped.group_count,
+        groups_used,
+        shares_used,
+    );
+    Ok(RecoveredSecret::new(secret, info))
+}
+
+/// Checks the fingerprint that each of `shares` carries, if their format
+/// carries one, against the master secret recovered from them. The secret is
+/// zeroized rather than returned if any of them differs.
+fn check_embedded_fingerprints(shares: &[SSKRShareRef<'_>], secret: Secret) -> Result<Secret, SSKRError> {
+    if shares.first().map(SSKRShareRef::version) != Some(ShareVersion::V3) {
+        return Ok(secret);
+    }
+    let expected = FingerprintAlg::Sha256Trunc4.fingerprint(secret.data());
+    if !shares.iter().all(|share| fingerprints_match(&share.fingerprint(), &expected)) {
+        drop(secret.into_zeroizing());
+        return Err(SSKRError::FingerprintMismatch);
+    }
+    Ok(secret)
+}
+
+/// Combines the given SSKR shares, releasing the recovered group secrets if
+/// the master secret cannot be reconstructed and the policy allows it.
+fn combine_shares_partial(shares: &[SSKRShareRef<'_>], policy: PartialPolicy) -> PartialRecovery {
+    let grouped = match group_shares(shares) {
+        Ok(grouped) => grouped,
+        Err(error) => return PartialRecovery::Incomplete { group_secrets: Vec::new(), error },
+    };
+    let group_threshold = grouped.group_threshold;
+    let shortfall = not_enough_member_shares(&grouped.groups);
+    let complete_groups = complete_group_count(&grouped.groups);
+
+    // Recover every group we can, so that an incomplete recovery reports all
+    // of the intact groups rather than just the first few.
+    let (group_secrets, failure) = recover_group_secrets(grouped.version, grouped.groups, usize::MAX);
+
+    let error = if group_secrets.len() < group_threshold {
+        failure.or(shortfall).unwrap_or(SSKRError::NotEnoughGroups)
+    } else {
+        match recover_master_secret(grouped.version, &group_secrets[..group_threshold], complete_groups)
+            .and_then(|secret| check_embedded_fingerprints(shares, secret))
+        {
+            Ok(secret) => return PartialRecovery::Complete(secret),
+            Err(error) => error,
+        }
+    };
+
+    let group_secrets = match policy {
+        PartialPolicy::WithholdGroupSecrets => Vec::new(),
+        PartialPolicy::AllowGroupSecrets => group_secrets,
+    };
+
+    PartialRecovery::Incomplete { group_secrets, error }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::GroupSpec;
+    use std::collections::HashSet;
+
+    fn to_owned_share(share: &SSKRShareRef<'_>) -> SSKRShare {
+        SSKRShare::new(
+            share.identifier(),
+            share.group_index(),
+            share.group_threshold(),
+            share.group_count(),
+            share.member_index(),
+            share.member_threshold(),
+            ShareValue::new(share.value().to_vec()),
+        )
+    }
+
+    fn generate_test_shares() -> Vec<Vec<SSKRShare>> {
+        let mut rng = bc_rand::make_fake_random_number_generator();
+        let secret = Secret::new(rng.random_data(32)).unwrap();
+        let spec = Spec::new(2, vec![GroupSpec::new(2, 3).unwrap(), GroupSpec::new(3, 5).unwrap()]).unwrap();
+        generate_shares(&spec, &secret, ShareVersion::V0, &mut rng).unwrap()
+    }
+
+    #[test]
+    fn test_combine_shares() {
+        let shares = generate_test_shares();
+        let bytes: Vec<Vec<Vec<u8>>> = shares
+            .iter()
+            .map(|group| group.iter().map(serialize_share).collect())
+            .collect();
+        let secret = sskr_combine(&[&bytes[0][2], &bytes[0][0], &bytes[1][4], &bytes[1][1], &bytes[1][0]]).unwrap();
+        let input = [&shares[0][2], &shares[0][0], &shares[1][4], &shares[1][1], &shares[1][0]];
+        assert_eq!(sskr_combine_shares(&input.map(SSKRShare::clone)).unwrap(), secret);
+
+        // Bad sets fail with the same errors either way.
+        let share = |identifier, group_index, member_index, value: Vec<u8>| {
+            SSKRShare::new(identifier, group_index, 2, 2, member_index, 3, ShareValue::new(value))
+        };
+        let identifier = shares[0][0].identifier();
+        let other = share(identifier ^ 1, 1, 2, shares[1][2].value().data().to_vec());
+        let conflicting = share(identifier, 1, 4, vec![0; 32]);
+        let bad_sets = [
+            vec![],
+            vec![shares[0][2].clone(), shares[0][0].clone(), shares[1][4].clone()],
+            vec![shares[0][2].clone(), other],
+            vec![shares[1][4].clone(), shares[0][0].clone(), conflicting],
+            vec![shares[0][1].clone(), shares[0][1].clone(), shares[1][1].clone()],
+        ];
+        for set in bad_sets {
+            let set_bytes: Vec<Vec<u8>> = set.iter().map(serialize_share).collect();
+            let expected = sskr_combine(&set_bytes).unwrap_err();
+            let error = sskr_combine_shares(&set).unwrap_err();
+            assert_eq!((error.code(), error.to_string()), (expected.code(), expected.to_string()));
+        }
+
+        // Fields that no serialized share could hold are refused.
+        assert!(matches!(
+            sskr_combine_shares(&[share(identifier, 2, 0, vec![0; 32])]),
+            Err(SSKRError::GroupIndexInvalid { share_index: Some(0), group_index: 2, group_count: 2 })
+        ));
+        assert!(matches!(
+            sskr_combine_shares(&[share(identifier, 1, 16, vec![0; 32])]),
+            Err(SSKRError::ShareMemberIndexInvalid { share_index: Some(0), member_index: 16 })
+        ));
+        assert!(matches!(
+            sskr_combine_shares(&[share(identifier, 1, 0, vec![0; 15])]),
+            Err(SSKRError::SharePayloadTooShort { share_index: Some(0), len: 15 })
+        ));
+    }
+
+    #[test]
+    fn test_share_equality() {
+        let shares = generate_test_shares();
+
+        // Shares from the same generation differ in member index and value.
+        assert_ne!(shares[0][0], shares[0][1]);
+        assert_ne!(shares[1][2], shares[1][3]);
+        assert_eq!(shares[0][0], shares[0][0].clone());
+
+        // Changing only the member index makes the shares unequal.
+        let share = &shares[0][0];
+        let relabeled = SSKRShare::new(
+            share.identifier(),
+            share.group_index(),
+            share.group_threshold(),
+            share.group_count(),
+            share.member_index() + 1,
+            share.member_threshold(),
+            share.value().clone(),
+        );
+        assert_ne!(share, &relabeled);
+    }
+
+    #[test]
+    fn test_share_equality_round_trip() {
+        let shares = generate_test_shares();
+        for share in shares.iter().flatten() {
+            let bytes = serialize_share(share);
+            let decoded = to_owned_share(&deserialize_share(&bytes, 0).unwrap());
+            assert_eq!(&decoded, share);
+            assert_eq!(serialize_share(&decoded), bytes);
+        }
+    }
+
+    #[test]
+    fn test_share_formatting_redacts_value() {
+        let shares = generate_test_shares();
+        let share = &shares[1][4];
+        let display = share.to_string();
+        assert_eq!(
+            display,
+            format!("SSKRShare(id: {:04x}, group 2/2 threshold 2, member 5 threshold 3, 32-byte value)", share.identifier())
+        );
+
+        for share in shares.iter().flatten() {
+            let value_hex = hex::encode(share.value().data());
+            let bytes = serialize_share(share);
+            let share_ref = deserialize_share(&bytes, 0).unwrap();
+            for formatted in [
+                share.to_string(),
+                format!("{:?}", share),
+                format!("{:#?}", share),
+                share_ref.to_string(),
+                format!("{:?}", share_ref),
+            ] {
+                assert!(!formatted.contains(&value_hex));
+                assert!(!formatted.contains(&value_hex[..8]));
+            }
+            assert!(format!("{:?}", share).contains("<32 bytes redacted>"));
+        }
+    }
+
+    #[test]
+    fn test_share_hash_dedup() {
+        let shares = generate_test_shares();
+        let mut pile: Vec<SSKRShare> = shares.iter().flatten().cloned().collect();
+        pile.extend(shares[1].iter().cloned());
+        pile.push(shares[0][2].clone());
+        let unique: HashSet<SSKRShare> = pile.into_iter().collect();
+        assert_eq!(unique.len(), 8);
+    }
+
+    #[test]
+    fn test_deserialize_share_borrows_value() {
+        let mut rng = bc_rand::make_fake_random_number_generator();
+        let secret = Secret::new(rng.random_data(32)).unwrap();
+        let spec = Spec::new(1, vec![GroupSpec::new(2, 3).unwrap()]).unwrap();
+        let shares = sskr_generate_using(&spec, &secret, &mut rng).unwrap();
+
+        let bytes = &shares[0][1];
+        let share = deserialize_share(bytes, 0).unwrap();
+        assert_eq!(share.member_index(), 1);
+        assert_eq!(share.member_threshold(), 2);
+        // The value is a view into the caller's buffer, not a copy.
+        assert_eq!(share.value(), &bytes[METADATA_SIZE_BYTES..]);
+        assert!(std::ptr::eq(share.value().as_ptr(), bytes[METADATA_SIZE_BYTES..].as_ptr()));
+    }
+
+    /// Generates shares for `spec` and checks that every share's metadata
+    /// survives serialization exactly.
+    fn assert_metadata_round_trips(spec: &Spec, rng: &mut impl RandomNumberGenerator) {
+        let secret = Secret::new(rng.random_data(16)).unwrap();
+        let shares = sskr_generate_using(spec, &secret, rng).unwrap();
+        assert_eq!(shares.len(), spec.group_count());
+        let identifier = deserialize_share(&shares[0][0], 0).unwrap().identifier();
+        for (group_index, (group, group_shares)) in spec.groups().iter().zip(&shares).enumerate() {
+            assert_eq!(group_shares.len(), group.member_count());
+            for (member_index, bytes) in group_shares.iter().enumerate() {
+                let share = deserialize_share(bytes, 0).unwrap();
+                assert_eq!(share.identifier(), identifier);
+                assert_eq!(share.group_index(), group_index);
+                assert_eq!(share.group_threshold(), spec.group_threshold());
+                assert_eq!(share.group_count(), spec.group_count());
+                assert_eq!(share.member_index(), member_index);
+                assert_eq!(share.member_threshold(), group.member_threshold());
+                assert_eq!(share.value().len(), secret.len());
+            }
+        }
+    }
+
+    #[test]
+    fn test_metadata_round_trips_for_every_group_spec() {
+        let mut rng = bc_rand::make_fake_random_number_generator();
+        for member_count in 1..=crate::MAX_SHARE_COUNT {
+            for member_threshold in 1..=member_count {
+                let group = GroupSpec::new(member_threshold, member_count).unwrap();
+                let spec = Spec::new(1, vec![group]).unwrap();
+                assert_metadata_round_trips(&spec, &mut rng);
+            }
+        }
+    }
+
+    #[test]
+    fn test_metadata_round_trips_for_every_group_threshold() {
+        let mut rng = bc_rand::make_fake_random_number_generator();
+        // Cycle through the boundary group shapes so that every group index
+        // is exercised with thresholds and counts of both 1 and 16.
+        let shapes = [(1, 1), (16, 16), (1, 16), (2, 3), (15, 16)];
+        for group_count in 1..=crate::MAX_GROUPS_COUNT {
+            let groups: Vec<GroupSpec> = (0..group_count)
+                .map(|i| {
+                    let (t, n) = shapes[i % shapes.len()];
+                    GroupSpec::new(t, n).unwrap()
+                })
+                .collect();
+            for group_threshold in 1..=group_count {
+                let spec = Spec::new(group_threshold, groups.clone()).unwrap();
+                assert_metadata_round_trips(&spec, &mut rng);
+            }
+        }
+    }
+
+    /// Serializes a standard-format share behind the escape with `version`
+    /// as its format version, so as to produce shares in formats this crate
+    /// doesn't understand.
+    fn serialize_share_with_version(share: &SSKRShare, version: u8) -> Vec<u8> {
+        let mut bytes = serialize_share(share);
+        bytes[4] |= SHARE_VERSION_ESCAPE << 4;
+        bytes.insert(METADATA_SIZE_BYTES, version);
+        bytes
+    }
+
+    #[test]
+    fn test_unsupported_share_version() {
+        let shares = generate_test_shares();
+        let share = &shares[0][0];
 
-    Ok(master_secret)
+        for version in [0, 4, 0x10, 0xff] {
+            let bytes = serialize_share_with_version(share, version);
+            assert!(matches!(deserialize_share(&bytes, 0), Err(SSKRError::UnsupportedShareVersion { share_index: Some(0), version: v }) if v == version));
+
+            // The version is checked before the layout it governs, so a
+            // future share is never reported as corrupt.
+            let mut future = bytes.clone();
+            future[2] = 0xf0;
+            future.truncate(METADATA_SIZE_BYTES + 1);
+            assert!(matches!(deserialize_share(&future, 0), Err(SSKRError::UnsupportedShareVersion { share_index: Some(0), version: v }) if v == version));
+        }
+        let mut escaped = serialize_share_with_version(share, 0);
+        escaped.truncate(METADATA_SIZE_BYTES);
+        assert!(matches!(deserialize_share(&escaped, 0), Err(SSKRError::ShareLengthInvalid { share_index: Some(0), len: 5 })));
+
+        // No other value of the reserved bits marks a format: they are
+        // application data, or mark a protected share.
+        for nibble in 2..=0xf {
+            let mut bytes = serialize_share(share);
+            bytes[4] |= nibble << 4;
+            let error = deserialize_share(&bytes, 0).unwrap_err();
+            if nibble <= 13 {
+                assert!(matches!(error, SSKRError::AppNibblePresent { share_index: Some(0), app_nibble } if app_nibble == nibble));
+            } else {
+                assert!(matches!(error, SSKRError::ShareReservedBitsInvalid { share_index: Some(0) }));
+            }
+        }
+    }
+
+    #[test]
+    fn test_validation_rejects_unencodable_specs() {
+        // Every value the validated API accepts must fit the 4-bit fields.
+        assert!(GroupSpec::new(0, 3).is_err());
+        assert!(GroupSpec::new(1, 0).is_err());
+        assert!(GroupSpec::new(1, 17).is_err());
+        assert!(GroupSpec::new(17, 17).is_err());
+        assert!(GroupSpec::new(4, 3).is_err());
+        assert!(GroupSpec::parse("0-of-3").is_err());
+        assert!(GroupSpec::parse("1-of-17").is_err());
+        assert_eq!(GroupSpec::default(), GroupSpec::new(1, 1).unwrap());
+
+        let group = GroupSpec::default();
+        assert!(Spec::new(0, vec![group.clone()]).is_err());
+        assert!(Spec::new(1, vec![]).is_err());
+        assert!(Spec::new(2, vec![group.clone()]).is_err());
+        assert!(Spec::new(1, vec![group; 17]).is_err());
+    }
+
+    type Grouping = Vec<(usize, usize, Vec<usize>, Vec<Vec<u8>>)>;
+
+    /// The grouping of `group_shares`, as plain values.
+    fn grouping(shares: &[SSKRShareRef<'_>]) -> Result<Grouping, &'static str> {
+        let grouped = group_shares(shares).map_err(|error| error.code())?;
+        Ok(grouped.groups.into_iter().map(|group| {
+            let values = group.member_shares.iter().map(|value| value.to_vec()).collect();
+            (group.group_index, group.member_threshold, group.member_indexes, values)
+        }).collect())
+    }
+
+    /// The grouping of `group_shares` as computed before groups were looked
+    /// up by index, by scanning every group and member for each share.
+    fn linear_grouping(shares: &[SSKRShareRef<'_>]) -> Result<Grouping, &'static str> {
+        let first = shares.first().ok_or("shares_empty")?;
+        let mut groups: Grouping = Vec::new();
+        for (i, share) in shares.iter().enumerate() {
+            check_share_matches(first, share, i).map_err(|error| error.code())?;
+            let Some(group) = groups.iter_mut().find(|group| group.0 == share.group_index()) else {
+                groups.push((share.group_index(), share.member_threshold(), vec![share.member_index()], vec![share.value().to_vec()]));
+                continue;
+            };
+            if share.member_threshold() != group.1 {
+                return Err("member_threshold_mismatch");
+            }
+            match group.2.iter().position(|&index| index == share.member_index()) {
+                Some(k) if group.3[k] == share.value() => {}
+                Some(_) => return Err("conflicting_duplicate_share"),
+                None => {
+                    group.2.push(share.member_index());
+                    group.3.push(share.value().to_vec());
+                }
+            }
+        }
+        Ok(groups)
+    }
+
+    #[test]
+    fn test_group_shares_matches_linear_grouping() {
+        let mut rng = bc_rand::make_fake_random_number_generator();
+        for _ in 0..100 {
+            let group_count = bc_rand::rng_next_in_closed_range(&mut rng, &(1..=crate::MAX_GROUPS_COUNT));
+            let group_specs = (0..group_count)
+                .map(|_| {
+                    let member_count = bc_rand::rng_next_in_closed_range(&mut rng, &(1..=crate::MAX_SHARE_COUNT));
+                    let member_threshold = bc_rand::rng_next_in_closed_range(&mut rng, &(1..=member_count));
+                    GroupSpec::new(member_threshold, member_count).unwrap()
+                })
+                .collect();
+            let group_threshold = bc_rand::rng_next_in_closed_range(&mut rng, &(1..=group_count));
+            let spec = Spec::new(group_threshold, group_specs).unwrap();
+            let secret = Secret::new(rng.random_data(16)).unwrap();
+            let shares: Vec<Vec<u8>> = crate::sskr_generate_using(&spec, &secret, &mut rng).unwrap().concat();
+
+            // A shuffled pile with some shares repeated and, now and then, a
+            // corrupt copy of one.
+            let mut pile: Vec<Vec<u8>> = (0..shares.len() + shares.len() / 4)
+                .map(|_| shares[bc_rand::rng_next_in_closed_range(&mut rng, &(0..=shares.len() - 1))].clone())
+                .collect();
+            if bc_rand::rng_next_in_closed_range(&mut rng, &(0..=3)) == 0 {
+                let i = bc_rand::rng_next_in_closed_range(&mut rng, &(0..=pile.len() - 1));
+                let mut corrupt = pile[i].clone();
+                *corrupt.last_mut().unwrap() ^= 0x01;
+                pile.push(corrupt);
+            }
+            let pile: Vec<SSKRShareRef<'_>> = pile.iter().enumerate().map(|(i, share)| deserialize_share(share, i).unwrap()).collect();
+            assert_eq!(grouping(&pile), linear_grouping(&pile));
+        }
+    }
+
+    #[test]
+    fn test_group_shares_largest_split() {
+        // Every share of 16 groups of 16 members, listed twice, last group
+        // first.
+        let spec = Spec::new(16, vec![GroupSpec::new(16, 16).unwrap(); 16]).unwrap();
+        let secret = Secret::new([0x5a; 32]).unwrap();
+        let shares = crate::sskr_generate_using(&spec, &secret, &mut bc_rand::make_fake_random_number_generator()).unwrap();
+        let pile: Vec<&Vec<u8>> = shares.iter().rev().flatten().chain(shares.iter().flatten()).collect();
+        let pile: Vec<SSKRShareRef<'_>> = pile.iter().enumerate().map(|(i, share)| deserialize_share(share, i).unwrap()).collect();
+
+        let grouped = group_shares(&pile).unwrap();
+        let group_indexes: Vec<usize> = grouped.groups.iter().map(|group| group.group_index).collect();
+        assert_eq!(group_indexes, (0..16).rev().collect::<Vec<_>>());
+        assert!(grouped.groups.iter().all(|group| group.member_indexes == (0..16).collect::<Vec<_>>()));
+        assert_eq!(grouping(&pile), linear_grouping(&pile));
+        assert_eq!(combine_shares(&pile).unwrap().into_secret(), secret);
+    }
+
+    /// Counts the allocations made on the current thread, so that tests
+    /// running in parallel don't disturb each other's counts.
+    struct CountingAllocator;
+
+    thread_local! {
+        static ALLOCATIONS: std::cell::Cell<usize> = const { std::cell::Cell::new(0) };
+    }
+
+    unsafe impl std::alloc::GlobalAlloc for CountingAllocator {
+        unsafe fn alloc(&self, layout: std::alloc::Layout) -> *mut u8 {
+            let _ = ALLOCATIONS.try_with(|count| count.set(count.get() + 1));
+            unsafe { std::alloc::System.alloc(layout) }
+        }
+
+        unsafe fn dealloc(&self, ptr: *mut u8, layout: std::alloc::Layout) {
+            unsafe { std::alloc::System.dealloc(ptr, layout) }
+        }
+    }
+
+    #[global_allocator]
+    static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+    fn count_allocations<R>(f: impl FnOnce() -> R) -> (R, usize) {
+        let before = ALLOCATIONS.with(|count| count.get());
+        let result = f();
+        (result, ALLOCATIONS.with(|count| count.get()) - before)
+    }
+
+    #[test]
+    fn test_generate_allocations() {
+        let spec = Spec::new(16, vec![GroupSpec::new(16, 16).unwrap(); 16]).unwrap();
+        let secret = Secret::new([0x5a; 32]).unwrap();
+        let (shares, allocations) = count_allocations(|| {
+            crate::sskr_generate_using(&spec, &secret, &mut bc_rand::make_fake_random_number_generator()).unwrap()
+        });
+        // Each share is built in a single buffer of its final size. The
+        // member splits are made by `bc-shamir`, whose working buffers
+        // account for most of the allocations.
+        assert!(shares.iter().flatten().all(|share| share.capacity() == share.len()));
+        let share_count = shares.iter().map(Vec::len).sum::<usize>();
+        assert!(allocations < 8 * share_count, "{allocations} allocations");
+
+        let expected = sskr_generate_shares_using(&spec, &secret, &mut bc_rand::make_fake_random_number_generator()).unwrap();
+        assert_eq!(shares, expected.iter().map(|group| group.iter().map(SSKRShare::serialize).collect::<Vec<_>>()).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_combine_allocations() {
+        let spec = Spec::new(16, vec![GroupSpec::new(16, 16).unwrap(); 16]).unwrap();
+        let secret = Secret::new([0x5a; 32]).unwrap();
+        let shares = crate::sskr_generate_using(&spec, &secret, &mut bc_rand::make_fake_random_number_generator()).unwrap().concat();
+        assert_eq!(sskr_combine(&shares).unwrap(), secret);
+        // Share values are read in place while the shares are sorted into
+        // groups, so the allocations grow with the number of groups rather
+        // than the number of shares. Recovery itself is left to `bc-shamir`,
+        // which copies the shares it interpolates.
+        let (group_count, allocations) = count_allocations(|| {
+            let sskr_shares = deserialize_shares(&shares, ReservedBits::Version).unwrap();
+            group_shares(&sskr_shares).unwrap().groups.len()
+        });
+        assert_eq!(group_count, 16);
+        assert!(allocations < shares.len(), "{allocations} allocations");
+    }
+
+    #[test]
+    fn test_recover_group() {
+        let secret = Secret::new([0x5a; 16]).unwrap();
+        let spec = Spec::new(2, vec![GroupSpec::new(2, 3).unwrap(), GroupSpec::new(1, 2).unwrap(), GroupSpec::new(3, 3).unwrap()]).unwrap();
+        let shares = crate::sskr_generate_using(&spec, &secret, &mut bc_rand::make_fake_random_number_generator()).unwrap();
+
+        // The group secrets that `generate_shares` split the secret into,
+        // drawn from the random number generator after the identifier.
+        let mut rng = bc_rand::make_fake_random_number_generator();
+        rng.random_data(2);
+        let group_secrets = split_secret(ShareVersion::V0, 2, 3, secret.data(), &mut rng).unwrap();
+
+        assert_eq!(sskr_recover_group(&[&shares[0][2], &shares[0][0]]).unwrap().data(), group_secrets[0]);
+        assert_eq!(sskr_recover_group(&[&shares[0][1], &shares[0][1], &shares[0][2]]).unwrap().data(), group_secrets[0]);
+        assert_eq!(sskr_recover_group(&[&shares[1][1]]).unwrap().data(), group_secrets[1]);
+        assert_eq!(sskr_recover_group(&shares[2]).unwrap().data(), group_secrets[2]);
+
+        assert!(matches!(
+            sskr_recover_group(&[&shares[0][0], &shares[0][1], &shares[1][0]]),
+            Err(SSKRError::GroupIndexMismatch { expected: 0, found: 1, share_index: 2 })
+        ));
+        assert!(matches!(
+            sskr_recover_group(&[&shares[2][0], &shares[2][2]]),
+            Err(SSKRError::NotEnoughMemberShares { group_index: 2, have: 2, need: 3 })
+        ));
+        let mut corrupt = shares[0][1].clone();
+        corrupt[6] ^= 0x01;
+        assert!(matches!(
+            sskr_recover_group(&[&shares[0][0], &corrupt]),
+            Err(SSKRError::GroupRecoveryFailed { group_index: 0, .. })
+        ));
+        assert!(matches!(sskr_recover_group::<Vec<u8>>(&[]), Err(SSKRError::SharesEmpty)));
+    }
+
+    #[test]
+    fn test_extend_group() {
+        let secret = Secret::new([0x5a; 16]).unwrap();
+        let spec = Spec::new(2, vec![GroupSpec::new(2, 3).unwrap(), GroupSpec::new(3, 3).unwrap()]).unwrap();
+        let shares = crate::sskr_generate_using(&spec, &secret, &mut bc_rand::make_fake_random_number_generator()).unwrap();
+
+        // The number of members does not change the random draws of a split,
+        // so extending a group gives the shares a larger group would have had.
+        let larger = Spec::new(2, vec![GroupSpec::new(2, 3).unwrap(), GroupSpec::new(3, 5).unwrap()]).unwrap();
+        let larger_shares = crate::sskr_generate_using(&larger, &secret, &mut bc_rand::make_fake_random_number_generator()).unwrap();
+        let extended = sskr_extend_group(&[&shares[1][2], &shares[1][0], &shares[1][1]], &[4, 3]).unwrap();
+        assert_eq!(extended, [larger_shares[1][4].clone(), larger_shares[1][3].clone()]);
+
+        // New shares combine with the old ones.
+        let new = sskr_extend_group(&[&shares[0][1], &shares[0][2]], &[15, 7]).unwrap();
+        assert_eq!(crate::sskr_combine(&[&new[0], &shares[1][0], &shares[1][1], &shares[1][2], &shares[0][0]]).unwrap(), secret);
+        assert_eq!(crate::sskr_combine(&[&new[1], &new[0], &extended[0], &shares[1][1], &extended[1]]).unwrap(), secret);
+        assert!(sskr_extend_group(&[&shares[0][0]], &[]).is_err());
+        assert!(sskr_extend_group(&shares[0], &[]).unwrap().is_empty());
+
+        assert!(matches!(
+            sskr_extend_group(&[&shares[0][0], &shares[0][1]], &[3, 16]),
+            Err(SSKRError::MemberIndexInvalid { member_index: 16, max: 16 })
+        ));
+        assert!(matches!(
+            sskr_extend_group(&[&shares[0][0], &shares[0][1]], &[3, 1]),
+            Err(SSKRError::MemberIndexInUse { member_index: 1 })
+        ));
+        assert!(matches!(
+            sskr_extend_group(&[&shares[0][0], &shares[0][1]], &[5, 4, 5]),
+            Err(SSKRError::MemberIndexInUse { member_index: 5 })
+        ));
+        assert!(matches!(
+            sskr_extend_group(&[&shares[0][0], &shares[0][1], &shares[1][0]], &[3]),
+            Err(SSKRError::GroupIndexMismatch { expected: 0, found: 1, share_index: 2 })
+        ));
+        let mut corrupt = shares[0][1].clone();
+        corrupt[6] ^= 0x01;
+        assert!(matches!(
+            sskr_extend_group(&[&shares[0][0], &corrupt], &[3]),
+            Err(SSKRError::GroupRecoveryFailed { group_index: 0, .. })
+        ));
+
+        // Extended shares may use any index of their format.
+        let mut rng = bc_rand::make_fake_random_number_generator();
+        let shares = crate::sskr_generate_with_version_using(&spec, &secret, ShareVersion::V1, &mut rng).unwrap();
+        let new = sskr_extend_group(&shares[0][..2], &[200]).unwrap();
+        assert_eq!(crate::sskr_combine(&[&new[0], &shares[0][2], &shares[1][0], &shares[1][1], &shares[1][2]]).unwrap(), secret);
+    }
+
+    #[test]
+    fn test_combine_from_group_secrets() {
+        let mut rng = bc_rand::make_fake_random_number_generator();
+        for version in [ShareVersion::V0, ShareVersion::V1, ShareVersion::V2, ShareVersion::V3] {
+            let secret = Secret::new(rng.random_data(32)).unwrap();
+            let spec = Spec::new(2, vec![GroupSpec::new(2, 3).unwrap(), GroupSpec::new(1, 2).unwrap(), GroupSpec::new(3, 5).unwrap()]).unwrap();
+            let shares = crate::sskr_generate_with_version_using(&spec, &secret, version, &mut rng).unwrap();
+            let metadata = SplitMetadata::from_share(&shares[0][0]).unwrap();
+
+            // Each group is recovered in its own ceremony.
+            let group = |group_index: usize, members: &[usize]| {
+                let shares: Vec<&Vec<u8>> = members.iter().map(|&i| &shares[group_index][i]).collect();
+                (group_index, sskr_recover_group(&shares).unwrap())
+            };
+            let entries = [group(2, &[4, 0, 1]), group(0, &[1, 2])];
+            assert_eq!(sskr_combine_from_group_secrets(&entries, &metadata).unwrap(), secret);
+            let entries = [group(1, &[0]), group(0, &[0, 1]), group(2, &[2, 3, 4])];
+            assert_eq!(sskr_combine_from_group_secrets(&entries, &metadata).unwrap(), secret);
+
+            assert!(matches!(sskr_combine_from_group_secrets(&entries[..1], &metadata), Err(SSKRError::NotEnoughGroups)));
+            assert!(matches!(
+                sskr_combine_from_group_secrets(&[group(1, &[0]), group(1, &[1])], &metadata),
+                Err(SSKRError::DuplicateGroupSecret { group_index: 1 })
+            ));
+            let (_, group_secret) = group(1, &[0]);
+            assert!(matches!(
+                sskr_combine_from_group_secrets(&[group(0, &[0, 1]), (3, group_secret.clone())], &metadata),
+                Err(SSKRError::GroupSecretIndexInvalid { group_index: 3, group_count: 3 })
+            ));
+            let short = GroupSecret::new(group_secret.data()[..16].to_vec());
+            assert!(matches!(
+                sskr_combine_from_group_secrets(&[group(0, &[0, 1]), (1, short)], &metadata),
+                Err(SSKRError::GroupSecretLengthMismatch { group_index: 1, expected: 32, found: 16 })
+            ));
+
+            // Group secrets swapped between groups don't decode.
+            let entries = [(0, group(1, &[0]).1), (1, group(0, &[0, 1]).1)];
+            assert!(matches!(
+                sskr_combine_from_group_secrets(&entries, &metadata),
+                Err(SSKRError::MasterRecoveryFailed { .. })
+            ));
+        }
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn test_parallel_member_splits() {
+        use rand_chacha::ChaCha20Rng;
+        use rand_core::SeedableRng;
+
+        let spec = Spec::new(8, (1..=16).map(|threshold| GroupSpec::new(threshold, 16).unwrap()).collect()).unwrap();
+        let group_secrets = split_secret(ShareVersion::V0, spec.group_threshold(), spec.group_count(), &[0x5a; 32], &mut bc_rand::make_fake_random_number_generator()).unwrap();
+        let outputs = || vec![vec![Vec::new(); 16]; 16];
+
+        let mut serial = outputs();
+        split_group_members_serial(&spec, ShareVersion::V0, &group_secrets, &mut serial, &mut bc_rand::make_fake_random_number_generator()).unwrap();
+        let mut parallel = outputs();
+        split_group_members_parallel(&spec, ShareVersion::V0, &group_secrets, &mut parallel, &mut bc_rand::make_fake_random_number_generator()).unwrap();
+        assert_eq!(serial, parallel);
+
+        let mut serial = outputs();
+        split_group_members_serial(&spec, ShareVersion::V0, &group_secrets, &mut serial, &mut crate::CryptoRngAdapter::new(ChaCha20Rng::seed_from_u64(42))).unwrap();
+        let mut parallel = outputs();
+        split_group_members_parallel(&spec, ShareVersion::V0, &group_secrets, &mut parallel, &mut crate::CryptoRngAdapter::new(ChaCha20Rng::seed_from_u64(42))).unwrap();
+        assert_eq!(serial, parallel);
+    }
 }
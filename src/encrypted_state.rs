@@ -0,0 +1,201 @@
+use bc_crypto::{
+    aead_chacha20_poly1305_decrypt_with_aad,
+    aead_chacha20_poly1305_encrypt_with_aad,
+    SYMMETRIC_AUTH_SIZE,
+    SYMMETRIC_NONCE_SIZE,
+};
+use bc_rand::RandomNumberGenerator;
+use zeroize::Zeroizing;
+
+use crate::{CollectorState, SSKRError, ShareCollector, passphrase::derive_key};
+
+/// The prefix that marks a collector state sealed by `CollectorState::seal`.
+const SEALED_STATE_MAGIC: [u8; 4] = *b"SKCS";
+
+/// The number of PBKDF2 iterations used to derive the key from the
+/// passphrase.
+pub const STATE_SEAL_ITERATIONS: u32 = 600_000;
+
+/// The most PBKDF2 iterations `CollectorState::unseal` will perform, so that
+/// a forged header can't make it spin indefinitely.
+const MAX_STATE_SEAL_ITERATIONS: u32 = 10_000_000;
+
+const SALT_SIZE: usize = 16;
+const HEADER_SIZE: usize = SEALED_STATE_MAGIC.len() + 4 + 4 + SALT_SIZE + SYMMETRIC_NONCE_SIZE;
+
+impl CollectorState {
+    /// Encrypts this state under `passphrase`, so that it can be written to
+    /// disk.
+    ///
+    /// The key is derived from the passphrase with PBKDF2-HMAC-SHA256 over
+    /// a random salt, and the state is encrypted with ChaCha20-Poly1305. The
+    /// sealed state begins with a header holding the iteration count, the
+    /// length of the plaintext, the salt and the nonce, all of which are
+    /// authenticated.
+    ///
+    /// Only the shares are sealed; the metadata is recomputed from them by
+    /// `unseal`. The plaintext is zeroized once it has been encrypted.
+    #[cfg(feature = "default-rng")]
+    pub fn seal(&self, passphrase: &str) -> Vec<u8> {
+        let mut rng = bc_rand::SecureRandomNumberGenerator;
+        self.seal_using(passphrase, &mut rng)
+    }
+
+    /// Encrypts this state under `passphrase`, using the given random number
+    /// generator for the salt and nonce.
+    pub fn seal_using(&self, passphrase: &str, rng: &mut impl RandomNumberGenerator) -> Vec<u8> {
+        self.seal_with_iterations(passphrase, STATE_SEAL_ITERATIONS, rng)
+    }
+
+    fn seal_with_iterations(&self, passphrase: &str, iterations: u32, rng: &mut impl RandomNumberGenerator) -> Vec<u8> {
+        let mut plaintext = Zeroizing::new(Vec::new());
+        for share in self.shares() {
+            plaintext.extend_from_slice(&(share.len() as u16).to_be_bytes());
+            plaintext.extend_from_slice(share.data());
+        }
+
+        let mut result = Vec::with_capacity(HEADER_SIZE + plaintext.len() + SYMMETRIC_AUTH_SIZE);
+        result.extend_from_slice(&SEALED_STATE_MAGIC);
+        result.extend_from_slice(&iterations.to_be_bytes());
+        result.extend_from_slice(&(plaintext.len() as u32).to_be_bytes());
+        result.extend_from_slice(&rng.random_data(SALT_SIZE));
+        result.extend_from_slice(&rng.random_data(SYMMETRIC_NONCE_SIZE));
+
+        let (salt, nonce) = result[HEADER_SIZE - SALT_SIZE - SYMMETRIC_NONCE_SIZE..].split_at(SALT_SIZE);
+        let key = derive_key(passphrase, salt, iterations);
+        let nonce: [u8; SYMMETRIC_NONCE_SIZE] = nonce.try_into().unwrap();
+        let (ciphertext, auth) = aead_chacha20_poly1305_encrypt_with_aad(&*plaintext, &key, &nonce, &result);
+        result.extend_from_slice(&ciphertext);
+        result.extend_from_slice(&auth);
+        result
+    }
+
+    /// Decrypts a state sealed by [`seal`](Self::seal).
+    ///
+    /// The shares are added to a new collector one at a time, so the state
+    /// returned always agrees with them.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SSKRError::CollectorStateTruncated` if `sealed` is shorter
+    /// than its header says it should be, and
+    /// `SSKRError::CollectorStateDecryptFailed` if the passphrase is wrong or
+    /// the sealed state has been altered. Returns
+    /// `SSKRError::CollectorStateInvalid` if `sealed` is not a sealed state,
+    /// or any error `ShareCollector::add_share` returns for its shares.
+    pub fn unseal(sealed: &[u8], passphrase: &str) -> Result<Self, SSKRError> {
+        if !sealed.starts_with(&SEALED_STATE_MAGIC) {
+            return Err(SSKRError::CollectorStateInvalid);
+        }
+        if sealed.len() < HEADER_SIZE {
+            return Err(SSKRError::CollectorStateTruncated);
+        }
+        let (header, body) = sealed.split_at(HEADER_SIZE);
+        let iterations = u32::from_be_bytes(header[4..8].try_into().unwrap());
+        let plaintext_len = u32::from_be_bytes(header[8..12].try_into().unwrap()) as usize;
+        if iterations == 0 || iterations > MAX_STATE_SEAL_ITERATIONS {
+            return Err(SSKRError::CollectorStateInvalid);
+        }
+        match body.len().cmp(&(plaintext_len + SYMMETRIC_AUTH_SIZE)) {
+            std::cmp::Ordering::Less => return Err(SSKRError::CollectorStateTruncated),
+            std::cmp::Ordering::Greater => return Err(SSKRError::CollectorStateInvalid),
+            std::cmp::Ordering::Equal => {}
+        }
+
+        let (salt, nonce) = header[12..].split_at(SALT_SIZE);
+        let key = derive_key(passphrase, salt, iterations);
+        let nonce: [u8; SYMMETRIC_NONCE_SIZE] = nonce.try_into().unwrap();
+        let (ciphertext, auth) = body.split_at(plaintext_len);
+        let auth: [u8; SYMMETRIC_AUTH_SIZE] = auth.try_into().unwrap();
+        let plaintext = Zeroizing::new(
+            aead_chacha20_poly1305_decrypt_with_aad(ciphertext, &key, &nonce, header, &auth)
+                .map_err(|_| SSKRError::CollectorStateDecryptFailed)?
+        );
+
+        let mut collector = ShareCollector::new();
+        let mut rest = &plaintext[..];
+        while !rest.is_empty() {
+            if rest.len() < 2 {
+                return Err(SSKRError::CollectorStateInvalid);
+            }
+            let len = u16::from_be_bytes([rest[0], rest[1]]) as usize;
+            let share = rest.get(2..2 + len).ok_or(SSKRError::CollectorStateInvalid)?;
+            collector.add_share(share)?;
+            rest = &rest[2 + len..];
+        }
+        Ok(collector.to_state())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{GroupSpec, Secret, Spec, sskr_generate_using, test_support};
+    
+
+    const TEST_ITERATIONS: u32 = 1_000;
+
+    fn sealed_state() -> (Secret, Vec<Vec<Vec<u8>>>, CollectorState, Vec<u8>) {
+        let mut rng = bc_rand::make_fake_random_number_generator();
+        let secret = test_support::secret();
+        let spec = Spec::new(2, vec![GroupSpec::new(2, 3).unwrap(), GroupSpec::new(3, 5).unwrap()]).unwrap();
+        let shares = sskr_generate_using(&spec, &secret, &mut rng).unwrap();
+        let mut collector = ShareCollector::new();
+        collector.add_share(&shares[0][1]).unwrap();
+        collector.add_share(&shares[1][4]).unwrap();
+        collector.add_share(&shares[1][0]).unwrap();
+        let state = collector.to_state();
+        let sealed = state.seal_with_iterations("correct horse", TEST_ITERATIONS, &mut rng);
+        (secret, shares, state, sealed)
+    }
+
+    #[test]
+    fn test_sealed_state_round_trip() {
+        let (secret, shares, state, sealed) = sealed_state();
+        assert_eq!(sealed.len(), HEADER_SIZE + 3 * (2 + shares[0][0].len()) + SYMMETRIC_AUTH_SIZE);
+        assert!(!sealed.windows(shares[0][1].len()).any(|window| window == shares[0][1]));
+
+        let unsealed = CollectorState::unseal(&sealed, "correct horse").unwrap();
+        assert_eq!(unsealed, state);
+        let mut collector = ShareCollector::from_state(unsealed).unwrap();
+        collector.add_share(&shares[0][2]).unwrap();
+        collector.add_share(&shares[1][2]).unwrap();
+        assert_eq!(collector.combine().unwrap(), secret);
+
+        // An empty state seals too, and the seal is randomized.
+        let empty = CollectorState::default();
+        let sealed_empty = empty.seal_with_iterations("", TEST_ITERATIONS, &mut bc_rand::make_fake_random_number_generator());
+        assert_eq!(CollectorState::unseal(&sealed_empty, "").unwrap(), empty);
+        assert_ne!(empty.seal_with_iterations("", TEST_ITERATIONS, &mut bc_rand::SecureRandomNumberGenerator), sealed_empty);
+    }
+
+    #[test]
+    fn test_sealed_state_rejects_corruption() {
+        let (_, _, _, sealed) = sealed_state();
+        assert!(matches!(CollectorState::unseal(&sealed, "wrong horse"), Err(SSKRError::CollectorStateDecryptFailed)));
+
+        for len in 0..sealed.len() {
+            let error = CollectorState::unseal(&sealed[..len], "correct horse").unwrap_err();
+            if len < SEALED_STATE_MAGIC.len() {
+                assert!(matches!(error, SSKRError::CollectorStateInvalid));
+            } else {
+                assert!(matches!(error, SSKRError::CollectorStateTruncated));
+            }
+        }
+        assert!(matches!(CollectorState::unseal(&[&sealed[..], &[0]].concat(), "correct horse"), Err(SSKRError::CollectorStateInvalid)));
+
+        // Altering the salt, nonce, ciphertext or tag fails authentication.
+        for i in 12..sealed.len() {
+            let mut damaged = sealed.clone();
+            damaged[i] ^= 0x01;
+            assert!(matches!(CollectorState::unseal(&damaged, "correct horse"), Err(SSKRError::CollectorStateDecryptFailed)));
+        }
+
+        let mut damaged = sealed.clone();
+        damaged[0] ^= 0x01;
+        assert!(matches!(CollectorState::unseal(&damaged, "correct horse"), Err(SSKRError::CollectorStateInvalid)));
+        let mut damaged = sealed.clone();
+        damaged[4..8].copy_from_slice(&u32::MAX.to_be_bytes());
+        assert!(matches!(CollectorState::unseal(&damaged, "correct horse"), Err(SSKRError::CollectorStateInvalid)));
+    }
+}
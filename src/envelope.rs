@@ -0,0 +1,311 @@
+use crate::{SSKRError, ShareBytes};
+
+/// The prefix that marks a serialized `ShareEnvelope`.
+///
+/// The high four bits of its fifth byte are where a share keeps its format
+/// version, and are set to a value reserved for envelopes, so no SSKR share
+/// can begin with this prefix.
+pub const SHARE_ENVELOPE_MAGIC: [u8; 5] = [0x53, 0x53, 0x4b, 0x45, 0xf1];
+
+/// The maximum length, in bytes, of the note or label of a `ShareEnvelope`.
+pub const MAX_ENVELOPE_FIELD_LEN: usize = u16::MAX as usize;
+
+const HAS_NOTE: u8 = 0x01;
+const HAS_LABEL: u8 = 0x02;
+const HAS_CREATED: u8 = 0x04;
+
+/// A share together with information for the person holding it.
+///
+/// The SSKR share format has no room for anything but the share itself. An
+/// envelope carries a share alongside an optional free-text note (such as
+/// "kept in the blue safe"), a label (such as the holder's name), and a
+/// creation time. None of these take part in recovery, and they are not
+/// protected in any way.
+///
+/// An envelope serializes with `to_bytes` to a format distinct from that of a
+/// bare share: it begins with `SHARE_ENVELOPE_MAGIC`, followed by
+///
+/// - the length of the share as a big-endian `u16`, and the share;
+/// - a flags byte: `0x01` if a note follows, `0x02` if a label follows, and
+///   `0x04` if a creation time follows;
+/// - the note and then the label, each present only if flagged, as a
+///   big-endian `u16` length and that many bytes of UTF-8;
+/// - the creation time, if flagged, as a big-endian `u64`.
+///
+/// An envelope dereferences to its share's bytes through `AsRef<[u8]>`, so a
+/// slice of envelopes can be passed directly to `sskr_combine`. The
+/// serialized envelope cannot: `sskr_combine` refuses it with
+/// `SSKRError::EnvelopeRequired`, and it is read back with `from_bytes`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ShareEnvelope {
+    share: ShareBytes,
+    note: Option<String>,
+    label: Option<String>,
+    created: Option<u64>,
+}
+
+impl ShareEnvelope {
+    /// Creates a new `ShareEnvelope` holding `share` and nothing else.
+    pub fn new(share: ShareBytes) -> Self {
+        Self { share, note: None, label: None, created: None }
+    }
+
+    /// Returns this envelope with the given note.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the note is longer than `MAX_ENVELOPE_FIELD_LEN`
+    /// bytes.
+    pub fn with_note(mut self, note: impl Into<String>) -> Result<Self, SSKRError> {
+        self.note = Some(Self::validate_field(note.into())?);
+        Ok(self)
+    }
+
+    /// Returns this envelope with the given label, such as the name of the
+    /// share's holder.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the label is longer than `MAX_ENVELOPE_FIELD_LEN`
+    /// bytes.
+    pub fn with_label(mut self, label: impl Into<String>) -> Result<Self, SSKRError> {
+        self.label = Some(Self::validate_field(label.into())?);
+        Ok(self)
+    }
+
+    /// Returns this envelope with the given creation time, in seconds since
+    /// the Unix epoch.
+    pub fn with_created(mut self, created: u64) -> Self {
+        self.created = Some(created);
+        self
+    }
+
+    fn validate_field(field: String) -> Result<String, SSKRError> {
+        if field.len() > MAX_ENVELOPE_FIELD_LEN {
+            return Err(SSKRError::EnvelopeFieldTooLong);
+        }
+        Ok(field)
+    }
+
+    /// Returns the share held by this envelope.
+    pub fn share(&self) -> &ShareBytes {
+        &self.share
+    }
+
+    /// Consumes this envelope, returning the share it holds.
+    pub fn into_share(self) -> ShareBytes {
+        self.share
+    }
+
+    /// Returns the note, if any.
+    pub fn note(&self) -> Option<&str> {
+        self.note.as_deref()
+    }
+
+    /// Returns the label, if any.
+    pub fn label(&self) -> Option<&str> {
+        self.label.as_deref()
+    }
+
+    /// Returns the creation time in seconds since the Unix epoch, if any.
+    pub fn created(&self) -> Option<u64> {
+        self.created
+    }
+
+    /// Returns `true` if `bytes` begins with the envelope prefix, and so
+    /// should be decoded with `from_bytes` rather than as a bare share.
+    pub fn is_envelope(bytes: &[u8]) -> bool {
+        bytes.starts_with(&SHARE_ENVELOPE_MAGIC)
+    }
+
+    /// Serializes this envelope.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        fn push_field(result: &mut Vec<u8>, field: &str) {
+            result.extend_from_slice(&(field.len() as u16).to_be_bytes());
+            result.extend_from_slice(field.as_bytes());
+        }
+
+        let mut result = Vec::with_capacity(SHARE_ENVELOPE_MAGIC.len() + 2 + self.share.len() + 1);
+        result.extend_from_slice(&SHARE_ENVELOPE_MAGIC);
+        result.extend_from_slice(&(self.share.len() as u16).to_be_bytes());
+        result.extend_from_slice(self.share.data());
+
+        let mut flags = 0;
+        if self.note.is_some() {
+            flags |= HAS_NOTE;
+        }
+        if self.label.is_some() {
+            flags |= HAS_LABEL;
+        }
+        if self.created.is_some() {
+            flags |= HAS_CREATED;
+        }
+        result.push(flags);
+
+        if let Some(note) = &self.note {
+            push_field(&mut result, note);
+        }
+        if let Some(label) = &self.label {
+            push_field(&mut result, label);
+        }
+        if let Some(created) = self.created {
+            result.extend_from_slice(&created.to_be_bytes());
+        }
+
+        result
+    }
+
+    /// Deserializes an envelope serialized by `to_bytes`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SSKRError::EnvelopeInvalid` if `bytes` is not an envelope,
+    /// including if it is a bare share, or is truncated, has trailing bytes,
+    /// or holds text that is not UTF-8. Returns the share's own error if the
+    /// share inside is malformed.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, SSKRError> {
+        let mut reader = Reader(bytes);
+        if reader.take(SHARE_ENVELOPE_MAGIC.len())? != SHARE_ENVELOPE_MAGIC {
+            return Err(SSKRError::EnvelopeInvalid);
+        }
+
+        let share_len = reader.read_u16()? as usize;
+        let share = ShareBytes::new(reader.take(share_len)?)?;
+        let mut envelope = Self::new(share);
+
+        let flags = reader.take(1)?[0];
+        if flags & !(HAS_NOTE | HAS_LABEL | HAS_CREATED) != 0 {
+            return Err(SSKRError::EnvelopeInvalid);
+        }
+        if flags & HAS_NOTE != 0 {
+            envelope.note = Some(reader.read_string()?);
+        }
+        if flags & HAS_LABEL != 0 {
+            envelope.label = Some(reader.read_string()?);
+        }
+        if flags & HAS_CREATED != 0 {
+            let created = reader.take(8)?.try_into().unwrap();
+            envelope.created = Some(u64::from_be_bytes(created));
+        }
+
+        if !reader.0.is_empty() {
+            return Err(SSKRError::EnvelopeInvalid);
+        }
+
+        Ok(envelope)
+    }
+}
+
+impl AsRef<[u8]> for ShareEnvelope {
+    fn as_ref(&self) -> &[u8] {
+        self.share.data()
+    }
+}
+
+/// Reads the fields of a serialized envelope in order.
+struct Reader<'a>(&'a [u8]);
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], SSKRError> {
+        if self.0.len() < len {
+            return Err(SSKRError::EnvelopeInvalid);
+        }
+        let (head, tail) = self.0.split_at(len);
+        self.0 = tail;
+        Ok(head)
+    }
+
+    fn read_u16(&mut self) -> Result<u16, SSKRError> {
+        let bytes = self.take(2)?;
+        Ok(u16::from_be_bytes([bytes[0], bytes[1]]))
+    }
+
+    fn read_string(&mut self) -> Result<String, SSKRError> {
+        let len = self.read_u16()? as usize;
+        let bytes = self.take(len)?;
+        String::from_utf8(bytes.to_vec()).map_err(|_| SSKRError::EnvelopeInvalid)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{GroupSpec, Spec, sskr_combine, test_support};
+
+    #[test]
+    fn test_envelope_round_trip() {
+        let secret = test_support::secret();
+        let shares = test_support::generate_share_bytes(&Spec::new(1, vec![GroupSpec::new(2, 3).unwrap()]).unwrap()).concat();
+        let envelopes = vec![
+            ShareEnvelope::new(shares[0].clone()),
+            ShareEnvelope::new(shares[1].clone())
+                .with_note("kept in the blue safe").unwrap()
+                .with_label("Alice").unwrap()
+                .with_created(1_700_000_000),
+            ShareEnvelope::new(shares[2].clone())
+                .with_note("").unwrap()
+                .with_label("x".repeat(MAX_ENVELOPE_FIELD_LEN)).unwrap(),
+        ];
+
+        for envelope in &envelopes {
+            let bytes = envelope.to_bytes();
+            assert!(ShareEnvelope::is_envelope(&bytes));
+            assert_eq!(&ShareEnvelope::from_bytes(&bytes).unwrap(), envelope);
+        }
+        assert_eq!(envelopes[0].to_bytes().len(), SHARE_ENVELOPE_MAGIC.len() + 2 + shares[0].len() + 1);
+        assert_eq!(envelopes[1].note(), Some("kept in the blue safe"));
+        assert_eq!(envelopes[1].label(), Some("Alice"));
+        assert_eq!(envelopes[1].created(), Some(1_700_000_000));
+        assert_eq!(envelopes[2].note(), Some(""));
+        assert_eq!(envelopes[2].created(), None);
+
+        assert_eq!(sskr_combine(&envelopes[1..]).unwrap(), secret);
+        assert_eq!(envelopes[0].clone().into_share(), shares[0]);
+    }
+
+    #[test]
+    fn test_envelope_rejects_invalid() {
+        let shares = test_support::generate_share_bytes(&Spec::new(1, vec![GroupSpec::new(2, 3).unwrap()]).unwrap()).concat();
+        assert!(matches!(
+            ShareEnvelope::new(shares[0].clone()).with_note("x".repeat(MAX_ENVELOPE_FIELD_LEN + 1)),
+            Err(SSKRError::EnvelopeFieldTooLong)
+        ));
+
+        // A bare share is never mistaken for an envelope.
+        for share in &shares {
+            assert!(!ShareEnvelope::is_envelope(share.data()));
+            assert!(matches!(ShareEnvelope::from_bytes(share.data()), Err(SSKRError::EnvelopeInvalid)));
+        }
+
+        // An envelope is never mistaken for a bare share.
+        let bytes = ShareEnvelope::new(shares[0].clone()).to_bytes();
+        assert!(matches!(ShareBytes::new(bytes.clone()), Err(SSKRError::EnvelopeRequired { share_index: None })));
+        let error = sskr_combine(&[shares[1].data(), &bytes]).unwrap_err();
+        assert!(matches!(error, SSKRError::EnvelopeRequired { share_index: Some(1) }));
+        assert_eq!(error.to_string(), "SSKR share at index 1 is wrapped in a share envelope and must be unwrapped with `ShareEnvelope::from_bytes`");
+        assert_eq!(error.code(), "envelope_required");
+
+        let bytes = ShareEnvelope::new(shares[0].clone()).with_label("Bob").unwrap().to_bytes();
+        for len in 0..bytes.len() {
+            assert!(ShareEnvelope::from_bytes(&bytes[..len]).is_err());
+        }
+        let with_byte = |i: usize, b: u8| {
+            let mut bytes = bytes.clone();
+            bytes[i] = b;
+            bytes
+        };
+        let flags = SHARE_ENVELOPE_MAGIC.len() + 2 + shares[0].len();
+        assert!(matches!(ShareEnvelope::from_bytes(&[bytes.clone(), vec![0]].concat()), Err(SSKRError::EnvelopeInvalid)));
+        assert!(matches!(ShareEnvelope::from_bytes(&with_byte(flags, 0x08 | HAS_LABEL)), Err(SSKRError::EnvelopeInvalid)));
+        assert!(matches!(ShareEnvelope::from_bytes(&with_byte(flags + 3, 0xff)), Err(SSKRError::EnvelopeInvalid)));
+
+        // The share inside must be well formed, and not itself an envelope.
+        assert!(matches!(
+            ShareEnvelope::from_bytes(&with_byte(SHARE_ENVELOPE_MAGIC.len() + 2 + 4, 0xf0)),
+            Err(SSKRError::ShareReservedBitsInvalid { share_index: None })
+        ));
+        let mut nested = bytes.clone();
+        nested[SHARE_ENVELOPE_MAGIC.len() + 2..][..SHARE_ENVELOPE_MAGIC.len()].copy_from_slice(&SHARE_ENVELOPE_MAGIC);
+        assert!(matches!(ShareEnvelope::from_bytes(&nested), Err(SSKRError::EnvelopeRequired { share_index: None })));
+    }
+}
@@ -1,52 +1,298 @@
 use thiserror::Error;
 
 /// Errors that can occur when using the SSKR library.
+///
+/// With the `serde` feature, errors serialize as an object whose `code` field
+/// is the error's [`code`](Self::code), with any values the error carries in
+/// a `data` field.
 #[derive(Debug, Error)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize), serde(tag = "code", content = "data", rename_all = "snake_case"))]
 pub enum SSKRError {
+    #[error("SSKR application data must fit in four bits")]
+    AppNibbleInvalid,
+
+    #[error("SSKR application data {app_nibble} is reserved to mark a share format or a protected share")]
+    AppNibbleReserved { app_nibble: u8 },
+
+    #[error("{share} carries application data {app_nibble} and must be combined with `sskr_combine_lenient`", share = share_label(share_index))]
+    AppNibblePresent { share_index: Option<usize>, app_nibble: u8 },
+
+    #[error("SSKR share at index {share_index} has application data {found}, but the first share has {expected}")]
+    AppNibbleMismatch { expected: u8, found: u8, share_index: usize },
+
+    #[error("SSKR share envelope is malformed")]
+    EnvelopeInvalid,
+
+    #[error("SSKR share envelope field is longer than {max} bytes", max = crate::MAX_ENVELOPE_FIELD_LEN)]
+    EnvelopeFieldTooLong,
+
+    #[error("{share} is wrapped in a share envelope and must be unwrapped with `ShareEnvelope::from_bytes`", share = share_label(share_index))]
+    EnvelopeRequired { share_index: Option<usize> },
+
     #[error("When combining shares, the provided shares contained a duplicate member index")]
     DuplicateMemberIndex,
 
+    #[error("SSKR shares disagree: there are two different shares for member {member_index} of group {group_index}")]
+    ConflictingDuplicateShare { group_index: usize, member_index: usize },
+
+    #[error("SSKR share for member {member_index} of group {group_index} does not agree with the other shares of its group")]
+    InconsistentShare { group_index: usize, member_index: usize },
+
     #[error("Invalid group specification.")]
     GroupSpecInvalid,
 
+    #[error("Invalid split specification: expected {expected} at position {position}")]
+    SpecParseFailed { position: usize, expected: &'static str },
+
+    #[error("Invalid split specification: {}", crate::spec_builder::describe_problems(problems))]
+    SpecBuildFailed { problems: Vec<crate::SpecProblem> },
+
     #[error("When creating a split spec, the group count is invalid")]
     GroupCountInvalid,
 
-    #[error("SSKR group threshold is invalid")]
-    GroupThresholdInvalid,
+    #[error("SSKR identifier {identifier:x} does not fit in the identifier of format {version:?}")]
+    IdentifierInvalid { identifier: u32, version: crate::ShareVersion },
+
+    #[error("SSKR could not draw an identifier: all are among the {avoided} to be avoided")]
+    IdentifierSpaceExhausted { avoided: usize },
+
+    #[error("SSKR member counts were given for {}, but the split has {expected}", count_of(*found, "group"))]
+    MemberCountsInvalid { expected: usize, found: usize },
+
+    #[error("SSKR member threshold of the group at index {group_index} is unknown: none of its shares were provided")]
+    MemberThresholdUnknown { group_index: usize },
+
+    #[error("SSKR fixed share for member {member_index} of group {group_index} is not in the spec or is fixed more than once")]
+    FixedShareIndexInvalid { group_index: usize, member_index: usize },
+
+    #[error("SSKR fixed share for member {member_index} of group {group_index} is {found} bytes, but the secret is {expected}")]
+    FixedShareLengthMismatch { group_index: usize, member_index: usize, expected: usize, found: usize },
+
+    #[error("SSKR group {group_index} has {count} fixed shares, but at most {max} can be fixed")]
+    TooManyFixedShares { group_index: usize, count: usize, max: usize },
+
+    #[error("{share} is corrupt: its group index {group_index} is out of range for {groups}", share = share_label(share_index), groups = count_of(*group_count, "group"))]
+    GroupIndexInvalid { share_index: Option<usize>, group_index: usize, group_count: usize },
+
+    #[error("SSKR share at index {share_index} is of group {found}, not group {expected} like the shares before it")]
+    GroupIndexMismatch { expected: usize, found: usize, share_index: usize },
+
+    #[error("SSKR group secret for group {group_index} is out of range for {}", count_of(*group_count, "group"))]
+    GroupSecretIndexInvalid { group_index: usize, group_count: usize },
+
+    #[error("SSKR group secret for group {group_index} was given more than once")]
+    DuplicateGroupSecret { group_index: usize },
+
+    #[error("SSKR group secret for group {group_index} is {found} bytes, but the first is {expected}")]
+    GroupSecretLengthMismatch { group_index: usize, expected: usize, found: usize },
 
-    #[error("SSKR member count is invalid")]
-    MemberCountInvalid,
+    #[error("SSKR escrowed group secret at index {escrow_index} is of a different split than the first")]
+    EscrowMismatch { escrow_index: usize },
 
-    #[error("SSKR member threshold is invalid")]
-    MemberThresholdInvalid,
+    #[error("SSKR group {group_index} could not be recovered from its member shares: {source}")]
+    GroupRecoveryFailed {
+        group_index: usize,
+        #[cfg_attr(feature = "serde", serde(serialize_with = "serialize_error_message"))]
+        source: bc_shamir::Error,
+    },
+
+    #[error("SSKR secret could not be recovered from the group secrets ({} not used): {source}", count_of(*unused_complete_groups, "other complete group"))]
+    MasterRecoveryFailed {
+        unused_complete_groups: usize,
+        #[cfg_attr(feature = "serde", serde(serialize_with = "serialize_error_message"))]
+        source: bc_shamir::Error,
+    },
+
+    #[error("SSKR group threshold {threshold} is invalid: it must be between 1 and the group count of {group_count}")]
+    GroupThresholdInvalid { threshold: usize, group_count: usize },
+
+    #[error("SSKR member count {count} is invalid: it must be between 1 and {max}")]
+    MemberCountInvalid { count: usize, max: usize },
+
+    #[error("SSKR member threshold {threshold} is invalid: it must be between 1 and the member count of {count}")]
+    MemberThresholdInvalid { threshold: usize, count: usize },
+
+    #[error("SSKR holder at index {holder_index} has weight {weight}, but a weight must be at least one and less than the member threshold {member_threshold}")]
+    HolderWeightInvalid { holder_index: usize, weight: usize, member_threshold: usize },
+
+    #[error("SSKR required member count {required_count} must be at least one and less than the member threshold {member_threshold}")]
+    RequiredCountInvalid { required_count: usize, member_threshold: usize },
+
+    #[error("SSKR social group threshold {threshold} would let one member recover the secret alone: it must be at least 2")]
+    SocialThresholdInvalid { threshold: usize },
+
+    #[error("SSKR generation must make at least one copy of each member's share")]
+    CopiesPerMemberInvalid,
 
     #[error("SSKR shares did not contain enough groups")]
     NotEnoughGroups,
 
-    #[error("SSKR secret is not of even length")]
-    SecretLengthNotEven,
+    #[error("SSKR shares are not enough to recover the secret: group {group_index} has {have} of the {need} member shares it needs")]
+    NotEnoughMemberShares { group_index: usize, have: usize, need: usize },
+
+    #[error("SSKR secret is {len} bytes, but its length must be even")]
+    SecretLengthNotEven { len: usize },
+
+    #[error("SSKR secret is {len} bytes, which is longer than the maximum of {max}")]
+    SecretTooLong { len: usize, max: usize },
+
+    #[error("SSKR secret is {len} bytes, which is shorter than the minimum of {min}")]
+    SecretTooShort { len: usize, min: usize },
+
+    #[error("{share} is corrupt: it is {len} bytes, which is too short to contain the share metadata", share = share_label(share_index))]
+    ShareLengthInvalid { share_index: Option<usize>, len: usize },
+
+    #[error("{share} is corrupt: its seal does not match its contents", share = share_label(share_index))]
+    ShareChecksumInvalid { share_index: Option<usize> },
+
+    #[error("SSKR share is not valid hex")]
+    ShareHexInvalid,
+
+    #[error("SSKR share at index {share_index} is not one of the shares recorded in the transcript")]
+    ShareNotInTranscript { share_index: usize },
+
+    #[error("SSKR shares recovered a secret that does not match the expected fingerprint")]
+    FingerprintMismatch,
+
+    #[error("SSKR group {group_index} recovered a group secret that does not match its digest member")]
+    DigestMismatch { group_index: usize },
+
+    #[error("SSKR group {group_index} has a member at every index, leaving none for the digest member")]
+    DigestMemberUnavailable { group_index: usize },
+
+    #[error("SSKR output buffer is {len} bytes, which is too short for the {required}-byte secret")]
+    OutputBufferTooSmall { len: usize, required: usize },
+
+    #[error("SSKR share records of {share_len} bytes are too short to hold a share of at least {min} bytes")]
+    RecordLengthInvalid { share_len: usize, min: usize },
+
+    #[error("SSKR concatenated shares are {len} bytes, which is not a whole number of {share_len}-byte records")]
+    ConcatenatedLengthInvalid { len: usize, share_len: usize },
 
-    #[error("SSKR secret is too long")]
-    SecretTooLong,
+    #[error("SSKR share string at index {input_index} could not be decoded as {}", crate::strings::describe_attempts(attempted))]
+    ShareStringInvalid { input_index: usize, attempted: Vec<crate::ShareEncoding> },
 
-    #[error("SSKR secret is too short")]
-    SecretTooShort,
+    #[error("{share} is corrupt: its payload is {len} bytes, which is shorter than the minimum of {min}", min = crate::MIN_SECRET_LEN, share = share_label(share_index))]
+    SharePayloadTooShort { share_index: Option<usize>, len: usize },
 
-    #[error("SSKR shares did not contain enough serialized bytes")]
-    ShareLengthInvalid,
+    #[error("{share} is corrupt: its payload is {len} bytes, which is longer than the maximum of {max}", max = crate::MAX_SECRET_LEN, share = share_label(share_index))]
+    SharePayloadTooLong { share_index: Option<usize>, len: usize },
 
-    #[error("SSKR shares contained invalid reserved bits")]
-    ShareReservedBitsInvalid,
+    #[error("{share} is corrupt: its payload is {len} bytes, which is not an even length", share = share_label(share_index))]
+    SharePayloadLengthNotEven { share_index: Option<usize>, len: usize },
+
+    #[error("{share} uses format version {version}, which this version of the library does not support", share = share_label(share_index))]
+    UnsupportedShareVersion { share_index: Option<usize>, version: u8 },
+
+    #[error("{share} is corrupt: its reserved bits are not zero", share = share_label(share_index))]
+    ShareReservedBitsInvalid { share_index: Option<usize> },
+
+    #[error("{share} is corrupt: its group threshold {group_threshold} exceeds its group count {group_count}", share = share_label(share_index))]
+    ShareGroupThresholdInvalid { share_index: Option<usize>, group_threshold: usize, group_count: usize },
+
+    #[error("{share} is corrupt: its group count {group_count} exceeds the maximum of {max}", max = crate::MAX_EXTENDED_GROUPS_COUNT, share = share_label(share_index))]
+    ShareGroupCountInvalid { share_index: Option<usize>, group_count: usize },
+
+    #[error("{share} is corrupt: its member threshold {member_threshold} exceeds the maximum of {max}", max = crate::MAX_EXTENDED_SHARE_COUNT, share = share_label(share_index))]
+    ShareMemberThresholdInvalid { share_index: Option<usize>, member_threshold: usize },
+
+    #[error("{share} is corrupt: its member index {member_index} is out of range", share = share_label(share_index))]
+    ShareMemberIndexInvalid { share_index: Option<usize>, member_index: usize },
+
+    #[error("SSKR member index {member_index} is out of range: the largest is {}", max - 1)]
+    MemberIndexInvalid { member_index: usize, max: usize },
+
+    #[error("SSKR member index {member_index} is already in use in its group")]
+    MemberIndexInUse { member_index: usize },
+
+    #[error("SSKR flat shares numbered {shares}, but {indexes} indexes were given")]
+    FlatIndexCountMismatch { indexes: usize, shares: usize },
+
+    #[error("SSKR share at index {share_index} is from a different split: its identifier is {found:04x}, not {expected:04x}")]
+    IdentifierMismatch { expected: u32, found: u32, share_index: usize },
+
+    #[error("SSKR share at index {share_index} is from a different split: it requires {}, not {expected}", count_of(*found, "group"))]
+    GroupThresholdMismatch { expected: usize, found: usize, share_index: usize },
+
+    #[error("SSKR share at index {share_index} is from a different split: it has {}, not {expected}", count_of(*found, "group"))]
+    GroupCountMismatch { expected: usize, found: usize, share_index: usize },
+
+    #[error("SSKR share at index {share_index} is from a different split: its payload is {found} bytes, not {expected}")]
+    ShareLengthMismatch { expected: usize, found: usize, share_index: usize },
+
+    #[error("SSKR share at index {share_index} is from a different split: it uses format {found:?}, not {expected:?}")]
+    ShareVersionMismatch { expected: crate::ShareVersion, found: crate::ShareVersion, share_index: usize },
+
+    #[error("SSKR share at index {share_index} is from a different split: the group at index {group_index} requires {found} of its shares, not {expected}")]
+    MemberThresholdMismatch { group_index: usize, expected: usize, found: usize, share_index: usize },
+
+    #[error("SSKR share at index {share_index} is not from the expected split: its {field} is {found}, not {expected}")]
+    SpecMismatch { field: crate::SpecField, expected: usize, found: usize, share_index: usize },
+
+    #[error("SSKR collector state does not agree with the shares it holds")]
+    CollectorStateInvalid,
+
+    #[error("SSKR collector state is truncated")]
+    CollectorStateTruncated,
+
+    #[error("SSKR collector state could not be decrypted: the passphrase is wrong or the state has been altered")]
+    CollectorStateDecryptFailed,
+
+    #[error("SSKR share for member {member_index} of group {group_index} has no recipient")]
+    HolderRecipientMissing { group_index: usize, member_index: usize },
+
+    #[error("SSKR recipient at index {recipient_index} is for member {member_index} of group {group_index}, which is not a share of the split or already has a recipient")]
+    HolderRecipientInvalid { recipient_index: usize, group_index: usize, member_index: usize },
+
+    #[error("SSKR encrypted share is malformed")]
+    HolderShareInvalid,
+
+    #[error("SSKR encrypted share could not be decrypted: the private key is wrong or the share has been altered")]
+    HolderShareDecryptFailed,
+
+    #[error("{share} is protected by a passphrase and must be combined with `sskr_combine_encrypted`", share = share_label(share_index))]
+    PassphraseRequired { share_index: Option<usize> },
+
+    #[error("SSKR share at index {share_index} is not protected by a passphrase")]
+    PassphraseShareInvalid { share_index: usize },
+
+    #[error("SSKR share at index {share_index} is from a different split: its passphrase parameters differ from those of the first share")]
+    PassphraseParametersMismatch { share_index: usize },
+
+    #[error("SSKR passphrase is incorrect")]
+    PassphraseIncorrect,
+
+    #[error("SSKR share is not in the collector")]
+    ShareNotCollected,
 
     #[error("SSKR shares were empty")]
     SharesEmpty,
 
+    #[deprecated(note = "combining reports the specific mismatch, such as `IdentifierMismatch`")]
     #[error("SSKR shares were invalid")]
     ShareSetInvalid,
 
+    #[error("SSKR shares were rejected by strict combine: {} of them were not used to recover the secret", violations.len())]
+    StrictCombineRejected { violations: Vec<crate::StrictViolation> },
+
+    #[error("SSKR shares numbered {count}, but no split has more than {max}")]
+    TooManyShares { count: usize, max: usize },
+
+    #[error("SSKR randomness is unavailable: {0}")]
+    RandomnessUnavailable(
+        #[source]
+        #[cfg_attr(feature = "serde", serde(serialize_with = "serialize_error_message"))]
+        Box<dyn std::error::Error + Send + Sync>,
+    ),
+
+    #[error("SSKR random number generator failed the {test} health check")]
+    WeakRandomness { test: &'static str },
+
     #[error("SSKR Shamir error: {0}")]
-    ShamirError(bc_shamir::Error),
+    ShamirError(
+        #[cfg_attr(feature = "serde", serde(serialize_with = "serialize_error_message"))]
+        bc_shamir::Error,
+    ),
 }
 
 impl From<bc_shamir::Error> for SSKRError {
@@ -54,3 +300,132 @@ impl From<bc_shamir::Error> for SSKRError {
         SSKRError::ShamirError(err)
     }
 }
+
+impl SSKRError {
+    /// Returns a stable identifier for the kind of error, for programs that
+    /// need to tell errors apart without parsing their messages.
+    ///
+    /// The code is the name of the variant in snake case, such as
+    /// `"identifier_mismatch"` for `SSKRError::IdentifierMismatch`. It will
+    /// not change for an existing variant.
+    #[allow(deprecated)]
+    pub fn code(&self) -> &'static str {
+        match self {
+            SSKRError::AppNibbleInvalid => "app_nibble_invalid",
+            SSKRError::AppNibbleReserved { .. } => "app_nibble_reserved",
+            SSKRError::AppNibblePresent { .. } => "app_nibble_present",
+            SSKRError::AppNibbleMismatch { .. } => "app_nibble_mismatch",
+            SSKRError::EnvelopeInvalid => "envelope_invalid",
+            SSKRError::EnvelopeFieldTooLong => "envelope_field_too_long",
+            SSKRError::EnvelopeRequired { .. } => "envelope_required",
+            SSKRError::DuplicateMemberIndex => "duplicate_member_index",
+            SSKRError::ConflictingDuplicateShare { .. } => "conflicting_duplicate_share",
+            SSKRError::InconsistentShare { .. } => "inconsistent_share",
+            SSKRError::GroupSpecInvalid => "group_spec_invalid",
+            SSKRError::SpecParseFailed { .. } => "spec_parse_failed",
+            SSKRError::SpecBuildFailed { .. } => "spec_build_failed",
+            SSKRError::GroupCountInvalid => "group_count_invalid",
+            SSKRError::IdentifierInvalid { .. } => "identifier_invalid",
+            SSKRError::IdentifierSpaceExhausted { .. } => "identifier_space_exhausted",
+            SSKRError::MemberCountsInvalid { .. } => "member_counts_invalid",
+            SSKRError::MemberThresholdUnknown { .. } => "member_threshold_unknown",
+            SSKRError::FixedShareIndexInvalid { .. } => "fixed_share_index_invalid",
+            SSKRError::FixedShareLengthMismatch { .. } => "fixed_share_length_mismatch",
+            SSKRError::TooManyFixedShares { .. } => "too_many_fixed_shares",
+            SSKRError::GroupIndexInvalid { .. } => "group_index_invalid",
+            SSKRError::GroupIndexMismatch { .. } => "group_index_mismatch",
+            SSKRError::GroupSecretIndexInvalid { .. } => "group_secret_index_invalid",
+            SSKRError::DuplicateGroupSecret { .. } => "duplicate_group_secret",
+            SSKRError::GroupSecretLengthMismatch { .. } => "group_secret_length_mismatch",
+            SSKRError::EscrowMismatch { .. } => "escrow_mismatch",
+            SSKRError::GroupRecoveryFailed { .. } => "group_recovery_failed",
+            SSKRError::MasterRecoveryFailed { .. } => "master_recovery_failed",
+            SSKRError::GroupThresholdInvalid { .. } => "group_threshold_invalid",
+            SSKRError::MemberCountInvalid { .. } => "member_count_invalid",
+            SSKRError::MemberThresholdInvalid { .. } => "member_threshold_invalid",
+            SSKRError::HolderWeightInvalid { .. } => "holder_weight_invalid",
+            SSKRError::RequiredCountInvalid { .. } => "required_count_invalid",
+            SSKRError::SocialThresholdInvalid { .. } => "social_threshold_invalid",
+            SSKRError::CopiesPerMemberInvalid => "copies_per_member_invalid",
+            SSKRError::NotEnoughGroups => "not_enough_groups",
+            SSKRError::NotEnoughMemberShares { .. } => "not_enough_member_shares",
+            SSKRError::SecretLengthNotEven { .. } => "secret_length_not_even",
+            SSKRError::SecretTooLong { .. } => "secret_too_long",
+            SSKRError::SecretTooShort { .. } => "secret_too_short",
+            SSKRError::ShareLengthInvalid { .. } => "share_length_invalid",
+            SSKRError::ShareChecksumInvalid { .. } => "share_checksum_invalid",
+            SSKRError::ShareHexInvalid => "share_hex_invalid",
+            SSKRError::ShareNotInTranscript { .. } => "share_not_in_transcript",
+            SSKRError::FingerprintMismatch => "fingerprint_mismatch",
+            SSKRError::DigestMismatch { .. } => "digest_mismatch",
+            SSKRError::DigestMemberUnavailable { .. } => "digest_member_unavailable",
+            SSKRError::OutputBufferTooSmall { .. } => "output_buffer_too_small",
+            SSKRError::RecordLengthInvalid { .. } => "record_length_invalid",
+            SSKRError::ConcatenatedLengthInvalid { .. } => "concatenated_length_invalid",
+            SSKRError::ShareStringInvalid { .. } => "share_string_invalid",
+            SSKRError::SharePayloadTooShort { .. } => "share_payload_too_short",
+            SSKRError::SharePayloadTooLong { .. } => "share_payload_too_long",
+            SSKRError::SharePayloadLengthNotEven { .. } => "share_payload_length_not_even",
+            SSKRError::UnsupportedShareVersion { .. } => "unsupported_share_version",
+            SSKRError::ShareReservedBitsInvalid { .. } => "share_reserved_bits_invalid",
+            SSKRError::ShareGroupThresholdInvalid { .. } => "share_group_threshold_invalid",
+            SSKRError::ShareGroupCountInvalid { .. } => "share_group_count_invalid",
+            SSKRError::ShareMemberThresholdInvalid { .. } => "share_member_threshold_invalid",
+            SSKRError::ShareMemberIndexInvalid { .. } => "share_member_index_invalid",
+            SSKRError::MemberIndexInvalid { .. } => "member_index_invalid",
+            SSKRError::MemberIndexInUse { .. } => "member_index_in_use",
+            SSKRError::FlatIndexCountMismatch { .. } => "flat_index_count_mismatch",
+            SSKRError::IdentifierMismatch { .. } => "identifier_mismatch",
+            SSKRError::GroupThresholdMismatch { .. } => "group_threshold_mismatch",
+            SSKRError::GroupCountMismatch { .. } => "group_count_mismatch",
+            SSKRError::ShareLengthMismatch { .. } => "share_length_mismatch",
+            SSKRError::ShareVersionMismatch { .. } => "share_version_mismatch",
+            SSKRError::MemberThresholdMismatch { .. } => "member_threshold_mismatch",
+            SSKRError::SpecMismatch { .. } => "spec_mismatch",
+            SSKRError::CollectorStateInvalid => "collector_state_invalid",
+            SSKRError::CollectorStateTruncated => "collector_state_truncated",
+            SSKRError::CollectorStateDecryptFailed => "collector_state_decrypt_failed",
+            SSKRError::HolderRecipientMissing { .. } => "holder_recipient_missing",
+            SSKRError::HolderRecipientInvalid { .. } => "holder_recipient_invalid",
+            SSKRError::HolderShareInvalid => "holder_share_invalid",
+            SSKRError::HolderShareDecryptFailed => "holder_share_decrypt_failed",
+            SSKRError::PassphraseRequired { .. } => "passphrase_required",
+            SSKRError::PassphraseShareInvalid { .. } => "passphrase_share_invalid",
+            SSKRError::PassphraseParametersMismatch { .. } => "passphrase_parameters_mismatch",
+            SSKRError::PassphraseIncorrect => "passphrase_incorrect",
+            SSKRError::ShareNotCollected => "share_not_collected",
+            SSKRError::SharesEmpty => "shares_empty",
+            SSKRError::ShareSetInvalid => "share_set_invalid",
+            SSKRError::StrictCombineRejected { .. } => "strict_combine_rejected",
+            SSKRError::TooManyShares { .. } => "too_many_shares",
+            SSKRError::RandomnessUnavailable(_) => "randomness_unavailable",
+            SSKRError::WeakRandomness { .. } => "weak_randomness",
+            SSKRError::ShamirError(_) => "shamir_error",
+        }
+    }
+}
+
+/// Names a share in an error message by its position in the caller's input,
+/// or not at all for a share passed on its own.
+fn share_label(share_index: &Option<usize>) -> String {
+    match share_index {
+        Some(share_index) => format!("SSKR share at index {share_index}"),
+        None => "SSKR share".to_string(),
+    }
+}
+
+/// Writes `count` followed by `noun`, made plural unless `count` is one.
+pub(crate) fn count_of(count: usize, noun: &str) -> String {
+    if count == 1 { format!("1 {noun}") } else { format!("{count} {noun}s") }
+}
+
+/// Serializes an error from another crate as its message, as such errors are
+/// not themselves serializable.
+#[cfg(feature = "serde")]
+fn serialize_error_message<E, S>(error: &E, serializer: S) -> Result<S::Ok, S::Error>
+where
+    E: std::fmt::Display + ?Sized,
+    S: serde::Serializer,
+{
+    serializer.collect_str(error)
+}
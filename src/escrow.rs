@@ -0,0 +1,249 @@
+use bc_rand::RandomNumberGenerator;
+
+use crate::{
+    GroupSecret,
+    SSKRError,
+    Secret,
+    Spec,
+    SplitHeader,
+    sskr_combine,
+    sskr_combine_from_group_secrets,
+    sskr_split_group_members,
+    sskr_split_groups,
+    encoding::{ReservedBits, deserialize_shares, group_shares, recover_group_secrets},
+    transcript::GroupedShareBytes,
+};
+
+/// The group secret of one group of a split, held in escrow as a recovery
+/// path outside the group's members, as returned by
+/// [`sskr_generate_with_group_secrets`].
+///
+/// **An escrowed group secret stands in for a whole group.** Anyone who
+/// holds it needs no member of that group to recover the master secret, and
+/// the escrowed secrets of group-threshold groups recover it with no member
+/// shares at all. Each must be protected as carefully as the master secret.
+///
+/// It is a distinct type, not a `Secret`, so that it cannot be mistaken for
+/// the master secret. It records the split it belongs to, so that it can only
+/// be combined with that split's shares. Its bytes are zeroized when it is
+/// dropped.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GroupSecretEscrow {
+    header: SplitHeader,
+    group_index: usize,
+    group_secret: GroupSecret,
+}
+
+impl GroupSecretEscrow {
+    /// Recreates an escrowed group secret from its parts, such as those
+    /// recorded by the escrow holder.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SSKRError::GroupSecretIndexInvalid` if `group_index` is not a
+    /// group of the split described by `header`, and the errors
+    /// `Secret::new` gives if `data` is not of a valid secret length.
+    pub fn from_parts(header: SplitHeader, group_index: usize, data: Vec<u8>) -> Result<Self, SSKRError> {
+        let group_count = header.metadata().group_count();
+        if group_index >= group_count {
+            return Err(SSKRError::GroupSecretIndexInvalid { group_index, group_count });
+        }
+        Secret::validate_len(data.len())?;
+        Ok(Self { header, group_index, group_secret: GroupSecret::new(data) })
+    }
+
+    /// Returns the header of the split the group secret belongs to.
+    pub fn header(&self) -> &SplitHeader {
+        &self.header
+    }
+
+    /// Returns the identifier of the split the group secret belongs to.
+    pub fn identifier(&self) -> u32 {
+        self.header.identifier()
+    }
+
+    /// Returns the index of the group.
+    pub fn group_index(&self) -> usize {
+        self.group_index
+    }
+
+    /// Returns the group secret.
+    pub fn group_secret(&self) -> &GroupSecret {
+        &self.group_secret
+    }
+
+    /// Returns the bytes of the group secret.
+    pub fn data(&self) -> &[u8] {
+        self.group_secret.data()
+    }
+}
+
+/// Generates SSKR shares for the given `Spec` and `Secret` using the provided
+/// random number generator, also returning the secret of every group for
+/// escrow.
+///
+/// **The escrowed group secrets weaken the split.** The shares are those
+/// `sskr_generate_using` returns with the same random number generator, but
+/// each escrowed group secret can replace its group's entire member quorum
+/// in a recovery. Use this only where policy requires such a recovery path,
+/// give each escrow to a different holder, and never store them together.
+///
+/// The escrowed group secrets are combined with the shares of the other
+/// groups by [`sskr_combine_with_escrow`].
+///
+/// # Errors
+///
+/// Returns the same errors as `sskr_generate`.
+pub fn sskr_generate_with_group_secrets<R>(
+    spec: &Spec,
+    master_secret: &Secret,
+    random_generator: &mut R
+) -> Result<(GroupedShareBytes, Vec<GroupSecretEscrow>), SSKRError>
+where
+    R: RandomNumberGenerator
+{
+    let (header, group_secrets) = sskr_split_groups(spec, master_secret, random_generator)?;
+    let shares = spec
+        .groups()
+        .iter()
+        .zip(&group_secrets)
+        .enumerate()
+        .map(|(group_index, (group_spec, group_secret))| {
+            sskr_split_group_members(&header, group_index, group_spec, group_secret, random_generator)
+        })
+        .collect::<Result<_, _>>()?;
+    let escrows = group_secrets
+        .into_iter()
+        .enumerate()
+        .map(|(group_index, group_secret)| GroupSecretEscrow { header, group_index, group_secret })
+        .collect();
+    Ok((shares, escrows))
+}
+
+/// Combines SSKR shares with escrowed group secrets of the same split into a
+/// `Secret`.
+///
+/// Each escrowed group secret counts as its group. The group secrets of the
+/// other groups are recovered from their member shares, and the master
+/// secret is recovered from all of them as
+/// [`sskr_combine_from_group_secrets`] recovers it. Member shares of a group
+/// whose secret is escrowed are not used. With no escrows this is
+/// `sskr_combine`.
+///
+/// # Errors
+///
+/// Returns `SSKRError::EscrowMismatch` if the escrows are of different
+/// splits, the mismatch errors of `sskr_combine`, such as
+/// `SSKRError::IdentifierMismatch`, for a share of another split than the
+/// escrows, `SSKRError::GroupRecoveryFailed` if the member shares of a group
+/// are needed but do not recover its secret, and otherwise the errors of
+/// `sskr_combine_from_group_secrets`.
+pub fn sskr_combine_with_escrow<T>(shares: &[T], escrows: &[GroupSecretEscrow]) -> Result<Secret, SSKRError>
+where
+    T: AsRef<[u8]>
+{
+    let Some(first) = escrows.first() else {
+        return sskr_combine(shares);
+    };
+    let header = first.header();
+    if let Some(escrow_index) = escrows.iter().position(|escrow| escrow.header() != header) {
+        return Err(SSKRError::EscrowMismatch { escrow_index });
+    }
+    let metadata = header.metadata();
+
+    let mut entries: Vec<(usize, GroupSecret)> = escrows
+        .iter()
+        .map(|escrow| (escrow.group_index(), escrow.group_secret().clone()))
+        .collect();
+
+    let sskr_shares = deserialize_shares(shares, ReservedBits::Version)?;
+    for (share_index, share) in sskr_shares.iter().enumerate() {
+        if share.identifier() != header.identifier() {
+            return Err(SSKRError::IdentifierMismatch { expected: header.identifier(), found: share.identifier(), share_index });
+        }
+        if share.version() != metadata.version() {
+            return Err(SSKRError::ShareVersionMismatch { expected: metadata.version(), found: share.version(), share_index });
+        }
+        if share.group_threshold() != metadata.group_threshold() {
+            return Err(SSKRError::GroupThresholdMismatch { expected: metadata.group_threshold(), found: share.group_threshold(), share_index });
+        }
+        if share.group_count() != metadata.group_count() {
+            return Err(SSKRError::GroupCountMismatch { expected: metadata.group_count(), found: share.group_count(), share_index });
+        }
+    }
+
+    let needed = metadata.group_threshold().saturating_sub(entries.len());
+    if needed > 0 && !sskr_shares.is_empty() {
+        let mut grouped = group_shares(&sskr_shares)?;
+        grouped.groups.retain(|group| !entries.iter().any(|(group_index, _)| *group_index == group.group_index));
+        let (group_secrets, failure) = recover_group_secrets(metadata.version(), grouped.groups, needed);
+        if group_secrets.len() < needed {
+            if let Some(failure) = failure {
+                return Err(failure);
+            }
+        }
+        entries.extend(group_secrets);
+    }
+
+    sskr_combine_from_group_secrets(&entries, metadata)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{GroupSpec, sskr_generate_using, test_support};
+    
+
+    #[test]
+    fn test_escrow() {
+        let secret = test_support::secret();
+        let spec = Spec::new(2, vec![GroupSpec::new(2, 3).unwrap(), GroupSpec::new(3, 5).unwrap(), GroupSpec::new(2, 2).unwrap()]).unwrap();
+        let (shares, escrows) = sskr_generate_with_group_secrets(&spec, &secret, &mut bc_rand::make_fake_random_number_generator()).unwrap();
+        assert_eq!(shares, sskr_generate_using(&spec, &secret, &mut bc_rand::make_fake_random_number_generator()).unwrap());
+        assert_eq!(escrows.len(), 3);
+        let identifier = u16::from_be_bytes([shares[0][0][0], shares[0][0][1]]) as u32;
+        for (group_index, escrow) in escrows.iter().enumerate() {
+            assert_eq!((escrow.identifier(), escrow.group_index(), escrow.data().len()), (identifier, group_index, 16));
+            assert_eq!(format!("{:?}", escrow.group_secret()), "GroupSecret(16 bytes, redacted)");
+        }
+
+        // An escrowed group secret and a quorum of another group.
+        assert_eq!(sskr_combine_with_escrow(&[&shares[1][4], &shares[1][0], &shares[1][2]], &escrows[..1]).unwrap(), secret);
+        assert_eq!(sskr_combine_with_escrow(&shares[0][1..], &escrows[2..]).unwrap(), secret);
+        // Two escrowed group secrets need no shares, and with none this is
+        // `sskr_combine`.
+        assert_eq!(sskr_combine_with_escrow::<Vec<u8>>(&[], &escrows[1..]).unwrap(), secret);
+        assert_eq!(sskr_combine_with_escrow(&[&shares[0][0], &shares[0][2], &shares[2][0], &shares[2][1]], &[]).unwrap(), secret);
+        // Shares of an escrowed group don't count again.
+        assert!(matches!(sskr_combine_with_escrow(&shares[0][..2], &escrows[..1]), Err(SSKRError::NotEnoughGroups)));
+
+        // An escrow recorded by its holder is recreated from its parts.
+        let restored = GroupSecretEscrow::from_parts(*escrows[0].header(), 0, escrows[0].data().to_vec()).unwrap();
+        assert_eq!(restored, escrows[0]);
+        assert_eq!(sskr_combine_with_escrow(&shares[2], &[restored]).unwrap(), secret);
+        assert!(matches!(
+            GroupSecretEscrow::from_parts(*escrows[0].header(), 3, escrows[0].data().to_vec()),
+            Err(SSKRError::GroupSecretIndexInvalid { group_index: 3, group_count: 3 })
+        ));
+    }
+
+    #[test]
+    fn test_escrow_mismatch() {
+        let secret = test_support::secret();
+        let spec = Spec::new(2, vec![GroupSpec::new(2, 3).unwrap(), GroupSpec::new(2, 3).unwrap()]).unwrap();
+        let mut rng = bc_rand::make_fake_random_number_generator();
+        let (shares, escrows) = sskr_generate_with_group_secrets(&spec, &secret, &mut rng).unwrap();
+        let (other_shares, other_escrows) = sskr_generate_with_group_secrets(&spec, &secret, &mut rng).unwrap();
+        assert_ne!(escrows[0].identifier(), other_escrows[0].identifier());
+
+        assert!(matches!(
+            sskr_combine_with_escrow::<Vec<u8>>(&[], &[escrows[0].clone(), other_escrows[1].clone()]),
+            Err(SSKRError::EscrowMismatch { escrow_index: 1 })
+        ));
+        assert!(matches!(
+            sskr_combine_with_escrow(&[&shares[1][0], &other_shares[1][1]], &escrows[..1]),
+            Err(SSKRError::IdentifierMismatch { share_index: 1, .. })
+        ));
+        assert_eq!(sskr_combine_with_escrow(&other_shares[1][1..], &other_escrows[..1]).unwrap(), secret);
+    }
+}
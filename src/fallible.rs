@@ -0,0 +1,171 @@
+use std::convert::Infallible;
+
+use bc_rand::RandomNumberGenerator;
+use rand_core::{CryptoRng, RngCore};
+
+use crate::{SSKRError, SSKRShare, Secret, Spec, sskr_generate_iter};
+
+/// A source of random data that can fail, such as a hardware random number
+/// generator or an HSM.
+///
+/// Every `RandomNumberGenerator` is a `FallibleRandomNumberGenerator` that
+/// never fails.
+pub trait FallibleRandomNumberGenerator {
+    /// The error the generator fails with.
+    type Error: std::error::Error + Send + Sync + 'static;
+
+    /// Fills `data` with random bytes, or fails.
+    fn try_fill_random_data(&mut self, data: &mut [u8]) -> Result<(), Self::Error>;
+}
+
+impl<R> FallibleRandomNumberGenerator for R
+where
+    R: RandomNumberGenerator
+{
+    type Error = Infallible;
+
+    fn try_fill_random_data(&mut self, data: &mut [u8]) -> Result<(), Self::Error> {
+        self.fill_random_data(data);
+        Ok(())
+    }
+}
+
+/// Generates SSKR shares for the given `Spec` and `Secret` using the provided
+/// random number generator, which may fail.
+///
+/// With a generator that does not fail, the shares are those
+/// `sskr_generate_using` returns with it. If the generator fails, generation
+/// stops at once: no more random data is requested, and the group secrets and
+/// shares computed so far are zeroized before the error is returned.
+///
+/// # Errors
+///
+/// Returns `SSKRError::RandomnessUnavailable` with the generator's error if
+/// the generator fails, and otherwise the same errors as `sskr_generate`.
+pub fn sskr_try_generate_using<R>(
+    spec: &Spec,
+    master_secret: &Secret,
+    random_generator: &mut R
+) -> Result<Vec<Vec<Vec<u8>>>, SSKRError>
+where
+    R: FallibleRandomNumberGenerator
+{
+    let mut recorder = FailureRecorder { random_generator, error: None };
+    let mut iter = sskr_generate_iter(spec, master_secret, &mut recorder)?;
+
+    // The shares are kept unserialized, so that their values are zeroized if
+    // the generator fails partway through, as the group secrets not yet split
+    // are when the iterator is dropped.
+    let mut shares: Vec<Vec<SSKRShare>> = spec.groups().iter().map(|group| Vec::with_capacity(group.member_count())).collect();
+    loop {
+        let share = iter.next_share();
+        if let Some(error) = iter.random_generator().error.take() {
+            return Err(SSKRError::RandomnessUnavailable(Box::new(error)));
+        }
+        let Some((group_index, _, share)) = share else {
+            break;
+        };
+        shares[group_index].push(share);
+    }
+    Ok(shares.iter().map(|group| group.iter().map(SSKRShare::serialize).collect()).collect())
+}
+
+/// Adapts a `FallibleRandomNumberGenerator` to a `RandomNumberGenerator`,
+/// recording its first failure. Once it has failed, it yields zeros without
+/// calling the generator again.
+struct FailureRecorder<'a, R>
+where
+    R: FallibleRandomNumberGenerator
+{
+    random_generator: &'a mut R,
+    error: Option<R::Error>,
+}
+
+impl<R> RngCore for FailureRecorder<'_, R>
+where
+    R: FallibleRandomNumberGenerator
+{
+    fn next_u32(&mut self) -> u32 {
+        rand_core::impls::next_u32_via_fill(self)
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        rand_core::impls::next_u64_via_fill(self)
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        if self.error.is_none() {
+            match self.random_generator.try_fill_random_data(dest) {
+                Ok(()) => return,
+                Err(error) => self.error = Some(error),
+            }
+        }
+        dest.fill(0);
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand_core::Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
+
+impl<R> CryptoRng for FailureRecorder<'_, R> where R: FallibleRandomNumberGenerator {}
+
+impl<R> RandomNumberGenerator for FailureRecorder<'_, R> where R: FallibleRandomNumberGenerator {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{GroupSpec, sskr_combine, sskr_generate_using, test_support};
+    
+
+    #[derive(Debug, thiserror::Error)]
+    #[error("device unplugged")]
+    struct DeviceError;
+
+    /// Fails on the call numbered `fail_at`, counting from zero.
+    struct FailingRandomNumberGenerator {
+        calls: usize,
+        fail_at: usize,
+        inner: bc_rand::SecureRandomNumberGenerator,
+    }
+
+    impl FallibleRandomNumberGenerator for FailingRandomNumberGenerator {
+        type Error = DeviceError;
+
+        fn try_fill_random_data(&mut self, data: &mut [u8]) -> Result<(), Self::Error> {
+            self.calls += 1;
+            if self.calls - 1 == self.fail_at {
+                return Err(DeviceError);
+            }
+            self.inner.fill_random_data(data);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_try_generate() {
+        let secret = test_support::secret();
+        let spec = Spec::new(2, vec![GroupSpec::new(2, 3).unwrap(), GroupSpec::new(3, 5).unwrap()]).unwrap();
+
+        let shares = sskr_try_generate_using(&spec, &secret, &mut bc_rand::make_fake_random_number_generator()).unwrap();
+        assert_eq!(shares, sskr_generate_using(&spec, &secret, &mut bc_rand::make_fake_random_number_generator()).unwrap());
+
+        // The identifier, the digest of the top-level split, then the seed
+        // of each group.
+        let mut rng = FailingRandomNumberGenerator { calls: 0, fail_at: usize::MAX, inner: bc_rand::SecureRandomNumberGenerator };
+        let shares = sskr_try_generate_using(&spec, &secret, &mut rng).unwrap();
+        assert_eq!(rng.calls, 4);
+        assert_eq!(sskr_combine(&[&shares[0][0], &shares[0][1], &shares[1][4], &shares[1][2], &shares[1][0]]).unwrap(), secret);
+
+        for fail_at in 0..4 {
+            let mut rng = FailingRandomNumberGenerator { calls: 0, fail_at, inner: bc_rand::SecureRandomNumberGenerator };
+            let error = sskr_try_generate_using(&spec, &secret, &mut rng).unwrap_err();
+            assert!(matches!(&error, SSKRError::RandomnessUnavailable(source) if source.is::<DeviceError>()));
+            assert_eq!(error.to_string(), "SSKR randomness is unavailable: device unplugged");
+            assert_eq!(error.code(), "randomness_unavailable");
+            // Generation stops at the failure.
+            assert_eq!(rng.calls, fail_at + 1);
+        }
+    }
+}
@@ -0,0 +1,50 @@
+use bc_crypto::sha256;
+
+use crate::FINGERPRINT_SIZE_BYTES;
+
+/// An algorithm for the fingerprint of the master secret that shares can
+/// carry, as
+/// [`GenerateOptions::with_embedded_fingerprint`](crate::GenerateOptions::with_embedded_fingerprint)
+/// embeds it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[non_exhaustive]
+pub enum FingerprintAlg {
+    /// The first `FINGERPRINT_SIZE_BYTES` bytes of the SHA-256 digest of the
+    /// secret's bytes, carried by `ShareVersion::V3` shares.
+    Sha256Trunc4,
+}
+
+impl FingerprintAlg {
+    /// Returns the fingerprint of `secret` under this algorithm.
+    pub(crate) fn fingerprint(&self, secret: &[u8]) -> [u8; FINGERPRINT_SIZE_BYTES] {
+        match self {
+            FingerprintAlg::Sha256Trunc4 => {
+                let digest = sha256(secret);
+                let mut fingerprint = [0u8; FINGERPRINT_SIZE_BYTES];
+                fingerprint.copy_from_slice(&digest[..FINGERPRINT_SIZE_BYTES]);
+                fingerprint
+            }
+        }
+    }
+}
+
+/// Returns whether two fingerprints are equal, taking the same time wherever
+/// they differ.
+pub(crate) fn fingerprints_match(a: &[u8; FINGERPRINT_SIZE_BYTES], b: &[u8; FINGERPRINT_SIZE_BYTES]) -> bool {
+    a.iter().zip(b).fold(0u8, |acc, (a, b)| acc | (a ^ b)) == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hex_literal::hex;
+
+    #[test]
+    fn test_sha256_trunc4() {
+        // SHA-256 of "abc" is ba7816bf...
+        assert_eq!(FingerprintAlg::Sha256Trunc4.fingerprint(b"abc"), hex!("ba7816bf"));
+        assert!(fingerprints_match(&hex!("ba7816bf"), &hex!("ba7816bf")));
+        assert!(!fingerprints_match(&hex!("ba7816bf"), &hex!("ba7816be")));
+    }
+}
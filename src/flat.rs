@@ -0,0 +1,99 @@
+use bc_rand::RandomNumberGenerator;
+
+use crate::{MAX_SHARE_COUNT, SSKRError, Secret, ShareVersion, encoding::{recover_secret, split_secret}};
+
+/// Splits `secret` into `count` bare Shamir shares, any `threshold` of which
+/// can recover it.
+///
+/// **These are not SSKR shares.** They are the payloads that
+/// `bc_shamir::split_secret` produces, with no SSKR metadata header, so they
+/// record neither their index nor their split. The caller must keep track of
+/// the index of each share, which is its position in the result, and must
+/// combine them with [`sskr_combine_flat`]; `sskr_combine` rejects them,
+/// and the values of SSKR shares cannot be mixed with them.
+///
+/// # Errors
+///
+/// Returns `SSKRError::ShamirError` if `threshold` is zero or more than
+/// `count`, or `count` is more than `MAX_SHARE_COUNT`.
+pub fn sskr_split_flat(
+    threshold: usize,
+    count: usize,
+    secret: &Secret,
+    random_generator: &mut impl RandomNumberGenerator
+) -> Result<Vec<Vec<u8>>, SSKRError> {
+    split_secret(ShareVersion::V0, threshold, count, secret.data(), random_generator)
+}
+
+/// Recovers a secret from bare Shamir shares, as produced by
+/// [`sskr_split_flat`] or `bc_shamir::split_secret`.
+///
+/// `indexes[i]` is the index of `shares[i]` in its split. Exactly a
+/// threshold of shares must be given, as with `bc_shamir::recover_secret`.
+///
+/// # Errors
+///
+/// Returns `SSKRError::FlatIndexCountMismatch` if there is not one index per
+/// share, `SSKRError::MemberIndexInvalid` for an index of `MAX_SHARE_COUNT`
+/// or more, `SSKRError::DuplicateMemberIndex` for an index given twice, and
+/// `SSKRError::ShamirError` if the shares do not recover a secret, as when
+/// there are too few of them or they are SSKR shares.
+pub fn sskr_combine_flat<T>(indexes: &[usize], shares: &[T]) -> Result<Secret, SSKRError>
+where
+    T: AsRef<[u8]>
+{
+    if indexes.len() != shares.len() {
+        return Err(SSKRError::FlatIndexCountMismatch { indexes: indexes.len(), shares: shares.len() });
+    }
+    if let Some(&member_index) = indexes.iter().find(|&&index| index >= MAX_SHARE_COUNT) {
+        return Err(SSKRError::MemberIndexInvalid { member_index, max: MAX_SHARE_COUNT });
+    }
+    if indexes.iter().enumerate().any(|(i, index)| indexes[..i].contains(index)) {
+        return Err(SSKRError::DuplicateMemberIndex);
+    }
+    let secret = recover_secret(ShareVersion::V0, indexes, shares).map_err(SSKRError::ShamirError)?;
+    Secret::from_vec(secret)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{GroupSpec, Spec, sskr_combine, sskr_generate_using, test_support};
+    
+
+    #[test]
+    fn test_flat_round_trip() {
+        let secret = test_support::secret();
+        let mut rng = bc_rand::make_fake_random_number_generator();
+        let shares = sskr_split_flat(3, 5, &secret, &mut rng).unwrap();
+        assert!(shares.iter().all(|share| share.len() == 16));
+        assert_eq!(shares, bc_shamir::split_secret(3, 5, secret.data(), &mut bc_rand::make_fake_random_number_generator()).unwrap());
+
+        assert_eq!(sskr_combine_flat(&[4, 0, 2], &[&shares[4], &shares[0], &shares[2]]).unwrap(), secret);
+        assert!(matches!(sskr_combine_flat(&[4, 0], &[&shares[4], &shares[0]]), Err(SSKRError::ShamirError(_))));
+        assert!(matches!(
+            sskr_combine_flat(&[4, 0, 1], &[&shares[4], &shares[0]]),
+            Err(SSKRError::FlatIndexCountMismatch { indexes: 3, shares: 2 })
+        ));
+        assert!(matches!(
+            sskr_combine_flat(&[4, 0, 16], &[&shares[4], &shares[0], &shares[1]]),
+            Err(SSKRError::MemberIndexInvalid { member_index: 16, max: 16 })
+        ));
+        assert!(matches!(
+            sskr_combine_flat(&[4, 0, 4], &[&shares[4], &shares[0], &shares[4]]),
+            Err(SSKRError::DuplicateMemberIndex)
+        ));
+        assert!(matches!(sskr_split_flat(6, 5, &secret, &mut rng), Err(SSKRError::ShamirError(_))));
+    }
+
+    #[test]
+    fn test_flat_rejects_sskr_shares() {
+        let secret = test_support::secret();
+        let flat = sskr_split_flat(2, 3, &secret, &mut bc_rand::make_fake_random_number_generator()).unwrap();
+        assert!(sskr_combine(&flat[..2]).is_err());
+
+        let spec = Spec::new(1, vec![GroupSpec::new(2, 3).unwrap()]).unwrap();
+        let sskr = sskr_generate_using(&spec, &secret, &mut bc_rand::make_fake_random_number_generator()).unwrap();
+        assert!(matches!(sskr_combine_flat(&[0, 1], &sskr[0][..2]), Err(SSKRError::ShamirError(_))));
+    }
+}
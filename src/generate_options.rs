@@ -0,0 +1,589 @@
+use std::{collections::BTreeSet, fmt};
+
+use bc_rand::RandomNumberGenerator;
+
+use crate::{
+    FingerprintAlg,
+    SSKRError,
+    Secret,
+    ShareVersion,
+    Spec,
+    digest_member::{append_digest_members, digest_member_index},
+    encoding::{generate_serialized, generate_serialized_with_identifier, random_identifier},
+    rng_health::check_rng_health,
+};
+
+/// Options for [`sskr_generate_with_options`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct GenerateOptions {
+    copies_per_member: usize,
+    identifier: Option<u32>,
+    avoided_identifiers: BTreeSet<u32>,
+    rng_health_check: bool,
+    digest_member: bool,
+    embedded_fingerprint: Option<FingerprintAlg>,
+}
+
+impl GenerateOptions {
+    /// The number of times an identifier that is to be avoided is redrawn
+    /// before one is chosen from those that remain.
+    pub const MAX_IDENTIFIER_DRAWS: usize = 16;
+
+    /// Creates the default options: one copy of each member's share, a
+    /// random identifier, no health check of the random number generator,
+    /// no digest member, and no embedded fingerprint.
+    pub fn new() -> Self {
+        Self {
+            copies_per_member: 1,
+            identifier: None,
+            avoided_identifiers: BTreeSet::new(),
+            rng_health_check: false,
+            digest_member: false,
+            embedded_fingerprint: None,
+        }
+    }
+
+    /// Returns these options with `copies` copies of each member's share.
+    pub fn with_copies_per_member(mut self, copies: usize) -> Self {
+        self.copies_per_member = copies;
+        self
+    }
+
+    /// Returns the number of copies of each member's share.
+    pub fn copies_per_member(&self) -> usize {
+        self.copies_per_member
+    }
+
+    /// Returns these options with every split given `identifier` rather than
+    /// a random identifier.
+    ///
+    /// This is a privacy mode. The identifier of a split is the same in all
+    /// of its shares, so anyone who observes shares, such as in transit, can
+    /// tell which belong together by it. With a constant identifier, such as
+    /// zero, shared by every split, the identifier reveals nothing.
+    ///
+    /// The cost is that the identifier no longer tells splits apart when the
+    /// shares are combined. Combine such shares with
+    /// [`sskr_combine_ignoring_identifier`](crate::sskr_combine_ignoring_identifier),
+    /// and keep the shares of different splits apart: shares of two splits
+    /// that are mixed up are caught only when the recovered values fail the
+    /// checksum of the split, not by their metadata. See
+    /// [`sskr_generate_with_identifier`](crate::sskr_generate_with_identifier).
+    ///
+    /// The shares are generated in a format with a 16-bit identifier, so
+    /// generation fails with `SSKRError::IdentifierInvalid` if `identifier`
+    /// is greater than `0xffff`.
+    pub fn with_identifier(mut self, identifier: u32) -> Self {
+        self.identifier = Some(identifier);
+        self
+    }
+
+    /// Returns the identifier every split is given, or `None` if each is
+    /// given a random identifier.
+    pub fn identifier(&self) -> Option<u32> {
+        self.identifier
+    }
+
+    /// Returns these options with the random identifier drawn from outside
+    /// `identifiers`, such as those of splits already in use.
+    ///
+    /// The identifier is still drawn from the random number generator. It is
+    /// redrawn while it is one to be avoided, up to
+    /// [`MAX_IDENTIFIER_DRAWS`](Self::MAX_IDENTIFIER_DRAWS) times, and then
+    /// chosen at random from the identifiers that remain. If the first draw is
+    /// free, the shares are the same as those made without the option.
+    ///
+    /// The identifiers to avoid add to any given before. They do not apply
+    /// to an identifier fixed with [`with_identifier`](Self::with_identifier).
+    /// Identifiers greater than `0xffff` can never be drawn, so avoiding them
+    /// has no effect.
+    pub fn with_avoided_identifiers(mut self, identifiers: &[u32]) -> Self {
+        self.avoided_identifiers.extend(identifiers);
+        self
+    }
+
+    /// Returns the identifiers the random identifier is drawn from outside.
+    pub fn avoided_identifiers(&self) -> &BTreeSet<u32> {
+        &self.avoided_identifiers
+    }
+
+    /// Returns these options with the health check of the random number
+    /// generator enabled or disabled.
+    ///
+    /// When enabled, a sample is drawn from the generator before splitting
+    /// and checked for the signs of a catastrophically broken generator:
+    /// output that is constant, that restarts on every call, or that repeats
+    /// a byte far more often than chance allows, in the spirit of the health
+    /// tests of NIST SP 800-90B. A generator that fails is rejected with
+    /// `SSKRError::WeakRandomness` and nothing is split.
+    ///
+    /// The check is a tripwire, not a guarantee: it catches a generator that
+    /// is badly broken, but not one that is merely predictable, such as a
+    /// PRNG with a guessable seed. It consumes output from the generator, so
+    /// leave it disabled for deterministic generators whose shares must be
+    /// reproducible, such as in tests.
+    pub fn with_rng_health_check(mut self, enabled: bool) -> Self {
+        self.rng_health_check = enabled;
+        self
+    }
+
+    /// Returns `true` if the random number generator is checked before
+    /// splitting.
+    pub fn rng_health_check(&self) -> bool {
+        self.rng_health_check
+    }
+
+    /// Returns these options with a digest member added to each group, or
+    /// not.
+    ///
+    /// The digest member is an extra share in each group, after its other
+    /// members, that holds an HMAC-SHA256 digest of the group secret rather
+    /// than a point of the group's polynomial, much as SLIP-39 reserves a
+    /// share index for a digest of the secret. It takes the highest member
+    /// index of the shares' format, so a group can have one member fewer.
+    /// Combined with [`sskr_combine_with_digest_member`](crate::sskr_combine_with_digest_member),
+    /// it lets a group secret recovered from corrupt shares be detected even
+    /// where the split's checksum cannot detect it, as in a group with a
+    /// member threshold of one, whose share is its group secret.
+    ///
+    /// The digest member is not a share of the group's secret, but it is
+    /// formatted as one, and to `sskr_combine` and to other SSKR
+    /// implementations it is indistinguishable from one. Keep it out of the
+    /// shares given to them: in place of a member's share it makes recovery
+    /// fail. The other shares are those made without the option.
+    pub fn with_digest_member(mut self, enabled: bool) -> Self {
+        self.digest_member = enabled;
+        self
+    }
+
+    /// Returns `true` if a digest member is added to each group.
+    pub fn digest_member(&self) -> bool {
+        self.digest_member
+    }
+
+    /// Returns these options with a fingerprint of the master secret,
+    /// computed with `alg`, embedded in every share.
+    ///
+    /// The shares are generated in the fingerprinted format,
+    /// `ShareVersion::V3`, and `sskr_combine` checks the secret it recovers
+    /// against the fingerprint they carry, failing with
+    /// `SSKRError::FingerprintMismatch` rather than returning a secret that
+    /// does not match. This catches what the split's checksums cannot, such
+    /// as shares of a group with a member threshold of one that were
+    /// altered. **Older versions of this crate and other SSKR implementations
+    /// reject these shares** as being of an unsupported format.
+    ///
+    /// **The fingerprint is a public digest of the secret.** Anyone who holds
+    /// a single share learns it, and with it can confirm a guess of the
+    /// secret, such as one of a low-entropy secret, or recognize the shares
+    /// of the same secret split again, or the secret itself wherever its
+    /// fingerprint appears. At `FINGERPRINT_SIZE_BYTES` bytes it confirms a
+    /// guess only to about one chance in four billion, but leave it off for
+    /// secrets that must not be linkable to their shares.
+    pub fn with_embedded_fingerprint(mut self, alg: FingerprintAlg) -> Self {
+        self.embedded_fingerprint = Some(alg);
+        self
+    }
+
+    /// Returns the algorithm of the fingerprint embedded in every share, or
+    /// `None` if the shares carry none.
+    pub fn embedded_fingerprint(&self) -> Option<FingerprintAlg> {
+        self.embedded_fingerprint
+    }
+}
+
+impl Default for GenerateOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// One copy of a member's share, as returned by
+/// [`sskr_generate_with_options`].
+///
+/// Every copy of a share has the same serialized bytes; the copy index is
+/// not recorded in them, so that any copy combines as the share itself.
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct ShareCopy {
+    group_index: usize,
+    member_index: usize,
+    copy_index: usize,
+    copy_count: usize,
+    share: Vec<u8>,
+}
+
+impl ShareCopy {
+    /// Returns the index of the share's group.
+    pub fn group_index(&self) -> usize {
+        self.group_index
+    }
+
+    /// Returns the index of the share within its group.
+    pub fn member_index(&self) -> usize {
+        self.member_index
+    }
+
+    /// Returns which copy of the share this is.
+    pub fn copy_index(&self) -> usize {
+        self.copy_index
+    }
+
+    /// Returns the number of copies made of the share.
+    pub fn copy_count(&self) -> usize {
+        self.copy_count
+    }
+
+    /// Returns the serialized share.
+    pub fn share(&self) -> &[u8] {
+        &self.share
+    }
+
+    /// Consumes the copy, returning the serialized share.
+    pub fn into_share(self) -> Vec<u8> {
+        self.share
+    }
+}
+
+impl AsRef<[u8]> for ShareCopy {
+    fn as_ref(&self) -> &[u8] {
+        &self.share
+    }
+}
+
+/// Labels the copy without revealing the share. Group, member and copy
+/// numbers are shown one-based, as they would be to a user.
+impl fmt::Display for ShareCopy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "group {} member {} copy {} of {}",
+            self.group_index + 1,
+            self.member_index + 1,
+            self.copy_index + 1,
+            self.copy_count
+        )
+    }
+}
+
+/// The share is redacted so that copies can be logged safely.
+impl fmt::Debug for ShareCopy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ShareCopy")
+            .field("group_index", &self.group_index)
+            .field("member_index", &self.member_index)
+            .field("copy_index", &self.copy_index)
+            .field("copy_count", &self.copy_count)
+            .field("share", &format_args!("<{} bytes redacted>", self.share.len()))
+            .finish()
+    }
+}
+
+/// Generates SSKR shares for the given `Spec` and `Secret` with the given
+/// options.
+///
+/// See [`sskr_generate_with_options_using`].
+#[cfg(feature = "default-rng")]
+pub fn sskr_generate_with_options(
+    spec: &Spec,
+    master_secret: &Secret,
+    options: &GenerateOptions
+) -> Result<Vec<Vec<ShareCopy>>, SSKRError> {
+    let mut rng = bc_rand::SecureRandomNumberGenerator;
+    sskr_generate_with_options_using(spec, master_secret, options, &mut rng)
+}
+
+/// Generates SSKR shares for the given `Spec` and `Secret` with the given
+/// options, using the provided random number generator.
+///
+/// The shares are those `sskr_generate_using` returns with the same random
+/// number generator, or `sskr_generate_with_identifier_using` if the options
+/// fix the identifier, grouped by group, with each member's share repeated
+/// `options.copies_per_member()` times in a row, and with a digest member
+/// added to the end of each group if the options ask for one. With an
+/// embedded fingerprint they are instead in the fingerprinted format. Combining
+/// tolerates identical shares, so any copy of a share can stand in for any
+/// other, and two copies of one share count as that one share.
+///
+/// # Errors
+///
+/// Returns `SSKRError::CopiesPerMemberInvalid` if `options` asks for no
+/// copies, `SSKRError::IdentifierInvalid` if the options fix an identifier
+/// greater than `0xffff`, `SSKRError::IdentifierSpaceExhausted` if the
+/// options avoid every identifier, `SSKRError::WeakRandomness` if the options enable the health check
+/// and the generator fails it, `SSKRError::DigestMemberUnavailable` if the
+/// options ask for a digest member and a group has no member index left for
+/// it, and otherwise the same errors as
+/// `sskr_generate`.
+pub fn sskr_generate_with_options_using(
+    spec: &Spec,
+    master_secret: &Secret,
+    options: &GenerateOptions,
+    random_generator: &mut impl RandomNumberGenerator
+) -> Result<Vec<Vec<ShareCopy>>, SSKRError> {
+    let copy_count = options.copies_per_member();
+    if copy_count == 0 {
+        return Err(SSKRError::CopiesPerMemberInvalid);
+    }
+    if options.rng_health_check() {
+        check_rng_health(random_generator)?;
+    }
+    let version = match options.embedded_fingerprint() {
+        Some(FingerprintAlg::Sha256Trunc4) => ShareVersion::V3,
+        None => ShareVersion::V0,
+    };
+    let mut shares = match options.identifier() {
+        Some(identifier) => generate_serialized_with_identifier(spec, master_secret, version, identifier, 0, random_generator)?,
+        None if options.avoided_identifiers().is_empty() => generate_serialized(spec, master_secret, version, 0, random_generator)?.1,
+        None => {
+            spec.validate_for_version(version)?;
+            let identifier = draw_identifier(version, options.avoided_identifiers(), random_generator)?;
+            generate_serialized_with_identifier(spec, master_secret, version, identifier, 0, random_generator)?
+        }
+    };
+    if options.digest_member() {
+        append_digest_members(&mut shares)?;
+    }
+    Ok(shares
+        .into_iter()
+        .enumerate()
+        .map(|(group_index, group)| {
+            let member_count = spec.groups()[group_index].member_count();
+            group
+                .into_iter()
+                .enumerate()
+                .flat_map(|(member_index, share)| {
+                    // The digest member follows the members of the spec.
+                    let member_index = if member_index < member_count {
+                        member_index
+                    } else {
+                        digest_member_index(version.max_share_count())
+                    };
+                    (0..copy_count).map(move |copy_index| ShareCopy {
+                        group_index,
+                        member_index,
+                        copy_index,
+                        copy_count,
+                        share: share.clone(),
+                    })
+                })
+                .collect()
+        })
+        .collect())
+}
+
+/// Draws an identifier for a split in the format `version` from
+/// `random_generator` as `sskr_generate_using` does, redrawing it while it is
+/// in `avoided` up to `GenerateOptions::MAX_IDENTIFIER_DRAWS` times, and then
+/// choosing one at random from those that remain, so that a generator that
+/// keeps returning an avoided identifier cannot stall it.
+///
+/// Fails with `SSKRError::IdentifierSpaceExhausted` if every identifier of
+/// the format is avoided.
+pub(crate) fn draw_identifier(
+    version: ShareVersion,
+    avoided: &BTreeSet<u32>,
+    random_generator: &mut impl RandomNumberGenerator
+) -> Result<u32, SSKRError> {
+    for _ in 0..GenerateOptions::MAX_IDENTIFIER_DRAWS {
+        let identifier = random_identifier(version, random_generator);
+        if !avoided.contains(&identifier) {
+            return Ok(identifier);
+        }
+    }
+    let max_identifier = (1u64 << (version.identifier_size_bytes() * 8)) - 1;
+    let avoided_in_range = avoided.range(..=max_identifier as u32);
+    let avoided_count = avoided_in_range.clone().count();
+    if avoided_count as u64 > max_identifier {
+        return Err(SSKRError::IdentifierSpaceExhausted { avoided: avoided_count });
+    }
+    // Choose the identifier at a random index among those not avoided, by
+    // stepping over each avoided one at or below it. The index is reduced
+    // from 64 random bits rather than drawn by rejection, which a generator
+    // with constant output could stall; the bias is below 2^-32.
+    let free_count = max_identifier + 1 - avoided_count as u64;
+    let mut identifier = random_generator.next_u64() % free_count;
+    for &avoided_identifier in avoided_in_range {
+        if avoided_identifier as u64 > identifier {
+            break;
+        }
+        identifier += 1;
+    }
+    Ok(identifier as u32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{CryptoRngAdapter, FINGERPRINTED_METADATA_SIZE_BYTES, SHARE_VERSION_ESCAPE, GroupSpec, sskr_combine, sskr_combine_ignoring_identifier, sskr_generate_using, test_support::{self, ZeroRandomNumberGenerator}};
+    use rand_chacha::ChaCha20Rng;
+    use rand_core::SeedableRng;
+    use hex_literal::hex;
+
+    #[test]
+    fn test_generate_copies() {
+        let secret = test_support::secret();
+        let spec = Spec::new(1, vec![GroupSpec::new(2, 3).unwrap()]).unwrap();
+        let options = GenerateOptions::new().with_copies_per_member(2);
+        assert_eq!(options.copies_per_member(), 2);
+        let copies = sskr_generate_with_options_using(&spec, &secret, &options, &mut bc_rand::make_fake_random_number_generator()).unwrap();
+        let shares = sskr_generate_using(&spec, &secret, &mut bc_rand::make_fake_random_number_generator()).unwrap();
+
+        let group = &copies[0];
+        assert_eq!(group.len(), 6);
+        for (i, copy) in group.iter().enumerate() {
+            assert_eq!((copy.group_index(), copy.member_index(), copy.copy_index(), copy.copy_count()), (0, i / 2, i % 2, 2));
+            assert_eq!(copy.share(), shares[0][i / 2]);
+        }
+        assert_eq!(group[3].to_string(), "group 1 member 2 copy 2 of 2");
+        assert_eq!(format!("{:?}", group[3]), "ShareCopy { group_index: 0, member_index: 1, copy_index: 1, copy_count: 2, share: <21 bytes redacted> }");
+
+        // Two copies of one share and one other share recover the secret, but
+        // two copies alone are one share.
+        assert_eq!(sskr_combine(&[&group[0], &group[1], &group[4]]).unwrap(), secret);
+        assert!(matches!(
+            sskr_combine(&group[2..4]),
+            Err(SSKRError::NotEnoughMemberShares { group_index: 0, have: 1, need: 2 })
+        ));
+
+        let single = sskr_generate_with_options_using(&spec, &secret, &GenerateOptions::default(), &mut bc_rand::make_fake_random_number_generator()).unwrap();
+        assert_eq!(single[0].iter().map(|copy| copy.clone().into_share()).collect::<Vec<_>>(), shares[0]);
+        assert!(matches!(
+            sskr_generate_with_options_using(&spec, &secret, &GenerateOptions::new().with_copies_per_member(0), &mut bc_rand::SecureRandomNumberGenerator),
+            Err(SSKRError::CopiesPerMemberInvalid)
+        ));
+    }
+
+    #[test]
+    fn test_generate_private() {
+        let secret = test_support::secret();
+        let other_secret = Secret::new(hex!("00112233445566778899aabbccddeeff")).unwrap();
+        let spec = Spec::new(2, vec![GroupSpec::new(2, 3).unwrap(), GroupSpec::new(2, 3).unwrap()]).unwrap();
+        let options = GenerateOptions::new().with_identifier(0);
+        assert_eq!(options.identifier(), Some(0));
+        assert_eq!(GenerateOptions::new().identifier(), None);
+
+        let mut rng = bc_rand::make_fake_random_number_generator();
+        let shares = sskr_generate_with_options_using(&spec, &secret, &options, &mut rng).unwrap();
+        let other = sskr_generate_with_options_using(&spec, &other_secret, &options, &mut rng).unwrap();
+        assert!(shares.iter().chain(&other).flatten().all(|copy| copy.share()[..2] == [0, 0]));
+
+        assert_eq!(sskr_combine_ignoring_identifier(&[&shares[0][0], &shares[0][2], &shares[1][1], &shares[1][0]]).unwrap(), secret);
+        assert_eq!(sskr_combine_ignoring_identifier(&[&other[1][2], &other[1][0], &other[0][1], &other[0][2]]).unwrap(), other_secret);
+
+        // Mixed up, the shares of the two splits fail only at the checksum.
+        assert!(matches!(
+            sskr_combine_ignoring_identifier(&[&shares[0][0], &other[0][2], &shares[1][1], &shares[1][0]]),
+            Err(SSKRError::GroupRecoveryFailed { group_index: 0, .. })
+        ));
+        assert!(matches!(
+            sskr_combine_ignoring_identifier(&[&shares[0][0], &shares[0][2], &other[1][1], &other[1][0]]),
+            Err(SSKRError::MasterRecoveryFailed { .. })
+        ));
+        assert!(matches!(sskr_combine_ignoring_identifier::<Vec<u8>>(&[]), Err(SSKRError::SharesEmpty)));
+    }
+
+    #[test]
+    fn test_rng_health_check() {
+        let secret = test_support::secret();
+        let spec = Spec::new(1, vec![GroupSpec::new(2, 3).unwrap()]).unwrap();
+        let options = GenerateOptions::new().with_rng_health_check(true);
+        assert!(options.rng_health_check());
+        assert!(!GenerateOptions::new().rng_health_check());
+
+        let shares = sskr_generate_with_options_using(&spec, &secret, &options, &mut CryptoRngAdapter::new(ChaCha20Rng::seed_from_u64(42))).unwrap();
+        assert_eq!(sskr_combine(&shares[0][1..]).unwrap(), secret);
+
+        // A generator that only ever returns zeros is rejected, but is still
+        // accepted with the check left off.
+        let mut zeros = ZeroRandomNumberGenerator;
+        assert!(matches!(
+            sskr_generate_with_options_using(&spec, &secret, &options, &mut zeros),
+            Err(SSKRError::WeakRandomness { test: "constant output" })
+        ));
+        assert!(sskr_generate_with_options_using(&spec, &secret, &GenerateOptions::new(), &mut zeros).is_ok());
+    }
+
+    #[test]
+    fn test_avoided_identifiers() {
+        let secret = test_support::secret();
+        let spec = Spec::new(1, vec![GroupSpec::new(2, 3).unwrap()]).unwrap();
+        let identifier = |shares: &[Vec<ShareCopy>]| u16::from_be_bytes([shares[0][0].share()[0], shares[0][0].share()[1]]) as u32;
+        let generate = |options: &GenerateOptions| {
+            sskr_generate_with_options_using(&spec, &secret, options, &mut bc_rand::make_fake_random_number_generator())
+        };
+
+        // A first draw that is free is kept.
+        let plain = generate(&GenerateOptions::new()).unwrap();
+        let first = identifier(&plain);
+        assert_eq!(generate(&GenerateOptions::new().with_avoided_identifiers(&[first ^ 1])).unwrap(), plain);
+
+        let options = GenerateOptions::new().with_avoided_identifiers(&[first]).with_avoided_identifiers(&[first ^ 1]);
+        assert_eq!(options.avoided_identifiers().len(), 2);
+        let shares = generate(&options).unwrap();
+        assert!(!options.avoided_identifiers().contains(&identifier(&shares)));
+        assert_eq!(sskr_combine(&shares[0][1..]).unwrap(), secret);
+
+        // With all but one identifier avoided, that one is chosen.
+        let taken: Vec<u32> = (0..=u16::MAX as u32).filter(|&identifier| identifier != 0x1234).collect();
+        let shares = generate(&GenerateOptions::new().with_avoided_identifiers(&taken)).unwrap();
+        assert_eq!(identifier(&shares), 0x1234);
+        assert_eq!(sskr_combine(&shares[0][..2]).unwrap(), secret);
+
+        let full = GenerateOptions::new().with_avoided_identifiers(&taken).with_avoided_identifiers(&[0x1234]);
+        assert!(matches!(generate(&full), Err(SSKRError::IdentifierSpaceExhausted { avoided: 65536 })));
+
+        // Identifiers that can never be drawn are not counted.
+        let wide = full.clone().with_avoided_identifiers(&[0x1_0000]);
+        assert!(matches!(generate(&wide), Err(SSKRError::IdentifierSpaceExhausted { avoided: 65536 })));
+
+        // A fixed identifier is not subject to the list, but must fit in the
+        // identifier of the shares.
+        assert_eq!(identifier(&generate(&full.clone().with_identifier(0x1234)).unwrap()), 0x1234);
+        assert!(matches!(
+            generate(&full.with_identifier(0x1_0000)),
+            Err(SSKRError::IdentifierInvalid { identifier: 0x1_0000, version: ShareVersion::V0 })
+        ));
+    }
+
+    #[test]
+    fn test_embedded_fingerprint() {
+        let secret = test_support::secret();
+        let spec = Spec::new(2, vec![GroupSpec::new(2, 3).unwrap(), GroupSpec::new(1, 2).unwrap()]).unwrap();
+        let options = GenerateOptions::new().with_embedded_fingerprint(FingerprintAlg::Sha256Trunc4);
+        assert_eq!(options.embedded_fingerprint(), Some(FingerprintAlg::Sha256Trunc4));
+        assert_eq!(GenerateOptions::new().embedded_fingerprint(), None);
+
+        let shares = test_support::generate_with_options(&spec, &options);
+        for share in shares.iter().flatten() {
+            assert_eq!(share.len(), FINGERPRINTED_METADATA_SIZE_BYTES + secret.len());
+            assert_eq!((share[4] >> 4, share[5]), (SHARE_VERSION_ESCAPE, 3));
+            assert_eq!(share[6..10], hex!("135aa434"));
+        }
+        assert_eq!(sskr_combine(&[&shares[1][1], &shares[0][2], &shares[0][0]]).unwrap(), secret);
+
+        // Apart from the fingerprint, the shares are the standard ones.
+        let standard = sskr_generate_using(&spec, &secret, &mut bc_rand::make_fake_random_number_generator()).unwrap();
+        for (share, standard) in shares.iter().flatten().zip(standard.iter().flatten()) {
+            assert_eq!(share[..4], standard[..4]);
+            assert_eq!(share[10..], standard[5..]);
+        }
+
+        // A share of a split of one group with a member threshold of one is
+        // the secret, so an altered one is caught only by the fingerprint.
+        let single = Spec::new(1, vec![GroupSpec::new(1, 2).unwrap()]).unwrap();
+        let copies = sskr_generate_with_options_using(&single, &secret, &options, &mut bc_rand::make_fake_random_number_generator()).unwrap();
+        let mut altered = copies[0][0].clone().into_share();
+        *altered.last_mut().unwrap() ^= 0x01;
+        assert!(matches!(sskr_combine(&[&altered]), Err(SSKRError::FingerprintMismatch)));
+        assert_eq!(sskr_combine(&[&copies[0][1]]).unwrap(), secret);
+        let mut tampered = shares[0][0].clone();
+        tampered[8] ^= 0x01;
+        assert!(matches!(sskr_combine(&[&shares[1][0], &tampered, &shares[0][1]]), Err(SSKRError::FingerprintMismatch)));
+
+        // Fingerprinted shares don't mix with standard ones.
+        assert!(matches!(
+            sskr_combine(&[&shares[1][0], &standard[0][0], &standard[0][1]]),
+            Err(SSKRError::ShareVersionMismatch { expected: ShareVersion::V3, found: ShareVersion::V0, share_index: 1 })
+        ));
+    }
+}
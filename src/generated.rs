@@ -0,0 +1,52 @@
+use crate::Spec;
+
+/// The shares of a split generated by
+/// [`sskr_generate_detailed`](crate::sskr_generate_detailed), with the
+/// identifier they were given.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GeneratedShares {
+    identifier: u32,
+    spec: Spec,
+    shares: Vec<Vec<Vec<u8>>>,
+}
+
+impl GeneratedShares {
+    pub(crate) fn new(identifier: u32, spec: Spec, shares: Vec<Vec<Vec<u8>>>) -> Self {
+        Self { identifier, spec, shares }
+    }
+
+    /// Returns the identifier of the split, as encoded in every share.
+    pub fn identifier(&self) -> u32 {
+        self.identifier
+    }
+
+    /// Returns the spec the shares were generated for.
+    pub fn spec(&self) -> &Spec {
+        &self.spec
+    }
+
+    /// Returns the serialized shares, grouped as `sskr_generate` groups them.
+    pub fn shares(&self) -> &[Vec<Vec<u8>>] {
+        &self.shares
+    }
+
+    /// Returns the shares of the holder at `holder_index` of the group at
+    /// `group_index`, as laid out by the group's
+    /// [`HolderLayout`](crate::HolderLayout), or `None` if there is no such
+    /// holder.
+    pub fn holder_shares(&self, group_index: usize, holder_index: usize) -> Option<&[Vec<u8>]> {
+        let layout = self.spec.groups().get(group_index)?.holder_layout();
+        Some(&self.shares[group_index][layout.member_indexes(holder_index)?])
+    }
+
+    /// Returns the owner's shares if the spec has the shape of one created
+    /// by [`Spec::owner_plus_social`], or `None` otherwise.
+    pub fn owner_shares(&self) -> Option<&[Vec<u8>]> {
+        self.spec.owner_group_index().map(|group_index| self.shares[group_index].as_slice())
+    }
+
+    /// Consumes this value, returning the serialized shares.
+    pub fn into_shares(self) -> Vec<Vec<Vec<u8>>> {
+        self.shares
+    }
+}
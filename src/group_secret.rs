@@ -0,0 +1,45 @@
+use zeroize::Zeroizing;
+
+/// A group-level secret recovered from the member shares of a single group.
+///
+/// This is the top-level Shamir share that the group's member shares were
+/// split from, not the master secret. Any `group_threshold` group secrets from
+/// the same split are enough to reconstruct the master secret, so a
+/// `GroupSecret` must be protected as carefully as the master secret itself.
+/// Its bytes are zeroized when it is dropped.
+#[derive(Clone, PartialEq, Eq)]
+pub struct GroupSecret(Zeroizing<Vec<u8>>);
+
+impl GroupSecret {
+    pub(crate) fn new(data: Vec<u8>) -> Self {
+        Self(Zeroizing::new(data))
+    }
+
+    /// Returns the length of the group secret.
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Returns `true` if the group secret is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns a reference to the group secret data.
+    pub fn data(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl AsRef<[u8]> for GroupSecret {
+    /// Returns a reference to the group secret data.
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl std::fmt::Debug for GroupSecret {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "GroupSecret({} bytes, redacted)", self.len())
+    }
+}
@@ -0,0 +1,248 @@
+use bc_crypto::{
+    aead_chacha20_poly1305_decrypt_with_aad,
+    aead_chacha20_poly1305_encrypt_with_aad,
+    x25519_agreement_public_key_from_private_key,
+    x25519_new_agreement_private_key_using,
+    x25519_shared_key,
+    SYMMETRIC_AUTH_SIZE,
+    SYMMETRIC_NONCE_SIZE,
+    X25519_PRIVATE_KEY_SIZE,
+    X25519_PUBLIC_KEY_SIZE,
+};
+use bc_rand::RandomNumberGenerator;
+use zeroize::Zeroizing;
+
+use crate::{SSKRError, Secret, Spec, sskr_generate_using};
+
+/// The prefix that marks a share encrypted by `sskr_generate_sealed`.
+const HOLDER_SHARE_MAGIC: [u8; 4] = *b"SKHE";
+
+const HEADER_SIZE: usize = HOLDER_SHARE_MAGIC.len() + X25519_PUBLIC_KEY_SIZE + SYMMETRIC_NONCE_SIZE;
+
+/// The number of bytes encryption to a holder adds to a serialized share.
+pub const HOLDER_SHARE_OVERHEAD: usize = HEADER_SIZE + SYMMETRIC_AUTH_SIZE;
+
+/// Generates SSKR shares for the given `Spec` and `Secret`, each encrypted to
+/// the X25519 public key of its holder.
+///
+/// Uses the default random number generator; see
+/// [`sskr_generate_sealed_using`].
+#[cfg(feature = "default-rng")]
+pub fn sskr_generate_sealed(
+    spec: &Spec,
+    secret: &Secret,
+    recipients: &[(usize, usize, [u8; X25519_PUBLIC_KEY_SIZE])]
+) -> Result<Vec<Vec<u8>>, SSKRError> {
+    let mut rng = bc_rand::SecureRandomNumberGenerator;
+    sskr_generate_sealed_using(spec, secret, recipients, &mut rng)
+}
+
+/// Generates SSKR shares for the given `Spec` and `Secret` using the provided
+/// random number generator, each encrypted to the X25519 public key of its
+/// holder.
+///
+/// `recipients` gives the group index, member index and public key of the
+/// holder of each share, and an encrypted share is returned for each
+/// recipient, in order. Every share of the split must have exactly one
+/// recipient, so that no share is generated that no one can decrypt.
+///
+/// Each share is encrypted with a key agreed between a fresh ephemeral X25519
+/// key and the holder's key, and then with ChaCha20-Poly1305. The encrypted
+/// share begins with a header holding the ephemeral public key and the
+/// nonce, which is authenticated along with the holder's public key, so that
+/// only the holder can decrypt it and any change to it is detected. It is
+/// decrypted with [`unseal_holder_share`], which yields the share for
+/// `sskr_combine`.
+///
+/// These are not the checksum seals of `seal_share`: an encrypted share
+/// cannot be unsealed with `unseal_share` or combined with
+/// `sskr_combine_sealed`.
+///
+/// # Errors
+///
+/// Returns `SSKRError::HolderRecipientInvalid` for a recipient that is not a
+/// share of the split or repeats one before it,
+/// `SSKRError::HolderRecipientMissing` for a share with no recipient, and
+/// otherwise the same errors as `sskr_generate`.
+pub fn sskr_generate_sealed_using<R>(
+    spec: &Spec,
+    secret: &Secret,
+    recipients: &[(usize, usize, [u8; X25519_PUBLIC_KEY_SIZE])],
+    random_generator: &mut R
+) -> Result<Vec<Vec<u8>>, SSKRError>
+where
+    R: RandomNumberGenerator
+{
+    let mut has_recipient: Vec<Vec<bool>> = spec.groups().iter().map(|group| vec![false; group.member_count()]).collect();
+    for (recipient_index, &(group_index, member_index, _)) in recipients.iter().enumerate() {
+        match has_recipient.get_mut(group_index).and_then(|group| group.get_mut(member_index)) {
+            Some(slot) if !*slot => *slot = true,
+            _ => return Err(SSKRError::HolderRecipientInvalid { recipient_index, group_index, member_index }),
+        }
+    }
+    for (group_index, group) in has_recipient.iter().enumerate() {
+        if let Some(member_index) = group.iter().position(|&has| !has) {
+            return Err(SSKRError::HolderRecipientMissing { group_index, member_index });
+        }
+    }
+
+    let shares = Zeroizing::new(sskr_generate_using(spec, secret, random_generator)?);
+    Ok(recipients
+        .iter()
+        .map(|(group_index, member_index, public_key)| {
+            seal_to_holder(&shares[*group_index][*member_index], public_key, random_generator)
+        })
+        .collect())
+}
+
+/// Decrypts a share encrypted to its holder by `sskr_generate_sealed` with
+/// the holder's X25519 private key, returning the serialized share.
+///
+/// # Errors
+///
+/// Returns `SSKRError::HolderShareInvalid` if `sealed` is not an encrypted
+/// share, and `SSKRError::HolderShareDecryptFailed` if the private key is
+/// not the holder's or the encrypted share has been altered.
+pub fn unseal_holder_share(sealed: &[u8], private_key: &[u8; X25519_PRIVATE_KEY_SIZE]) -> Result<Vec<u8>, SSKRError> {
+    if !sealed.starts_with(&HOLDER_SHARE_MAGIC) || sealed.len() < HOLDER_SHARE_OVERHEAD {
+        return Err(SSKRError::HolderShareInvalid);
+    }
+    let (header, body) = sealed.split_at(HEADER_SIZE);
+    let ephemeral_public_key: [u8; X25519_PUBLIC_KEY_SIZE] = header[HOLDER_SHARE_MAGIC.len()..][..X25519_PUBLIC_KEY_SIZE].try_into().unwrap();
+    let nonce: [u8; SYMMETRIC_NONCE_SIZE] = header[HEADER_SIZE - SYMMETRIC_NONCE_SIZE..].try_into().unwrap();
+    let (ciphertext, auth) = body.split_at(body.len() - SYMMETRIC_AUTH_SIZE);
+    let auth: [u8; SYMMETRIC_AUTH_SIZE] = auth.try_into().unwrap();
+
+    let key = Zeroizing::new(x25519_shared_key(private_key, &ephemeral_public_key));
+    let aad = associated_data(header, &x25519_agreement_public_key_from_private_key(private_key));
+    aead_chacha20_poly1305_decrypt_with_aad(ciphertext, &key, &nonce, &aad, &auth)
+        .map_err(|_| SSKRError::HolderShareDecryptFailed)
+}
+
+/// Encrypts `share` to the holder of `public_key` under a fresh ephemeral
+/// key.
+fn seal_to_holder(
+    share: &[u8],
+    public_key: &[u8; X25519_PUBLIC_KEY_SIZE],
+    rng: &mut impl RandomNumberGenerator
+) -> Vec<u8> {
+    let ephemeral_private_key = Zeroizing::new(x25519_new_agreement_private_key_using(rng));
+    let key = Zeroizing::new(x25519_shared_key(&ephemeral_private_key, public_key));
+
+    let mut result = Vec::with_capacity(share.len() + HOLDER_SHARE_OVERHEAD);
+    result.extend_from_slice(&HOLDER_SHARE_MAGIC);
+    result.extend_from_slice(&x25519_agreement_public_key_from_private_key(&ephemeral_private_key));
+    result.extend_from_slice(&rng.random_data(SYMMETRIC_NONCE_SIZE));
+
+    let nonce: [u8; SYMMETRIC_NONCE_SIZE] = result[HEADER_SIZE - SYMMETRIC_NONCE_SIZE..].try_into().unwrap();
+    let aad = associated_data(&result, public_key);
+    let (ciphertext, auth) = aead_chacha20_poly1305_encrypt_with_aad(share, &key, &nonce, &aad);
+    result.extend_from_slice(&ciphertext);
+    result.extend_from_slice(&auth);
+    result
+}
+
+/// The data authenticated with an encrypted share: its header, followed by
+/// the holder's public key.
+fn associated_data(header: &[u8], public_key: &[u8; X25519_PUBLIC_KEY_SIZE]) -> Vec<u8> {
+    [header, public_key].concat()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{GroupSpec, sskr_combine, test_support};
+    
+
+    fn holder_keys(spec: &Spec, rng: &mut impl RandomNumberGenerator) -> Vec<(usize, usize, [u8; X25519_PRIVATE_KEY_SIZE])> {
+        spec.groups()
+            .iter()
+            .enumerate()
+            .flat_map(|(group_index, group)| (0..group.member_count()).map(move |member_index| (group_index, member_index)))
+            .map(|(group_index, member_index)| (group_index, member_index, x25519_new_agreement_private_key_using(rng)))
+            .collect()
+    }
+
+    fn recipients(keys: &[(usize, usize, [u8; X25519_PRIVATE_KEY_SIZE])]) -> Vec<(usize, usize, [u8; X25519_PUBLIC_KEY_SIZE])> {
+        keys.iter()
+            .map(|(group_index, member_index, private_key)| (*group_index, *member_index, x25519_agreement_public_key_from_private_key(private_key)))
+            .collect()
+    }
+
+    #[test]
+    fn test_holder_encryption() {
+        let mut rng = bc_rand::make_fake_random_number_generator();
+        let secret = test_support::secret();
+        let spec = Spec::new(2, vec![GroupSpec::new(2, 3).unwrap(), GroupSpec::new(1, 1).unwrap()]).unwrap();
+        let mut keys = holder_keys(&spec, &mut rng);
+        keys.reverse();
+        let sealed = sskr_generate_sealed_using(&spec, &secret, &recipients(&keys), &mut rng).unwrap();
+        assert_eq!(sealed.len(), 4);
+
+        let shares: Vec<Vec<u8>> = sealed.iter().zip(&keys).map(|(sealed, (_, _, private_key))| unseal_holder_share(sealed, private_key).unwrap()).collect();
+        for (share, (sealed, (group_index, member_index, _))) in shares.iter().zip(sealed.iter().zip(&keys)) {
+            assert_eq!(sealed.len(), share.len() + HOLDER_SHARE_OVERHEAD);
+            assert_eq!((share[3] >> 4) as usize, *group_index);
+            assert_eq!((share[4] & 0xf) as usize, *member_index);
+            assert!(!sealed.windows(share.len()).any(|window| window == &share[..]));
+        }
+        assert_eq!(sskr_combine(&[&shares[0], &shares[3], &shares[2]]).unwrap(), secret);
+
+        // Another holder's key does not decrypt the share.
+        assert!(matches!(unseal_holder_share(&sealed[0], &keys[1].2), Err(SSKRError::HolderShareDecryptFailed)));
+    }
+
+    #[test]
+    fn test_holder_encryption_tampered() {
+        let mut rng = bc_rand::make_fake_random_number_generator();
+        let secret = test_support::secret();
+        let spec = Spec::new(1, vec![GroupSpec::new(1, 1).unwrap()]).unwrap();
+        let keys = holder_keys(&spec, &mut rng);
+        let sealed = sskr_generate_sealed_using(&spec, &secret, &recipients(&keys), &mut rng).unwrap().remove(0);
+        let private_key = &keys[0].2;
+        assert!(unseal_holder_share(&sealed, private_key).is_ok());
+
+        // A change to the ephemeral key, the nonce, the ciphertext or the tag
+        // is detected.
+        for i in HOLDER_SHARE_MAGIC.len()..sealed.len() {
+            let mut tampered = sealed.clone();
+            tampered[i] ^= 0x01;
+            assert!(matches!(unseal_holder_share(&tampered, private_key), Err(SSKRError::HolderShareDecryptFailed)), "byte {i}");
+        }
+        let mut tampered = sealed.clone();
+        tampered[0] ^= 0x01;
+        assert!(matches!(unseal_holder_share(&tampered, private_key), Err(SSKRError::HolderShareInvalid)));
+        assert!(matches!(unseal_holder_share(&sealed[..sealed.len() - 1], private_key), Err(SSKRError::HolderShareDecryptFailed)));
+        assert!(matches!(unseal_holder_share(&sealed[..HOLDER_SHARE_OVERHEAD - 1], private_key), Err(SSKRError::HolderShareInvalid)));
+    }
+
+    #[test]
+    fn test_holder_recipients_invalid() {
+        let mut rng = bc_rand::make_fake_random_number_generator();
+        let secret = test_support::secret();
+        let spec = Spec::new(1, vec![GroupSpec::new(2, 3).unwrap()]).unwrap();
+        let mut recipients = recipients(&holder_keys(&spec, &mut rng));
+
+        let missing = &recipients[..2];
+        assert!(matches!(
+            sskr_generate_sealed_using(&spec, &secret, missing, &mut rng),
+            Err(SSKRError::HolderRecipientMissing { group_index: 0, member_index: 2 })
+        ));
+
+        recipients.push(recipients[1]);
+        assert!(matches!(
+            sskr_generate_sealed_using(&spec, &secret, &recipients, &mut rng),
+            Err(SSKRError::HolderRecipientInvalid { recipient_index: 3, group_index: 0, member_index: 1 })
+        ));
+        recipients[3].1 = 3;
+        assert!(matches!(
+            sskr_generate_sealed_using(&spec, &secret, &recipients, &mut rng),
+            Err(SSKRError::HolderRecipientInvalid { recipient_index: 3, group_index: 0, member_index: 3 })
+        ));
+        recipients[3] = (1, 0, recipients[0].2);
+        assert!(matches!(
+            sskr_generate_sealed_using(&spec, &secret, &recipients, &mut rng),
+            Err(SSKRError::HolderRecipientInvalid { recipient_index: 3, group_index: 1, member_index: 0 })
+        ));
+    }
+}
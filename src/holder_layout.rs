@@ -0,0 +1,45 @@
+use std::ops::Range;
+
+/// Which member shares of a group go to which holder, as returned by
+/// [`GroupSpec::holder_layout`](crate::GroupSpec::holder_layout).
+///
+/// Each holder is given a run of consecutive member indexes, as many as the
+/// holder's weight. In a group without weights each holder has one share,
+/// whose member index is the holder's index.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct HolderLayout {
+    holders: Vec<Range<usize>>,
+}
+
+impl HolderLayout {
+    pub(crate) fn from_weights(weights: &[usize]) -> Self {
+        let mut start = 0;
+        let holders = weights
+            .iter()
+            .map(|&weight| {
+                let range = start..start + weight;
+                start = range.end;
+                range
+            })
+            .collect();
+        Self { holders }
+    }
+
+    /// Returns the number of holders.
+    pub fn holder_count(&self) -> usize {
+        self.holders.len()
+    }
+
+    /// Returns the member indexes of the shares of the holder at
+    /// `holder_index`, or `None` if there is no such holder.
+    pub fn member_indexes(&self, holder_index: usize) -> Option<Range<usize>> {
+        self.holders.get(holder_index).cloned()
+    }
+
+    /// Returns the index of the holder of the share at `member_index`, or
+    /// `None` if there is no such share.
+    pub fn holder_of(&self, member_index: usize) -> Option<usize> {
+        self.holders.iter().position(|range| range.contains(&member_index))
+    }
+}
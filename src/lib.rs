@@ -1,4 +1,4 @@
-#![doc(html_root_url = "https://docs.rs/sskr/0.4.2")]
+#![doc(html_root_url = "https://docs.rs/sskr/0.5.0")]
 #![warn(rust_2018_idioms)]
 
 //! # Introduction
@@ -9,13 +9,13 @@
 //!
 //! ```toml
 //! [dependencies]
-//! sskr = "0.4.2"
+//! sskr = "0.5.0"
 //! ```
 //!
 //! # Example
 //!
 //! ```
-//! # use sskr::{Secret, GroupSpec, Spec, sskr_generate, sskr_combine};
+//! # use sskr::{Secret, GroupSpec, Spec, SpecBuilder, sskr_generate_using, sskr_combine};
 //! let secret_string = b"my secret belongs to me.";
 //! let secret = Secret::new(secret_string).unwrap();
 //!
@@ -28,9 +28,16 @@
 //! let group2 = GroupSpec::new(3, 5).unwrap();
 //! let spec = Spec::new(2, vec![group1, group2]).unwrap();
 //!
+//! // The same spec can be built with its groups first, checking them all at
+//! // once. Without a group threshold, every group is needed.
+//! let built = SpecBuilder::new().group(2, 3).group(3, 5).build().unwrap();
+//! assert_eq!(built, spec);
+//!
 //! // The result is a vector of groups, each containing a vector of shares,
-//! // each of which is a vector of bytes.
-//! let shares: Vec<Vec<Vec<u8>>> = sskr_generate(&spec, &secret).unwrap();
+//! // each of which is a vector of bytes. The random number generator is
+//! // passed explicitly; with the `default-rng` feature, `sskr_generate`
+//! // does the same using the operating system's secure generator.
+//! let shares: Vec<Vec<Vec<u8>>> = sskr_generate_using(&spec, &secret, &mut bc_rand::SecureRandomNumberGenerator).unwrap();
 //!
 //! assert_eq!(shares.len(), 2);
 //! assert_eq!(shares[0].len(), 3);
@@ -52,6 +59,20 @@
 //! let recovered_secret = sskr_combine(&recovered_shares).unwrap();
 //! assert_eq!(recovered_secret, secret);
 //! ```
+//!
+//! # Features
+//!
+//! * `default-rng` (enabled by default): the generation functions that draw
+//!   from `bc_rand::SecureRandomNumberGenerator` themselves, such as
+//!   `sskr_generate`. Without it only the `_using` variants are available, so
+//!   every source of randomness must be passed explicitly at the call site.
+//! * `serde`: serialization of specs, reports and errors.
+//! * `encrypted-state`: sealing collector state under a passphrase.
+//! * `holder-encryption`: encrypting generated shares to their holders'
+//!   X25519 public keys.
+//! * `async`: the async share collector and `sskr_generate_async`.
+//! * `rayon`: splitting the groups of a split into their members' shares in
+//!   parallel. The shares are the same as without it.
 
 /// The minimum length of a secret.
 pub const MIN_SECRET_LEN: usize = bc_shamir::MIN_SECRET_LEN;
@@ -65,26 +86,274 @@ pub const MAX_SHARE_COUNT: usize = bc_shamir::MAX_SHARE_COUNT;
 /// The maximum number of groups in a split.
 pub const MAX_GROUPS_COUNT: usize = MAX_SHARE_COUNT;
 
+/// The maximum number of shares in a single split: `MAX_GROUPS_COUNT` groups
+/// of `MAX_SHARE_COUNT` members each.
+///
+/// A set of distinct shares from one split can never be larger than this, so
+/// `sskr_combine` rejects larger inputs of standard-format shares without
+/// parsing them. Callers accepting shares from untrusted sources can use this
+/// to check input sizes up front. Extended-format splits are bounded by
+/// `ShareVersion::max_total_share_count` instead.
+pub const MAX_TOTAL_SHARE_COUNT: usize = MAX_GROUPS_COUNT * MAX_SHARE_COUNT;
+
 /// The number of bytes used to encode the metadata for a share.
 pub const METADATA_SIZE_BYTES: usize = 5;
 
+/// The value of the four reserved bits of a share's metadata that marks a
+/// share in a format other than the standard one. The format's version is
+/// the byte that follows the standard metadata.
+///
+/// This is the only value of the reserved bits that will ever mark a share
+/// format, so every other value, except those of `PASSPHRASE_SHARE_MAGIC`
+/// and `SHARE_ENVELOPE_MAGIC`, is application data.
+pub const SHARE_VERSION_ESCAPE: u8 = 1;
+
+/// The maximum number of member shares in a group of an extended-format
+/// (`ShareVersion::V1`) split.
+///
+/// Every x coordinate in GF(2^8) is available except the two that hold the
+/// secret and its digest.
+pub const MAX_EXTENDED_SHARE_COUNT: usize = 254;
+
+/// The maximum number of groups in an extended-format split.
+pub const MAX_EXTENDED_GROUPS_COUNT: usize = MAX_EXTENDED_SHARE_COUNT;
+
+/// The number of bytes used to encode the metadata for an extended-format
+/// share, including its version byte.
+pub const EXTENDED_METADATA_SIZE_BYTES: usize = 9;
+
+/// The number of bytes used to encode the metadata for a wide-identifier
+/// (`ShareVersion::V2`) share, including its version byte.
+pub const WIDE_IDENTIFIER_METADATA_SIZE_BYTES: usize = 8;
+
+/// The number of bytes in the fingerprint of the master secret that a
+/// fingerprinted (`ShareVersion::V3`) share carries.
+pub const FINGERPRINT_SIZE_BYTES: usize = 4;
+
+/// The number of bytes used to encode the metadata for a fingerprinted
+/// share, including its version byte and the fingerprint.
+pub const FINGERPRINTED_METADATA_SIZE_BYTES: usize = METADATA_SIZE_BYTES + 1 + FINGERPRINT_SIZE_BYTES;
+
 /// The minimum number of bytes required to encode a share.
 pub const MIN_SERIALIZE_SIZE_BYTES: usize = METADATA_SIZE_BYTES + MIN_SECRET_LEN;
 
 mod encoding;
-pub use encoding::{ sskr_generate, sskr_generate_using, sskr_combine };
+#[cfg(feature = "default-rng")]
+pub use encoding::{
+    sskr_generate,
+    sskr_generate_shares,
+    sskr_generate_detailed,
+    sskr_generate_share_bytes,
+    sskr_generate_with_version,
+    sskr_generate_with_identifier,
+    sskr_generate_with_app_nibble,
+};
+pub use encoding::{
+    sskr_generate_using,
+    sskr_generate_shares_using,
+    sskr_generate_detailed_using,
+    sskr_generate_share_bytes_using,
+    sskr_generate_with_version_using,
+    sskr_generate_with_identifier_using,
+    sskr_generate_with_app_nibble_using,
+    sskr_app_nibble,
+    sskr_combine,
+    sskr_combine_detailed,
+    sskr_combine_iter,
+    sskr_combine_into,
+    sskr_combine_ignoring_identifier,
+    sskr_combine_expecting,
+    sskr_recover_group,
+    sskr_extend_group,
+    sskr_combine_from_group_secrets,
+    sskr_combine_lenient,
+    sskr_combine_partial,
+    sskr_combine_verified,
+    sskr_combine_with_spec,
+    sskr_combine_shares,
+    sort_shares,
+    describe_share,
+};
 
 mod share;
+pub use share::{ SSKRShare, ShareValue };
+
+mod share_bytes;
+pub use share_bytes::ShareBytes;
+
+mod share_iter;
+pub use share_iter::{ sskr_generate_iter, ShareIter };
+
+mod share_sink;
+pub use share_sink::{ sskr_generate_to_sink, ShareSink, SinkError, VecSink };
+
+mod split_groups;
+pub use split_groups::{ sskr_split_groups, sskr_split_group_members, SplitHeader };
+
+mod escrow;
+pub use escrow::{ sskr_generate_with_group_secrets, sskr_combine_with_escrow, GroupSecretEscrow };
+
+mod reshare;
+pub use reshare::{ sskr_reshare, sskr_rotate, RotationResult };
+
+mod constrained;
+#[cfg(feature = "default-rng")]
+pub use constrained::sskr_generate_constrained;
+pub use constrained::{ sskr_generate_constrained_using, GroupConstraint };
+
+mod generate_options;
+#[cfg(feature = "default-rng")]
+pub use generate_options::sskr_generate_with_options;
+pub use generate_options::{ sskr_generate_with_options_using, GenerateOptions, ShareCopy };
+
+mod digest_member;
+pub use digest_member::sskr_combine_with_digest_member;
+
+mod fingerprint;
+pub use fingerprint::FingerprintAlg;
+
+mod flat;
+pub use flat::{ sskr_split_flat, sskr_combine_flat };
+
+mod fallible;
+pub use fallible::{ sskr_try_generate_using, FallibleRandomNumberGenerator };
+
+mod rand_core_rng;
+pub use rand_core_rng::{ sskr_generate_with_rng, CryptoRngAdapter };
+
+mod rng_health;
+
+mod share_set;
+#[cfg(feature = "default-rng")]
+pub use share_set::sskr_generate_share_set;
+pub use share_set::{ sskr_generate_share_set_using, ShareSet };
 
 mod secret;
 pub use secret::Secret;
 
 mod spec;
-pub use spec::{ Spec, GroupSpec };
+pub use spec::{ Spec, GroupSpec, PartialSpec, SpecField };
+
+mod spec_builder;
+pub use spec_builder::{ SpecBuilder, SpecProblem };
+
+mod policy_warning;
+pub use policy_warning::PolicyWarning;
+
+mod split_plan;
+pub use split_plan::SplitPlan;
+
+mod holder_layout;
+pub use holder_layout::HolderLayout;
+
+mod required_members;
+pub use required_members::RequiredMembers;
 
 mod error;
 pub use error::SSKRError;
 
+mod shamir;
+
+mod collector;
+pub use collector::{ ShareCollector, ShareCheck, CollectorEvent, CollectorState, CollectorStatus, GroupStatus, GroupSuggestion };
+
+#[cfg(feature = "encrypted-state")]
+mod encrypted_state;
+#[cfg(feature = "encrypted-state")]
+pub use encrypted_state::STATE_SEAL_ITERATIONS;
+
+#[cfg(feature = "holder-encryption")]
+mod holder_encryption;
+#[cfg(all(feature = "holder-encryption", feature = "default-rng"))]
+pub use holder_encryption::sskr_generate_sealed;
+#[cfg(feature = "holder-encryption")]
+pub use holder_encryption::{ sskr_generate_sealed_using, unseal_holder_share, HOLDER_SHARE_OVERHEAD };
+
+mod sync_collector;
+pub use sync_collector::SyncShareCollector;
+
+#[cfg(feature = "async")]
+mod async_collector;
+#[cfg(feature = "async")]
+pub use async_collector::AsyncShareCollector;
+
+mod seeded_rng;
+
+#[cfg(feature = "async")]
+mod async_generate;
+#[cfg(feature = "async")]
+pub use async_generate::{ sskr_generate_async, AsyncRandomNumberGenerator };
+
+mod envelope;
+pub use envelope::{ ShareEnvelope, SHARE_ENVELOPE_MAGIC, MAX_ENVELOPE_FIELD_LEN };
+
+mod checkwords;
+pub use checkwords::{ share_checkwords, verify_checkwords };
+
+mod seal;
+pub use seal::{ SEAL_SIZE_BYTES, seal_share, unseal_share, sskr_combine_sealed };
+
+mod passphrase;
+#[cfg(feature = "default-rng")]
+pub use passphrase::sskr_generate_encrypted;
+pub use passphrase::{ sskr_generate_encrypted_using, sskr_combine_encrypted, PASSPHRASE_SHARE_MAGIC, PASSPHRASE_ITERATIONS };
+
+mod share_version;
+pub use share_version::ShareVersion;
+
+mod group_secret;
+pub use group_secret::GroupSecret;
+
+mod split_metadata;
+pub use split_metadata::SplitMetadata;
+
+mod partial;
+pub use partial::{ PartialPolicy, PartialRecovery };
+
+mod recovered;
+pub use recovered::{ RecoveredSecret, RecoveryInfo };
+
+mod generated;
+pub use generated::GeneratedShares;
+
+mod deterministic;
+pub use deterministic::sskr_generate_deterministic;
+
+mod transcript;
+#[cfg(feature = "default-rng")]
+pub use transcript::sskr_generate_with_transcript;
+pub use transcript::{ sskr_generate_with_transcript_using, Transcript, ShareRecord };
+
+mod shortfall;
+pub use shortfall::{ shares_needed, RecoveryShortfall, GroupShortfall };
+
+mod progress;
+pub use progress::{ recovery_progress, Progress };
+
+mod report;
+pub use report::{ sskr_combine_with_report, sskr_combine_strict, sskr_combine_with_warnings, CombineReport, CombineWarning, GroupReport, GroupOutcome, ShareUsage, StrictViolation };
+
+mod diagnose;
+pub use diagnose::{ sskr_diagnose, Diagnosis, GroupDiagnosis, MAX_DIAGNOSED_MEMBERS };
+
+mod multi;
+pub use multi::{ sskr_combine_multi, MultiRecovery, SessionResult };
+
+mod sessions;
+pub use sessions::{ group_shares_by_session, ShareSessions, SessionSummary, GroupSummary };
+
+mod concatenated;
+pub use concatenated::{ sskr_combine_concatenated, sskr_serialize_concatenated };
+
+mod reader;
+pub use reader::{ sskr_combine_from_reader, FrameFormat, ReadCombineError };
+
+mod strings;
+pub use strings::{ sskr_combine_strings, ShareEncoding };
+
+#[cfg(test)]
+mod test_support;
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -135,7 +404,7 @@ mod tests {
     #[test]
     fn test_split_3_5() {
         let mut rng = FakeRandomNumberGenerator;
-        let secret = Secret::new(hex!("0ff784df000c4380a5ed683f7e6e3dcf")).unwrap();
+        let secret = test_support::secret();
         let group = GroupSpec::new(3, 5).unwrap();
         let spec = Spec::new(1, vec![group]).unwrap();
         let shares = sskr_generate_using(&spec, &secret, &mut rng).unwrap();
@@ -240,6 +509,1062 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_sort_shares() {
+        let mut rng = bc_rand::make_fake_random_number_generator();
+        let secret = Secret::new(rng.random_data(16)).unwrap();
+        let spec = Spec::new(2, vec![GroupSpec::new(2, 3).unwrap(), GroupSpec::new(3, 5).unwrap()]).unwrap();
+        let shares_a = sskr_generate_using(&spec, &secret, &mut rng).unwrap();
+        let shares_b = sskr_generate_using(&spec, &secret, &mut rng).unwrap();
+        let id = |share: &[u8]| u16::from_be_bytes([share[0], share[1]]);
+        assert_ne!(id(&shares_a[0][0]), id(&shares_b[0][0]));
+
+        // The canonical order is group-major, member-minor within each split,
+        // with the splits ordered by identifier.
+        let (first, second) = if id(&shares_a[0][0]) < id(&shares_b[0][0]) {
+            (&shares_a, &shares_b)
+        } else {
+            (&shares_b, &shares_a)
+        };
+        let expected: Vec<Vec<u8>> = first.iter().flatten().chain(second.iter().flatten()).cloned().collect();
+
+        let mut pile = expected.clone();
+        fisher_yates_shuffle(&mut pile, &mut rng);
+        assert_ne!(pile, expected);
+        sort_shares(&mut pile).unwrap();
+        assert_eq!(pile, expected);
+
+        // Sorting borrowed shares works the same way.
+        let mut borrowed: Vec<&Vec<u8>> = expected.iter().collect();
+        fisher_yates_shuffle(&mut borrowed, &mut rng);
+        sort_shares(&mut borrowed).unwrap();
+        assert!(borrowed.iter().zip(&expected).all(|(a, b)| *a == b));
+    }
+
+    #[test]
+    fn test_sort_shares_rejects_malformed() {
+        let mut rng = bc_rand::make_fake_random_number_generator();
+        let secret = Secret::new(rng.random_data(16)).unwrap();
+        let spec = Spec::new(1, vec![GroupSpec::new(2, 3).unwrap()]).unwrap();
+        let mut shares: Vec<Vec<u8>> = sskr_generate_using(&spec, &secret, &mut rng).unwrap().concat();
+        shares.reverse();
+        shares.push(vec![0u8; 3]);
+        let before = shares.clone();
+        assert!(sort_shares(&mut shares).is_err());
+        assert_eq!(shares, before);
+    }
+
+    #[test]
+    fn test_share_ordering() {
+        use crate::share::{SSKRShare, ShareValue};
+
+        let mut rng = bc_rand::make_fake_random_number_generator();
+        let value = ShareValue::new(rng.random_data(16));
+        let mut expected = Vec::new();
+        for identifier in [0x0102, 0x1f2e] {
+            for group_index in 0..3 {
+                for member_index in 0..4 {
+                    expected.push(SSKRShare::new(identifier, group_index, 2, 3, member_index, 2, value.clone()));
+                }
+            }
+        }
+        let mut shares = expected.clone();
+        fisher_yates_shuffle(&mut shares, &mut rng);
+        assert_ne!(shares, expected);
+        shares.sort();
+        assert_eq!(shares, expected);
+    }
+
+    #[test]
+    fn test_describe_share() {
+        let mut rng = FakeRandomNumberGenerator;
+        let secret = Secret::new(hex!("204188bfa6b440a1bdfd6753ff55a8241e07af5c5be943db917e3efabc184b1a")).unwrap();
+        let spec = Spec::new(2, vec![GroupSpec::new(2, 3).unwrap(), GroupSpec::new(3, 5).unwrap()]).unwrap();
+        let shares = sskr_generate_using(&spec, &secret, &mut rng).unwrap();
+        assert_eq!(
+            describe_share(&shares[0][2]).unwrap(),
+            "SSKR share for split 0x0011: this is share 3 of group 1; the split has 2 groups, 2 groups required, and this group needs 2 of its shares. The share carries a 32-byte payload."
+        );
+        assert_eq!(
+            describe_share(&shares[1][0]).unwrap(),
+            "SSKR share for split 0x0011: this is share 1 of group 2; the split has 2 groups, 2 groups required, and this group needs 3 of its shares. The share carries a 32-byte payload."
+        );
+
+        let spec = Spec::new(1, vec![GroupSpec::new(1, 1).unwrap()]).unwrap();
+        let secret = test_support::secret();
+        let shares = sskr_generate_using(&spec, &secret, &mut rng).unwrap();
+        assert_eq!(
+            describe_share(&shares[0][0]).unwrap(),
+            "SSKR share for split 0x0011: this is share 1 of group 1; the split has 1 group, 1 group required, and this group needs 1 of its shares. The share carries a 16-byte payload."
+        );
+
+        let mut truncated = shares[0][0].clone();
+        truncated.truncate(4);
+        assert!(describe_share(&truncated).is_err());
+        let mut reserved = shares[0][0].clone();
+        reserved[4] |= 0x30;
+        assert!(describe_share(&reserved).is_err());
+    }
+
+    #[test]
+    fn test_group_index_out_of_range() {
+        let mut rng = FakeRandomNumberGenerator;
+        let secret = test_support::secret();
+        let spec = Spec::new(1, vec![GroupSpec::new(2, 3).unwrap(), GroupSpec::new(2, 3).unwrap()]).unwrap();
+        let shares = sskr_generate_using(&spec, &secret, &mut rng).unwrap();
+
+        let with_group_index = |share: &Vec<u8>, group_index: u8| {
+            let mut share = share.clone();
+            share[3] = (group_index << 4) | (share[3] & 0xf);
+            share
+        };
+
+        // A group index equal to the group count is out of range.
+        let equal = with_group_index(&shares[1][0], 2);
+        let error = describe_share(&equal).unwrap_err();
+        assert!(matches!(error, SSKRError::GroupIndexInvalid { share_index: None, group_index: 2, group_count: 2 }));
+        // A share described on its own has no position to report.
+        assert_eq!(error.to_string(), "SSKR share is corrupt: its group index 2 is out of range for 2 groups");
+        assert_eq!(describe_share(&shares[0][0][..3]).unwrap_err().to_string(), "SSKR share is corrupt: it is 3 bytes, which is too short to contain the share metadata");
+        let recovered_shares = vec![shares[0][0].clone(), shares[0][1].clone(), equal];
+        assert!(matches!(sskr_combine(&recovered_shares), Err(SSKRError::GroupIndexInvalid { share_index: Some(2), .. })));
+
+        // As is one far above it, even though the other shares satisfy the quorum.
+        let far_above = with_group_index(&shares[1][1], 12);
+        let recovered_shares = vec![shares[0][0].clone(), far_above, shares[0][1].clone()];
+        assert!(matches!(sskr_combine(&recovered_shares), Err(SSKRError::GroupIndexInvalid { share_index: Some(1), group_index: 12, group_count: 2 })));
+    }
+
+    #[test]
+    fn test_malformed_share_errors() {
+        let mut rng = FakeRandomNumberGenerator;
+        let secret = test_support::secret();
+        let spec = Spec::new(1, vec![GroupSpec::new(2, 3).unwrap()]).unwrap();
+        let shares = sskr_generate_using(&spec, &secret, &mut rng).unwrap();
+        let good = shares[0][0].clone();
+        let header = &good[..METADATA_SIZE_BYTES];
+        let with_payload = |len: usize| [header, &vec![0u8; len][..]].concat();
+        let with_header_byte = |i: usize, b: u8| {
+            let mut share = good.clone();
+            share[i] = b;
+            share
+        };
+
+        type Check = fn(&SSKRError) -> bool;
+        let cases: Vec<(Vec<u8>, Check, &str)> = vec![
+            (vec![], |e| matches!(e, SSKRError::ShareLengthInvalid { share_index: Some(3), len: 0 }),
+                "SSKR share at index 3 is corrupt: it is 0 bytes, which is too short to contain the share metadata"),
+            (good[..4].to_vec(), |e| matches!(e, SSKRError::ShareLengthInvalid { share_index: Some(3), len: 4 }),
+                "SSKR share at index 3 is corrupt: it is 4 bytes, which is too short to contain the share metadata"),
+            (header.to_vec(), |e| matches!(e, SSKRError::SharePayloadTooShort { share_index: Some(3), len: 0 }),
+                "SSKR share at index 3 is corrupt: its payload is 0 bytes, which is shorter than the minimum of 16"),
+            (with_payload(14), |e| matches!(e, SSKRError::SharePayloadTooShort { share_index: Some(3), len: 14 }),
+                "SSKR share at index 3 is corrupt: its payload is 14 bytes, which is shorter than the minimum of 16"),
+            (with_payload(37), |e| matches!(e, SSKRError::SharePayloadTooLong { share_index: Some(3), len: 37 }),
+                "SSKR share at index 3 is corrupt: its payload is 37 bytes, which is longer than the maximum of 32"),
+            (with_payload(17), |e| matches!(e, SSKRError::SharePayloadLengthNotEven { share_index: Some(3), len: 17 }),
+                "SSKR share at index 3 is corrupt: its payload is 17 bytes, which is not an even length"),
+            (with_header_byte(4, 0x80), |e| matches!(e, SSKRError::AppNibblePresent { share_index: Some(3), app_nibble: 8 }),
+                "SSKR share at index 3 carries application data 8 and must be combined with `sskr_combine_lenient`"),
+            ([&good[..4], &[SHARE_VERSION_ESCAPE << 4, 8], &good[5..]].concat(), |e| matches!(e, SSKRError::UnsupportedShareVersion { share_index: Some(3), version: 8 }),
+                "SSKR share at index 3 uses format version 8, which this version of the library does not support"),
+            (with_header_byte(2, 0x21), |e| matches!(e, SSKRError::ShareGroupThresholdInvalid { share_index: Some(3), group_threshold: 3, group_count: 2 }),
+                "SSKR share at index 3 is corrupt: its group threshold 3 exceeds its group count 2"),
+            (with_header_byte(3, 0x51), |e| matches!(e, SSKRError::GroupIndexInvalid { share_index: Some(3), group_index: 5, group_count: 1 }),
+                "SSKR share at index 3 is corrupt: its group index 5 is out of range for 1 group"),
+        ];
+
+        for (share, check, message) in cases {
+            let input = vec![shares[0][0].clone(), shares[0][1].clone(), shares[0][2].clone(), share];
+            let error = sskr_combine(&input).unwrap_err();
+            assert!(check(&error), "unexpected error: {:?}", error);
+            assert_eq!(error.to_string(), message);
+        }
+    }
+
+    #[test]
+    fn test_too_many_shares() {
+        assert_eq!(MAX_TOTAL_SHARE_COUNT, 256);
+
+        let mut rng = bc_rand::make_fake_random_number_generator();
+        let secret = test_support::secret();
+        let spec = Spec::new(16, vec![GroupSpec::new(16, 16).unwrap(); 16]).unwrap();
+        let full = sskr_generate_using(&spec, &secret, &mut rng).unwrap().concat();
+        let other = sskr_generate_using(&spec, &secret, &mut rng).unwrap().concat();
+
+        // More distinct shares than any split holds are refused as soon as
+        // the first share too many is parsed.
+        let mut pile = full.clone();
+        pile.push(other[0].clone());
+        pile.push(vec![0xffu8; 1024]);
+        let error = sskr_combine(&pile).unwrap_err();
+        assert!(matches!(error, SSKRError::TooManyShares { count: 257, max: 256 }));
+        assert_eq!(error.to_string(), "SSKR shares numbered 257, but no split has more than 256");
+        match sskr_combine_partial(&pile, PartialPolicy::AllowGroupSecrets) {
+            PartialRecovery::Incomplete { error, .. } => assert!(matches!(error, SSKRError::TooManyShares { .. })),
+            PartialRecovery::Complete(_) => panic!("expected an incomplete recovery"),
+        }
+        let mut parsed = sskr_generate_shares_using(&spec, &secret, &mut rng).unwrap().concat();
+        parsed.push(sskr_generate_shares_using(&spec, &secret, &mut rng).unwrap()[0][0].clone());
+        assert!(matches!(sskr_combine_shares(&parsed), Err(SSKRError::TooManyShares { count: 257, max: 256 })));
+
+        // Exact duplicates count once, however many there are.
+        let mut pile = full.clone();
+        pile.extend(full.iter().cloned());
+        assert_eq!(sskr_combine(&pile).unwrap(), secret);
+
+        // A first share claiming the extended format does not lift the limit
+        // for the standard shares after it.
+        let extended_spec = Spec::new_with_version(1, vec![GroupSpec::new(1, 1).unwrap()], ShareVersion::V1).unwrap();
+        let extended = sskr_generate_with_version_using(&extended_spec, &secret, ShareVersion::V1, &mut rng).unwrap();
+        let mut pile = vec![extended[0][0].clone()];
+        pile.extend(full.iter().cloned());
+        assert!(matches!(sskr_combine(&pile), Err(SSKRError::TooManyShares { count: 257, max: 256 })));
+    }
+
+    #[test]
+    fn test_max_split_combines() {
+        let mut rng = bc_rand::make_fake_random_number_generator();
+        let secret = Secret::new(rng.random_data(32)).unwrap();
+        let group = GroupSpec::new(16, 16).unwrap();
+        let spec = Spec::new(16, vec![group; 16]).unwrap();
+        let shares = sskr_generate_using(&spec, &secret, &mut rng).unwrap();
+        let flattened_shares = shares.into_iter().flatten().collect::<Vec<_>>();
+        assert_eq!(flattened_shares.len(), MAX_TOTAL_SHARE_COUNT);
+        assert_eq!(sskr_combine(&flattened_shares).unwrap(), secret);
+    }
+
+    #[test]
+    fn test_app_nibble() {
+        let mut rng = FakeRandomNumberGenerator;
+        let secret = test_support::secret();
+        let spec = Spec::new(2, vec![GroupSpec::new(2, 3).unwrap(), GroupSpec::new(1, 1).unwrap()]).unwrap();
+        let shares = sskr_generate_with_app_nibble_using(&spec, &secret, 0xa, &mut rng).unwrap();
+        let recovered_shares = vec![shares[0][0].clone(), shares[0][2].clone(), shares[1][0].clone()];
+
+        // The nibble survives serialization and is the only difference from
+        // standard shares generated with the same randomness.
+        let standard = sskr_generate_using(&spec, &secret, &mut rng).unwrap();
+        for (share, standard_share) in shares.iter().flatten().zip(standard.iter().flatten()) {
+            assert_eq!(sskr_app_nibble(share).unwrap(), 0xa);
+            assert_eq!(sskr_app_nibble(standard_share).unwrap(), 0);
+            assert_eq!(share[4] >> 4, 0xa);
+            let mut cleared = share.clone();
+            cleared[4] &= 0xf;
+            assert_eq!(&cleared, standard_share);
+        }
+
+        // Strict decoders reject the shares; lenient ones combine them.
+        assert!(matches!(sskr_combine(&recovered_shares), Err(SSKRError::AppNibblePresent { share_index: Some(0), app_nibble: 0xa })));
+        assert_eq!(sskr_combine_lenient(&recovered_shares).unwrap(), secret);
+
+        // Standard shares still combine leniently.
+        let standard_shares = vec![standard[0][1].clone(), standard[0][2].clone(), standard[1][0].clone()];
+        assert_eq!(sskr_combine_lenient(&standard_shares).unwrap(), secret);
+
+        // Shares tagged with different application data don't mix.
+        let mut mixed = recovered_shares.clone();
+        mixed[2][4] = (0x5 << 4) | (mixed[2][4] & 0xf);
+        assert!(matches!(sskr_combine_lenient(&mixed), Err(SSKRError::AppNibbleMismatch { expected: 0xa, found: 0x5, share_index: 2 })));
+
+        assert!(matches!(sskr_generate_with_app_nibble_using(&spec, &secret, 0x10, &mut rng), Err(SSKRError::AppNibbleInvalid)));
+    }
+
+    #[test]
+    fn test_app_nibble_reserved() {
+        let mut rng = FakeRandomNumberGenerator;
+        let secret = test_support::secret();
+        let spec = Spec::new(1, vec![GroupSpec::new(2, 3).unwrap()]).unwrap();
+
+        // The values that mark a share format or a protected share would be
+        // misread, so they can't carry application data.
+        for app_nibble in [SHARE_VERSION_ESCAPE, 14, 15] {
+            assert!(matches!(
+                sskr_generate_with_app_nibble_using(&spec, &secret, app_nibble, &mut rng),
+                Err(SSKRError::AppNibbleReserved { app_nibble: found }) if found == app_nibble
+            ));
+        }
+
+        // Every other value is application data, which strict decoders
+        // reject cleanly.
+        for app_nibble in 2..=13 {
+            let shares = sskr_generate_with_app_nibble_using(&spec, &secret, app_nibble, &mut rng).unwrap();
+            assert!(matches!(sskr_combine(&shares[0][..2]), Err(SSKRError::AppNibblePresent { share_index: Some(0), app_nibble: v }) if v == app_nibble));
+            assert!(matches!(describe_share(&shares[0][0]), Err(SSKRError::AppNibblePresent { share_index: None, app_nibble: v }) if v == app_nibble));
+            assert_eq!(sskr_combine_lenient(&shares[0][1..]).unwrap(), secret);
+        }
+    }
+
+    #[test]
+    fn test_extended_format() {
+        let mut rng = FakeRandomNumberGenerator;
+        let secret = Secret::new(hex!("7daa851251002874e1a1995f0897e6b1")).unwrap();
+
+        // The standard bounds still apply unless the extended format is
+        // requested.
+        assert!(matches!(GroupSpec::new(20, 30), Err(SSKRError::MemberCountInvalid { count: 30, max: 16 })));
+        let group = GroupSpec::new_with_version(20, 30, ShareVersion::V1).unwrap();
+        assert!(GroupSpec::new_with_version(20, MAX_EXTENDED_SHARE_COUNT + 1, ShareVersion::V1).is_err());
+        assert!(Spec::new(1, vec![GroupSpec::default(); 17]).is_err());
+        assert!(Spec::new_with_version(1, vec![GroupSpec::default(); 17], ShareVersion::V1).is_ok());
+
+        let spec = Spec::new_with_version(2, vec![group, GroupSpec::new(2, 3).unwrap()], ShareVersion::V1).unwrap();
+        assert!(matches!(sskr_generate_using(&spec, &secret, &mut rng), Err(SSKRError::MemberCountInvalid { count: 30, max: 16 })));
+
+        let shares = sskr_generate_with_version_using(&spec, &secret, ShareVersion::V1, &mut rng).unwrap();
+        assert_eq!(shares[0].len(), 30);
+        for share in shares.iter().flatten() {
+            assert_eq!(share.len(), EXTENDED_METADATA_SIZE_BYTES + secret.len());
+            assert_eq!(share[4..6], [SHARE_VERSION_ESCAPE << 4, 1]);
+        }
+
+        let mut recovered_shares: Vec<Vec<u8>> = shares[0][5..25].to_vec();
+        recovered_shares.push(shares[1][0].clone());
+        recovered_shares.push(shares[1][2].clone());
+        fisher_yates_shuffle(&mut recovered_shares, &mut bc_rand::make_fake_random_number_generator());
+        assert_eq!(sskr_combine(&recovered_shares).unwrap(), secret);
+
+        // Nineteen members of the large group are not enough.
+        let too_few: Vec<Vec<u8>> = shares[0][..19].iter().chain(&shares[1][..2]).cloned().collect();
+        assert!(matches!(sskr_combine(&too_few), Err(SSKRError::NotEnoughMemberShares { group_index: 0, have: 19, need: 20 })));
+
+        // Extended shares can't be mixed with standard ones, and a decoder
+        // that predates the extended format sees an unknown version.
+        let standard = sskr_generate_using(&Spec::new(1, vec![GroupSpec::new(2, 3).unwrap()]).unwrap(), &secret, &mut rng).unwrap();
+        let mixed = vec![standard[0][0].clone(), shares[1][0].clone(), shares[1][1].clone()];
+        assert!(matches!(
+            sskr_combine(&mixed),
+            Err(SSKRError::ShareVersionMismatch { expected: ShareVersion::V0, found: ShareVersion::V1, share_index: 1 })
+        ));
+        let mut future = shares[0][0].clone();
+        future[5] = 4;
+        assert!(matches!(sskr_combine(&[future.clone()]), Err(SSKRError::UnsupportedShareVersion { share_index: Some(0), version: 4 })));
+        assert_eq!(describe_share(&future).unwrap_err().to_string(), "SSKR share uses format version 4, which this version of the library does not support");
+        assert_eq!(sskr_combine_lenient(&recovered_shares).unwrap(), secret);
+
+        // Every field is checked against the extended bounds.
+        let with_byte = |i: usize, b: u8| {
+            let mut share = shares[0][0].clone();
+            share[i] = b;
+            share
+        };
+        assert!(matches!(sskr_combine(&[with_byte(4, 0x11)]), Err(SSKRError::ShareReservedBitsInvalid { share_index: Some(0) })));
+        assert!(matches!(sskr_combine(&[with_byte(3, 0xff)]), Err(SSKRError::ShareGroupCountInvalid { share_index: Some(0), group_count: 256 })));
+        assert!(matches!(sskr_combine(&[with_byte(2, 0x05)]), Err(SSKRError::ShareGroupThresholdInvalid { share_index: Some(0), group_threshold: 6, group_count: 2 })));
+        assert!(matches!(sskr_combine(&[with_byte(6, 0x02)]), Err(SSKRError::GroupIndexInvalid { share_index: Some(0), group_index: 2, group_count: 2 })));
+        assert!(matches!(sskr_combine(&[with_byte(7, 0xfe)]), Err(SSKRError::ShareMemberThresholdInvalid { share_index: Some(0), member_threshold: 255 })));
+        assert!(matches!(sskr_combine(&[with_byte(8, 0xfe)]), Err(SSKRError::ShareMemberIndexInvalid { share_index: Some(0), member_index: 254 })));
+        assert!(matches!(sskr_combine(&[shares[0][0][..8].to_vec()]), Err(SSKRError::ShareLengthInvalid { share_index: Some(0), len: 8 })));
+        assert_eq!(describe_share(&shares[0][29]).unwrap(),
+            "SSKR share for split 0x0011: this is share 30 of group 1; the split has 2 groups, 2 groups required, and this group needs 20 of its shares. The share carries a 16-byte payload.");
+    }
+
+    #[test]
+    fn test_wide_identifier() {
+        let secret_a = test_support::secret();
+        let secret_b = Secret::new(hex!("204188bfa6b440a1bdfd6753ff55a824")).unwrap();
+        let spec = Spec::new(2, vec![GroupSpec::new(2, 3).unwrap(), GroupSpec::new(2, 3).unwrap()]).unwrap();
+
+        // Two independent standard splits that happen to draw the same 16-bit
+        // identifier are indistinguishable, so mixing their shares gets as far
+        // as the final checksum before failing.
+        let a = sskr_generate_using(&spec, &secret_a, &mut FakeRandomNumberGenerator).unwrap();
+        let b = sskr_generate_using(&spec, &secret_b, &mut FakeRandomNumberGenerator).unwrap();
+        assert_eq!(a[0][0][..2], b[0][0][..2]);
+        let mixed = vec![a[0][0].clone(), a[0][1].clone(), b[1][0].clone(), b[1][1].clone()];
+        assert!(matches!(
+            sskr_combine(&mixed),
+            Err(SSKRError::MasterRecoveryFailed { unused_complete_groups: 0, source: bc_shamir::Error::ChecksumFailure })
+        ));
+
+        // With wide identifiers, splits that agree on 16 bits of their
+        // identifiers are still told apart before any recovery is attempted.
+        let a = sskr_generate_with_version_using(&spec, &secret_a, ShareVersion::V2, &mut FakeRandomNumberGenerator).unwrap();
+        let mut b = sskr_generate_with_version_using(&spec, &secret_b, ShareVersion::V2, &mut FakeRandomNumberGenerator).unwrap();
+        for share in b.iter_mut().flatten() {
+            share[6] ^= 0x01;
+        }
+        for share in a.iter().flatten() {
+            assert_eq!(share.len(), WIDE_IDENTIFIER_METADATA_SIZE_BYTES + secret_a.len());
+            assert_eq!((share[4] >> 4, share[5]), (SHARE_VERSION_ESCAPE, 2));
+        }
+        assert_eq!(a[0][0][..5], b[0][0][..5]);
+        let mixed = vec![a[0][0].clone(), a[0][1].clone(), b[1][0].clone(), b[1][1].clone()];
+        assert!(matches!(sskr_combine(&mixed), Err(SSKRError::IdentifierMismatch { expected: 0x00112233, share_index: 2, .. })));
+
+        let quorum = vec![a[1][2].clone(), a[0][0].clone(), a[1][0].clone(), a[0][1].clone()];
+        assert_eq!(sskr_combine(&quorum).unwrap(), secret_a);
+        let quorum = vec![b[0][2].clone(), b[1][1].clone(), b[0][1].clone(), b[1][2].clone()];
+        assert_eq!(sskr_combine(&quorum).unwrap(), secret_b);
+
+        // Wide-identifier shares don't mix with standard ones either, and are
+        // described with their full identifier.
+        let standard = sskr_generate_using(&spec, &secret_a, &mut FakeRandomNumberGenerator).unwrap();
+        let mixed = vec![standard[0][0].clone(), standard[0][1].clone(), a[1][0].clone(), a[1][1].clone()];
+        assert!(matches!(sskr_combine(&mixed), Err(SSKRError::ShareVersionMismatch { share_index: 2, .. })));
+        assert!(describe_share(&a[0][0]).unwrap().starts_with("SSKR share for split 0x00112233:"));
+        assert!(matches!(sskr_combine(&[a[0][0][..6].to_vec()]), Err(SSKRError::ShareLengthInvalid { share_index: Some(0), len: 6 })));
+    }
+
+    #[test]
+    fn test_not_enough_member_shares() {
+        let mut rng = bc_rand::make_fake_random_number_generator();
+        let secret = test_support::secret();
+        let spec = Spec::new(2, vec![
+            GroupSpec::new(3, 5).unwrap(),
+            GroupSpec::new(2, 3).unwrap(),
+            GroupSpec::new(4, 4).unwrap(),
+        ]).unwrap();
+        let shares = sskr_generate_using(&spec, &secret, &mut rng).unwrap();
+
+        // A single group one share short, with nothing else to fall back on.
+        let spec_1 = Spec::new(1, vec![GroupSpec::new(3, 5).unwrap()]).unwrap();
+        let single = sskr_generate_using(&spec_1, &secret, &mut rng).unwrap();
+        let error = sskr_combine(&single[0][1..3]).unwrap_err();
+        assert!(matches!(error, SSKRError::NotEnoughMemberShares { group_index: 0, have: 2, need: 3 }));
+        assert_eq!(error.to_string(), "SSKR shares are not enough to recover the secret: group 0 has 2 of the 3 member shares it needs");
+
+        // The group closest to its threshold is named.
+        let short = [&shares[0][0], &shares[2][0], &shares[2][1], &shares[2][3]];
+        assert!(matches!(sskr_combine(&short), Err(SSKRError::NotEnoughMemberShares { group_index: 2, have: 3, need: 4 })));
+
+        // A short group is skipped when enough other groups are complete.
+        let enough = [&shares[0][0], &shares[1][2], &shares[1][0], &shares[2][0], &shares[2][1], &shares[2][3], &shares[2][2]];
+        assert_eq!(sskr_combine(&enough).unwrap(), secret);
+
+        // Too few groups to reach the group threshold is still reported as such.
+        assert!(matches!(sskr_combine(&shares[2][..2]), Err(SSKRError::NotEnoughGroups)));
+    }
+
+    #[test]
+    fn test_combine_mismatch_errors() {
+        let mut rng = bc_rand::make_fake_random_number_generator();
+        let secret = test_support::secret();
+        let spec = Spec::new(2, vec![GroupSpec::new(2, 3).unwrap(), GroupSpec::new(2, 3).unwrap()]).unwrap();
+        let shares = sskr_generate_using(&spec, &secret, &mut rng).unwrap();
+        let identifier = u16::from_be_bytes([shares[0][0][0], shares[0][0][1]]) as u32;
+        let with_share = |share: Vec<u8>| vec![shares[0][0].clone(), shares[0][1].clone(), share];
+
+        // Shares of another split.
+        let other = sskr_generate_using(&spec, &secret, &mut rng).unwrap();
+        let other_identifier = u16::from_be_bytes([other[1][0][0], other[1][0][1]]) as u32;
+        let error = sskr_combine(&with_share(other[1][0].clone())).unwrap_err();
+        assert!(matches!(error, SSKRError::IdentifierMismatch { expected, found, share_index: 2 } if expected == identifier && found == other_identifier));
+
+        // A split with the same identifier but a different group threshold
+        // or group count.
+        let patched = |byte: u8| {
+            let mut share = shares[1][0].clone();
+            share[2] = byte;
+            share
+        };
+        assert!(matches!(
+            sskr_combine(&with_share(patched(0x22))),
+            Err(SSKRError::GroupThresholdMismatch { expected: 2, found: 3, share_index: 2 })
+        ));
+        assert!(matches!(
+            sskr_combine(&with_share(patched(0x12))),
+            Err(SSKRError::GroupCountMismatch { expected: 2, found: 3, share_index: 2 })
+        ));
+
+        // A share of a different length.
+        let mut longer = shares[1][0].clone();
+        longer.extend_from_slice(&[0, 0]);
+        assert!(matches!(
+            sskr_combine(&with_share(longer)),
+            Err(SSKRError::ShareLengthMismatch { expected: 16, found: 18, share_index: 2 })
+        ));
+
+        // A member threshold that disagrees with the rest of its group.
+        let mut member = shares[0][2].clone();
+        member[3] = (member[3] & 0xf0) | 0x02;
+        assert!(matches!(
+            sskr_combine(&with_share(member)),
+            Err(SSKRError::MemberThresholdMismatch { group_index: 0, expected: 2, found: 3, share_index: 2 })
+        ));
+    }
+
+    #[test]
+    fn test_recovery_failure_errors() {
+        use std::error::Error;
+
+        let secret = test_support::secret();
+        let spec = Spec::new(2, vec![
+            GroupSpec::new(2, 3).unwrap(),
+            GroupSpec::new(3, 5).unwrap(),
+            GroupSpec::new(2, 2).unwrap(),
+        ]).unwrap();
+        let shares = sskr_generate_using(&spec, &secret, &mut bc_rand::make_fake_random_number_generator()).unwrap();
+
+        // A corrupt byte in one group's shares is blamed on that group.
+        let mut corrupt = shares[1][3].clone();
+        corrupt[METADATA_SIZE_BYTES + 3] ^= 0x40;
+        let recovered_shares = vec![shares[0][0].clone(), shares[0][2].clone(), shares[1][0].clone(), corrupt.clone(), shares[1][4].clone()];
+        let error = sskr_combine(&recovered_shares).unwrap_err();
+        assert!(matches!(error, SSKRError::GroupRecoveryFailed { group_index: 1, source: bc_shamir::Error::ChecksumFailure }));
+        assert!(error.source().unwrap().downcast_ref::<bc_shamir::Error>().is_some());
+        assert!(error.to_string().starts_with("SSKR group 1 could not be recovered from its member shares"));
+
+        // The corrupt group is skipped when another complete group can take
+        // its place.
+        let mut with_alternative = recovered_shares.clone();
+        with_alternative.extend([shares[2][0].clone(), shares[2][1].clone()]);
+        assert_eq!(sskr_combine(&with_alternative).unwrap(), secret);
+
+        // A failure of the final interpolation is reported separately, with
+        // the number of complete groups left unused. The second split draws
+        // the same identifier, so its shares pass for the first's.
+        let mut rng = bc_rand::make_fake_random_number_generator();
+        let other_secret = Secret::new(hex!("204188bfa6b440a1bdfd6753ff55a824")).unwrap();
+        let other = sskr_generate_using(&spec, &other_secret, &mut rng).unwrap();
+        let mixed = vec![
+            shares[0][0].clone(), shares[0][1].clone(),
+            other[2][0].clone(), other[2][1].clone(),
+            shares[1][0].clone(), shares[1][1].clone(), shares[1][2].clone(),
+        ];
+        let error = sskr_combine(&mixed).unwrap_err();
+        assert!(matches!(error, SSKRError::MasterRecoveryFailed { unused_complete_groups: 1, source: bc_shamir::Error::ChecksumFailure }));
+        assert!(error.source().is_some());
+    }
+
+    #[test]
+    fn test_invalid_parameter_errors() {
+        let error = Secret::new([0u8; 31]).unwrap_err();
+        assert!(matches!(error, SSKRError::SecretLengthNotEven { len: 31 }));
+        assert_eq!(error.to_string(), "SSKR secret is 31 bytes, but its length must be even");
+        let error = Secret::new([0u8; 14]).unwrap_err();
+        assert!(matches!(error, SSKRError::SecretTooShort { len: 14, min: 16 }));
+        assert_eq!(error.to_string(), "SSKR secret is 14 bytes, which is shorter than the minimum of 16");
+        let error = Secret::new([0u8; 34]).unwrap_err();
+        assert!(matches!(error, SSKRError::SecretTooLong { len: 34, max: 32 }));
+        assert_eq!(error.to_string(), "SSKR secret is 34 bytes, which is longer than the maximum of 32");
+
+        let error = GroupSpec::new(0, 3).unwrap_err();
+        assert!(matches!(error, SSKRError::MemberThresholdInvalid { threshold: 0, count: 3 }));
+        assert_eq!(error.to_string(), "SSKR member threshold 0 is invalid: it must be between 1 and the member count of 3");
+        assert!(matches!(GroupSpec::new(4, 3), Err(SSKRError::MemberThresholdInvalid { threshold: 4, count: 3 })));
+        let error = GroupSpec::new(1, 0).unwrap_err();
+        assert!(matches!(error, SSKRError::MemberCountInvalid { count: 0, max: 16 }));
+        assert_eq!(error.to_string(), "SSKR member count 0 is invalid: it must be between 1 and 16");
+
+        let groups = vec![GroupSpec::new(2, 3).unwrap(), GroupSpec::new(2, 3).unwrap()];
+        let error = Spec::new(3, groups.clone()).unwrap_err();
+        assert!(matches!(error, SSKRError::GroupThresholdInvalid { threshold: 3, group_count: 2 }));
+        assert_eq!(error.to_string(), "SSKR group threshold 3 is invalid: it must be between 1 and the group count of 2");
+        assert!(matches!(Spec::new(0, groups), Err(SSKRError::GroupThresholdInvalid { threshold: 0, group_count: 2 })));
+    }
+
+    #[test]
+    fn test_duplicate_shares() {
+        let secret = test_support::secret();
+        let spec = Spec::new(2, vec![
+            GroupSpec::new(2, 3).unwrap(),
+            GroupSpec::new(3, 5).unwrap(),
+        ]).unwrap();
+        let shares = sskr_generate_using(&spec, &secret, &mut FakeRandomNumberGenerator).unwrap();
+
+        // A share given twice is used once.
+        let identical = [&shares[0][1], &shares[1][0], &shares[0][1]];
+        assert!(matches!(sskr_combine(&identical), Err(SSKRError::NotEnoughMemberShares { group_index: 0, have: 1, need: 2 })));
+
+        // Duplicates alongside enough distinct shares still recover the secret,
+        // including a duplicate of a share beyond the member threshold.
+        let with_duplicates = [
+            &shares[0][0], &shares[1][2], &shares[0][0], &shares[1][0], &shares[0][2],
+            &shares[1][2], &shares[1][4], &shares[0][2], &shares[1][3], &shares[1][3],
+        ];
+        assert_eq!(sskr_combine(&with_duplicates).unwrap(), secret);
+
+        // Two different values for the same member are an error.
+        let mut conflicting = shares[1][4].clone();
+        conflicting[6] ^= 0x01;
+        let error = sskr_combine(&[&shares[1][4], &shares[0][0], &conflicting]).unwrap_err();
+        assert!(matches!(error, SSKRError::ConflictingDuplicateShare { group_index: 1, member_index: 4 }));
+        assert!(matches!(
+            sskr_combine(&[&shares[1][0], &shares[1][1], &shares[1][2], &shares[1][4], &conflicting]),
+            Err(SSKRError::ConflictingDuplicateShare { group_index: 1, member_index: 4 })
+        ));
+    }
+
+    #[test]
+    fn test_combine_verified() {
+        let secret = test_support::secret();
+        let spec = Spec::new(1, vec![GroupSpec::new(2, 3).unwrap()]).unwrap();
+        let shares = sskr_generate_using(&spec, &secret, &mut FakeRandomNumberGenerator).unwrap();
+        assert_eq!(sskr_combine_verified(&shares[0]).unwrap(), secret);
+        assert_eq!(sskr_combine_verified(&shares[0][1..]).unwrap(), secret);
+
+        // A corrupt extra share is ignored by `sskr_combine`, but detected here.
+        let mut corrupt = shares[0][2].clone();
+        corrupt[7] ^= 0x01;
+        let input = [&shares[0][0], &shares[0][1], &corrupt];
+        assert_eq!(sskr_combine(&input).unwrap(), secret);
+        let error = sskr_combine_verified(&input).unwrap_err();
+        assert!(matches!(error, SSKRError::InconsistentShare { group_index: 0, member_index: 2 }));
+        assert_eq!(error.code(), "inconsistent_share");
+
+        // Extra shares of a 1-of-n group, and of extended-format splits, are
+        // checked too.
+        let spec = Spec::new(2, vec![GroupSpec::new(1, 3).unwrap(), GroupSpec::new(3, 5).unwrap()]).unwrap();
+        for version in [ShareVersion::V0, ShareVersion::V1] {
+            let shares = sskr_generate_with_version_using(&spec, &secret, version, &mut FakeRandomNumberGenerator).unwrap();
+            let all_shares: Vec<&Vec<u8>> = shares.iter().flatten().collect();
+            assert_eq!(sskr_combine_verified(&all_shares).unwrap(), secret);
+
+            let mut corrupt = shares[0][2].clone();
+            let last = corrupt.len() - 1;
+            corrupt[last] ^= 0x80;
+            let input = [&shares[1][4], &shares[1][0], &shares[1][2], &shares[0][0], &shares[1][1], &corrupt];
+            assert_eq!(sskr_combine(&input).unwrap(), secret);
+            assert!(matches!(sskr_combine_verified(&input), Err(SSKRError::InconsistentShare { group_index: 0, member_index: 2 })));
+        }
+    }
+
+    #[test]
+    fn test_combine_with_spec() {
+        let secret = test_support::secret();
+        let spec = Spec::new(2, vec![GroupSpec::new(2, 3).unwrap(), GroupSpec::new(3, 5).unwrap()]).unwrap();
+        let shares = sskr_generate_using(&spec, &secret, &mut FakeRandomNumberGenerator).unwrap();
+        let input = [&shares[0][0], &shares[0][2], &shares[1][0], &shares[1][1], &shares[1][4]];
+        assert_eq!(sskr_combine_with_spec(&spec, &input).unwrap(), secret);
+
+        // Member counts are not checked.
+        let more_members = Spec::new(2, vec![GroupSpec::new(2, 4).unwrap(), GroupSpec::new(3, 5).unwrap()]).unwrap();
+        assert_eq!(sskr_combine_with_spec(&more_members, &input).unwrap(), secret);
+
+        let other = Spec::new(2, vec![GroupSpec::new(2, 3).unwrap(), GroupSpec::new(2, 5).unwrap()]).unwrap();
+        let error = sskr_combine_with_spec(&other, &input).unwrap_err();
+        assert!(matches!(
+            error,
+            SSKRError::SpecMismatch { field: SpecField::MemberThreshold { group_index: 1 }, expected: 2, found: 3, share_index: 2 }
+        ));
+        assert_eq!(
+            error.to_string(),
+            "SSKR share at index 2 is not from the expected split: its member threshold of group 1 is 3, not 2"
+        );
+
+        let other = Spec::new(1, vec![GroupSpec::new(2, 3).unwrap(), GroupSpec::new(3, 5).unwrap()]).unwrap();
+        assert!(matches!(
+            sskr_combine_with_spec(&other, &input),
+            Err(SSKRError::SpecMismatch { field: SpecField::GroupThreshold, expected: 1, found: 2, share_index: 0 })
+        ));
+        let other = test_support::spec();
+        assert!(matches!(
+            sskr_combine_with_spec(&other, &input),
+            Err(SSKRError::SpecMismatch { field: SpecField::GroupCount, expected: 3, found: 2, share_index: 0 })
+        ));
+    }
+
+    #[test]
+    fn test_spec_from_shares() {
+        let secret = test_support::secret();
+        let spec = test_support::spec();
+        let shares = sskr_generate_using(&spec, &secret, &mut FakeRandomNumberGenerator).unwrap();
+
+        let partial = Spec::from_shares(&[&shares[2][0], &shares[0][1]]).unwrap();
+        assert_eq!(partial.version(), ShareVersion::V0);
+        assert_eq!((partial.group_threshold(), partial.group_count()), (2, 3));
+        assert_eq!(partial.member_thresholds(), [Some(2), None, Some(1)]);
+        assert_eq!(partial.member_threshold(1), None);
+        assert_eq!(partial.member_threshold(3), None);
+        assert!(!partial.is_complete());
+
+        let partial = Spec::from_shares(&[&shares[1][4], &shares[2][0], &shares[0][1]]).unwrap();
+        assert_eq!(partial.member_thresholds(), [Some(2), Some(3), Some(1)]);
+        assert!(partial.is_complete());
+
+        let mut mismatched = shares[1][0].clone();
+        mismatched[0] ^= 0x01;
+        assert!(matches!(
+            Spec::from_shares(&[&shares[0][0], &mismatched]),
+            Err(SSKRError::IdentifierMismatch { share_index: 1, .. })
+        ));
+        assert!(matches!(Spec::from_shares::<Vec<u8>>(&[]), Err(SSKRError::SharesEmpty)));
+    }
+
+    #[test]
+    fn test_combine_detailed() {
+        let secret = test_support::secret();
+        let spec = test_support::spec();
+        let shares = sskr_generate_using(&spec, &secret, &mut FakeRandomNumberGenerator).unwrap();
+
+        let input = [&shares[2][0], &shares[1][0], &shares[1][3], &shares[0][2], &shares[1][1], &shares[1][2]];
+        let recovered = sskr_combine_detailed(&input).unwrap();
+        assert_eq!(*recovered, secret);
+        assert_eq!(recovered.len(), secret.len());
+        let info = recovered.info();
+        assert_eq!(info.identifier(), 0x0011);
+        assert_eq!(info.version(), ShareVersion::V0);
+        assert_eq!((info.group_threshold(), info.group_count()), (2, 3));
+        assert_eq!(info.groups_used(), [2, 1]);
+        assert_eq!(info.shares_used(), 4);
+
+        let (recovered_secret, _) = recovered.into_parts();
+        assert_eq!(recovered_secret, sskr_combine(&input).unwrap());
+        assert!(matches!(sskr_combine_detailed(&input[..2]), Err(SSKRError::NotEnoughMemberShares { group_index: 1, .. })));
+    }
+
+    #[test]
+    fn test_combine_iter() {
+        let secret = test_support::secret();
+        let spec = Spec::new(1, vec![GroupSpec::new(2, 3).unwrap()]).unwrap();
+        let shares = sskr_generate_using(&spec, &secret, &mut FakeRandomNumberGenerator).unwrap();
+
+        assert_eq!(sskr_combine_iter(shares[0].clone()).unwrap(), secret);
+        assert_eq!(sskr_combine_iter(&shares[0][1..]).unwrap(), secret);
+        let hex_rows = shares[0].iter().map(hex::encode).collect::<Vec<_>>();
+        assert_eq!(sskr_combine_iter(hex_rows.iter().map(|row| hex::decode(row).unwrap())).unwrap(), secret);
+
+        // Errors refer to the position the iterator yielded the share at.
+        let mut corrupt = shares[0][2].clone();
+        corrupt[0] ^= 1;
+        let error = sskr_combine_iter([&shares[0][0], &shares[0][1], &corrupt]).unwrap_err();
+        assert!(matches!(error, SSKRError::IdentifierMismatch { share_index: 2, .. }));
+        assert!(matches!(sskr_combine_iter(std::iter::empty::<Vec<u8>>()), Err(SSKRError::SharesEmpty)));
+    }
+
+    #[test]
+    fn test_combine_into() {
+        let secret = test_support::secret();
+        let spec = Spec::new(1, vec![GroupSpec::new(2, 3).unwrap()]).unwrap();
+        let shares = sskr_generate_using(&spec, &secret, &mut FakeRandomNumberGenerator).unwrap();
+
+        let mut out = [0u8; 16];
+        assert_eq!(sskr_combine_into(&shares[0][..2], &mut out).unwrap(), 16);
+        assert_eq!(out, secret.data());
+
+        // The rest of a larger buffer is left as it was.
+        let mut out = [0xaa; 24];
+        assert_eq!(sskr_combine_into(&shares[0][1..], &mut out).unwrap(), 16);
+        assert_eq!(&out[..16], secret.data());
+        assert_eq!(out[16..], [0xaa; 8]);
+
+        // A short buffer is refused before the shares are combined, even
+        // when they could not be.
+        let mut out = [0xaa; 15];
+        assert!(matches!(
+            sskr_combine_into(&shares[0][..1], &mut out),
+            Err(SSKRError::OutputBufferTooSmall { len: 15, required: 16 })
+        ));
+        assert_eq!(out, [0xaa; 15]);
+        assert!(matches!(sskr_combine_into::<Vec<u8>>(&[], &mut out), Err(SSKRError::SharesEmpty)));
+    }
+
+    #[test]
+    fn test_combine_expecting() {
+        let secret = test_support::secret();
+        let spec = Spec::new(1, vec![GroupSpec::new(2, 3).unwrap()]).unwrap();
+        let shares = sskr_generate_using(&spec, &secret, &mut FakeRandomNumberGenerator).unwrap();
+        let digest = bc_crypto::hash::sha256(secret.data());
+
+        assert_eq!(sskr_combine_expecting(&shares[0][..2], &digest).unwrap(), secret);
+
+        // Shares of a superseded split combine, but to another secret.
+        let old_secret = Secret::new(hex!("00112233445566778899aabbccddeeff")).unwrap();
+        let old_shares = sskr_generate_using(&spec, &old_secret, &mut FakeRandomNumberGenerator).unwrap();
+        assert!(matches!(sskr_combine_expecting(&old_shares[0][1..], &digest), Err(SSKRError::FingerprintMismatch)));
+        let mut last_bit = digest;
+        last_bit[31] ^= 0x01;
+        assert!(matches!(sskr_combine_expecting(&shares[0][..2], &last_bit), Err(SSKRError::FingerprintMismatch)));
+
+        // Only a full SHA-256 digest can be expected.
+        assert!(<&[u8; 32]>::try_from(&digest[..20]).is_err());
+
+        assert!(matches!(sskr_combine_expecting(&shares[0][..1], &digest), Err(SSKRError::NotEnoughMemberShares { .. })));
+    }
+
+    #[test]
+    fn test_generate_with_identifier() {
+        let secret = test_support::secret();
+        let spec = Spec::new(1, vec![GroupSpec::new(2, 3).unwrap()]).unwrap();
+        let shares = sskr_generate_with_identifier_using(&spec, &secret, 0x1234, &mut FakeRandomNumberGenerator).unwrap();
+        assert!(shares[0].iter().all(|share| share[..2] == [0x12, 0x34]));
+        assert_eq!(sskr_combine(&shares[0][1..]).unwrap(), secret);
+        assert_eq!(sskr_combine_detailed(&shares[0][..2]).unwrap().info().identifier(), 0x1234);
+
+        let shares = sskr_generate_with_identifier_using(&spec, &secret, 0xffff, &mut bc_rand::SecureRandomNumberGenerator).unwrap();
+        assert_eq!(shares[0][0][..2], [0xff, 0xff]);
+        assert_eq!(sskr_combine(&shares[0][..2]).unwrap(), secret);
+
+        // The identifier of standard shares is 16 bits.
+        assert!(matches!(
+            sskr_generate_with_identifier_using(&spec, &secret, 0x1_0000, &mut FakeRandomNumberGenerator),
+            Err(SSKRError::IdentifierInvalid { identifier: 0x1_0000, version: ShareVersion::V0 })
+        ));
+
+        // Splits that share an identifier can't tell their shares apart.
+        let other = sskr_generate_with_identifier_using(&spec, &secret, 0xffff, &mut bc_rand::SecureRandomNumberGenerator).unwrap();
+        assert!(matches!(sskr_combine(&[&shares[0][0], &other[0][1]]), Err(SSKRError::GroupRecoveryFailed { group_index: 0, .. })));
+        assert!(matches!(
+            sskr_combine(&[&shares[0][0], &other[0][0], &shares[0][1]]),
+            Err(SSKRError::ConflictingDuplicateShare { group_index: 0, member_index: 0 })
+        ));
+    }
+
+    #[test]
+    fn test_generate_detailed() {
+        let secret = test_support::secret();
+        let spec = Spec::new(2, vec![GroupSpec::new(2, 3).unwrap(), GroupSpec::new(3, 5).unwrap()]).unwrap();
+        let generated = sskr_generate_detailed_using(&spec, &secret, &mut FakeRandomNumberGenerator).unwrap();
+        assert_eq!(generated.identifier(), 0x0011);
+        assert_eq!(generated.spec(), &spec);
+        assert_eq!(generated.shares(), sskr_generate_using(&spec, &secret, &mut FakeRandomNumberGenerator).unwrap());
+
+        let generated = sskr_generate_detailed_using(&spec, &secret, &mut bc_rand::SecureRandomNumberGenerator).unwrap();
+        let identifier = generated.identifier();
+        for share in generated.shares().iter().flatten() {
+            assert_eq!(u16::from_be_bytes([share[0], share[1]]) as u32, identifier);
+        }
+        let shares = generated.into_shares();
+        assert_eq!(sskr_combine(&[&shares[0][0], &shares[0][1], &shares[1][0], &shares[1][1], &shares[1][2]]).unwrap(), secret);
+    }
+
+    #[test]
+    fn test_generate_shares() {
+        let secret = test_support::secret();
+        let spec = Spec::new(2, vec![GroupSpec::new(2, 3).unwrap(), GroupSpec::new(3, 5).unwrap()]).unwrap();
+        let shares = sskr_generate_shares_using(&spec, &secret, &mut FakeRandomNumberGenerator).unwrap();
+        let bytes = sskr_generate_using(&spec, &secret, &mut FakeRandomNumberGenerator).unwrap();
+        assert_eq!(shares.len(), bytes.len());
+        for (group_index, (group, group_bytes)) in shares.iter().zip(&bytes).enumerate() {
+            assert_eq!(group.len(), group_bytes.len());
+            for (member_index, (share, share_bytes)) in group.iter().zip(group_bytes).enumerate() {
+                assert_eq!(&share.serialize(), share_bytes);
+                assert_eq!((share.group_index(), share.member_index()), (group_index, member_index));
+                assert_eq!(share.identifier(), 0x0011);
+            }
+        }
+
+        let shares = sskr_generate_shares_using(&spec, &secret, &mut bc_rand::SecureRandomNumberGenerator).unwrap();
+        assert_eq!(sskr_combine_shares(&[&shares[0][..2], &shares[1][2..]].concat()).unwrap(), secret);
+    }
+
+    #[test]
+    fn test_weighted_group() {
+        let secret = Secret::new(b"my secret belongs to me.").unwrap();
+        let group = GroupSpec::with_weights(3, &[2, 1, 1, 1]).unwrap();
+        assert_eq!((group.member_threshold(), group.member_count(), group.weights()), (3, 5, Some(&[2, 1, 1, 1][..])));
+        let layout = group.holder_layout();
+        assert_eq!(layout.holder_count(), 4);
+        assert_eq!(layout.member_indexes(0), Some(0..2));
+        assert_eq!(layout.member_indexes(3), Some(4..5));
+        assert_eq!(layout.member_indexes(4), None);
+        assert_eq!((layout.holder_of(1), layout.holder_of(2), layout.holder_of(5)), (Some(0), Some(1), None));
+
+        let spec = Spec::new(1, vec![group]).unwrap();
+        let generated = sskr_generate_detailed_using(&spec, &secret, &mut FakeRandomNumberGenerator).unwrap();
+        let matriarch = generated.holder_shares(0, 0).unwrap();
+        assert_eq!(matriarch.len(), 2);
+        let other = &generated.holder_shares(0, 2).unwrap()[0];
+        assert_eq!(sskr_combine(&[&matriarch[0], &matriarch[1], other]).unwrap(), secret);
+        assert!(matches!(
+            sskr_combine(matriarch),
+            Err(SSKRError::NotEnoughMemberShares { group_index: 0, have: 2, need: 3 })
+        ));
+        assert!(generated.holder_shares(0, 4).is_none());
+        assert!(generated.holder_shares(1, 0).is_none());
+
+        // Without weights each share has its own holder.
+        let layout = GroupSpec::new(2, 3).unwrap().holder_layout();
+        assert_eq!((layout.holder_count(), layout.member_indexes(2)), (3, Some(2..3)));
+        assert_eq!(GroupSpec::new(2, 3).unwrap().weights(), None);
+
+        assert!(matches!(
+            GroupSpec::with_weights(3, &[1, 3, 1]),
+            Err(SSKRError::HolderWeightInvalid { holder_index: 1, weight: 3, member_threshold: 3 })
+        ));
+        assert!(matches!(GroupSpec::with_weights(2, &[1, 0, 1]), Err(SSKRError::HolderWeightInvalid { holder_index: 1, .. })));
+        assert!(matches!(GroupSpec::with_weights(3, &[2, 2, 2, 2, 2, 2, 2, 2, 2]), Err(SSKRError::MemberCountInvalid { count: 18, max: 16 })));
+        assert!(matches!(GroupSpec::with_weights(3, &[]), Err(SSKRError::MemberCountInvalid { count: 0, .. })));
+    }
+
+    #[test]
+    fn test_required_member() {
+        let secret = Secret::new(b"my secret belongs to me.").unwrap();
+        let (spec, required) = Spec::with_required_member(GroupSpec::new(3, 5).unwrap(), 1).unwrap();
+        assert_eq!(spec.group_threshold(), 2);
+        assert_eq!(spec.groups(), [GroupSpec::new(1, 1).unwrap(), GroupSpec::new(2, 4).unwrap()]);
+        assert_eq!((required.required_count(), required.member_count()), (1, 5));
+        assert!(required.is_required(0) && !required.is_required(1));
+        assert_eq!(required.share_location(0), Some((RequiredMembers::REQUIRED_GROUP_INDEX, 0)));
+        assert_eq!(required.share_location(4), Some((RequiredMembers::OTHER_GROUP_INDEX, 3)));
+        assert_eq!(required.share_location(5), None);
+
+        let shares = sskr_generate_using(&spec, &secret, &mut FakeRandomNumberGenerator).unwrap();
+        let share = |member_index: usize| {
+            let (group_index, member_index) = required.share_location(member_index).unwrap();
+            shares[group_index][member_index].clone()
+        };
+
+        // Every set of three members recovers the secret if and only if it
+        // includes the officer.
+        for a in 0..5 {
+            for b in a + 1..5 {
+                for c in b + 1..5 {
+                    let result = sskr_combine(&[share(a), share(b), share(c)]);
+                    if a == 0 {
+                        assert_eq!(result.unwrap(), secret);
+                    } else {
+                        assert!(matches!(result, Err(SSKRError::NotEnoughGroups)));
+                    }
+                }
+            }
+        }
+        assert!(sskr_combine(&[share(1), share(2), share(3), share(4)]).is_err());
+
+        let (spec, required) = Spec::with_required_member(GroupSpec::new(4, 6).unwrap(), 2).unwrap();
+        assert_eq!(spec.groups(), [GroupSpec::new(2, 2).unwrap(), GroupSpec::new(2, 4).unwrap()]);
+        assert_eq!(required.share_location(2), Some((1, 0)));
+
+        assert!(matches!(
+            Spec::with_required_member(GroupSpec::new(3, 5).unwrap(), 3),
+            Err(SSKRError::RequiredCountInvalid { required_count: 3, member_threshold: 3 })
+        ));
+        assert!(matches!(Spec::with_required_member(GroupSpec::new(3, 5).unwrap(), 0), Err(SSKRError::RequiredCountInvalid { .. })));
+    }
+
+    #[test]
+    fn test_owner_plus_social() {
+        let secret = Secret::new(b"my secret belongs to me.").unwrap();
+        let spec = Spec::owner_plus_social(2, GroupSpec::new(3, 5).unwrap()).unwrap();
+        assert_eq!(spec.group_threshold(), 1);
+        assert_eq!(spec.groups(), [GroupSpec::new(1, 2).unwrap(), GroupSpec::new(3, 5).unwrap()]);
+        assert_eq!(spec.owner_group_index(), Some(Spec::OWNER_GROUP_INDEX));
+        assert_eq!(
+            spec.describe(),
+            "Recoverable by the owner alone with any 1 of their 2 shares, or without the owner by 3 of the 5 social shares together."
+        );
+
+        let generated = sskr_generate_detailed_using(&spec, &secret, &mut FakeRandomNumberGenerator).unwrap();
+        let owner = generated.owner_shares().unwrap();
+        assert_eq!(owner, generated.shares()[0]);
+        let social = &generated.shares()[1];
+
+        assert_eq!(sskr_combine(&owner[..1]).unwrap(), secret);
+        assert_eq!(sskr_combine(&owner[1..]).unwrap(), secret);
+        assert_eq!(sskr_combine(&[&social[4], &social[0], &social[2]]).unwrap(), secret);
+        assert!(matches!(
+            sskr_combine(&social[..2]),
+            Err(SSKRError::NotEnoughMemberShares { group_index: 1, have: 2, need: 3 })
+        ));
+
+        assert!(matches!(
+            Spec::owner_plus_social(1, GroupSpec::new(1, 3).unwrap()),
+            Err(SSKRError::SocialThresholdInvalid { threshold: 1 })
+        ));
+        assert!(matches!(Spec::owner_plus_social(0, GroupSpec::new(2, 3).unwrap()), Err(SSKRError::MemberCountInvalid { .. })));
+
+        let spec = Spec::new(2, vec![GroupSpec::new(2, 3).unwrap(), GroupSpec::new(1, 1).unwrap(), GroupSpec::new(3, 5).unwrap()]).unwrap();
+        assert_eq!(spec.owner_group_index(), None);
+        assert_eq!(
+            spec.describe(),
+            "Recoverable with 2 of the 3 groups: group 1 needs 2 of its 3 shares, group 2 needs 1 of its 1 share, and group 3 needs 3 of its 5 shares."
+        );
+        let spec = Spec::new(1, vec![GroupSpec::new(2, 3).unwrap(), GroupSpec::new(1, 1).unwrap()]).unwrap();
+        assert_eq!(spec.describe(), "Recoverable with 1 of the 2 groups: group 1 needs 2 of its 3 shares and group 2 needs 1 of its 1 share.");
+        assert!(sskr_generate_detailed_using(&spec, &secret, &mut FakeRandomNumberGenerator).unwrap().owner_shares().is_none());
+        let spec = Spec::new(1, vec![GroupSpec::new(2, 3).unwrap()]).unwrap();
+        assert_eq!(spec.describe(), "Recoverable with 1 of the 1 group: group 1 needs 2 of its 3 shares.");
+    }
+
+    #[cfg(feature = "default-rng")]
+    #[test]
+    fn test_default_rng() {
+        let secret = test_support::secret();
+        let spec = Spec::new(1, vec![GroupSpec::new(2, 3).unwrap()]).unwrap();
+
+        let shares = sskr_generate(&spec, &secret).unwrap();
+        assert_eq!(sskr_combine(&shares[0][1..]).unwrap(), secret);
+        let shares = sskr_generate_with_version(&spec, &secret, ShareVersion::V1).unwrap();
+        assert_eq!(sskr_combine(&shares[0][..2]).unwrap(), secret);
+        let shares = sskr_generate_with_identifier(&spec, &secret, 0x1234).unwrap();
+        assert_eq!(sskr_combine(&shares[0][..2]).unwrap(), secret);
+        let shares = sskr_generate_with_app_nibble(&spec, &secret, 0x5).unwrap();
+        assert_eq!(sskr_app_nibble(&shares[0][0]).unwrap(), 0x5);
+        let shares = sskr_generate_constrained(&spec, &secret, &[]).unwrap();
+        assert_eq!(sskr_combine(&shares[0][..2]).unwrap(), secret);
+        let (shares, _) = sskr_generate_with_transcript(&spec, &secret, 0).unwrap();
+        assert_eq!(sskr_combine(&shares[0][..2]).unwrap(), secret);
+
+        let shares = sskr_generate_shares(&spec, &secret).unwrap();
+        assert_eq!(sskr_combine_shares(&shares[0][..2]).unwrap(), secret);
+        let generated = sskr_generate_detailed(&spec, &secret).unwrap();
+        assert_eq!(sskr_combine(&generated.shares()[0][..2]).unwrap(), secret);
+        let shares = sskr_generate_share_bytes(&spec, &secret).unwrap();
+        assert_eq!(sskr_combine(&shares[0][..2]).unwrap(), secret);
+        let share_set = sskr_generate_share_set(&spec, &secret).unwrap();
+        assert_eq!(sskr_combine(&share_set.group(0)[..2]).unwrap(), secret);
+        let copies = sskr_generate_with_options(&spec, &secret, &GenerateOptions::new()).unwrap();
+        assert_eq!(sskr_combine(&copies[0][..2]).unwrap(), secret);
+    }
+
+    #[test]
+    fn test_error_codes() {
+        assert_eq!(SSKRError::SharesEmpty.code(), "shares_empty");
+        assert_eq!(SSKRError::UnsupportedShareVersion { share_index: None, version: 3 }.code(), "unsupported_share_version");
+        assert_eq!(SSKRError::IdentifierMismatch { expected: 1, found: 2, share_index: 1 }.code(), "identifier_mismatch");
+        assert_eq!(SSKRError::from(bc_shamir::Error::ChecksumFailure).code(), "shamir_error");
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_error_json() {
+        let json = |error: &SSKRError| serde_json::to_string(error).unwrap();
+
+        // These snapshots are the format front-ends depend on; a change to
+        // any of them is a breaking change.
+        assert_eq!(json(&SSKRError::SharesEmpty), r#"{"code":"shares_empty"}"#);
+        assert_eq!(
+            json(&SSKRError::UnsupportedShareVersion { share_index: Some(1), version: 3 }),
+            r#"{"code":"unsupported_share_version","data":{"share_index":1,"version":3}}"#
+        );
+        assert_eq!(
+            json(&SSKRError::IdentifierMismatch { expected: 0x0011, found: 0x2233, share_index: 2 }),
+            r#"{"code":"identifier_mismatch","data":{"expected":17,"found":8755,"share_index":2}}"#
+        );
+        assert_eq!(
+            json(&SSKRError::ShareVersionMismatch { expected: ShareVersion::V0, found: ShareVersion::V2, share_index: 1 }),
+            r#"{"code":"share_version_mismatch","data":{"expected":"V0","found":"V2","share_index":1}}"#
+        );
+        assert_eq!(
+            json(&SSKRError::GroupRecoveryFailed { group_index: 1, source: bc_shamir::Error::ChecksumFailure }),
+            r#"{"code":"group_recovery_failed","data":{"group_index":1,"source":"checksum failure"}}"#
+        );
+
+        // The serialized code is the one `code` returns.
+        let error = sskr_combine::<Vec<u8>>(&[]).unwrap_err();
+        let value = serde_json::to_value(&error).unwrap();
+        assert_eq!(value["code"], error.code());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_report_json() {
+        let secret = test_support::secret();
+        let spec = Spec::new(1, vec![GroupSpec::new(2, 3).unwrap()]).unwrap();
+        let shares = sskr_generate_using(&spec, &secret, &mut FakeRandomNumberGenerator).unwrap();
+
+        let (_, report) = sskr_combine_with_report(&[&shares[0][0], &shares[0][0]]);
+        assert_eq!(
+            serde_json::to_string(&report).unwrap(),
+            concat!(
+                r#"{"identifiers":[17],"share_count":2,"share_usage":["GroupIncomplete","Duplicate"],"#,
+                r#""groups":[{"group_index":0,"member_threshold":2,"shares_provided":1,"shares_used":0,"outcome":"NotEnoughShares"}],"#,
+                r#""malformed_shares":[],"mismatched_shares":[],"duplicate_shares":[1]}"#,
+            )
+        );
+
+        let mut collector = ShareCollector::new();
+        let status = collector.add_share(&shares[0][2]).unwrap();
+        assert_eq!(
+            serde_json::to_string(&status).unwrap(),
+            concat!(
+                r#"{"identifier":17,"group_threshold":1,"group_count":1,"share_count":1,"#,
+                r#""groups":[{"group_index":0,"member_threshold":2,"member_indexes":[2]}]}"#,
+            )
+        );
+    }
+
     struct RecoverSpec {
         secret: Secret,
         spec: Spec,
@@ -355,7 +1680,7 @@ mod tests {
 
     #[test]
     fn example_encode() {
-        use crate::{ Secret, GroupSpec, Spec, sskr_generate, sskr_combine };
+        use crate::{ Secret, GroupSpec, Spec, sskr_generate_using, sskr_combine };
 
         let secret_string = b"my secret belongs to me.";
         let secret = Secret::new(secret_string).unwrap();
@@ -371,7 +1696,7 @@ mod tests {
 
         // The result is a vector of groups, each containing a vector of shares,
         // each of which is a vector of bytes.
-        let shares: Vec<Vec<Vec<u8>>> = sskr_generate(&spec, &secret).unwrap();
+        let shares: Vec<Vec<Vec<u8>>> = sskr_generate_using(&spec, &secret, &mut bc_rand::SecureRandomNumberGenerator).unwrap();
 
         assert_eq!(shares.len(), 2);
         assert_eq!(shares[0].len(), 3);
@@ -397,7 +1722,7 @@ mod tests {
     /// Test fix for [#1](https://github.com/BlockchainCommons/bc-sskr-rust/issues/1).
     #[test]
     fn example_encode_3() {
-        use crate::{ SSKRError, Secret, GroupSpec, Spec, sskr_generate, sskr_combine };
+        use crate::{ SSKRError, Secret, GroupSpec, Spec, sskr_generate_using, sskr_combine };
         use std::str::from_utf8;
 
         const TEXT: &str = "my secret belongs to me.";
@@ -405,7 +1730,7 @@ mod tests {
         fn roundtrip(m: usize, n: usize) -> Result<Secret, SSKRError> {
             let secret = Secret::new(TEXT).unwrap();
             let spec = Spec::new(1, vec![GroupSpec::new(m, n).unwrap()]).unwrap();
-            let shares: Vec<Vec<Vec<u8>>> = sskr_generate(&spec, &secret).unwrap();
+            let shares: Vec<Vec<Vec<u8>>> = sskr_generate_using(&spec, &secret, &mut bc_rand::SecureRandomNumberGenerator).unwrap();
             sskr_combine(&shares.iter().flatten().collect::<Vec<&Vec<u8>>>())
         }
 
@@ -428,16 +1753,93 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_combine_partial_one_group_of_two() {
+        let mut rng = FakeRandomNumberGenerator;
+        let secret = test_support::secret();
+        let group1 = GroupSpec::new(2, 3).unwrap();
+        let group2 = GroupSpec::new(3, 5).unwrap();
+        let spec = Spec::new(2, vec![group1, group2]).unwrap();
+        let shares = sskr_generate_using(&spec, &secret, &mut rng).unwrap();
+
+        // The first group is satisfied, the second is one share short.
+        let first_group_only = vec![
+            shares[0][0].clone(),
+            shares[0][2].clone(),
+            shares[1][1].clone(),
+            shares[1][3].clone(),
+        ];
+        let (first_group_secrets, error) = match sskr_combine_partial(&first_group_only, PartialPolicy::AllowGroupSecrets) {
+            PartialRecovery::Incomplete { group_secrets, error } => (group_secrets, error),
+            PartialRecovery::Complete(_) => panic!("expected an incomplete recovery"),
+        };
+        assert!(matches!(error, SSKRError::NotEnoughMemberShares { group_index: 1, have: 2, need: 3 }));
+        assert_eq!(first_group_secrets.len(), 1);
+        assert_eq!(first_group_secrets[0].0, 0);
+        assert_eq!(first_group_secrets[0].1.len(), secret.len());
+
+        // The default policy reports the failure but withholds group secrets.
+        match sskr_combine_partial(&first_group_only, PartialPolicy::default()) {
+            PartialRecovery::Incomplete { group_secrets, error } => {
+                assert!(group_secrets.is_empty());
+                assert!(matches!(error, SSKRError::NotEnoughMemberShares { group_index: 1, have: 2, need: 3 }));
+            }
+            PartialRecovery::Complete(_) => panic!("expected an incomplete recovery"),
+        }
+        assert!(matches!(sskr_combine(&first_group_only), Err(SSKRError::NotEnoughMemberShares { group_index: 1, have: 2, need: 3 })));
+
+        // Recovering the other group separately yields a second group secret,
+        // and the two together reconstruct the master secret.
+        let second_group_only = vec![
+            shares[1][0].clone(),
+            shares[1][2].clone(),
+            shares[1][4].clone(),
+        ];
+        let second_group_secrets = match sskr_combine_partial(&second_group_only, PartialPolicy::AllowGroupSecrets) {
+            PartialRecovery::Incomplete { group_secrets, .. } => group_secrets,
+            PartialRecovery::Complete(_) => panic!("expected an incomplete recovery"),
+        };
+        assert_eq!(second_group_secrets.len(), 1);
+        assert_eq!(second_group_secrets[0].0, 1);
+
+        let master = bc_shamir::recover_secret(
+            &[first_group_secrets[0].0, second_group_secrets[0].0],
+            &[first_group_secrets[0].1.data(), second_group_secrets[0].1.data()],
+        ).unwrap();
+        assert_eq!(master, secret.data());
+    }
+
+    #[test]
+    fn test_combine_partial_complete() {
+        let mut rng = FakeRandomNumberGenerator;
+        let secret = test_support::secret();
+        let group1 = GroupSpec::new(2, 3).unwrap();
+        let group2 = GroupSpec::new(3, 5).unwrap();
+        let spec = Spec::new(2, vec![group1, group2]).unwrap();
+        let shares = sskr_generate_using(&spec, &secret, &mut rng).unwrap();
+
+        let recovered_shares = vec![
+            shares[0][0].clone(),
+            shares[0][2].clone(),
+            shares[1][0].clone(),
+            shares[1][1].clone(),
+            shares[1][4].clone(),
+        ];
+        let recovery = sskr_combine_partial(&recovered_shares, PartialPolicy::AllowGroupSecrets);
+        assert!(recovery.is_complete());
+        assert_eq!(recovery.secret(), Some(&secret));
+    }
+
     /// Test fix for [seedtool-cli #6](https://github.com/BlockchainCommons/seedtool-cli-rust/issues/6).
     #[test]
     fn example_encode_4() {
-        use crate::{ Secret, GroupSpec, Spec, sskr_generate, sskr_combine };
+        use crate::{ Secret, GroupSpec, Spec, sskr_generate_using, sskr_combine };
         use std::str::from_utf8;
 
         const TEXT: &str = "my secret belongs to me.";
         let secret = Secret::new(TEXT).unwrap();
         let spec = Spec::new(1, vec![GroupSpec::new(2, 3).unwrap(), GroupSpec::new(2, 3).unwrap()]).unwrap();
-        let groupd_shares: Vec<Vec<Vec<u8>>> = sskr_generate(&spec, &secret).unwrap();
+        let groupd_shares: Vec<Vec<Vec<u8>>> = sskr_generate_using(&spec, &secret, &mut bc_rand::SecureRandomNumberGenerator).unwrap();
         let flattened_shares = groupd_shares.into_iter().flatten().collect::<Vec<Vec<u8>>>();
         // The group threshold is 1, but we're providing an additional share from the second group.
         // This was previously causing an error, because the second group could not be decoded.
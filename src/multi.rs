@@ -0,0 +1,207 @@
+use crate::{
+    RecoveryShortfall,
+    SSKRError,
+    Secret,
+    ShareVersion,
+    group_shares_by_session,
+    shares_needed,
+    sskr_combine,
+};
+
+/// Combines a pile of shares from any number of splits, recovering each
+/// split's secret independently.
+///
+/// The shares are sorted into sessions as [`group_shares_by_session`] sorts
+/// them: by format version and identifier, with a share whose metadata
+/// disagrees with the first share of its session listed among the session's
+/// mismatched shares. The remaining shares of each session are then combined
+/// on their own, exactly as `sskr_combine` would combine them. Shares that
+/// cannot be parsed are set aside rather than failing the whole pile.
+pub fn sskr_combine_multi<T>(shares: &[T]) -> MultiRecovery
+where
+    T: AsRef<[u8]>
+{
+    let sorted = group_shares_by_session(shares);
+    let mut sessions: Vec<SessionResult> = sorted
+        .sessions()
+        .values()
+        .map(|session| {
+            let mismatched_shares = session.mismatched_shares().to_vec();
+            let share_indexes: Vec<usize> = session.share_indexes()
+                .iter()
+                .copied()
+                .filter(|share_index| !mismatched_shares.contains(share_index))
+                .collect();
+            let session_shares: Vec<&[u8]> = share_indexes.iter().map(|&i| shares[i].as_ref()).collect();
+            let result = sskr_combine(&session_shares);
+            let shortfall = match result {
+                Ok(_) => None,
+                Err(_) => shares_needed(&session_shares).ok().filter(|shortfall| !shortfall.is_satisfied()),
+            };
+            SessionResult {
+                version: session.version(),
+                identifier: session.identifier(),
+                share_indexes,
+                mismatched_shares,
+                result,
+                shortfall,
+            }
+        })
+        .collect();
+    // The first share of a session is never mismatched.
+    sessions.sort_by_key(|session| session.share_indexes[0]);
+
+    MultiRecovery { sessions, unrecognized_shares: sorted.unparseable_shares().to_vec() }
+}
+
+/// The outcome of [`sskr_combine_multi`].
+#[derive(Debug)]
+pub struct MultiRecovery {
+    sessions: Vec<SessionResult>,
+    unrecognized_shares: Vec<usize>,
+}
+
+impl MultiRecovery {
+    /// Returns the outcome of each session, in the order their first shares
+    /// appear in the input.
+    pub fn sessions(&self) -> &[SessionResult] {
+        &self.sessions
+    }
+
+    /// Consumes this result, returning the outcome of each session.
+    pub fn into_sessions(self) -> Vec<SessionResult> {
+        self.sessions
+    }
+
+    /// Returns the positions of the shares that could not be parsed.
+    pub fn unrecognized_shares(&self) -> &[usize] {
+        &self.unrecognized_shares
+    }
+}
+
+/// The outcome of combining the shares of one session in
+/// [`sskr_combine_multi`].
+#[derive(Debug)]
+pub struct SessionResult {
+    version: ShareVersion,
+    identifier: u32,
+    share_indexes: Vec<usize>,
+    mismatched_shares: Vec<usize>,
+    result: Result<Secret, SSKRError>,
+    shortfall: Option<RecoveryShortfall>,
+}
+
+impl SessionResult {
+    /// Returns the format of the session's shares.
+    pub fn version(&self) -> ShareVersion {
+        self.version
+    }
+
+    /// Returns the identifier of the session's split.
+    pub fn identifier(&self) -> u32 {
+        self.identifier
+    }
+
+    /// Returns the positions in the input of the session's shares that were
+    /// combined: those that agree with its first share.
+    pub fn share_indexes(&self) -> &[usize] {
+        &self.share_indexes
+    }
+
+    /// Returns the positions in the input of the shares with the session's
+    /// format and identifier but metadata that disagrees with its first
+    /// share, which were not combined.
+    pub fn mismatched_shares(&self) -> &[usize] {
+        &self.mismatched_shares
+    }
+
+    /// Returns the recovered secret, or `None` if the session's shares could
+    /// not be combined.
+    pub fn secret(&self) -> Option<&Secret> {
+        self.result.as_ref().ok()
+    }
+
+    /// Returns the error combining the session's shares, or `None` if the
+    /// secret was recovered.
+    ///
+    /// A share index in the error is a position among the session's shares;
+    /// [`share_indexes`](Self::share_indexes) maps it back to the input.
+    pub fn error(&self) -> Option<&SSKRError> {
+        self.result.as_ref().err()
+    }
+
+    /// Returns what is missing before the session's secret can be recovered,
+    /// or `None` if it was recovered or nothing is missing, as when its
+    /// shares are corrupt.
+    pub fn shortfall(&self) -> Option<&RecoveryShortfall> {
+        self.shortfall.as_ref()
+    }
+
+    /// Consumes this result, returning the recovered secret or the error.
+    pub fn into_result(self) -> Result<Secret, SSKRError> {
+        self.result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{GroupSpec, Spec, sskr_generate_using, sskr_generate_with_version_using, test_support};
+    use hex_literal::hex;
+
+    #[test]
+    fn test_combine_multi() {
+        let secret1 = test_support::secret();
+        let secret2 = Secret::new(hex!("204188bfa6b440a1bdfd6753ff55a8241e07af5c5be943db917e3efabc184b1a")).unwrap();
+        let mut rng = bc_rand::make_fake_random_number_generator();
+        let spec = Spec::new(1, vec![GroupSpec::new(2, 3).unwrap()]).unwrap();
+        let shares1 = sskr_generate_using(&spec, &secret1, &mut rng).unwrap();
+        let spec = Spec::new(2, vec![GroupSpec::new(2, 3).unwrap(), GroupSpec::new(1, 1).unwrap()]).unwrap();
+        let shares2 = sskr_generate_with_version_using(&spec, &secret2, ShareVersion::V2, &mut rng).unwrap();
+        let spec = Spec::new(1, vec![GroupSpec::new(3, 5).unwrap()]).unwrap();
+        let shares3 = sskr_generate_using(&spec, &secret1, &mut rng).unwrap();
+        // A share of the first split that claims a different group count.
+        let mut mismatched = shares1[0][1].clone();
+        mismatched[2] = 0x11;
+
+        let pile = vec![
+            shares2[0][1].clone(),
+            shares1[0][2].clone(),
+            b"not a share".to_vec(),
+            shares3[0][4].clone(),
+            shares2[1][0].clone(),
+            shares1[0][0].clone(),
+            shares3[0][0].clone(),
+            vec![],
+            shares2[0][0].clone(),
+            mismatched,
+        ];
+        let recovery = sskr_combine_multi(&pile);
+        assert_eq!(recovery.unrecognized_shares(), [2, 7]);
+
+        let sessions = recovery.sessions();
+        assert_eq!(sessions.len(), 3);
+        let sorted = group_shares_by_session(&pile);
+        for session in sessions {
+            assert_eq!(sorted.session(session.version(), session.identifier()).unwrap().mismatched_shares(), session.mismatched_shares());
+        }
+        assert_eq!(sessions[0].share_indexes(), [0, 4, 8]);
+        assert_eq!(sessions[0].secret(), Some(&secret2));
+        assert!(sessions[0].identifier() > 0xffff);
+        assert_eq!(sessions[0].version(), ShareVersion::V2);
+        assert_eq!(sessions[1].share_indexes(), [1, 5]);
+        assert_eq!(sessions[1].mismatched_shares(), [9]);
+        assert_eq!(sessions[1].version(), ShareVersion::V0);
+        assert_eq!(sessions[1].secret(), Some(&secret1));
+        assert!(sessions[1].shortfall().is_none());
+
+        // The third session is incomplete.
+        assert_eq!(sessions[2].share_indexes(), [3, 6]);
+        assert!(matches!(sessions[2].error(), Some(SSKRError::NotEnoughMemberShares { group_index: 0, have: 2, need: 3 })));
+        let shortfall = sessions[2].shortfall().unwrap();
+        assert_eq!(shortfall.groups()[0].shares_needed(), Some(1));
+
+        let empty = sskr_combine_multi::<Vec<u8>>(&[]);
+        assert!(empty.sessions().is_empty() && empty.unrecognized_shares().is_empty());
+    }
+}
@@ -0,0 +1,49 @@
+use crate::{GroupSecret, SSKRError, Secret};
+
+/// Whether [`sskr_combine_partial`](crate::sskr_combine_partial) may release
+/// group secrets when the master secret cannot be recovered.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PartialPolicy {
+    /// Never release group secrets. An incomplete recovery only reports the
+    /// reason for the failure.
+    #[default]
+    WithholdGroupSecrets,
+
+    /// Release the group secrets of every group whose member threshold was
+    /// met. This increases the exposure of the split; see
+    /// [`sskr_combine_partial`](crate::sskr_combine_partial).
+    AllowGroupSecrets,
+}
+
+/// The result of [`sskr_combine_partial`](crate::sskr_combine_partial).
+#[derive(Debug)]
+pub enum PartialRecovery {
+    /// The shares met the quorum and the master secret was recovered.
+    Complete(Secret),
+
+    /// The master secret could not be recovered.
+    Incomplete {
+        /// The group index and group secret of each group that could be
+        /// recovered. Always empty unless the policy was
+        /// `PartialPolicy::AllowGroupSecrets`.
+        group_secrets: Vec<(usize, GroupSecret)>,
+
+        /// The reason the master secret could not be recovered.
+        error: SSKRError,
+    },
+}
+
+impl PartialRecovery {
+    /// Returns `true` if the master secret was recovered.
+    pub fn is_complete(&self) -> bool {
+        matches!(self, Self::Complete(_))
+    }
+
+    /// Returns the recovered master secret, if any.
+    pub fn secret(&self) -> Option<&Secret> {
+        match self {
+            Self::Complete(secret) => Some(secret),
+            Self::Incomplete { .. } => None,
+        }
+    }
+}
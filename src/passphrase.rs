@@ -0,0 +1,264 @@
+use bc_crypto::{
+    aead_chacha20_poly1305_decrypt_with_aad,
+    aead_chacha20_poly1305_encrypt_with_aad,
+    hash::pbkdf2_hmac_sha256,
+    SYMMETRIC_AUTH_SIZE,
+    SYMMETRIC_KEY_SIZE,
+    SYMMETRIC_NONCE_SIZE,
+};
+use bc_rand::RandomNumberGenerator;
+use zeroize::Zeroizing;
+
+use crate::{SSKRError, Secret, Spec, sskr_combine, sskr_generate_using};
+
+/// The prefix that marks a share protected by a passphrase.
+///
+/// The high four bits of its fifth byte are where a share keeps its format
+/// version, and are set to a value reserved for passphrase-protected shares,
+/// so no SSKR share can begin with this prefix, and `sskr_combine` can tell
+/// that a share needs a passphrase.
+pub const PASSPHRASE_SHARE_MAGIC: [u8; 5] = [0x53, 0x53, 0x4b, 0x50, 0xe1];
+
+/// The number of PBKDF2 iterations used to derive the key from the
+/// passphrase.
+pub const PASSPHRASE_ITERATIONS: u32 = 600_000;
+
+/// The most PBKDF2 iterations `sskr_combine_encrypted` will perform, so that
+/// a forged header can't make it spin indefinitely.
+const MAX_PASSPHRASE_ITERATIONS: u32 = 10_000_000;
+
+const SALT_SIZE: usize = 16;
+const PARAMETERS_SIZE: usize = PASSPHRASE_SHARE_MAGIC.len() + 4 + SALT_SIZE + SYMMETRIC_NONCE_SIZE;
+const HEADER_SIZE: usize = PARAMETERS_SIZE + SYMMETRIC_AUTH_SIZE;
+
+/// Generates SSKR shares for the given `Spec` and `Secret`, protected by
+/// `passphrase`.
+///
+/// Uses the default random number generator; see
+/// [`sskr_generate_encrypted_using`].
+#[cfg(feature = "default-rng")]
+pub fn sskr_generate_encrypted(spec: &Spec, secret: &Secret, passphrase: &str) -> Result<Vec<Vec<Vec<u8>>>, SSKRError> {
+    let mut rng = bc_rand::SecureRandomNumberGenerator;
+    sskr_generate_encrypted_using(spec, secret, passphrase, &mut rng)
+}
+
+/// Generates SSKR shares for the given `Spec` and `Secret` using the provided
+/// random number generator, protected by `passphrase`.
+///
+/// The master secret is encrypted before it is split, so that gathering the
+/// shares recovers only the encrypted secret: the passphrase is needed as
+/// well. The key is derived from the passphrase with PBKDF2-HMAC-SHA256 over
+/// a random salt, and the secret is encrypted with ChaCha20-Poly1305, which
+/// leaves its length unchanged. The encrypted secret is split as
+/// `sskr_generate_using` splits a secret.
+///
+/// Each share is an SSKR share of the encrypted secret prefixed with a
+/// header: `PASSPHRASE_SHARE_MAGIC`, the iteration count as a big-endian
+/// `u32`, the salt, the nonce and the authentication tag. The header is the
+/// same for every share of a split, and all but the tag is authenticated.
+/// Because of the prefix, `sskr_combine` refuses the shares with
+/// `SSKRError::PassphraseRequired`; they are combined with
+/// [`sskr_combine_encrypted`].
+///
+/// # Errors
+///
+/// Returns the same errors as `sskr_generate`.
+pub fn sskr_generate_encrypted_using<R>(
+    spec: &Spec,
+    secret: &Secret,
+    passphrase: &str,
+    random_generator: &mut R
+) -> Result<Vec<Vec<Vec<u8>>>, SSKRError>
+where
+    R: RandomNumberGenerator
+{
+    generate_with_iterations(spec, secret, passphrase, PASSPHRASE_ITERATIONS, random_generator)
+}
+
+fn generate_with_iterations(
+    spec: &Spec,
+    secret: &Secret,
+    passphrase: &str,
+    iterations: u32,
+    rng: &mut impl RandomNumberGenerator
+) -> Result<Vec<Vec<Vec<u8>>>, SSKRError> {
+    let mut header = Vec::with_capacity(HEADER_SIZE);
+    header.extend_from_slice(&PASSPHRASE_SHARE_MAGIC);
+    header.extend_from_slice(&iterations.to_be_bytes());
+    header.extend_from_slice(&rng.random_data(SALT_SIZE));
+    header.extend_from_slice(&rng.random_data(SYMMETRIC_NONCE_SIZE));
+
+    let (salt, nonce) = header[PARAMETERS_SIZE - SALT_SIZE - SYMMETRIC_NONCE_SIZE..].split_at(SALT_SIZE);
+    let key = derive_key(passphrase, salt, iterations);
+    let nonce: [u8; SYMMETRIC_NONCE_SIZE] = nonce.try_into().unwrap();
+    let (ciphertext, auth) = aead_chacha20_poly1305_encrypt_with_aad(secret.data(), &key, &nonce, &header);
+    header.extend_from_slice(&auth);
+
+    let encrypted_secret = Secret::from_vec(ciphertext)?;
+    let shares = sskr_generate_using(spec, &encrypted_secret, rng)?;
+    Ok(shares
+        .into_iter()
+        .map(|group| group.into_iter().map(|share| [&header[..], &share].concat()).collect())
+        .collect())
+}
+
+/// Combines SSKR shares protected by a passphrase with
+/// `sskr_generate_encrypted` into a `Secret`.
+///
+/// The shares are combined as `sskr_combine` combines shares, recovering the
+/// encrypted secret, which is then decrypted with the key derived from
+/// `passphrase`.
+///
+/// # Errors
+///
+/// Returns `SSKRError::PassphraseShareInvalid` for a share that is not
+/// protected by a passphrase, `SSKRError::PassphraseParametersMismatch` for a
+/// share whose header differs from that of the first, and
+/// `SSKRError::PassphraseIncorrect` if the passphrase is wrong. Otherwise
+/// returns the same errors as `sskr_combine`.
+pub fn sskr_combine_encrypted<T>(shares: &[T], passphrase: &str) -> Result<Secret, SSKRError>
+where
+    T: AsRef<[u8]>
+{
+    let Some(first) = shares.first() else {
+        return Err(SSKRError::SharesEmpty);
+    };
+    let first = first.as_ref();
+    if !first.starts_with(&PASSPHRASE_SHARE_MAGIC) || first.len() < HEADER_SIZE {
+        return Err(SSKRError::PassphraseShareInvalid { share_index: 0 });
+    }
+    let header = &first[..HEADER_SIZE];
+    let iterations = u32::from_be_bytes(header[PASSPHRASE_SHARE_MAGIC.len()..][..4].try_into().unwrap());
+    if iterations == 0 || iterations > MAX_PASSPHRASE_ITERATIONS {
+        return Err(SSKRError::PassphraseShareInvalid { share_index: 0 });
+    }
+
+    let mut inner_shares = Vec::with_capacity(shares.len());
+    for (share_index, share) in shares.iter().enumerate() {
+        let share = share.as_ref();
+        if !share.starts_with(&PASSPHRASE_SHARE_MAGIC) || share.len() < HEADER_SIZE {
+            return Err(SSKRError::PassphraseShareInvalid { share_index });
+        }
+        let (share_header, inner_share) = share.split_at(HEADER_SIZE);
+        if share_header != header {
+            return Err(SSKRError::PassphraseParametersMismatch { share_index });
+        }
+        inner_shares.push(inner_share);
+    }
+    let encrypted_secret = sskr_combine(&inner_shares)?;
+
+    let (parameters, auth) = header.split_at(PARAMETERS_SIZE);
+    let (salt, nonce) = parameters[PARAMETERS_SIZE - SALT_SIZE - SYMMETRIC_NONCE_SIZE..].split_at(SALT_SIZE);
+    let key = derive_key(passphrase, salt, iterations);
+    let nonce: [u8; SYMMETRIC_NONCE_SIZE] = nonce.try_into().unwrap();
+    let auth: [u8; SYMMETRIC_AUTH_SIZE] = auth.try_into().unwrap();
+    let secret = Zeroizing::new(
+        aead_chacha20_poly1305_decrypt_with_aad(encrypted_secret.data(), &key, &nonce, parameters, &auth)
+            .map_err(|_| SSKRError::PassphraseIncorrect)?
+    );
+    Secret::new(&*secret)
+}
+
+/// Derives the key that a passphrase-protected share, or a sealed collector
+/// state, is encrypted under, with PBKDF2-HMAC-SHA256.
+pub(crate) fn derive_key(passphrase: &str, salt: &[u8], iterations: u32) -> Zeroizing<[u8; SYMMETRIC_KEY_SIZE]> {
+    let key = Zeroizing::new(pbkdf2_hmac_sha256(passphrase, salt, iterations, SYMMETRIC_KEY_SIZE));
+    Zeroizing::new(key[..].try_into().unwrap())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{GroupSpec, test_support};
+    use hex_literal::hex;
+
+    const TEST_ITERATIONS: u32 = 1_000;
+
+    fn encrypted_shares() -> (Secret, Vec<Vec<Vec<u8>>>) {
+        let secret = test_support::secret();
+        let spec = Spec::new(2, vec![GroupSpec::new(2, 3).unwrap(), GroupSpec::new(3, 5).unwrap()]).unwrap();
+        let shares = generate_with_iterations(&spec, &secret, "correct horse", TEST_ITERATIONS, &mut bc_rand::make_fake_random_number_generator()).unwrap();
+        (secret, shares)
+    }
+
+    #[test]
+    fn test_passphrase_key_vectors() {
+        // PBKDF2-HMAC-SHA256, as computed independently by Python's
+        // `hashlib.pbkdf2_hmac`.
+        let salt: Vec<u8> = (0..16).collect();
+        assert_eq!(
+            *derive_key("correct horse battery staple", &salt, TEST_ITERATIONS),
+            hex!("a69b179e3add3c1e0aaf227a0eb3aa2aa8645ab86fecf6ca00c17512697c719e")
+        );
+        assert_eq!(
+            *derive_key("correct horse battery staple", &salt, PASSPHRASE_ITERATIONS),
+            hex!("ef177144eec9420cbc1093d2a8b344a92bc506d0d4ec9c028dd19f8324d8c1e6")
+        );
+    }
+
+    #[test]
+    fn test_passphrase_share_vector() {
+        let (_, shares) = encrypted_shares();
+        // The magic and iteration count, the salt and nonce drawn from the
+        // generator, the tag, and the SSKR share of the encrypted secret.
+        assert_eq!(
+            shares[0][0],
+            hex!("53534b50e1000003e8" "7eb559bbbf6cce2632cf9f194aeb5094" "3de7e1cbad54dcfab27a4275" "5bfb501e3baefce4edd8e4be462adbd8" "9f5e11010006964c38a08d9f256f36101b71e51c7f")
+        );
+    }
+
+    #[test]
+    fn test_passphrase_round_trip() {
+        let (secret, shares) = encrypted_shares();
+        for share in shares.iter().flatten() {
+            assert_eq!(share.len(), HEADER_SIZE + 5 + secret.len());
+            assert_eq!(share[..HEADER_SIZE], shares[0][0][..HEADER_SIZE]);
+        }
+        let recovered = sskr_combine_encrypted(&[&shares[0][2], &shares[1][4], &shares[0][0], &shares[1][1], &shares[1][3]], "correct horse").unwrap();
+        assert_eq!(recovered, secret);
+
+        // The shares alone recover only the encrypted secret.
+        let inner: Vec<&[u8]> = [&shares[0][2], &shares[0][0], &shares[1][4], &shares[1][1], &shares[1][3]].iter().map(|share| &share[HEADER_SIZE..]).collect();
+        assert_ne!(sskr_combine(&inner).unwrap(), secret);
+    }
+
+    #[test]
+    fn test_passphrase_errors() {
+        let (_, shares) = encrypted_shares();
+        let quorum = [&shares[0][0], &shares[0][1], &shares[1][0], &shares[1][1], &shares[1][2]];
+        assert!(matches!(sskr_combine_encrypted(&quorum, "wrong horse"), Err(SSKRError::PassphraseIncorrect)));
+        assert!(matches!(sskr_combine_encrypted(&quorum, ""), Err(SSKRError::PassphraseIncorrect)));
+        assert!(matches!(sskr_combine(&quorum), Err(SSKRError::PassphraseRequired { share_index: Some(0) })));
+
+        // A share with another header, or no header, is refused.
+        let secret = test_support::secret();
+        let spec = Spec::new(2, vec![GroupSpec::new(2, 3).unwrap(), GroupSpec::new(3, 5).unwrap()]).unwrap();
+        let other = generate_with_iterations(&spec, &secret, "correct horse", TEST_ITERATIONS, &mut bc_rand::SecureRandomNumberGenerator).unwrap();
+        assert!(matches!(
+            sskr_combine_encrypted(&[&shares[0][0], &other[0][1]], "correct horse"),
+            Err(SSKRError::PassphraseParametersMismatch { share_index: 1 })
+        ));
+        let plain = shares[0][1][HEADER_SIZE..].to_vec();
+        assert!(matches!(
+            sskr_combine_encrypted(&[&shares[0][0], &plain], "correct horse"),
+            Err(SSKRError::PassphraseShareInvalid { share_index: 1 })
+        ));
+        assert!(matches!(sskr_combine_encrypted(&[&plain], "correct horse"), Err(SSKRError::PassphraseShareInvalid { share_index: 0 })));
+        assert!(matches!(sskr_combine_encrypted::<Vec<u8>>(&[], "correct horse"), Err(SSKRError::SharesEmpty)));
+
+        // Altering the authenticated parameters or the tag is detected.
+        for i in PASSPHRASE_SHARE_MAGIC.len() + 4..HEADER_SIZE {
+            let damaged: Vec<Vec<u8>> = quorum.iter().map(|share| {
+                let mut share = share.to_vec();
+                share[i] ^= 0x01;
+                share
+            }).collect();
+            assert!(matches!(sskr_combine_encrypted(&damaged, "correct horse"), Err(SSKRError::PassphraseIncorrect)), "byte {i}");
+        }
+        let mut damaged: Vec<Vec<u8>> = quorum.iter().map(|share| share.to_vec()).collect();
+        for share in &mut damaged {
+            share[PASSPHRASE_SHARE_MAGIC.len()..][..4].copy_from_slice(&u32::MAX.to_be_bytes());
+        }
+        assert!(matches!(sskr_combine_encrypted(&damaged, "correct horse"), Err(SSKRError::PassphraseShareInvalid { share_index: 0 })));
+    }
+}
@@ -0,0 +1,113 @@
+use std::fmt;
+
+/// A property of a spec that is valid but may not be what was meant, as
+/// returned by [`Spec::warnings`](crate::Spec::warnings).
+///
+/// Warnings do not stop a split from being generated. They are for showing
+/// to a user before shares are made, so that a spec that behaves
+/// surprisingly is not committed to by mistake.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[non_exhaustive]
+pub enum PolicyWarning {
+    /// The group has a member threshold of one and more than one member, so
+    /// any one of its members can recover the group's secret alone.
+    SingleMemberRecoversGroup { group_index: usize },
+    /// The group threshold is one and there is more than one group, so any
+    /// one group can recover the secret alone.
+    SingleGroupRecoversSecret,
+    /// The group's member threshold is its member count, so the group cannot
+    /// be recovered if any one of its shares is lost.
+    GroupHasNoLossTolerance { group_index: usize },
+    /// Every group is needed and one of them needs every share, so the
+    /// secret cannot be recovered if a single share is lost.
+    SpecHasNoLossTolerance,
+}
+
+impl PolicyWarning {
+    /// Returns the index of the group the warning is about, or `None` if it
+    /// is about the spec as a whole.
+    pub fn group_index(&self) -> Option<usize> {
+        match self {
+            PolicyWarning::SingleMemberRecoversGroup { group_index }
+            | PolicyWarning::GroupHasNoLossTolerance { group_index } => Some(*group_index),
+            PolicyWarning::SingleGroupRecoversSecret | PolicyWarning::SpecHasNoLossTolerance => None,
+        }
+    }
+
+    /// Returns a stable, machine-readable code for the kind of warning.
+    pub fn code(&self) -> &'static str {
+        match self {
+            PolicyWarning::SingleMemberRecoversGroup { .. } => "single_member_recovers_group",
+            PolicyWarning::SingleGroupRecoversSecret => "single_group_recovers_secret",
+            PolicyWarning::GroupHasNoLossTolerance { .. } => "group_has_no_loss_tolerance",
+            PolicyWarning::SpecHasNoLossTolerance => "spec_has_no_loss_tolerance",
+        }
+    }
+}
+
+/// Describes the warning for a user. Group numbers are one-based.
+impl fmt::Display for PolicyWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PolicyWarning::SingleMemberRecoversGroup { group_index } => {
+                write!(f, "any single share of group {} recovers that group", group_index + 1)
+            }
+            PolicyWarning::SingleGroupRecoversSecret => write!(f, "any single group recovers the secret"),
+            PolicyWarning::GroupHasNoLossTolerance { group_index } => {
+                write!(f, "group {} cannot be recovered if any of its shares is lost", group_index + 1)
+            }
+            PolicyWarning::SpecHasNoLossTolerance => write!(f, "the secret cannot be recovered if a single share is lost"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{GroupSpec, Spec};
+
+    #[test]
+    fn test_spec_warnings() {
+        let spec = Spec::new(2, vec![GroupSpec::new(2, 3).unwrap(), GroupSpec::new(3, 5).unwrap()]).unwrap();
+        assert!(spec.warnings().is_empty());
+
+        let spec = Spec::new(1, vec![GroupSpec::new(1, 3).unwrap(), GroupSpec::new(2, 2).unwrap()]).unwrap();
+        assert_eq!(spec.warnings(), [
+            PolicyWarning::SingleGroupRecoversSecret,
+            PolicyWarning::SingleMemberRecoversGroup { group_index: 0 },
+            PolicyWarning::GroupHasNoLossTolerance { group_index: 1 },
+        ]);
+
+        // A group that needs every share loses the secret only if every
+        // group is needed.
+        let spec = Spec::new(2, vec![GroupSpec::new(2, 3).unwrap(), GroupSpec::new(2, 2).unwrap()]).unwrap();
+        assert_eq!(spec.warnings(), [
+            PolicyWarning::GroupHasNoLossTolerance { group_index: 1 },
+            PolicyWarning::SpecHasNoLossTolerance,
+        ]);
+
+        // A single share of a single group is both.
+        let spec = Spec::new(1, vec![GroupSpec::new(1, 1).unwrap()]).unwrap();
+        assert_eq!(spec.warnings(), [
+            PolicyWarning::GroupHasNoLossTolerance { group_index: 0 },
+            PolicyWarning::SpecHasNoLossTolerance,
+        ]);
+    }
+
+    #[test]
+    fn test_warning_descriptions() {
+        let warning = PolicyWarning::SingleMemberRecoversGroup { group_index: 2 };
+        assert_eq!((warning.group_index(), warning.code()), (Some(2), "single_member_recovers_group"));
+        assert_eq!(warning.to_string(), "any single share of group 3 recovers that group");
+        let warning = PolicyWarning::GroupHasNoLossTolerance { group_index: 0 };
+        assert_eq!((warning.group_index(), warning.code()), (Some(0), "group_has_no_loss_tolerance"));
+        assert_eq!(warning.to_string(), "group 1 cannot be recovered if any of its shares is lost");
+        let warning = PolicyWarning::SingleGroupRecoversSecret;
+        assert_eq!((warning.group_index(), warning.code()), (None, "single_group_recovers_secret"));
+        assert_eq!(warning.to_string(), "any single group recovers the secret");
+        let warning = PolicyWarning::SpecHasNoLossTolerance;
+        assert_eq!((warning.group_index(), warning.code()), (None, "spec_has_no_loss_tolerance"));
+        assert_eq!(warning.to_string(), "the secret cannot be recovered if a single share is lost");
+    }
+}
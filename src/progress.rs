@@ -0,0 +1,169 @@
+use crate::{CollectorStatus, SSKRError, ShareCollector};
+
+/// Reports how close `shares` are to recovering their secret, as a
+/// [`Progress`] suitable for a progress bar.
+///
+/// Like [`shares_needed`](crate::shares_needed), only the share metadata is
+/// read, and a share that appears more than once is counted once.
+///
+/// # Errors
+///
+/// Returns `SSKRError::SharesEmpty` if `shares` is empty, or any error
+/// `ShareCollector::add_share` returns for a share that is malformed or
+/// belongs to a different split than the first.
+pub fn recovery_progress<T>(shares: &[T]) -> Result<Progress, SSKRError>
+where
+    T: AsRef<[u8]>
+{
+    if shares.is_empty() {
+        return Err(SSKRError::SharesEmpty);
+    }
+    let mut collector = ShareCollector::new();
+    for share in shares {
+        let share = share.as_ref();
+        if collector.shares().iter().any(|collected| collected.data() == share) {
+            continue;
+        }
+        collector.add_share(share)?;
+    }
+    Ok(collector.status().progress().unwrap())
+}
+
+/// How close a set of shares is to recovering its secret.
+///
+/// Each group's completion is the number of its member shares held divided
+/// by its member threshold, capped at 1; a group no shares are held for has
+/// a completion of 0. The `fraction` is the sum of the completions of the
+/// `required_groups` most complete groups, divided by `required_groups`.
+///
+/// The fraction is in the range 0 to 1. It never decreases as shares are
+/// added, and is exactly 1 when, and only when, enough groups are complete
+/// for the shares to be combined. For a split into a single 1-of-1 group, it
+/// is 1 as soon as the only share is held.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Progress {
+    completed_groups: usize,
+    required_groups: usize,
+    fraction: f64,
+}
+
+impl Progress {
+    /// Returns the number of groups that have reached their member
+    /// threshold.
+    pub fn completed_groups(&self) -> usize {
+        self.completed_groups
+    }
+
+    /// Returns the number of groups that must reach their member threshold,
+    /// which is the group threshold of the split.
+    pub fn required_groups(&self) -> usize {
+        self.required_groups
+    }
+
+    /// Returns how close the shares are to recovery, from 0 to 1.
+    pub fn fraction(&self) -> f64 {
+        self.fraction
+    }
+
+    /// Returns `true` if enough groups are complete to recover the secret.
+    pub fn is_complete(&self) -> bool {
+        self.completed_groups >= self.required_groups
+    }
+}
+
+impl CollectorStatus {
+    /// Returns how close the collected shares are to recovery, or `None` if
+    /// no shares have been collected.
+    pub fn progress(&self) -> Option<Progress> {
+        let required_groups = self.group_threshold()?;
+        let completed_groups = self.complete_group_count();
+
+        let mut completions: Vec<f64> = self.groups()
+            .iter()
+            .map(|group| (group.shares_held() as f64 / group.member_threshold() as f64).min(1.0))
+            .collect();
+        completions.sort_by(|a, b| b.total_cmp(a));
+        let fraction = if completed_groups >= required_groups {
+            1.0
+        } else {
+            // Rounding must not report an incomplete recovery as complete.
+            let sum: f64 = completions.iter().take(required_groups).sum();
+            (sum / required_groups as f64).min(1.0 - f64::EPSILON)
+        };
+
+        Some(Progress { completed_groups, required_groups, fraction })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{GroupSpec, Spec, sskr_combine, test_support};
+    
+    use rand::{SeedableRng, seq::SliceRandom};
+
+    #[test]
+    fn test_progress() {
+        let shares = test_support::generate(&Spec::new(2, vec![
+            GroupSpec::new(2, 3).unwrap(),
+            GroupSpec::new(4, 5).unwrap(),
+            GroupSpec::new(1, 1).unwrap(),
+        ]).unwrap());
+
+        let progress = recovery_progress(&[&shares[1][0]]).unwrap();
+        assert_eq!((progress.completed_groups(), progress.required_groups()), (0, 2));
+        assert_eq!(progress.fraction(), 0.125);
+
+        // Only the two most complete groups count.
+        let progress = recovery_progress(&[&shares[1][0], &shares[0][1], &shares[1][3], &shares[1][0]]).unwrap();
+        assert_eq!(progress.fraction(), (0.5 + 0.5) / 2.0);
+        let progress = recovery_progress(&[&shares[1][0], &shares[0][1], &shares[1][3], &shares[2][0]]).unwrap();
+        assert_eq!(progress.completed_groups(), 1);
+        assert_eq!(progress.fraction(), (1.0 + 0.5) / 2.0);
+        assert!(!progress.is_complete());
+
+        let progress = recovery_progress(&[&shares[0][0], &shares[0][1], &shares[2][0]]).unwrap();
+        assert_eq!(progress.fraction(), 1.0);
+        assert!(progress.is_complete());
+
+        assert!(matches!(recovery_progress::<Vec<u8>>(&[]), Err(SSKRError::SharesEmpty)));
+        assert_eq!(ShareCollector::new().status().progress(), None);
+    }
+
+    #[test]
+    fn test_progress_single_share() {
+        let shares = test_support::generate(&Spec::new(1, vec![GroupSpec::new(1, 1).unwrap()]).unwrap());
+        let progress = recovery_progress(&shares[0]).unwrap();
+        assert_eq!((progress.completed_groups(), progress.required_groups()), (1, 1));
+        assert_eq!(progress.fraction(), 1.0);
+    }
+
+    #[test]
+    fn test_progress_is_monotonic() {
+        let shares = test_support::generate(&Spec::new(3, vec![
+            GroupSpec::new(3, 5).unwrap(),
+            GroupSpec::new(1, 2).unwrap(),
+            GroupSpec::new(7, 9).unwrap(),
+            GroupSpec::new(2, 2).unwrap(),
+            GroupSpec::new(5, 6).unwrap(),
+        ]).unwrap());
+        let all_shares: Vec<&Vec<u8>> = shares.iter().flatten().collect();
+
+        for seed in 0..50 {
+            let mut rng = rand::rngs::StdRng::seed_from_u64(seed);
+            let mut order = all_shares.clone();
+            order.shuffle(&mut rng);
+
+            let mut previous = 0.0;
+            for len in 1..=order.len() {
+                let progress = recovery_progress(&order[..len]).unwrap();
+                assert!((0.0..=1.0).contains(&progress.fraction()));
+                assert!(progress.fraction() >= previous);
+                assert_eq!(progress.fraction() == 1.0, sskr_combine(&order[..len]).is_ok());
+                previous = progress.fraction();
+            }
+            assert_eq!(previous, 1.0);
+        }
+    }
+}
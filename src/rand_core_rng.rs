@@ -0,0 +1,125 @@
+use bc_rand::RandomNumberGenerator;
+use rand_core::{CryptoRng, RngCore};
+
+use crate::{SSKRError, Secret, Spec, sskr_generate_using};
+
+/// Adapts any `rand_core` cryptographically secure random number generator,
+/// such as `rand::rngs::OsRng` or `rand_chacha::ChaCha20Rng`, to a
+/// `RandomNumberGenerator`, so that it can be passed to the `*_using`
+/// functions of this crate.
+///
+/// The generator may be owned or borrowed, as `&mut R` is a generator
+/// whenever `R` is. Only generators marked `CryptoRng` are accepted.
+#[derive(Debug, Clone)]
+pub struct CryptoRngAdapter<R>(R);
+
+impl<R> CryptoRngAdapter<R>
+where
+    R: RngCore + CryptoRng
+{
+    /// Wraps `random_generator`.
+    pub fn new(random_generator: R) -> Self {
+        Self(random_generator)
+    }
+
+    /// Returns the wrapped generator.
+    pub fn into_inner(self) -> R {
+        self.0
+    }
+}
+
+impl<R> RngCore for CryptoRngAdapter<R>
+where
+    R: RngCore + CryptoRng
+{
+    fn next_u32(&mut self) -> u32 {
+        self.0.next_u32()
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.0.next_u64()
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.0.fill_bytes(dest)
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand_core::Error> {
+        self.0.try_fill_bytes(dest)
+    }
+}
+
+impl<R> CryptoRng for CryptoRngAdapter<R> where R: RngCore + CryptoRng {}
+
+impl<R> RandomNumberGenerator for CryptoRngAdapter<R> where R: RngCore + CryptoRng {}
+
+/// Generates SSKR shares for the given `Spec` and `Secret` using the provided
+/// `rand_core` random number generator.
+///
+/// This is `sskr_generate_using` with the generator wrapped in a
+/// [`CryptoRngAdapter`], and gives the same shares as it does with the same
+/// random data.
+///
+/// The generator must be marked `CryptoRng`; a generator that is not, such
+/// as one meant for simulations, is rejected at compile time:
+///
+/// ```compile_fail
+/// use rand_core::{impls, RngCore};
+/// use sskr::{GroupSpec, Secret, Spec, sskr_generate_with_rng};
+///
+/// struct Counter(u64);
+///
+/// impl RngCore for Counter {
+///     fn next_u32(&mut self) -> u32 { self.next_u64() as u32 }
+///     fn next_u64(&mut self) -> u64 { self.0 += 1; self.0 }
+///     fn fill_bytes(&mut self, dest: &mut [u8]) { impls::fill_bytes_via_next(self, dest) }
+///     fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand_core::Error> {
+///         self.fill_bytes(dest);
+///         Ok(())
+///     }
+/// }
+///
+/// let spec = Spec::new(1, vec![GroupSpec::new(2, 3).unwrap()]).unwrap();
+/// let secret = Secret::new([0u8; 16]).unwrap();
+/// sskr_generate_with_rng(&spec, &secret, &mut Counter(0)).unwrap();
+/// ```
+///
+/// # Errors
+///
+/// Returns the same errors as `sskr_generate`.
+pub fn sskr_generate_with_rng<R>(
+    spec: &Spec,
+    master_secret: &Secret,
+    random_generator: &mut R
+) -> Result<Vec<Vec<Vec<u8>>>, SSKRError>
+where
+    R: RngCore + CryptoRng
+{
+    sskr_generate_using(spec, master_secret, &mut CryptoRngAdapter::new(random_generator))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{GroupSpec, sskr_combine, test_support};
+    
+    use rand_chacha::ChaCha20Rng;
+    use rand_core::SeedableRng;
+
+    #[test]
+    fn test_generate_with_rng() {
+        let secret = test_support::secret();
+        let spec = Spec::new(2, vec![GroupSpec::new(2, 3).unwrap(), GroupSpec::new(3, 5).unwrap()]).unwrap();
+
+        let shares = sskr_generate_with_rng(&spec, &secret, &mut ChaCha20Rng::seed_from_u64(42)).unwrap();
+        assert_eq!(sskr_combine(&[&shares[1][4], &shares[0][0], &shares[1][1], &shares[0][2], &shares[1][3]]).unwrap(), secret);
+
+        let mut adapter = CryptoRngAdapter::new(ChaCha20Rng::seed_from_u64(42));
+        assert_eq!(sskr_generate_using(&spec, &secret, &mut adapter).unwrap(), shares);
+        let mut rng = adapter.into_inner();
+        assert_ne!(sskr_generate_with_rng(&spec, &secret, &mut rng).unwrap(), shares);
+
+        let shares = sskr_generate_with_rng(&spec, &secret, &mut rand::rngs::OsRng).unwrap();
+        assert_eq!(sskr_combine(&[&shares[0][1], &shares[0][2], &shares[1][0], &shares[1][1], &shares[1][2]]).unwrap(), secret);
+    }
+}
@@ -0,0 +1,196 @@
+use std::io::{self, Read};
+
+use thiserror::Error;
+
+use crate::{
+    EXTENDED_METADATA_SIZE_BYTES,
+    MAX_SECRET_LEN,
+    SSKRError,
+    Secret,
+    ShareBytes,
+    ShareCollector,
+};
+
+/// The longest line, in characters, that [`FrameFormat::HexLines`] accepts:
+/// enough for the hex of the largest share, with room for surrounding
+/// whitespace.
+const MAX_HEX_LINE_LEN: usize = 4 * (EXTENDED_METADATA_SIZE_BYTES + MAX_SECRET_LEN);
+
+/// How shares are framed in the stream read by [`sskr_combine_from_reader`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FrameFormat {
+    /// Each share is preceded by its length in bytes, as a big-endian `u16`.
+    LengthPrefixed,
+    /// Each share is a line of hex, ending in `\n` or `\r\n`. Blank lines are
+    /// skipped, and the last line need not end in a newline.
+    HexLines,
+}
+
+/// An error from [`sskr_combine_from_reader`]: either reading the stream
+/// failed, or the shares read could not be combined.
+#[derive(Debug, Error)]
+pub enum ReadCombineError {
+    #[error("reading SSKR shares failed: {0}")]
+    Io(#[from] io::Error),
+
+    #[error(transparent)]
+    Sskr(#[from] SSKRError),
+}
+
+/// Reads shares from `reader` and combines them into a `Secret`, stopping as
+/// soon as enough have been read.
+///
+/// Shares are read one frame at a time. After each share that completes a
+/// quorum, the shares read so far are combined, and if that succeeds nothing
+/// more is read from `reader`. Otherwise reading continues until the end of
+/// the stream, in case later shares allow a recovery. A share repeated in the
+/// stream is read once.
+///
+/// Frames are read exactly, so that nothing past the frame that completes the
+/// quorum is consumed. An unbuffered `reader` is read a byte at a time in
+/// `FrameFormat::HexLines`; wrap it in a `BufReader` if reading past the
+/// quorum is acceptable.
+///
+/// # Errors
+///
+/// Returns `ReadCombineError::Io` if reading fails, including when the stream
+/// ends partway through a frame. Returns `ReadCombineError::Sskr` for a share
+/// that is malformed or from a different split, or if the shares read do not
+/// recover the secret; a share index in the error is the share's position
+/// among the distinct shares read.
+pub fn sskr_combine_from_reader<R>(mut reader: R, format: FrameFormat) -> Result<Secret, ReadCombineError>
+where
+    R: Read
+{
+    let mut collector = ShareCollector::new();
+    let mut failure = None;
+    loop {
+        let frame = match format {
+            FrameFormat::LengthPrefixed => read_length_prefixed_frame(&mut reader)?,
+            FrameFormat::HexLines => read_hex_line_frame(&mut reader)?,
+        };
+        let Some(frame) = frame else {
+            break;
+        };
+        if collector.shares().iter().any(|share| share.data() == frame.data()) {
+            continue;
+        }
+        if collector.add_share(frame.data())?.can_combine() {
+            match collector.clone().combine() {
+                Ok(secret) => return Ok(secret),
+                Err(error) => failure = Some(error),
+            }
+        }
+    }
+    match failure {
+        Some(error) => Err(error.into()),
+        None => Ok(collector.combine()?),
+    }
+}
+
+/// Reads a length-prefixed frame, or returns `None` at the end of the stream.
+fn read_length_prefixed_frame(reader: &mut impl Read) -> Result<Option<ShareBytes>, ReadCombineError> {
+    let mut prefix = [0u8; 2];
+    if reader.read(&mut prefix[..1])? == 0 {
+        return Ok(None);
+    }
+    reader.read_exact(&mut prefix[1..])?;
+    let mut frame = vec![0u8; u16::from_be_bytes(prefix) as usize];
+    reader.read_exact(&mut frame)?;
+    Ok(Some(ShareBytes::new(frame)?))
+}
+
+/// Reads the next non-blank line of hex, or returns `None` at the end of the
+/// stream.
+fn read_hex_line_frame(reader: &mut impl Read) -> Result<Option<ShareBytes>, ReadCombineError> {
+    let mut line = Vec::new();
+    let mut byte = [0u8; 1];
+    loop {
+        let at_end = reader.read(&mut byte)? == 0;
+        if at_end || byte[0] == b'\n' {
+            let text = std::str::from_utf8(&line).map_err(|_| SSKRError::ShareHexInvalid)?;
+            if !text.trim().is_empty() {
+                return Ok(Some(text.parse()?));
+            }
+            if at_end {
+                return Ok(None);
+            }
+            line.clear();
+            continue;
+        }
+        if line.len() == MAX_HEX_LINE_LEN {
+            return Err(SSKRError::ShareHexInvalid.into());
+        }
+        line.push(byte[0]);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+    use crate::{GroupSpec, Spec, test_support};
+    
+
+    fn length_prefixed(shares: &[&Vec<u8>]) -> Vec<u8> {
+        shares
+            .iter()
+            .flat_map(|share| (share.len() as u16).to_be_bytes().into_iter().chain(share.iter().copied()))
+            .collect()
+    }
+
+    #[test]
+    fn test_length_prefixed() {
+        let secret = test_support::secret();
+        let shares = test_support::generate(&Spec::new(2, vec![GroupSpec::new(2, 3).unwrap(), GroupSpec::new(1, 2).unwrap()]).unwrap());
+        let stream = length_prefixed(&[&shares[0][1], &shares[0][1], &shares[1][1], &shares[0][2], &shares[1][0]]);
+
+        // Reading stops once the quorum is held.
+        let mut cursor = Cursor::new(stream);
+        assert_eq!(sskr_combine_from_reader(&mut cursor, FrameFormat::LengthPrefixed).unwrap(), secret);
+        assert_eq!(cursor.position() as usize, 4 * (2 + shares[0][0].len()));
+
+        // Not enough shares before the end of the stream.
+        let stream = length_prefixed(&[&shares[0][1], &shares[1][1]]);
+        let error = sskr_combine_from_reader(Cursor::new(stream), FrameFormat::LengthPrefixed).unwrap_err();
+        assert!(matches!(error, ReadCombineError::Sskr(SSKRError::NotEnoughMemberShares { group_index: 0, .. })));
+
+        // A truncated final frame is an IO error.
+        let mut stream = length_prefixed(&[&shares[0][1], &shares[1][1], &shares[0][2]]);
+        stream.truncate(stream.len() - 3);
+        let error = sskr_combine_from_reader(Cursor::new(stream), FrameFormat::LengthPrefixed).unwrap_err();
+        assert!(matches!(error, ReadCombineError::Io(ref error) if error.kind() == io::ErrorKind::UnexpectedEof));
+        let error = sskr_combine_from_reader(Cursor::new(vec![0x00]), FrameFormat::LengthPrefixed).unwrap_err();
+        assert!(matches!(error, ReadCombineError::Io(_)));
+
+        let error = sskr_combine_from_reader(Cursor::new(Vec::new()), FrameFormat::LengthPrefixed).unwrap_err();
+        assert!(matches!(error, ReadCombineError::Sskr(SSKRError::SharesEmpty)));
+    }
+
+    #[test]
+    fn test_hex_lines() {
+        let secret = test_support::secret();
+        let shares = test_support::generate(&Spec::new(2, vec![GroupSpec::new(2, 3).unwrap(), GroupSpec::new(1, 2).unwrap()]).unwrap());
+        let text = format!(
+            "{}\r\n\n{}\n  \n{}",
+            hex::encode(&shares[1][0]),
+            hex::encode(&shares[0][2]),
+            hex::encode(&shares[0][0]),
+        );
+        assert_eq!(sskr_combine_from_reader(text.as_bytes(), FrameFormat::HexLines).unwrap(), secret);
+
+        let text = format!("{}\n{}\nnot hex\n", hex::encode(&shares[1][0]), hex::encode(&shares[0][2]));
+        let error = sskr_combine_from_reader(text.as_bytes(), FrameFormat::HexLines).unwrap_err();
+        assert!(matches!(error, ReadCombineError::Sskr(SSKRError::ShareHexInvalid)));
+
+        // A truncated final line is not a whole share.
+        let text = format!("{}\n{}\n{}", hex::encode(&shares[1][0]), hex::encode(&shares[0][2]), &hex::encode(&shares[0][0])[..20]);
+        let error = sskr_combine_from_reader(text.as_bytes(), FrameFormat::HexLines).unwrap_err();
+        assert!(matches!(error, ReadCombineError::Sskr(_)));
+
+        let text = "0".repeat(MAX_HEX_LINE_LEN + 1);
+        let error = sskr_combine_from_reader(text.as_bytes(), FrameFormat::HexLines).unwrap_err();
+        assert!(matches!(error, ReadCombineError::Sskr(SSKRError::ShareHexInvalid)));
+    }
+}
@@ -0,0 +1,113 @@
+use std::ops::Deref;
+
+use crate::{Secret, ShareVersion};
+
+/// A secret recovered by [`sskr_combine_detailed`](crate::sskr_combine_detailed),
+/// with a record of how it was recovered.
+///
+/// Only this crate can create a `RecoveredSecret`, so its [`RecoveryInfo`]
+/// always describes the recovery that produced the secret. It dereferences
+/// to the `Secret`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecoveredSecret {
+    secret: Secret,
+    info: RecoveryInfo,
+}
+
+impl RecoveredSecret {
+    pub(crate) fn new(secret: Secret, info: RecoveryInfo) -> Self {
+        Self { secret, info }
+    }
+
+    /// Returns the recovered secret.
+    pub fn secret(&self) -> &Secret {
+        &self.secret
+    }
+
+    /// Returns the record of how the secret was recovered.
+    pub fn info(&self) -> &RecoveryInfo {
+        &self.info
+    }
+
+    /// Consumes this value, returning the recovered secret.
+    pub fn into_secret(self) -> Secret {
+        self.secret
+    }
+
+    /// Consumes this value, returning the recovered secret and the record of
+    /// how it was recovered.
+    pub fn into_parts(self) -> (Secret, RecoveryInfo) {
+        (self.secret, self.info)
+    }
+}
+
+impl Deref for RecoveredSecret {
+    type Target = Secret;
+
+    fn deref(&self) -> &Secret {
+        &self.secret
+    }
+}
+
+impl AsRef<Secret> for RecoveredSecret {
+    fn as_ref(&self) -> &Secret {
+        &self.secret
+    }
+}
+
+/// How a [`RecoveredSecret`] was recovered.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct RecoveryInfo {
+    identifier: u32,
+    version: ShareVersion,
+    group_threshold: usize,
+    group_count: usize,
+    groups_used: Vec<usize>,
+    shares_used: usize,
+}
+
+impl RecoveryInfo {
+    pub(crate) fn new(
+        identifier: u32,
+        version: ShareVersion,
+        group_threshold: usize,
+        group_count: usize,
+        groups_used: Vec<usize>,
+        shares_used: usize,
+    ) -> Self {
+        Self { identifier, version, group_threshold, group_count, groups_used, shares_used }
+    }
+
+    /// Returns the identifier of the split.
+    pub fn identifier(&self) -> u32 {
+        self.identifier
+    }
+
+    /// Returns the format of the shares.
+    pub fn version(&self) -> ShareVersion {
+        self.version
+    }
+
+    /// Returns the number of groups needed to recover the secret.
+    pub fn group_threshold(&self) -> usize {
+        self.group_threshold
+    }
+
+    /// Returns the number of groups in the split.
+    pub fn group_count(&self) -> usize {
+        self.group_count
+    }
+
+    /// Returns the indexes of the groups whose secrets were combined to
+    /// recover the secret, in the order they were used.
+    pub fn groups_used(&self) -> &[usize] {
+        &self.groups_used
+    }
+
+    /// Returns the number of member shares combined to recover the groups
+    /// used.
+    pub fn shares_used(&self) -> usize {
+        self.shares_used
+    }
+}
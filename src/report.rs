@@ -0,0 +1,588 @@
+use zeroize::Zeroizing;
+
+use crate::{
+    SSKRError,
+    Secret,
+    sskr_combine,
+    encoding::{check_share_matches, deserialize_share, recover_secret},
+    share::SSKRShareRef,
+};
+
+/// Combines the given SSKR shares into a `Secret`, and reports what was found
+/// in them.
+///
+/// The result is exactly what `sskr_combine` returns for `shares`. The report
+/// describes the shares whether or not the combine succeeded, so that a
+/// failure can be explained: which shares were malformed, which came from a
+/// different split, which repeated a member, and how far each group got.
+/// After a successful combine, [`CombineReport::share_usage`] tells whether
+/// each share contributed to the secret.
+pub fn sskr_combine_with_report<T>(shares: &[T]) -> (Result<Secret, SSKRError>, CombineReport)
+where
+    T: AsRef<[u8]>
+{
+    (sskr_combine(shares), CombineReport::new(shares))
+}
+
+/// Combines the given SSKR shares into a `Secret`, refusing any input that
+/// contains a share that was not needed.
+///
+/// Where `sskr_combine` recovers the secret whenever it can, this function
+/// also requires that every share was combined to recover it: an extra share
+/// of a group, a share of a group that was not needed or was incomplete, a
+/// duplicate, and a share of another split are all violations. A group whose
+/// shares disagree with each other cannot be recovered, or holds an extra
+/// share, so it always gives rise to a violation.
+///
+/// # Errors
+///
+/// Returns `SSKRError::StrictCombineRejected` listing every violation in the
+/// order of the input, and otherwise the same errors as `sskr_combine`.
+pub fn sskr_combine_strict<T>(shares: &[T]) -> Result<Secret, SSKRError>
+where
+    T: AsRef<[u8]>
+{
+    let (result, report) = sskr_combine_with_report(shares);
+    let violations: Vec<StrictViolation> = report
+        .share_usage()
+        .iter()
+        .enumerate()
+        .filter(|(_, usage)| **usage != ShareUsage::Used)
+        .map(|(share_index, &usage)| StrictViolation { share_index, usage })
+        .collect();
+    if !violations.is_empty() {
+        return Err(SSKRError::StrictCombineRejected { violations });
+    }
+    result
+}
+
+/// Combines the given SSKR shares into a `Secret`, along with warnings about
+/// complete groups that were ignored.
+///
+/// The secret is exactly what `sskr_combine` recovers. A group that holds
+/// enough shares is still ignored when its shares do not decode, as when one
+/// of them is corrupt, provided the other groups meet the group threshold;
+/// it is also ignored when enough groups listed before it were recovered.
+/// Either way its shares were thrown away, which the caller may want to tell
+/// the user about.
+///
+/// # Errors
+///
+/// Returns the same errors as `sskr_combine`.
+pub fn sskr_combine_with_warnings<T>(shares: &[T]) -> Result<(Secret, Vec<CombineWarning>), SSKRError>
+where
+    T: AsRef<[u8]>
+{
+    let (result, report) = sskr_combine_with_report(shares);
+    result.map(|secret| (secret, report.warnings()))
+}
+
+/// A description of the shares passed to [`sskr_combine_with_report`].
+///
+/// Shares are referred to by their position in the input. The report holds
+/// only share metadata; it never contains a share value or any secret.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct CombineReport {
+    identifiers: Vec<u32>,
+    share_count: usize,
+    share_usage: Vec<ShareUsage>,
+    groups: Vec<GroupReport>,
+    malformed_shares: Vec<usize>,
+    mismatched_shares: Vec<usize>,
+    duplicate_shares: Vec<usize>,
+}
+
+impl CombineReport {
+    fn new<T>(shares: &[T]) -> Self
+    where
+        T: AsRef<[u8]>
+    {
+        let mut report = CombineReport {
+            share_count: shares.len(),
+            share_usage: vec![ShareUsage::Malformed; shares.len()],
+            ..Default::default()
+        };
+        let mut expected: Option<SSKRShareRef<'_>> = None;
+        // The groups in the order they first appear, as `sskr_combine` takes
+        // them.
+        let mut groups: Vec<GroupShares<'_>> = Vec::new();
+
+        for (share_index, share) in shares.iter().enumerate() {
+            let Ok(share) = deserialize_share(share.as_ref(), share_index) else {
+                report.malformed_shares.push(share_index);
+                continue;
+            };
+            if !report.identifiers.contains(&share.identifier()) {
+                report.identifiers.push(share.identifier());
+            }
+            let expected = expected.get_or_insert(share);
+            if check_share_matches(expected, &share, share_index).is_err() {
+                report.mismatched_shares.push(share_index);
+                report.share_usage[share_index] = ShareUsage::Mismatched;
+                continue;
+            }
+
+            let position = groups.iter().position(|group| group.report.group_index == share.group_index());
+            let GroupShares { report: group, share_indexes, member_indexes, values } = match position {
+                Some(position) => &mut groups[position],
+                None => {
+                    groups.push(GroupShares {
+                        report: GroupReport {
+                            group_index: share.group_index(),
+                            member_threshold: share.member_threshold(),
+                            shares_provided: 0,
+                            shares_used: 0,
+                            outcome: GroupOutcome::NotEnoughShares,
+                        },
+                        share_indexes: Vec::new(),
+                        member_indexes: Vec::new(),
+                        values: Vec::new(),
+                    });
+                    groups.last_mut().unwrap()
+                }
+            };
+            if share.member_threshold() != group.member_threshold {
+                report.mismatched_shares.push(share_index);
+                report.share_usage[share_index] = ShareUsage::Mismatched;
+            } else if member_indexes.contains(&share.member_index()) {
+                report.duplicate_shares.push(share_index);
+                report.share_usage[share_index] = ShareUsage::Duplicate;
+            } else {
+                group.shares_provided += 1;
+                share_indexes.push(share_index);
+                member_indexes.push(share.member_index());
+                values.push(share.value());
+            }
+        }
+
+        // Recover the groups as `sskr_combine` would, in order, until enough
+        // have been recovered.
+        if let Some(expected) = expected {
+            let mut recovered = 0;
+            for GroupShares { report: group, member_indexes, values, .. } in &mut groups {
+                let threshold = group.member_threshold;
+                if member_indexes.len() < threshold {
+                    continue;
+                }
+                if recovered == expected.group_threshold() {
+                    group.outcome = GroupOutcome::NotNeeded;
+                    continue;
+                }
+                let group_secret = recover_secret(expected.version(), &member_indexes[..threshold], &values[..threshold]);
+                match group_secret.map(Zeroizing::new) {
+                    Ok(_) => {
+                        group.outcome = GroupOutcome::Recovered;
+                        group.shares_used = threshold;
+                        recovered += 1;
+                    }
+                    Err(_) => group.outcome = GroupOutcome::DecodeFailed,
+                }
+            }
+        }
+
+        for GroupShares { report: group, share_indexes, .. } in &groups {
+            for (position, &share_index) in share_indexes.iter().enumerate() {
+                report.share_usage[share_index] = match group.outcome {
+                    GroupOutcome::Recovered if position < group.shares_used => ShareUsage::Used,
+                    GroupOutcome::Recovered => ShareUsage::Redundant,
+                    GroupOutcome::NotNeeded => ShareUsage::GroupNotNeeded,
+                    GroupOutcome::NotEnoughShares => ShareUsage::GroupIncomplete,
+                    GroupOutcome::DecodeFailed => ShareUsage::GroupDecodeFailed,
+                };
+            }
+        }
+
+        report.groups = groups.into_iter().map(|group| group.report).collect();
+        report.groups.sort_by_key(|group| group.group_index);
+        report
+    }
+
+    /// Returns the identifier of the split being recovered, which is that of
+    /// the first well-formed share, or `None` if there were none.
+    pub fn identifier(&self) -> Option<u32> {
+        self.identifiers.first().copied()
+    }
+
+    /// Returns the identifier of every split the well-formed shares came
+    /// from, in the order they were first seen. More than one means shares
+    /// of different splits were mixed.
+    pub fn identifiers(&self) -> &[u32] {
+        &self.identifiers
+    }
+
+    /// Returns the number of shares provided.
+    pub fn share_count(&self) -> usize {
+        self.share_count
+    }
+
+    /// Returns what became of each share, in the order of the input.
+    pub fn share_usage(&self) -> &[ShareUsage] {
+        &self.share_usage
+    }
+
+    /// Returns the positions of the shares that were combined to recover
+    /// the secret or a group secret.
+    pub fn used_shares(&self) -> Vec<usize> {
+        self.share_usage
+            .iter()
+            .enumerate()
+            .filter(|(_, usage)| **usage == ShareUsage::Used)
+            .map(|(share_index, _)| share_index)
+            .collect()
+    }
+
+    /// Returns a report for each group of the split that shares were
+    /// provided for, in order of group index.
+    pub fn groups(&self) -> &[GroupReport] {
+        &self.groups
+    }
+
+    /// Returns the positions of the shares that could not be parsed.
+    pub fn malformed_shares(&self) -> &[usize] {
+        &self.malformed_shares
+    }
+
+    /// Returns the positions of the shares that don't belong to the split of
+    /// the first well-formed share, because they differ in identifier or any
+    /// other metadata.
+    pub fn mismatched_shares(&self) -> &[usize] {
+        &self.mismatched_shares
+    }
+
+    /// Returns the positions of the shares that repeat a member already
+    /// provided.
+    pub fn duplicate_shares(&self) -> &[usize] {
+        &self.duplicate_shares
+    }
+
+    /// Returns a warning for each group that held enough shares but was
+    /// ignored, in order of group index.
+    pub fn warnings(&self) -> Vec<CombineWarning> {
+        self.groups
+            .iter()
+            .filter_map(|group| match group.outcome {
+                GroupOutcome::DecodeFailed => Some(CombineWarning::GroupIgnoredUndecodable {
+                    group_index: group.group_index,
+                    shares_provided: group.shares_provided,
+                }),
+                GroupOutcome::NotNeeded => Some(CombineWarning::GroupIgnoredRedundant { group_index: group.group_index }),
+                GroupOutcome::Recovered | GroupOutcome::NotEnoughShares => None,
+            })
+            .collect()
+    }
+}
+
+/// The distinct members provided for a group, in the order they appear,
+/// with their positions in the input.
+struct GroupShares<'a> {
+    report: GroupReport,
+    share_indexes: Vec<usize>,
+    member_indexes: Vec<usize>,
+    values: Vec<&'a [u8]>,
+}
+
+/// What happened to one group of a split in [`sskr_combine_with_report`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct GroupReport {
+    group_index: usize,
+    member_threshold: usize,
+    shares_provided: usize,
+    shares_used: usize,
+    outcome: GroupOutcome,
+}
+
+impl GroupReport {
+    /// Returns the index of the group.
+    pub fn group_index(&self) -> usize {
+        self.group_index
+    }
+
+    /// Returns the member threshold of the group.
+    pub fn member_threshold(&self) -> usize {
+        self.member_threshold
+    }
+
+    /// Returns the number of distinct members provided for the group,
+    /// not counting duplicate or mismatched shares.
+    pub fn shares_provided(&self) -> usize {
+        self.shares_provided
+    }
+
+    /// Returns the number of shares combined to recover the group's secret:
+    /// the member threshold if the group was recovered, and zero otherwise.
+    pub fn shares_used(&self) -> usize {
+        self.shares_used
+    }
+
+    /// Returns what happened to the group.
+    pub fn outcome(&self) -> GroupOutcome {
+        self.outcome
+    }
+}
+
+/// What happened to a group in [`sskr_combine_with_report`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum GroupOutcome {
+    /// The group's secret was recovered from its shares.
+    Recovered,
+    /// The group had enough shares, but was not needed because enough other
+    /// groups had already been recovered.
+    NotNeeded,
+    /// The group had fewer shares than its member threshold.
+    NotEnoughShares,
+    /// The group had enough shares, but they did not decode to a valid group
+    /// secret, so the group was ignored. At least one of them is corrupt.
+    DecodeFailed,
+}
+
+/// What became of one share in [`sskr_combine_with_report`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum ShareUsage {
+    /// The share was combined to recover its group's secret.
+    Used,
+    /// The share's group was recovered from shares listed before it, which
+    /// already met the member threshold.
+    Redundant,
+    /// The share's group was not needed, because enough other groups had
+    /// already been recovered.
+    GroupNotNeeded,
+    /// The share's group had fewer shares than its member threshold.
+    GroupIncomplete,
+    /// The share's group did not decode to a valid group secret.
+    GroupDecodeFailed,
+    /// The share repeats a member already provided.
+    Duplicate,
+    /// The share does not belong to the split of the first well-formed
+    /// share.
+    Mismatched,
+    /// The share could not be parsed.
+    Malformed,
+}
+
+/// A complete group that was ignored in recovering the secret, as reported
+/// by [`sskr_combine_with_warnings`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum CombineWarning {
+    /// The group's shares did not decode to a valid group secret, so at
+    /// least one of them is corrupt, but the secret was recovered from other
+    /// groups.
+    GroupIgnoredUndecodable { group_index: usize, shares_provided: usize },
+    /// The group was not needed, because enough groups listed before it had
+    /// already been recovered.
+    GroupIgnoredRedundant { group_index: usize },
+}
+
+/// A share that [`sskr_combine_strict`] objected to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct StrictViolation {
+    share_index: usize,
+    usage: ShareUsage,
+}
+
+impl StrictViolation {
+    /// Returns the position of the share in the input.
+    pub fn share_index(&self) -> usize {
+        self.share_index
+    }
+
+    /// Returns what became of the share, which is anything but
+    /// `ShareUsage::Used`.
+    pub fn usage(&self) -> ShareUsage {
+        self.usage
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{GroupSpec, Spec, sskr_generate_using, test_support};
+    
+
+    fn identifier(shares: &[Vec<Vec<u8>>]) -> u32 {
+        u16::from_be_bytes([shares[0][0][0], shares[0][0][1]]) as u32
+    }
+
+    fn outcomes(report: &CombineReport) -> Vec<(usize, usize, usize, GroupOutcome)> {
+        report.groups().iter().map(|group| {
+            (group.group_index(), group.shares_provided(), group.shares_used(), group.outcome())
+        }).collect()
+    }
+
+    #[test]
+    fn test_report_success() {
+        let secret = test_support::secret();
+        let shares = test_support::generate(&test_support::spec());
+        let input = [&shares[1][0], &shares[1][3], &shares[0][2], &shares[1][1], &shares[1][4], &shares[2][0], &shares[0][0]];
+        let (result, report) = sskr_combine_with_report(&input);
+        assert_eq!(result.unwrap(), secret);
+        assert_eq!(report.identifier(), Some(identifier(&shares)));
+        assert_eq!(report.identifiers(), &[identifier(&shares)]);
+        assert_eq!(report.share_count(), 7);
+        assert_eq!(outcomes(&report), [
+            (0, 2, 2, GroupOutcome::Recovered),
+            (1, 4, 3, GroupOutcome::Recovered),
+            (2, 1, 0, GroupOutcome::NotNeeded),
+        ]);
+        assert!(report.malformed_shares().is_empty());
+        assert!(report.mismatched_shares().is_empty());
+        assert!(report.duplicate_shares().is_empty());
+        assert_eq!(report.share_usage(), [
+            ShareUsage::Used,
+            ShareUsage::Used,
+            ShareUsage::Used,
+            ShareUsage::Used,
+            ShareUsage::Redundant,
+            ShareUsage::GroupNotNeeded,
+            ShareUsage::Used,
+        ]);
+        assert_eq!(report.used_shares(), [0, 1, 2, 3, 6]);
+
+        // The report carries no share values.
+        let debug = format!("{report:?}");
+        for share in input {
+            assert!(!debug.contains(&format!("{}", share[5])));
+        }
+    }
+
+    #[test]
+    fn test_report_failure() {
+        let shares = test_support::generate(&test_support::spec());
+        let mut rng = bc_rand::make_fake_random_number_generator();
+        bc_rand::RandomNumberGenerator::random_data(&mut rng, 5);
+        let other_shares = test_support::generate_using(&test_support::spec(), &mut rng);
+
+        // A corrupt group is ignored, and shares of another split, duplicates
+        // and unparseable shares are each named.
+        let mut corrupt = shares[0][1].clone();
+        corrupt[8] ^= 0x01;
+        let input = vec![
+            shares[0][0].clone(),
+            corrupt,
+            other_shares[2][0].clone(),
+            shares[1][2].clone(),
+            shares[0][0].clone(),
+            vec![0x00, 0x11],
+            shares[2][0].clone(),
+        ];
+        let (result, report) = sskr_combine_with_report(&input);
+        assert!(result.is_err());
+        assert_eq!(report.identifiers(), &[identifier(&shares), identifier(&other_shares)]);
+        assert_eq!(outcomes(&report), [
+            (0, 2, 0, GroupOutcome::DecodeFailed),
+            (1, 1, 0, GroupOutcome::NotEnoughShares),
+            (2, 1, 1, GroupOutcome::Recovered),
+        ]);
+        assert_eq!(report.mismatched_shares(), &[2]);
+        assert_eq!(report.duplicate_shares(), &[4]);
+        assert_eq!(report.malformed_shares(), &[5]);
+        assert_eq!(report.share_usage(), [
+            ShareUsage::GroupDecodeFailed,
+            ShareUsage::GroupDecodeFailed,
+            ShareUsage::Mismatched,
+            ShareUsage::GroupIncomplete,
+            ShareUsage::Duplicate,
+            ShareUsage::Malformed,
+            ShareUsage::Used,
+        ]);
+
+        let (result, report) = sskr_combine_with_report::<Vec<u8>>(&[]);
+        assert!(matches!(result, Err(SSKRError::SharesEmpty)));
+        assert_eq!(report, CombineReport::default());
+    }
+
+    #[test]
+    fn test_report_unused_share() {
+        // Two groups of which either recovers the secret: the shares of the
+        // second group are provided but not used, and a third share of the
+        // first group is redundant.
+        let secret = test_support::secret();
+        let spec = Spec::new(1, vec![GroupSpec::new(2, 3).unwrap(), GroupSpec::new(2, 3).unwrap()]).unwrap();
+        let shares = sskr_generate_using(&spec, &secret, &mut bc_rand::make_fake_random_number_generator()).unwrap();
+
+        let input = [&shares[0][2], &shares[1][0], &shares[0][0], &shares[1][1], &shares[0][1], &shares[1][2]];
+        let (result, report) = sskr_combine_with_report(&input);
+        assert_eq!(result.unwrap(), secret);
+        assert_eq!(report.share_usage(), [
+            ShareUsage::Used,
+            ShareUsage::GroupNotNeeded,
+            ShareUsage::Used,
+            ShareUsage::GroupNotNeeded,
+            ShareUsage::Redundant,
+            ShareUsage::GroupNotNeeded,
+        ]);
+        assert_eq!(report.used_shares(), [0, 2]);
+
+        // A lone share of the second group doesn't make a group.
+        let (result, report) = sskr_combine_with_report(&[&shares[0][0], &shares[0][1], &shares[1][2]]);
+        assert_eq!(result.unwrap(), secret);
+        assert_eq!(report.share_usage(), [ShareUsage::Used, ShareUsage::Used, ShareUsage::GroupIncomplete]);
+    }
+
+    #[test]
+    fn test_combine_strict() {
+        let secret = test_support::secret();
+        let spec = Spec::new(1, vec![GroupSpec::new(2, 3).unwrap(), GroupSpec::new(2, 3).unwrap()]).unwrap();
+        let shares = sskr_generate_using(&spec, &secret, &mut bc_rand::make_fake_random_number_generator()).unwrap();
+
+        assert_eq!(sskr_combine_strict(&[&shares[1][2], &shares[1][0]]).unwrap(), secret);
+
+        // A share of a second group is accepted by `sskr_combine`, but not here.
+        let input = [&shares[0][0], &shares[0][1], &shares[1][2]];
+        assert_eq!(sskr_combine(&input).unwrap(), secret);
+        let Err(SSKRError::StrictCombineRejected { violations }) = sskr_combine_strict(&input) else {
+            panic!("expected a strict combine failure");
+        };
+        assert_eq!(violations, [StrictViolation { share_index: 2, usage: ShareUsage::GroupIncomplete }]);
+
+        // Every violation is listed.
+        let input = [&shares[0][0], &shares[0][1], &shares[0][0], &shares[0][2], &shares[1][0], &shares[1][1]];
+        let error = sskr_combine_strict(&input).unwrap_err();
+        assert_eq!(error.to_string(), "SSKR shares were rejected by strict combine: 4 of them were not used to recover the secret");
+        let SSKRError::StrictCombineRejected { violations } = error else {
+            panic!("expected a strict combine failure");
+        };
+        let violations: Vec<(usize, ShareUsage)> = violations.iter().map(|v| (v.share_index(), v.usage())).collect();
+        assert_eq!(violations, [
+            (2, ShareUsage::Duplicate),
+            (3, ShareUsage::Redundant),
+            (4, ShareUsage::GroupNotNeeded),
+            (5, ShareUsage::GroupNotNeeded),
+        ]);
+
+        // Inputs that can't be combined fail as they do for `sskr_combine`.
+        assert!(matches!(sskr_combine_strict::<Vec<u8>>(&[]), Err(SSKRError::SharesEmpty)));
+    }
+
+    #[test]
+    fn test_combine_with_warnings() {
+        // With a group threshold of one, shares of every group can be given
+        // and only the first complete group is used.
+        let secret = test_support::secret();
+        let spec = Spec::new(1, vec![GroupSpec::new(2, 3).unwrap(), GroupSpec::new(2, 3).unwrap()]).unwrap();
+        let shares = sskr_generate_using(&spec, &secret, &mut bc_rand::make_fake_random_number_generator()).unwrap();
+
+        let (recovered, warnings) = sskr_combine_with_warnings(&[&shares[1][0], &shares[0][1], &shares[1][2], &shares[0][0]]).unwrap();
+        assert_eq!(recovered, secret);
+        assert_eq!(warnings, [CombineWarning::GroupIgnoredRedundant { group_index: 0 }]);
+
+        // A corrupt share in the first group is only noticed as a warning.
+        let mut corrupt = shares[0][2].clone();
+        corrupt[8] ^= 0x01;
+        let input = [&shares[0][0], &corrupt, &shares[0][1], &shares[1][1], &shares[1][2]];
+        let (recovered, warnings) = sskr_combine_with_warnings(&input).unwrap();
+        assert_eq!(recovered, secret);
+        assert_eq!(warnings, [CombineWarning::GroupIgnoredUndecodable { group_index: 0, shares_provided: 3 }]);
+
+        // No group is ignored here.
+        let (_, warnings) = sskr_combine_with_warnings(&[&shares[0][0], &shares[0][1], &shares[1][1]]).unwrap();
+        assert!(warnings.is_empty());
+
+        assert!(matches!(sskr_combine_with_warnings(&[&shares[0][0], &corrupt]), Err(SSKRError::GroupRecoveryFailed { group_index: 0, .. })));
+    }
+}
@@ -0,0 +1,56 @@
+/// Where the members of a group with required members find their shares in
+/// the split returned by
+/// [`Spec::with_required_member`](crate::Spec::with_required_member).
+///
+/// The members of the original group are numbered from zero with the
+/// required members first. The required members hold the shares of group
+/// [`REQUIRED_GROUP_INDEX`](Self::REQUIRED_GROUP_INDEX) of the expanded
+/// split and the others the shares of group
+/// [`OTHER_GROUP_INDEX`](Self::OTHER_GROUP_INDEX), in the same order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct RequiredMembers {
+    required_count: usize,
+    member_count: usize,
+}
+
+impl RequiredMembers {
+    /// The index of the group of the required members.
+    pub const REQUIRED_GROUP_INDEX: usize = 0;
+
+    /// The index of the group of the other members.
+    pub const OTHER_GROUP_INDEX: usize = 1;
+
+    pub(crate) fn new(required_count: usize, member_count: usize) -> Self {
+        Self { required_count, member_count }
+    }
+
+    /// Returns the number of required members.
+    pub fn required_count(&self) -> usize {
+        self.required_count
+    }
+
+    /// Returns the number of members of the original group.
+    pub fn member_count(&self) -> usize {
+        self.member_count
+    }
+
+    /// Returns `true` if the member at `member_index` of the original group
+    /// is required.
+    pub fn is_required(&self, member_index: usize) -> bool {
+        member_index < self.required_count
+    }
+
+    /// Returns the group index and member index in the expanded split of the
+    /// share of the member at `member_index` of the original group, or
+    /// `None` if there is no such member.
+    pub fn share_location(&self, member_index: usize) -> Option<(usize, usize)> {
+        if member_index < self.required_count {
+            Some((Self::REQUIRED_GROUP_INDEX, member_index))
+        } else if member_index < self.member_count {
+            Some((Self::OTHER_GROUP_INDEX, member_index - self.required_count))
+        } else {
+            None
+        }
+    }
+}
@@ -0,0 +1,258 @@
+use std::collections::BTreeSet;
+
+use bc_rand::RandomNumberGenerator;
+
+use crate::{
+    GroupSpec,
+    SSKRError,
+    SSKRShare,
+    Secret,
+    ShareVersion,
+    Spec,
+    encoding::generate_shares_with_identifier,
+    generate_options::draw_identifier,
+    sskr_combine_detailed,
+    transcript::GroupedShareBytes,
+};
+
+/// Moves the secret of a split into a new split, without returning it.
+///
+/// `old_shares` are combined as `sskr_combine` combines them, and the
+/// recovered secret is split as `new_spec` describes, as by
+/// `sskr_generate_using`. The secret is held only inside this call and is
+/// zeroized before it returns. The new split is given a fresh random
+/// identifier that differs from the old one, so that old and new shares
+/// cannot be mistaken for shares of the same split.
+///
+/// The old shares remain valid: resharing does not revoke them, and anyone
+/// who holds a quorum of them can still recover the secret.
+///
+/// # Errors
+///
+/// Returns the same errors as `sskr_combine` if `old_shares` do not recover
+/// a secret, including `SSKRError::NotEnoughGroups` and
+/// `SSKRError::NotEnoughMemberShares` when they fall short of a quorum, and
+/// the same errors as `sskr_generate` if `new_spec` cannot split it.
+pub fn sskr_reshare<T, R>(
+    old_shares: &[T],
+    new_spec: &Spec,
+    random_generator: &mut R
+) -> Result<Vec<Vec<Vec<u8>>>, SSKRError>
+where
+    T: AsRef<[u8]>,
+    R: RandomNumberGenerator
+{
+    let (secret, info) = sskr_combine_detailed(old_shares)?.into_parts();
+    resplit(secret, new_spec, ShareVersion::V0, info.identifier(), random_generator).map(|(_, shares)| shares)
+}
+
+/// The result of [`sskr_rotate`]: the shares of the new split, with the
+/// identifiers of both splits for record-keeping.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RotationResult {
+    old_identifier: u32,
+    new_identifier: u32,
+    spec: Spec,
+    shares: Vec<Vec<Vec<u8>>>,
+}
+
+impl RotationResult {
+    /// Returns the identifier of the split that was rotated.
+    pub fn old_identifier(&self) -> u32 {
+        self.old_identifier
+    }
+
+    /// Returns the identifier of the new split.
+    pub fn new_identifier(&self) -> u32 {
+        self.new_identifier
+    }
+
+    /// Returns the spec of both splits.
+    pub fn spec(&self) -> &Spec {
+        &self.spec
+    }
+
+    /// Returns the shares of the new split, grouped as `sskr_generate`
+    /// returns them.
+    pub fn shares(&self) -> &[Vec<Vec<u8>>] {
+        &self.shares
+    }
+
+    /// Returns the shares of the new split.
+    pub fn into_shares(self) -> Vec<Vec<Vec<u8>>> {
+        self.shares
+    }
+}
+
+/// Replaces a split with a new split of the same secret and the same spec,
+/// without returning the secret.
+///
+/// `old_shares` are combined as `sskr_combine` combines them, and the secret
+/// is split again with a fresh identifier, new random polynomials and the
+/// format of the old shares. Shares of the old split, including any that may
+/// have been exposed, cannot be combined with those of the new one. The old
+/// shares are not revoked, though: a quorum of them still recovers the
+/// secret, so they must be destroyed once the new shares are distributed.
+///
+/// The group and member thresholds of the new split are read from
+/// `old_shares`, so they must include a share of every group. The number of
+/// members of a group is not recorded in its shares, so `member_counts`
+/// gives it for each group, in order of group index. The secret is held only
+/// inside this call and is zeroized before it returns.
+///
+/// # Errors
+///
+/// Returns the same errors as `sskr_combine` if `old_shares` do not recover
+/// a secret, `SSKRError::MemberThresholdUnknown` for a group with no share
+/// among `old_shares`, `SSKRError::MemberCountsInvalid` if `member_counts`
+/// does not give a count for each group, and `SSKRError::GroupSpecInvalid`
+/// if a count is less than its group's member threshold or more than the
+/// format allows.
+pub fn sskr_rotate<T, R>(
+    old_shares: &[T],
+    member_counts: &[usize],
+    random_generator: &mut R
+) -> Result<RotationResult, SSKRError>
+where
+    T: AsRef<[u8]>,
+    R: RandomNumberGenerator
+{
+    let partial = Spec::from_shares(old_shares)?;
+    if member_counts.len() != partial.group_count() {
+        return Err(SSKRError::MemberCountsInvalid { expected: partial.group_count(), found: member_counts.len() });
+    }
+    let version = partial.version();
+    let groups = partial
+        .member_thresholds()
+        .iter()
+        .zip(member_counts)
+        .enumerate()
+        .map(|(group_index, (member_threshold, &member_count))| {
+            let member_threshold = member_threshold.ok_or(SSKRError::MemberThresholdUnknown { group_index })?;
+            GroupSpec::new_with_version(member_threshold, member_count, version)
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    let spec = Spec::new_with_version(partial.group_threshold(), groups, version)?;
+
+    let (secret, info) = sskr_combine_detailed(old_shares)?.into_parts();
+    let (new_identifier, shares) = resplit(secret, &spec, version, info.identifier(), random_generator)?;
+    Ok(RotationResult { old_identifier: info.identifier(), new_identifier, spec, shares })
+}
+
+/// Splits `secret` as `spec` describes in the format `version`, with a random
+/// identifier other than `old_identifier`, and zeroizes it. Returns the new
+/// identifier with the shares.
+fn resplit<R>(
+    secret: Secret,
+    spec: &Spec,
+    version: ShareVersion,
+    old_identifier: u32,
+    random_generator: &mut R
+) -> Result<(u32, GroupedShareBytes), SSKRError>
+where
+    R: RandomNumberGenerator
+{
+    let identifier = draw_identifier(version, &BTreeSet::from([old_identifier]), random_generator)?;
+    let groups_shares = generate_shares_with_identifier(spec, &secret, version, identifier, random_generator);
+    drop(secret.into_zeroizing());
+    let shares = groups_shares?.iter().map(|group| group.iter().map(SSKRShare::serialize).collect()).collect();
+    Ok((identifier, shares))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{sskr_combine, sskr_generate_using, sskr_generate_with_version_using, test_support::{self, ZeroRandomNumberGenerator}};
+    
+
+    #[test]
+    fn test_reshare() {
+        let secret = test_support::secret();
+        let old_spec = Spec::new(1, vec![GroupSpec::new(2, 3).unwrap()]).unwrap();
+        let mut rng = bc_rand::make_fake_random_number_generator();
+        let old_shares = sskr_generate_using(&old_spec, &secret, &mut rng).unwrap();
+
+        // The fake generator repeats the old identifier at first, so the new
+        // split must draw another.
+        let new_spec = Spec::new(2, vec![GroupSpec::new(2, 3).unwrap(), GroupSpec::new(2, 3).unwrap()]).unwrap();
+        let new_shares = sskr_reshare(&[&old_shares[0][2], &old_shares[0][0]], &new_spec, &mut bc_rand::make_fake_random_number_generator()).unwrap();
+        assert_eq!(new_shares.iter().map(Vec::len).collect::<Vec<_>>(), [3, 3]);
+        assert_ne!(new_shares[0][0][..2], old_shares[0][0][..2]);
+        assert_eq!(sskr_combine(&[&new_shares[1][0], &new_shares[0][1], &new_shares[1][2], &new_shares[0][2]]).unwrap(), secret);
+
+        // Old and new shares do not mix.
+        assert!(matches!(
+            sskr_combine(&[&new_shares[0][0], &new_shares[0][1], &old_shares[0][0]]),
+            Err(SSKRError::IdentifierMismatch { share_index: 2, .. })
+        ));
+
+        assert!(matches!(
+            sskr_reshare(&old_shares[0][..1], &new_spec, &mut rng),
+            Err(SSKRError::NotEnoughMemberShares { group_index: 0, have: 1, need: 2 })
+        ));
+    }
+
+    #[test]
+    fn test_reshare_with_constant_generator() {
+        // A generator that only ever returns the old identifier still gives
+        // the new split another one, rather than redrawing forever.
+        let secret = test_support::secret();
+        let spec = Spec::new(1, vec![GroupSpec::new(2, 3).unwrap()]).unwrap();
+        for version in [ShareVersion::V0, ShareVersion::V2] {
+            let old_shares = sskr_generate_with_version_using(&spec, &secret, version, &mut ZeroRandomNumberGenerator).unwrap();
+            let rotation = sskr_rotate(&old_shares[0][..2], &[3], &mut ZeroRandomNumberGenerator).unwrap();
+            assert_eq!(rotation.old_identifier(), 0);
+            assert_ne!(rotation.new_identifier(), 0);
+            assert_eq!(sskr_combine(&rotation.shares()[0][1..]).unwrap(), secret);
+
+            let new_shares = sskr_reshare(&old_shares[0][..2], &spec, &mut ZeroRandomNumberGenerator).unwrap();
+            assert_ne!(new_shares[0][0][..2], [0, 0]);
+            assert_eq!(sskr_combine(&new_shares[0][..2]).unwrap(), secret);
+        }
+    }
+
+    #[test]
+    fn test_rotate() {
+        let secret = test_support::secret();
+        let spec = Spec::new(2, vec![GroupSpec::new(2, 3).unwrap(), GroupSpec::new(1, 2).unwrap(), GroupSpec::new(3, 5).unwrap()]).unwrap();
+        let old_shares = sskr_generate_using(&spec, &secret, &mut bc_rand::make_fake_random_number_generator()).unwrap();
+        let old_identifier = u16::from_be_bytes([old_shares[0][0][0], old_shares[0][0][1]]) as u32;
+
+        let quorum = [&old_shares[0][0], &old_shares[0][2], &old_shares[1][1], &old_shares[2][4]];
+        let rotation = sskr_rotate(&quorum, &[3, 2, 5], &mut bc_rand::make_fake_random_number_generator()).unwrap();
+        assert_eq!(rotation.old_identifier(), old_identifier);
+        assert_ne!(rotation.new_identifier(), old_identifier);
+        assert_eq!(rotation.spec(), &spec);
+        let new_shares = rotation.shares();
+        assert_eq!(new_shares.iter().map(Vec::len).collect::<Vec<_>>(), [3, 2, 5]);
+        assert_eq!(sskr_combine(&[&new_shares[1][0], &new_shares[2][1], &new_shares[2][3], &new_shares[2][4]]).unwrap(), secret);
+
+        // A leaked old share is no use with the new ones.
+        assert!(matches!(
+            sskr_combine(&[&new_shares[0][0], &old_shares[0][1], &new_shares[1][1]]),
+            Err(SSKRError::IdentifierMismatch { share_index: 1, .. })
+        ));
+        assert!(matches!(
+            sskr_combine(&[&old_shares[1][0], &new_shares[0][0], &new_shares[0][1]]),
+            Err(SSKRError::IdentifierMismatch { share_index: 1, .. })
+        ));
+
+        assert!(matches!(
+            sskr_rotate(&quorum, &[3, 2], &mut bc_rand::make_fake_random_number_generator()),
+            Err(SSKRError::MemberCountsInvalid { expected: 3, found: 2 })
+        ));
+        assert!(matches!(
+            sskr_rotate(&quorum[..3], &[3, 2, 5], &mut bc_rand::make_fake_random_number_generator()),
+            Err(SSKRError::MemberThresholdUnknown { group_index: 2 })
+        ));
+        assert!(sskr_rotate(&quorum, &[3, 2, 2], &mut bc_rand::make_fake_random_number_generator()).is_err());
+
+        // The format of the old shares is kept.
+        let wide_shares = sskr_generate_with_version_using(&spec, &secret, ShareVersion::V2, &mut bc_rand::make_fake_random_number_generator()).unwrap();
+        let quorum = [&wide_shares[0][0], &wide_shares[0][1], &wide_shares[1][0], &wide_shares[2][0]];
+        let rotation = sskr_rotate(&quorum, &[3, 2, 5], &mut bc_rand::make_fake_random_number_generator()).unwrap();
+        assert_eq!(rotation.shares()[0][0].len(), wide_shares[0][0].len());
+        assert_eq!(sskr_combine(&[&rotation.shares()[0][0], &rotation.shares()[0][1], &rotation.shares()[1][0]]).unwrap(), secret);
+        assert_eq!(rotation.into_shares().len(), 3);
+    }
+}
@@ -0,0 +1,137 @@
+use bc_rand::RandomNumberGenerator;
+
+use crate::SSKRError;
+
+/// The number of bytes drawn by each of the two draws of the health check.
+const SAMPLE_LEN: usize = 256;
+
+/// The entropy per byte, in bits, the cutoffs assume. This is well below what
+/// a working generator delivers, so that a working generator fails only with
+/// negligible probability.
+const ASSUMED_ENTROPY_BITS: usize = 4;
+
+/// The false positive rate the repetition count cutoff is set for, as a
+/// negative power of two.
+const FALSE_POSITIVE_BITS: usize = 40;
+
+/// The repetition count cutoff of SP 800-90B, section 4.4.1:
+/// `1 + ceil(FALSE_POSITIVE_BITS / ASSUMED_ENTROPY_BITS)`.
+const REPETITION_CUTOFF: usize = 1 + FALSE_POSITIVE_BITS.div_ceil(ASSUMED_ENTROPY_BITS);
+
+/// The adaptive proportion cutoff of SP 800-90B, section 4.4.2, for a window
+/// of 512 byte samples at the assumed entropy and a false positive rate of
+/// 2^-20. A generator with close to 8 bits of entropy per byte, as a working
+/// one has, essentially never reaches it.
+const PROPORTION_CUTOFF: usize = 62;
+
+/// Draws a sample from `random_generator` and checks it for the failures of a
+/// catastrophically broken generator.
+///
+/// Two samples are drawn by separate calls. The check fails if either is all
+/// zero or all one bits; if they are identical, as they are from a generator
+/// that restarts its output on every call; if any byte repeats too many
+/// times in a row; or if the first byte appears too often in the combined
+/// window.
+///
+/// This is a tripwire, not a guarantee: a generator that is predictable but
+/// well mixed, such as a seeded PRNG, passes.
+pub(crate) fn check_rng_health(random_generator: &mut impl RandomNumberGenerator) -> Result<(), SSKRError> {
+    let mut sample = [0u8; 2 * SAMPLE_LEN];
+    let (first, second) = sample.split_at_mut(SAMPLE_LEN);
+    random_generator.fill_random_data(first);
+    random_generator.fill_random_data(second);
+
+    if [&*first, &*second].iter().any(|half| half.iter().all(|&b| b == 0x00) || half.iter().all(|&b| b == 0xff)) {
+        return Err(SSKRError::WeakRandomness { test: "constant output" });
+    }
+    if first == second {
+        return Err(SSKRError::WeakRandomness { test: "repeated output" });
+    }
+
+    let mut run = 1;
+    for pair in sample.windows(2) {
+        run = if pair[0] == pair[1] { run + 1 } else { 1 };
+        if run >= REPETITION_CUTOFF {
+            return Err(SSKRError::WeakRandomness { test: "repetition count" });
+        }
+    }
+
+    if sample.iter().filter(|&&b| b == sample[0]).count() >= PROPORTION_CUTOFF {
+        return Err(SSKRError::WeakRandomness { test: "adaptive proportion" });
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::CryptoRngAdapter;
+    use rand_chacha::ChaCha20Rng;
+    use rand_core::{CryptoRng, RngCore, SeedableRng};
+
+    /// A generator that returns `pattern` repeated, starting over from its
+    /// beginning on every call if `restart` is set.
+    struct PatternRandomNumberGenerator {
+        pattern: Vec<u8>,
+        restart: bool,
+        position: usize,
+    }
+
+    impl PatternRandomNumberGenerator {
+        fn new(pattern: &[u8], restart: bool) -> Self {
+            Self { pattern: pattern.to_vec(), restart, position: 0 }
+        }
+    }
+
+    impl RngCore for PatternRandomNumberGenerator {
+        fn next_u32(&mut self) -> u32 {
+            rand_core::impls::next_u32_via_fill(self)
+        }
+
+        fn next_u64(&mut self) -> u64 {
+            rand_core::impls::next_u64_via_fill(self)
+        }
+
+        fn fill_bytes(&mut self, dest: &mut [u8]) {
+            if self.restart {
+                self.position = 0;
+            }
+            for byte in dest {
+                *byte = self.pattern[self.position % self.pattern.len()];
+                self.position += 1;
+            }
+        }
+
+        fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand_core::Error> {
+            self.fill_bytes(dest);
+            Ok(())
+        }
+    }
+
+    // Testing purposes only!
+    impl CryptoRng for PatternRandomNumberGenerator {}
+
+    impl RandomNumberGenerator for PatternRandomNumberGenerator {}
+
+    fn check(pattern: &[u8], restart: bool) -> Result<(), SSKRError> {
+        check_rng_health(&mut PatternRandomNumberGenerator::new(pattern, restart))
+    }
+
+    #[test]
+    fn test_rng_health() {
+        assert!(check_rng_health(&mut CryptoRngAdapter::new(ChaCha20Rng::seed_from_u64(42))).is_ok());
+        assert!(check_rng_health(&mut CryptoRngAdapter::new(rand::rngs::OsRng)).is_ok());
+        assert!(check_rng_health(&mut bc_rand::SecureRandomNumberGenerator).is_ok());
+
+        // The counting generator the crate's tests use.
+        let counting: Vec<u8> = (0..=255u8).map(|i| i.wrapping_mul(17)).collect();
+        assert!(matches!(check(&counting, true), Err(SSKRError::WeakRandomness { test: "repeated output" })));
+
+        assert!(matches!(check(&[0x00], false), Err(SSKRError::WeakRandomness { test: "constant output" })));
+        assert!(matches!(check(&[0xff], false), Err(SSKRError::WeakRandomness { test: "constant output" })));
+        let mut runs: Vec<u8> = (0..251u8).map(|i| i.wrapping_mul(17)).collect();
+        runs[100..100 + REPETITION_CUTOFF].fill(0x42);
+        assert!(matches!(check(&runs, false), Err(SSKRError::WeakRandomness { test: "repetition count" })));
+        assert!(matches!(check(&[0x42, 0x01, 0x42, 0x02, 0x42, 0x03], false), Err(SSKRError::WeakRandomness { test: "adaptive proportion" })));
+    }
+}
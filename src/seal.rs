@@ -0,0 +1,114 @@
+use bc_crypto::hash::{crc32_data, CRC32_SIZE};
+
+use crate::{SSKRError, Secret, sskr_combine};
+
+/// The number of bytes a seal adds to a serialized share.
+pub const SEAL_SIZE_BYTES: usize = CRC32_SIZE;
+
+/// Seals a serialized SSKR share by appending a CRC-32 of its bytes.
+///
+/// The checksum is the standard CRC-32 (as used by zlib and PNG) of the whole
+/// share, header and payload, appended in big-endian byte order. It lets a
+/// damaged share be identified on its own, rather than surfacing years later
+/// as a failed recovery with no indication of which share is at fault.
+///
+/// **A seal detects accidents, not tampering.** Anyone who can alter a share
+/// can recompute its CRC, so a valid seal says nothing about where a share
+/// came from.
+///
+/// Sealed shares are not SSKR shares: they must be unsealed with
+/// [`unseal_share`] or combined with [`sskr_combine_sealed`].
+pub fn seal_share(share: &[u8]) -> Vec<u8> {
+    let mut result = Vec::with_capacity(share.len() + SEAL_SIZE_BYTES);
+    result.extend_from_slice(share);
+    result.extend_from_slice(&crc32_data(share));
+    result
+}
+
+/// Checks the seal on a share sealed by [`seal_share`] and returns the share
+/// without it.
+///
+/// # Errors
+///
+/// Returns `SSKRError::ShareChecksumInvalid` if the share is too short to be
+/// sealed or its CRC does not match.
+pub fn unseal_share(sealed: &[u8]) -> Result<Vec<u8>, SSKRError> {
+    unseal_share_at(sealed, None).map(<[u8]>::to_vec)
+}
+
+/// Unseals the share at position `share_index` of the caller's input, if it
+/// has one, which is used only to report errors.
+fn unseal_share_at(sealed: &[u8], share_index: Option<usize>) -> Result<&[u8], SSKRError> {
+    if sealed.len() < SEAL_SIZE_BYTES {
+        return Err(SSKRError::ShareChecksumInvalid { share_index });
+    }
+    let (share, checksum) = sealed.split_at(sealed.len() - SEAL_SIZE_BYTES);
+    if crc32_data(share) != checksum {
+        return Err(SSKRError::ShareChecksumInvalid { share_index });
+    }
+    Ok(share)
+}
+
+/// Combines shares sealed by [`seal_share`] into a `Secret`.
+///
+/// Every seal is checked before any share is parsed, so a damaged share is
+/// reported by its position in `shares` instead of causing a failed recovery.
+///
+/// # Errors
+///
+/// Returns `SSKRError::ShareChecksumInvalid` with the index of the first
+/// share whose seal doesn't match, or any error `sskr_combine` returns for
+/// the unsealed shares.
+pub fn sskr_combine_sealed<T>(shares: &[T]) -> Result<Secret, SSKRError>
+where
+    T: AsRef<[u8]>
+{
+    let unsealed = shares
+        .iter()
+        .enumerate()
+        .map(|(share_index, share)| unseal_share_at(share.as_ref(), Some(share_index)))
+        .collect::<Result<Vec<_>, _>>()?;
+    sskr_combine(&unsealed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{GroupSpec, Spec, sskr_generate_using, test_support};
+    
+
+    #[test]
+    fn test_seal_round_trip() {
+        let mut rng = bc_rand::make_fake_random_number_generator();
+        let secret = test_support::secret();
+        let spec = Spec::new(1, vec![GroupSpec::new(2, 3).unwrap()]).unwrap();
+        let shares = sskr_generate_using(&spec, &secret, &mut rng).unwrap();
+        let sealed: Vec<Vec<u8>> = shares[0].iter().map(|share| seal_share(share)).collect();
+
+        for (share, sealed) in shares[0].iter().zip(&sealed) {
+            assert_eq!(sealed.len(), share.len() + SEAL_SIZE_BYTES);
+            assert_eq!(&unseal_share(sealed).unwrap(), share);
+        }
+        assert_eq!(sskr_combine_sealed(&sealed[1..]).unwrap(), secret);
+
+        // A single flipped bit anywhere is caught, and blamed on its share.
+        for i in 0..sealed[2].len() {
+            let mut damaged = sealed.clone();
+            damaged[2][i] ^= 0x04;
+            let error = unseal_share(&damaged[2]).unwrap_err();
+            assert!(matches!(error, SSKRError::ShareChecksumInvalid { share_index: None }));
+            assert_eq!(error.to_string(), "SSKR share is corrupt: its seal does not match its contents");
+            let error = sskr_combine_sealed(&damaged).unwrap_err();
+            assert!(matches!(error, SSKRError::ShareChecksumInvalid { share_index: Some(2) }));
+            assert_eq!(error.to_string(), "SSKR share at index 2 is corrupt: its seal does not match its contents");
+        }
+        assert!(matches!(unseal_share(&[0x00, 0x01, 0x02]), Err(SSKRError::ShareChecksumInvalid { share_index: None })));
+    }
+
+    #[test]
+    fn test_seal_vector() {
+        // The seal is a big-endian CRC-32 of the share bytes.
+        assert_eq!(seal_share(b"123456789"), b"123456789\xcb\xf4\x39\x26");
+        assert_eq!(seal_share(&[]), [0u8; 4]);
+    }
+}
@@ -1,7 +1,9 @@
+use zeroize::{Zeroize, Zeroizing};
+
 use crate::{SSKRError, MIN_SECRET_LEN, MAX_SECRET_LEN};
 
 /// A secret to be split into shares.
-#[derive(Clone, Debug, Eq, PartialEq)]
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
 pub struct Secret(Vec<u8>);
 
 impl Secret {
@@ -20,17 +22,39 @@ impl Secret {
         T: AsRef<[u8]>,
     {
         let data = data.as_ref();
-        let len = data.len();
+        Self::validate_len(data.len())?;
+        Ok(Self(data.to_vec()))
+    }
+
+    /// Creates a new `Secret` that takes ownership of `data` rather than
+    /// copying it. If `data` is not a valid secret it is zeroized before the
+    /// error is returned.
+    pub(crate) fn from_vec(mut data: Vec<u8>) -> Result<Self, SSKRError> {
+        if let Err(error) = Self::validate_len(data.len()) {
+            data.zeroize();
+            return Err(error);
+        }
+        Ok(Self(data))
+    }
+
+    /// Consumes the secret, returning its bytes in a buffer that is zeroized
+    /// when dropped.
+    pub(crate) fn into_zeroizing(self) -> Zeroizing<Vec<u8>> {
+        Zeroizing::new(self.0)
+    }
+
+    /// Checks that `len` is a valid secret length without allocating.
+    pub(crate) fn validate_len(len: usize) -> Result<(), SSKRError> {
         if len < MIN_SECRET_LEN {
-            return Err(SSKRError::SecretTooShort);
+            return Err(SSKRError::SecretTooShort { len, min: MIN_SECRET_LEN });
         }
         if len > MAX_SECRET_LEN {
-            return Err(SSKRError::SecretTooLong);
+            return Err(SSKRError::SecretTooLong { len, max: MAX_SECRET_LEN });
         }
         if len & 1 != 0 {
-            return Err(SSKRError::SecretLengthNotEven);
+            return Err(SSKRError::SecretLengthNotEven { len });
         }
-        Ok(Self(data.to_vec()))
+        Ok(())
     }
 
     /// Returns the length of the secret.
@@ -0,0 +1,84 @@
+use bc_crypto::hash::hmac_sha256;
+use bc_rand::RandomNumberGenerator;
+use rand_core::{CryptoRng, RngCore};
+use zeroize::Zeroizing;
+
+/// The size of the seed of a [`SeededRandomNumberGenerator`].
+pub(crate) const SEED_SIZE_BYTES: usize = 32;
+
+/// A random number generator that expands a 32-byte seed into the stream of
+/// blocks `HMAC-SHA256(seed, i)` for `i = 0, 1, 2, ...`, with `i` encoded as
+/// a 64-bit big-endian integer.
+pub(crate) struct SeededRandomNumberGenerator {
+    key: Zeroizing<[u8; SEED_SIZE_BYTES]>,
+    counter: u64,
+    block: Zeroizing<[u8; 32]>,
+    used: usize,
+}
+
+impl SeededRandomNumberGenerator {
+    pub(crate) fn new(seed: Zeroizing<[u8; SEED_SIZE_BYTES]>) -> Self {
+        Self { key: seed, counter: 0, block: Zeroizing::new([0; 32]), used: 32 }
+    }
+
+    /// Returns a generator seeded with a seed drawn from `random_generator`.
+    pub(crate) fn from_rng(random_generator: &mut impl RandomNumberGenerator) -> Self {
+        let mut seed = Zeroizing::new([0u8; SEED_SIZE_BYTES]);
+        random_generator.fill_random_data(&mut *seed);
+        Self::new(seed)
+    }
+}
+
+impl RngCore for SeededRandomNumberGenerator {
+    fn next_u32(&mut self) -> u32 {
+        rand_core::impls::next_u32_via_fill(self)
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        rand_core::impls::next_u64_via_fill(self)
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        for byte in dest {
+            if self.used == self.block.len() {
+                *self.block = hmac_sha256(self.key.as_slice(), self.counter.to_be_bytes());
+                self.counter += 1;
+                self.used = 0;
+            }
+            *byte = self.block[self.used];
+            self.used += 1;
+        }
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand_core::Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
+
+impl CryptoRng for SeededRandomNumberGenerator {}
+
+impl RandomNumberGenerator for SeededRandomNumberGenerator {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_seeded_stream() {
+        let seed = [0x5a; SEED_SIZE_BYTES];
+        let mut rng = SeededRandomNumberGenerator::new(Zeroizing::new(seed));
+        let data = rng.random_data(80);
+        assert_eq!(data[..32], hmac_sha256(seed, 0u64.to_be_bytes()));
+        assert_eq!(data[32..64], hmac_sha256(seed, 1u64.to_be_bytes()));
+        assert_eq!(data[64..], hmac_sha256(seed, 2u64.to_be_bytes())[..16]);
+
+        // Requests of any size continue the same stream.
+        let mut rng = SeededRandomNumberGenerator::new(Zeroizing::new(seed));
+        let mut pieces = vec![0u8; 80];
+        for chunk in pieces.chunks_mut(7) {
+            rng.fill_random_data(chunk);
+        }
+        assert_eq!(pieces, data);
+    }
+}
@@ -0,0 +1,272 @@
+use std::collections::BTreeMap;
+
+use crate::{
+    ShareVersion,
+    encoding::{check_share_matches, deserialize_share},
+    share::SSKRShareRef,
+};
+
+/// Sorts a pile of shares by the split they came from, without attempting
+/// any recovery.
+///
+/// Shares in the same format with the same identifier are taken to be of the
+/// same session, so sessions are keyed by format version and identifier:
+/// shares of two formats are never of one split, even where their
+/// identifiers are equal. The first share of a session establishes its
+/// metadata; a later share with the same key but different metadata is
+/// listed among the session's mismatched shares.
+///
+/// Sorting never fails, so no `Result` is returned: a share that cannot be
+/// parsed is not an error in a pile being organized, and its position is
+/// listed among the [`unparseable_shares`](ShareSessions::unparseable_shares)
+/// instead.
+///
+/// Only share metadata is read, and the summary holds only positions in
+/// `shares`; no share value is copied.
+pub fn group_shares_by_session<T>(shares: &[T]) -> ShareSessions
+where
+    T: AsRef<[u8]>
+{
+    let mut sessions: BTreeMap<(ShareVersion, u32), (SSKRShareRef<'_>, SessionSummary)> = BTreeMap::new();
+    let mut unparseable_shares = Vec::new();
+
+    for (share_index, share) in shares.iter().enumerate() {
+        let Ok(share) = deserialize_share(share.as_ref(), share_index) else {
+            unparseable_shares.push(share_index);
+            continue;
+        };
+        let (first, session) = sessions.entry((share.version(), share.identifier())).or_insert_with(|| {
+            (share, SessionSummary {
+                version: share.version(),
+                identifier: share.identifier(),
+                group_threshold: share.group_threshold(),
+                group_count: share.group_count(),
+                groups: BTreeMap::new(),
+                share_indexes: Vec::new(),
+                mismatched_shares: Vec::new(),
+            })
+        });
+        session.share_indexes.push(share_index);
+        if check_share_matches(first, &share, share_index).is_err() {
+            session.mismatched_shares.push(share_index);
+            continue;
+        }
+        let group = session.groups.entry(share.group_index()).or_insert_with(|| {
+            GroupSummary { member_threshold: share.member_threshold(), members: BTreeMap::new() }
+        });
+        if share.member_threshold() != group.member_threshold {
+            session.mismatched_shares.push(share_index);
+            continue;
+        }
+        group.members.entry(share.member_index()).or_default().push(share_index);
+    }
+
+    let sessions = sessions
+        .into_iter()
+        .map(|(key, (_, session))| (key, session))
+        .collect();
+    ShareSessions { sessions, unparseable_shares }
+}
+
+/// A pile of shares sorted by session, as returned by
+/// [`group_shares_by_session`].
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct ShareSessions {
+    #[cfg_attr(feature = "serde", serde(serialize_with = "serialize_sessions"))]
+    sessions: BTreeMap<(ShareVersion, u32), SessionSummary>,
+    unparseable_shares: Vec<usize>,
+}
+
+impl ShareSessions {
+    /// Returns the sessions, by format version and identifier.
+    pub fn sessions(&self) -> &BTreeMap<(ShareVersion, u32), SessionSummary> {
+        &self.sessions
+    }
+
+    /// Returns the session of the shares in format `version` with
+    /// `identifier`, if there were any.
+    pub fn session(&self, version: ShareVersion, identifier: u32) -> Option<&SessionSummary> {
+        self.sessions.get(&(version, identifier))
+    }
+
+    /// Returns the positions of the shares that could not be parsed.
+    pub fn unparseable_shares(&self) -> &[usize] {
+        &self.unparseable_shares
+    }
+}
+
+/// The shares of one session, as returned by [`group_shares_by_session`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct SessionSummary {
+    version: ShareVersion,
+    identifier: u32,
+    group_threshold: usize,
+    group_count: usize,
+    groups: BTreeMap<usize, GroupSummary>,
+    share_indexes: Vec<usize>,
+    mismatched_shares: Vec<usize>,
+}
+
+impl SessionSummary {
+    /// Returns the format of the session's shares.
+    pub fn version(&self) -> ShareVersion {
+        self.version
+    }
+
+    /// Returns the identifier of the session's shares.
+    pub fn identifier(&self) -> u32 {
+        self.identifier
+    }
+
+    /// Returns the number of groups needed to recover the secret.
+    pub fn group_threshold(&self) -> usize {
+        self.group_threshold
+    }
+
+    /// Returns the number of groups in the split.
+    pub fn group_count(&self) -> usize {
+        self.group_count
+    }
+
+    /// Returns the groups that shares were found for, by group index.
+    pub fn groups(&self) -> &BTreeMap<usize, GroupSummary> {
+        &self.groups
+    }
+
+    /// Returns the positions of all the shares with the session's identifier,
+    /// including mismatched ones.
+    pub fn share_indexes(&self) -> &[usize] {
+        &self.share_indexes
+    }
+
+    /// Returns the positions of the shares with the session's identifier but
+    /// metadata that disagrees with its first share.
+    pub fn mismatched_shares(&self) -> &[usize] {
+        &self.mismatched_shares
+    }
+}
+
+/// Serializes the sessions as a sequence of summaries, which carry their own
+/// version and identifier, as a map keyed by both has no form in JSON.
+#[cfg(feature = "serde")]
+fn serialize_sessions<S>(sessions: &BTreeMap<(ShareVersion, u32), SessionSummary>, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: serde::Serializer,
+{
+    serializer.collect_seq(sessions.values())
+}
+
+/// The shares of one group of a session, as returned by
+/// [`group_shares_by_session`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct GroupSummary {
+    member_threshold: usize,
+    members: BTreeMap<usize, Vec<usize>>,
+}
+
+impl GroupSummary {
+    /// Returns the member threshold of the group.
+    pub fn member_threshold(&self) -> usize {
+        self.member_threshold
+    }
+
+    /// Returns the members that shares were found for, by member index, with
+    /// the positions of their shares. A member with more than one position
+    /// was provided more than once.
+    pub fn members(&self) -> &BTreeMap<usize, Vec<usize>> {
+        &self.members
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{FingerprintAlg, GenerateOptions, GroupSpec, Spec, sskr_generate_using, sskr_generate_with_identifier_using, sskr_generate_with_options_using, test_support};
+    
+
+    #[test]
+    fn test_group_shares_by_session() {
+        let secret = test_support::secret();
+        let mut rng = bc_rand::make_fake_random_number_generator();
+        let spec = Spec::new(2, vec![GroupSpec::new(2, 3).unwrap(), GroupSpec::new(3, 5).unwrap()]).unwrap();
+        let shares1 = sskr_generate_using(&spec, &secret, &mut rng).unwrap();
+        let spec = Spec::new(1, vec![GroupSpec::new(1, 2).unwrap()]).unwrap();
+        let shares2 = sskr_generate_using(&spec, &secret, &mut rng).unwrap();
+        let id1 = u16::from_be_bytes([shares1[0][0][0], shares1[0][0][1]]) as u32;
+        let id2 = u16::from_be_bytes([shares2[0][0][0], shares2[0][0][1]]) as u32;
+
+        // A share claiming to be of the first split, but with another group
+        // threshold.
+        let mut mismatched = shares1[1][3].clone();
+        mismatched[2] = 0x01;
+
+        let pile = vec![
+            shares1[1][4].clone(),
+            shares2[0][1].clone(),
+            vec![1, 2, 3],
+            shares1[0][0].clone(),
+            shares1[1][4].clone(),
+            mismatched,
+            shares1[1][2].clone(),
+        ];
+        let summary = group_shares_by_session(&pile);
+        assert_eq!(summary.unparseable_shares(), [2]);
+        assert_eq!(summary.sessions().keys().copied().collect::<Vec<_>>(), {
+            let mut keys = vec![(ShareVersion::V0, id1), (ShareVersion::V0, id2)];
+            keys.sort();
+            keys
+        });
+
+        let session = summary.session(ShareVersion::V0, id1).unwrap();
+        assert_eq!((session.version(), session.identifier(), session.group_threshold(), session.group_count()), (ShareVersion::V0, id1, 2, 2));
+        assert_eq!(session.share_indexes(), [0, 3, 4, 5, 6]);
+        assert_eq!(session.mismatched_shares(), [5]);
+        assert_eq!(session.groups()[&0].member_threshold(), 2);
+        assert_eq!(session.groups()[&0].members(), &BTreeMap::from([(0, vec![3])]));
+        assert_eq!(session.groups()[&1].member_threshold(), 3);
+        assert_eq!(session.groups()[&1].members(), &BTreeMap::from([(2, vec![6]), (4, vec![0, 4])]));
+
+        let session = summary.session(ShareVersion::V0, id2).unwrap();
+        assert_eq!((session.group_threshold(), session.group_count()), (1, 1));
+        assert_eq!(session.groups()[&0].members(), &BTreeMap::from([(1, vec![1])]));
+
+        assert_eq!(group_shares_by_session::<Vec<u8>>(&[]), ShareSessions::default());
+    }
+
+    #[test]
+    fn test_sessions_keyed_by_version() {
+        let secret = test_support::secret();
+        let spec = Spec::new(1, vec![GroupSpec::new(2, 3).unwrap()]).unwrap();
+        let mut rng = bc_rand::make_fake_random_number_generator();
+        let standard = sskr_generate_with_identifier_using(&spec, &secret, 0x1234, &mut rng).unwrap();
+        let options = GenerateOptions::new().with_identifier(0x1234).with_embedded_fingerprint(FingerprintAlg::Sha256Trunc4);
+        let fingerprinted = sskr_generate_with_options_using(&spec, &secret, &options, &mut rng).unwrap();
+
+        // Shares of two formats with one identifier are two sessions, each
+        // with its own member 0.
+        let pile = [&standard[0][0][..], fingerprinted[0][0].share(), &standard[0][1][..]];
+        let summary = group_shares_by_session(&pile);
+        assert_eq!(summary.sessions().len(), 2);
+        let session = summary.session(ShareVersion::V0, 0x1234).unwrap();
+        assert_eq!((session.share_indexes(), session.mismatched_shares()), (&[0, 2][..], &[][..]));
+        let session = summary.session(ShareVersion::V3, 0x1234).unwrap();
+        assert_eq!((session.share_indexes(), session.mismatched_shares()), (&[1][..], &[][..]));
+        assert_eq!(session.groups()[&0].members(), &BTreeMap::from([(0, vec![1])]));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_sessions_serde() {
+        let secret = test_support::secret();
+        let spec = Spec::new(1, vec![GroupSpec::new(1, 1).unwrap()]).unwrap();
+        let shares = sskr_generate_with_identifier_using(&spec, &secret, 0x1234, &mut bc_rand::make_fake_random_number_generator()).unwrap();
+        let json = serde_json::to_string(&group_shares_by_session(&[&shares[0][0], &vec![1, 2, 3]])).unwrap();
+        assert_eq!(
+            json,
+            r#"{"sessions":[{"version":"V0","identifier":4660,"group_threshold":1,"group_count":1,"groups":{"0":{"member_threshold":1,"members":{"0":[0]}}},"share_indexes":[0],"mismatched_shares":[]}],"unparseable_shares":[1]}"#
+        );
+    }
+}
@@ -0,0 +1,386 @@
+//! Shamir's Secret Sharing over GF(2^8) for splits larger than `bc-shamir`
+//! allows.
+//!
+//! This follows the construction of `bc_shamir::split_secret` and
+//! `bc_shamir::recover_secret` exactly: the same field, the same placement of
+//! the secret and its digest, and the same order of random draws. For up to
+//! `MAX_SHARE_COUNT` shares the two produce identical output. The only
+//! difference is that any x coordinate not reserved for the secret or the
+//! digest may be used, so a split can have up to `MAX_EXTENDED_SHARE_COUNT`
+//! shares.
+//!
+//! **This module has not had the independent review `bc-shamir` has, and
+//! needs a dedicated security review before it is released.** Until then it
+//! is confined to what `bc-shamir` cannot do, and every other split and
+//! recovery goes through `bc-shamir`. Its only callers are:
+//!
+//! - splitting and recovering the extended format (`ShareVersion::V1`), in
+//!   `encoding::split_secret`, `encoding::recover_secret` and
+//!   `split_members_into`, because `bc-shamir` allows at most
+//!   `MAX_SHARE_COUNT` shares;
+//! - issuing further shares of a group, in `sskr_extend_group`, because
+//!   `bc-shamir` cannot evaluate a split at a new x coordinate;
+//! - splitting a group around fixed shares, in
+//!   `sskr_generate_constrained_using`, for the groups that have any,
+//!   because `bc-shamir` draws every point of the polynomial at random.
+//!
+//! The tests below check each of these against `bc-shamir` wherever their
+//! results can be compared, and must keep passing: a difference means this
+//! module has drifted from the reviewed implementation. The better fix is to
+//! add larger splits and fixed points to `bc-shamir` and depend on that.
+
+use bc_crypto::hash::hmac_sha256;
+use bc_rand::RandomNumberGenerator;
+use bc_shamir::Error;
+use zeroize::Zeroizing;
+
+use crate::{MAX_EXTENDED_SHARE_COUNT, MAX_SECRET_LEN, MIN_SECRET_LEN};
+
+const SECRET_INDEX: u8 = 255;
+const DIGEST_INDEX: u8 = 254;
+
+/// Multiplies two elements of GF(2^8) modulo x^8 + x^4 + x^3 + x + 1 without
+/// branching on either operand.
+fn mul(mut a: u8, mut b: u8) -> u8 {
+    let mut r = 0u8;
+    for _ in 0..8 {
+        r ^= a & 0u8.wrapping_sub(b & 1);
+        let carry = 0u8.wrapping_sub(a >> 7);
+        a = (a << 1) ^ (0x1b & carry);
+        b >>= 1;
+    }
+    r
+}
+
+/// Returns the multiplicative inverse of `a`, computed as a^254 so that it
+/// takes the same time for every input. The inverse of zero is zero.
+fn inv(a: u8) -> u8 {
+    let a2 = mul(a, a);
+    let a3 = mul(a2, a);
+    let a6 = mul(a3, a3);
+    let a12 = mul(a6, a6);
+    let a15 = mul(a12, a3);
+    let a30 = mul(a15, a15);
+    let a60 = mul(a30, a30);
+    let a120 = mul(a60, a60);
+    let a126 = mul(a120, a6);
+    let a252 = mul(a126, a126);
+    mul(a252, a2)
+}
+
+/// Evaluates at `x` the polynomial passing through the points `(xs[i],
+/// ys[i])`, for every byte position of the `ys`.
+fn interpolate<T>(xs: &[u8], ys: &[T], len: usize, x: u8) -> Zeroizing<Vec<u8>>
+where
+    T: AsRef<[u8]>
+{
+    let mut result = Zeroizing::new(vec![0u8; len]);
+    interpolate_into(xs, ys, x, &mut result);
+    result
+}
+
+/// Evaluates the polynomial as `interpolate` does, writing the result to
+/// `result`.
+fn interpolate_into<T>(xs: &[u8], ys: &[T], x: u8, result: &mut [u8])
+where
+    T: AsRef<[u8]>
+{
+    result.fill(0);
+    for (i, &xi) in xs.iter().enumerate() {
+        let mut numerator = 1u8;
+        let mut denominator = 1u8;
+        for (j, &xj) in xs.iter().enumerate() {
+            if i != j {
+                numerator = mul(numerator, x ^ xj);
+                denominator = mul(denominator, xi ^ xj);
+            }
+        }
+        let basis = mul(numerator, inv(denominator));
+        for (r, &y) in result.iter_mut().zip(ys[i].as_ref()) {
+            *r ^= mul(basis, y);
+        }
+    }
+}
+
+fn create_digest(random_data: &[u8], shared_secret: &[u8]) -> Zeroizing<[u8; 32]> {
+    Zeroizing::new(hmac_sha256(random_data, shared_secret))
+}
+
+fn validate_parameters(threshold: usize, share_count: usize, secret_length: usize) -> Result<(), Error> {
+    if share_count > MAX_EXTENDED_SHARE_COUNT {
+        return Err(Error::TooManyShares);
+    } else if threshold < 1 || threshold > share_count {
+        return Err(Error::InvalidThreshold);
+    } else if secret_length > MAX_SECRET_LEN {
+        return Err(Error::SecretTooLong);
+    } else if secret_length < MIN_SECRET_LEN {
+        return Err(Error::SecretTooShort);
+    } else if secret_length & 1 != 0 {
+        return Err(Error::SecretNotEvenLen);
+    }
+    Ok(())
+}
+
+/// Splits `secret` into `share_count` shares, any `threshold` of which can
+/// recover it. Share `i` is the polynomial evaluated at x = `i`.
+pub(crate) fn split_secret(
+    threshold: usize,
+    share_count: usize,
+    secret: &[u8],
+    random_generator: &mut impl RandomNumberGenerator
+) -> Result<Vec<Vec<u8>>, Error> {
+    split_secret_with_fixed(threshold, share_count, secret, &[], random_generator)
+}
+
+/// Splits `secret` as `split_secret` does, but with the share at each index
+/// of `fixed` given the value it is paired with.
+///
+/// A polynomial of a threshold-`t` split is determined by `t` points, two of
+/// which are the secret and its digest, so up to `t - 2` shares can be fixed:
+/// they take the place of the shares otherwise chosen at random. The indexes
+/// of `fixed` must be distinct, less than `share_count`, and the values as
+/// long as `secret`. With no fixed shares this is `split_secret`.
+pub(crate) fn split_secret_with_fixed(
+    threshold: usize,
+    share_count: usize,
+    secret: &[u8],
+    fixed: &[(usize, &[u8])],
+    random_generator: &mut impl RandomNumberGenerator
+) -> Result<Vec<Vec<u8>>, Error> {
+    let mut result: Vec<Vec<u8>> = (0..share_count).map(|_| Vec::with_capacity(secret.len())).collect();
+    split_secret_into(threshold, secret, fixed, &mut result, random_generator)?;
+    Ok(result)
+}
+
+/// Splits `secret` as `split_secret_with_fixed` does into as many shares as
+/// there are `outputs`, appending share `i` to `outputs[i]`. This lets a
+/// share be written after its header without being copied.
+pub(crate) fn split_secret_into(
+    threshold: usize,
+    secret: &[u8],
+    fixed: &[(usize, &[u8])],
+    outputs: &mut [Vec<u8>],
+    random_generator: &mut impl RandomNumberGenerator
+) -> Result<(), Error> {
+    let len = secret.len();
+    validate_parameters(threshold, outputs.len(), len)?;
+    if fixed.len() > threshold.saturating_sub(2) {
+        return Err(Error::InvalidThreshold);
+    }
+
+    if threshold == 1 {
+        outputs.iter_mut().for_each(|output| output.extend_from_slice(secret));
+        return Ok(());
+    }
+
+    // The points that determine the polynomial, in one buffer: the chosen
+    // shares, then the digest, then the secret.
+    let mut xs = Vec::with_capacity(threshold);
+    let mut points = Zeroizing::new(vec![0u8; threshold * len]);
+    let point = |xs: &[u8]| xs.len() * len..(xs.len() + 1) * len;
+
+    for &(index, value) in fixed {
+        points[point(&xs)].copy_from_slice(value);
+        xs.push(index as u8);
+    }
+    for index in 0..outputs.len() {
+        if xs.len() == threshold - 2 {
+            break;
+        }
+        if fixed.iter().any(|&(fixed_index, _)| fixed_index == index) {
+            continue;
+        }
+        random_generator.fill_random_data(&mut points[point(&xs)]);
+        xs.push(index as u8);
+    }
+    let chosen = xs.len();
+
+    let digest = &mut points[point(&xs)];
+    random_generator.fill_random_data(&mut digest[4..]);
+    let d = create_digest(&digest[4..], secret);
+    digest[..4].copy_from_slice(&d[..4]);
+    xs.push(DIGEST_INDEX);
+
+    points[point(&xs)].copy_from_slice(secret);
+    xs.push(SECRET_INDEX);
+
+    let ys: Vec<&[u8]> = points.chunks(len).collect();
+    for (index, output) in outputs.iter_mut().enumerate() {
+        match xs[..chosen].iter().position(|&x| x as usize == index) {
+            Some(point) => output.extend_from_slice(ys[point]),
+            None => {
+                let start = output.len();
+                output.resize(start + len, 0);
+                interpolate_into(&xs, &ys, index as u8, &mut output[start..]);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Recovers the secret from the shares at x = `indexes`, checking it against
+/// the digest embedded in the split.
+pub(crate) fn recover_secret<T>(indexes: &[usize], shares: &[T]) -> Result<Vec<u8>, Error>
+where
+    T: AsRef<[u8]>
+{
+    let threshold = shares.len();
+    if threshold == 0 || indexes.len() != threshold {
+        return Err(Error::InvalidThreshold);
+    }
+    let share_length = shares[0].as_ref().len();
+    validate_parameters(threshold, threshold, share_length)?;
+
+    if !shares.iter().all(|share| share.as_ref().len() == share_length) {
+        return Err(Error::SharesUnequalLength);
+    }
+    if indexes.iter().any(|&index| index >= MAX_EXTENDED_SHARE_COUNT) {
+        return Err(Error::InterpolationFailure);
+    }
+
+    if threshold == 1 {
+        return Ok(shares[0].as_ref().to_vec());
+    }
+
+    let xs: Vec<u8> = indexes.iter().map(|&index| index as u8).collect();
+    let digest = interpolate(&xs, shares, share_length, DIGEST_INDEX);
+    let secret = interpolate(&xs, shares, share_length, SECRET_INDEX);
+    let verify = create_digest(&digest[4..], &secret);
+
+    let mut valid = true;
+    for i in 0..4 {
+        valid &= digest[i] == verify[i];
+    }
+    if !valid {
+        return Err(Error::ChecksumFailure);
+    }
+
+    Ok(secret.to_vec())
+}
+
+/// Evaluates the polynomial through the shares at x = `indexes` at each of
+/// `new_indexes`, giving further shares of the same split. The shares are
+/// first checked against the digest embedded in the split, as
+/// `recover_secret` checks them.
+pub(crate) fn extend_shares<T>(indexes: &[usize], shares: &[T], new_indexes: &[usize]) -> Result<Vec<Vec<u8>>, Error>
+where
+    T: AsRef<[u8]>
+{
+    Zeroizing::new(recover_secret(indexes, shares)?);
+    if new_indexes.iter().any(|&index| index >= MAX_EXTENDED_SHARE_COUNT) {
+        return Err(Error::InterpolationFailure);
+    }
+    let share_length = shares[0].as_ref().len();
+    let xs: Vec<u8> = indexes.iter().map(|&index| index as u8).collect();
+    Ok(new_indexes.iter().map(|&index| interpolate(&xs, shares, share_length, index as u8).to_vec()).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_field_arithmetic() {
+        assert_eq!(mul(0x57, 0x83), 0xc1);
+        assert_eq!(mul(0x57, 0x13), 0xfe);
+        assert_eq!(inv(0), 0);
+        for a in 1..=255u8 {
+            assert_eq!(mul(a, inv(a)), 1);
+        }
+    }
+
+    #[test]
+    fn test_matches_bc_shamir() {
+        let secret = b"my secret belongs to me.";
+        for share_count in 1..=bc_shamir::MAX_SHARE_COUNT {
+            for threshold in 1..=share_count {
+                let mut rng = bc_rand::make_fake_random_number_generator();
+                let ours = split_secret(threshold, share_count, secret, &mut rng).unwrap();
+                let mut rng = bc_rand::make_fake_random_number_generator();
+                let theirs = bc_shamir::split_secret(threshold, share_count, secret, &mut rng).unwrap();
+                assert_eq!(ours, theirs);
+
+                let indexes: Vec<usize> = (share_count - threshold..share_count).collect();
+                let quorum = &theirs[share_count - threshold..];
+                assert_eq!(recover_secret(&indexes, quorum).unwrap(), secret);
+            }
+        }
+    }
+
+    #[test]
+    fn test_recovers_as_bc_shamir() {
+        // Every quorum of a split by `bc-shamir` is recovered, and corrupt
+        // ones are refused, exactly as `bc-shamir` recovers them.
+        let secret = b"my secret belongs to me.";
+        for share_count in 1..=bc_shamir::MAX_SHARE_COUNT {
+            for threshold in 1..=share_count {
+                let shares = bc_shamir::split_secret(threshold, share_count, secret, &mut bc_rand::make_fake_random_number_generator()).unwrap();
+                for start in 0..=share_count - threshold {
+                    let indexes: Vec<usize> = (start..start + threshold).collect();
+                    let quorum = &shares[start..start + threshold];
+                    assert_eq!(recover_secret(&indexes, quorum).unwrap(), bc_shamir::recover_secret(&indexes, quorum).unwrap());
+
+                    let mut corrupt = quorum.to_vec();
+                    corrupt[0][1] ^= 0x10;
+                    let ours = recover_secret(&indexes, &corrupt);
+                    let theirs = bc_shamir::recover_secret(&indexes, &corrupt);
+                    assert_eq!(ours.is_ok(), theirs.is_ok());
+                    if let (Ok(ours), Ok(theirs)) = (ours, theirs) {
+                        assert_eq!(ours, theirs);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_extend_matches_bc_shamir() {
+        // The further shares of a split by `bc-shamir` are the ones it would
+        // have made at those indexes.
+        let secret = b"my secret belongs to me.";
+        for threshold in 1..=bc_shamir::MAX_SHARE_COUNT {
+            let shares = bc_shamir::split_secret(threshold, bc_shamir::MAX_SHARE_COUNT, secret, &mut bc_rand::make_fake_random_number_generator()).unwrap();
+            let indexes: Vec<usize> = (0..threshold).collect();
+            let new_indexes: Vec<usize> = (threshold..bc_shamir::MAX_SHARE_COUNT).collect();
+            assert_eq!(extend_shares(&indexes, &shares[..threshold], &new_indexes).unwrap(), shares[threshold..]);
+        }
+    }
+
+    #[test]
+    fn test_fixed_shares_recover_with_bc_shamir() {
+        // A split around fixed shares holds them, and every quorum of it is
+        // recovered by `bc-shamir`.
+        let secret = b"my secret belongs to me.";
+        let fixed_value = [0x5au8; 24];
+        for share_count in 3..=bc_shamir::MAX_SHARE_COUNT {
+            for threshold in 3..=share_count {
+                let fixed: Vec<(usize, &[u8])> = (0..threshold - 2).map(|i| (share_count - 1 - i, &fixed_value[..])).collect();
+                let shares = split_secret_with_fixed(threshold, share_count, secret, &fixed, &mut bc_rand::make_fake_random_number_generator()).unwrap();
+                for &(index, value) in &fixed {
+                    assert_eq!(shares[index], value);
+                }
+                for start in 0..=share_count - threshold {
+                    let indexes: Vec<usize> = (start..start + threshold).collect();
+                    assert_eq!(bc_shamir::recover_secret(&indexes, &shares[start..start + threshold]).unwrap(), secret);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_large_split() {
+        let mut rng = bc_rand::make_fake_random_number_generator();
+        let secret = rng.random_data(32);
+        let shares = split_secret(30, MAX_EXTENDED_SHARE_COUNT, &secret, &mut rng).unwrap();
+        assert!(matches!(split_secret(2, MAX_EXTENDED_SHARE_COUNT + 1, &secret, &mut rng), Err(Error::TooManyShares)));
+
+        let indexes: Vec<usize> = (200..230).collect();
+        assert_eq!(recover_secret(&indexes, &shares[200..230]).unwrap(), secret);
+
+        let mut corrupt = shares[200..230].to_vec();
+        corrupt[7][0] ^= 1;
+        assert!(matches!(recover_secret(&indexes, &corrupt), Err(Error::ChecksumFailure)));
+        assert!(matches!(recover_secret(&[254, 0], &shares[..2]), Err(Error::InterpolationFailure)));
+    }
+}
@@ -1,25 +1,89 @@
-use crate::Secret;
+use std::{cmp::Ordering, fmt, hash::{Hash, Hasher}};
 
-#[derive(Debug, Clone)]
+use zeroize::Zeroizing;
+
+use crate::{FINGERPRINT_SIZE_BYTES, ShareVersion, encoding::{serialize_metadata, serialize_share}};
+
+/// The value carried by a single share: one point of a Shamir split.
+///
+/// Unlike `Secret`, a share value is not subject to the rules for user
+/// secrets; its length is checked where shares are deserialized. The bytes are
+/// zeroized when the value is dropped.
+#[derive(Clone, PartialEq, Eq)]
+pub struct ShareValue(Zeroizing<Vec<u8>>);
+
+impl ShareValue {
+    /// Creates a new `ShareValue` holding `data`.
+    pub fn new(data: Vec<u8>) -> Self {
+        Self(Zeroizing::new(data))
+    }
+
+    /// Returns the length of the value.
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Returns `true` if the value is empty.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Returns a reference to the value's bytes.
+    pub fn data(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl Hash for ShareValue {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.data().hash(state);
+    }
+}
+
+impl AsRef<[u8]> for ShareValue {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+/// The value is redacted so that shares can be logged safely.
+impl fmt::Debug for ShareValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "ShareValue({} bytes, redacted)", self.len())
+    }
+}
+
+/// A single SSKR share: its metadata and its value.
+///
+/// A share is normally produced by splitting a secret, but one can also be
+/// made from its fields with [`new`](Self::new). Its fields are not checked
+/// until it is combined.
+#[derive(Clone)]
 pub struct SSKRShare {
-    identifier: u16,
+    identifier: u32,
     group_index: usize,
     group_threshold: usize,
     group_count: usize,
     member_index: usize,
     member_threshold: usize,
-    value: Secret,
+    app_nibble: u8,
+    version: ShareVersion,
+    fingerprint: [u8; FINGERPRINT_SIZE_BYTES],
+    value: ShareValue,
 }
 
 impl SSKRShare {
+    /// Creates a standard-format share with the given metadata and value.
+    ///
+    /// Group and member indexes are zero-based.
     pub fn new(
-        identifier: u16,
+        identifier: u32,
         group_index: usize,
         group_threshold: usize,
         group_count: usize,
         member_index: usize,
         member_threshold: usize,
-        value: Secret,
+        value: ShareValue,
     ) -> Self {
         Self {
             identifier,
@@ -28,35 +92,294 @@ impl SSKRShare {
             group_count,
             member_index,
             member_threshold,
+            app_nibble: 0,
+            version: ShareVersion::V0,
+            fingerprint: [0; FINGERPRINT_SIZE_BYTES],
             value,
         }
     }
 
-    pub fn identifier(&self) -> u16 {
+    /// Returns this share carrying the given application data in its reserved
+    /// bits. Only the low four bits of `app_nibble` are used.
+    pub fn with_app_nibble(mut self, app_nibble: u8) -> Self {
+        self.app_nibble = app_nibble & 0xf;
+        self
+    }
+
+    /// Returns this share serialized in the given format version.
+    pub fn with_version(mut self, version: ShareVersion) -> Self {
+        self.version = version;
+        self
+    }
+
+    /// Returns this share carrying the given fingerprint of the master
+    /// secret. Only the fingerprinted format (`ShareVersion::V3`) serializes
+    /// it.
+    pub fn with_fingerprint(mut self, fingerprint: [u8; FINGERPRINT_SIZE_BYTES]) -> Self {
+        self.fingerprint = fingerprint;
+        self
+    }
+
+    /// Returns the identifier of the split.
+    pub fn identifier(&self) -> u32 {
         self.identifier
     }
 
+    /// Returns the index of the share's group.
     pub fn group_index(&self) -> usize {
         self.group_index
     }
 
+    /// Returns the number of groups needed to recover the secret.
     pub fn group_threshold(&self) -> usize {
         self.group_threshold
     }
 
+    /// Returns the number of groups in the split.
     pub fn group_count(&self) -> usize {
         self.group_count
     }
 
+    /// Returns the index of the share within its group.
     pub fn member_index(&self) -> usize {
         self.member_index
     }
 
+    /// Returns the number of shares of its group needed to recover the group.
     pub fn member_threshold(&self) -> usize {
         self.member_threshold
     }
 
-    pub fn value(&self) -> &Secret {
+    /// Returns the application data carried in the share's reserved bits.
+    pub fn app_nibble(&self) -> u8 {
+        self.app_nibble
+    }
+
+    /// Returns the format the share is serialized in.
+    pub fn version(&self) -> ShareVersion {
+        self.version
+    }
+
+    /// Returns the fingerprint of the master secret the share carries, which
+    /// is zero unless set with `with_fingerprint`.
+    pub fn fingerprint(&self) -> [u8; FINGERPRINT_SIZE_BYTES] {
+        self.fingerprint
+    }
+
+    /// Returns the share's value.
+    pub fn value(&self) -> &ShareValue {
         &self.value
     }
+
+    /// Returns the share serialized as `sskr_generate` returns it: the
+    /// metadata header followed by the value.
+    pub fn serialize(&self) -> Vec<u8> {
+        serialize_share(self)
+    }
+
+    /// Returns a borrowed view of this share.
+    pub fn as_share_ref(&self) -> SSKRShareRef<'_> {
+        SSKRShareRef::new(
+            self.identifier,
+            self.group_index,
+            self.group_threshold,
+            self.group_count,
+            self.member_index,
+            self.member_threshold,
+            self.value.data(),
+        ).with_app_nibble(self.app_nibble).with_version(self.version).with_fingerprint(self.fingerprint)
+    }
+}
+
+/// Formats a share for display without revealing its value.
+impl fmt::Display for SSKRShare {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.as_share_ref(), f)
+    }
+}
+
+/// The value is redacted so that shares can be logged safely.
+impl fmt::Debug for SSKRShare {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.as_share_ref().debug_fmt("SSKRShare", f)
+    }
+}
+
+/// Two shares are equal if and only if their serialized bytes are identical.
+impl PartialEq for SSKRShare {
+    fn eq(&self, other: &Self) -> bool {
+        serialize_metadata(self) == serialize_metadata(other) && self.value == other.value
+    }
+}
+
+impl Eq for SSKRShare {}
+
+impl Hash for SSKRShare {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        serialize_metadata(self).hash(state);
+        self.value.hash(state);
+    }
+}
+
+/// Shares are ordered by identifier, then group index, then member index, so
+/// that sorting a mixed pile clusters the shares of each split together in
+/// canonical order. Shares that agree on all three are ordered by their
+/// serialized bytes.
+impl Ord for SSKRShare {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (self.identifier, self.group_index, self.member_index)
+            .cmp(&(other.identifier, other.group_index, other.member_index))
+            .then_with(|| serialize_metadata(self).cmp(&serialize_metadata(other)))
+            .then_with(|| self.value.data().cmp(other.value.data()))
+    }
+}
+
+impl PartialOrd for SSKRShare {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// A share deserialized in place, borrowing its value from the caller's
+/// serialized bytes instead of copying it.
+#[derive(Clone, Copy)]
+pub struct SSKRShareRef<'a> {
+    identifier: u32,
+    group_index: usize,
+    group_threshold: usize,
+    group_count: usize,
+    member_index: usize,
+    member_threshold: usize,
+    app_nibble: u8,
+    version: ShareVersion,
+    fingerprint: [u8; FINGERPRINT_SIZE_BYTES],
+    value: &'a [u8],
+}
+
+impl<'a> SSKRShareRef<'a> {
+    pub fn new(
+        identifier: u32,
+        group_index: usize,
+        group_threshold: usize,
+        group_count: usize,
+        member_index: usize,
+        member_threshold: usize,
+        value: &'a [u8],
+    ) -> Self {
+        Self {
+            identifier,
+            group_index,
+            group_threshold,
+            group_count,
+            member_index,
+            member_threshold,
+            app_nibble: 0,
+            version: ShareVersion::V0,
+            fingerprint: [0; FINGERPRINT_SIZE_BYTES],
+            value,
+        }
+    }
+
+    /// Returns this share carrying the given application data in its reserved
+    /// bits. Only the low four bits of `app_nibble` are used.
+    pub fn with_app_nibble(mut self, app_nibble: u8) -> Self {
+        self.app_nibble = app_nibble & 0xf;
+        self
+    }
+
+    /// Returns this share serialized in the given format version.
+    pub fn with_version(mut self, version: ShareVersion) -> Self {
+        self.version = version;
+        self
+    }
+
+    /// Returns this share carrying the given fingerprint of the master
+    /// secret. Only the fingerprinted format (`ShareVersion::V3`) serializes
+    /// it.
+    pub fn with_fingerprint(mut self, fingerprint: [u8; FINGERPRINT_SIZE_BYTES]) -> Self {
+        self.fingerprint = fingerprint;
+        self
+    }
+
+    pub fn identifier(&self) -> u32 {
+        self.identifier
+    }
+
+    pub fn group_index(&self) -> usize {
+        self.group_index
+    }
+
+    pub fn group_threshold(&self) -> usize {
+        self.group_threshold
+    }
+
+    pub fn group_count(&self) -> usize {
+        self.group_count
+    }
+
+    pub fn member_index(&self) -> usize {
+        self.member_index
+    }
+
+    pub fn member_threshold(&self) -> usize {
+        self.member_threshold
+    }
+
+    pub fn app_nibble(&self) -> u8 {
+        self.app_nibble
+    }
+
+    pub fn version(&self) -> ShareVersion {
+        self.version
+    }
+
+    pub fn fingerprint(&self) -> [u8; FINGERPRINT_SIZE_BYTES] {
+        self.fingerprint
+    }
+
+    pub fn value(&self) -> &'a [u8] {
+        self.value
+    }
+}
+
+impl SSKRShareRef<'_> {
+    fn debug_fmt(&self, name: &str, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct(name)
+            .field("identifier", &self.identifier)
+            .field("group_index", &self.group_index)
+            .field("group_threshold", &self.group_threshold)
+            .field("group_count", &self.group_count)
+            .field("member_index", &self.member_index)
+            .field("member_threshold", &self.member_threshold)
+            .field("app_nibble", &self.app_nibble)
+            .field("version", &self.version)
+            .field("value", &format_args!("<{} bytes redacted>", self.value.len()))
+            .finish()
+    }
+}
+
+/// Formats a share for display without revealing its value. Group and member
+/// numbers are shown one-based, as they would be to a user.
+impl fmt::Display for SSKRShareRef<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "SSKRShare(id: {:0width$x}, group {}/{} threshold {}, member {} threshold {}, {}-byte value)",
+            self.identifier,
+            self.group_index + 1,
+            self.group_count,
+            self.group_threshold,
+            self.member_index + 1,
+            self.member_threshold,
+            self.value.len(),
+            width = self.version.identifier_size_bytes() * 2
+        )
+    }
+}
+
+/// The value is redacted so that shares can be logged safely.
+impl fmt::Debug for SSKRShareRef<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.debug_fmt("SSKRShareRef", f)
+    }
 }
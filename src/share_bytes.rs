@@ -0,0 +1,184 @@
+use std::{fmt, str::FromStr};
+
+use crate::{SSKRError, encoding::validate_share};
+
+/// A serialized SSKR share.
+///
+/// A `ShareBytes` always holds at least `MIN_SERIALIZE_SIZE_BYTES` bytes with
+/// a valid share header, so it cannot be confused with an arbitrary byte
+/// blob. It displays as lowercase hex and parses from hex, ignoring any
+/// whitespace.
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct ShareBytes(Vec<u8>);
+
+impl ShareBytes {
+    /// Creates a new `ShareBytes` from a serialized share.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `data` is not a well-formed SSKR share.
+    pub fn new<T>(data: T) -> Result<Self, SSKRError>
+    where
+        T: Into<Vec<u8>>,
+    {
+        let data = data.into();
+        validate_share(&data)?;
+        Ok(Self(data))
+    }
+
+    pub(crate) fn from_validated(data: Vec<u8>) -> Self {
+        Self(data)
+    }
+
+    /// Returns the length of the serialized share.
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Returns `true` if the serialized share is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns a reference to the serialized share.
+    pub fn data(&self) -> &[u8] {
+        &self.0
+    }
+
+    /// Returns the serialized share as a vector of bytes.
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.0
+    }
+}
+
+impl AsRef<[u8]> for ShareBytes {
+    /// Returns a reference to the serialized share.
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl From<ShareBytes> for Vec<u8> {
+    fn from(share: ShareBytes) -> Self {
+        share.0
+    }
+}
+
+impl TryFrom<Vec<u8>> for ShareBytes {
+    type Error = SSKRError;
+
+    fn try_from(data: Vec<u8>) -> Result<Self, Self::Error> {
+        Self::new(data)
+    }
+}
+
+impl TryFrom<&[u8]> for ShareBytes {
+    type Error = SSKRError;
+
+    fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
+        Self::new(data)
+    }
+}
+
+/// Formats the share as lowercase hex.
+impl fmt::Display for ShareBytes {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&hex::encode(&self.0))
+    }
+}
+
+/// The share bytes are redacted so that shares can be logged safely.
+impl fmt::Debug for ShareBytes {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "ShareBytes({} bytes, redacted)", self.len())
+    }
+}
+
+/// Parses a share from hex, ignoring any whitespace.
+impl FromStr for ShareBytes {
+    type Err = SSKRError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let compact: String = s.chars().filter(|c| !c.is_whitespace()).collect();
+        let data = hex::decode(compact).map_err(|_| SSKRError::ShareHexInvalid)?;
+        Self::new(data)
+    }
+}
+
+/// Serializes the share as lowercase hex.
+#[cfg(feature = "serde")]
+impl serde::Serialize for ShareBytes {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.collect_str(self)
+    }
+}
+
+/// Deserializes the share from hex, rejecting it unless it is well formed.
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for ShareBytes {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{GroupSpec, Spec, sskr_combine, test_support, MIN_SERIALIZE_SIZE_BYTES};
+
+    #[test]
+    fn test_share_bytes_match_raw_shares() {
+        let secret = test_support::secret();
+        let spec = Spec::new(1, vec![GroupSpec::new(2, 3).unwrap()]).unwrap();
+        let shares = test_support::generate_share_bytes(&spec);
+        let raw_shares = test_support::generate(&spec);
+        for (share, raw_share) in shares.iter().flatten().zip(raw_shares.iter().flatten()) {
+            assert_eq!(share.data(), raw_share.as_slice());
+        }
+        assert_eq!(sskr_combine(&shares[0][1..]).unwrap(), secret);
+    }
+
+    #[test]
+    fn test_share_bytes_hex_round_trip() {
+        let shares = test_support::generate_share_bytes(&test_support::spec());
+        let share = &shares[0][0];
+        let text = share.to_string();
+        assert_eq!(text, hex::encode(share.data()));
+        assert_eq!(text, text.to_lowercase());
+        assert_eq!(&text.parse::<ShareBytes>().unwrap(), share);
+
+        // Whitespace anywhere is ignored, and uppercase is accepted.
+        let spaced: String = text
+            .to_uppercase()
+            .as_bytes()
+            .chunks(4)
+            .map(|chunk| std::str::from_utf8(chunk).unwrap())
+            .collect::<Vec<_>>()
+            .join(" \n");
+        assert_eq!(&format!("  {}\t", spaced).parse::<ShareBytes>().unwrap(), share);
+
+        assert!(!format!("{:?}", share).contains(&text));
+    }
+
+    #[test]
+    fn test_share_bytes_validation() {
+        let shares = test_support::generate_share_bytes(&test_support::spec());
+        let share = shares[0][0].data();
+
+        assert!(matches!("xyz".parse::<ShareBytes>(), Err(SSKRError::ShareHexInvalid)));
+        assert!(matches!("abc".parse::<ShareBytes>(), Err(SSKRError::ShareHexInvalid)));
+        assert!(matches!(ShareBytes::new(&share[..MIN_SERIALIZE_SIZE_BYTES - 1]), Err(SSKRError::SharePayloadTooShort { .. })));
+        assert!(matches!(ShareBytes::new(&share[..3]), Err(SSKRError::ShareLengthInvalid { .. })));
+        let mut reserved = share.to_vec();
+        reserved[4] |= 0x40;
+        assert!(matches!(ShareBytes::try_from(reserved), Err(SSKRError::AppNibblePresent { share_index: None, app_nibble: 4 })));
+        assert!(ShareBytes::try_from(share).is_ok());
+    }
+}
@@ -0,0 +1,177 @@
+use bc_rand::RandomNumberGenerator;
+
+use crate::{
+    GroupSecret,
+    SSKRError,
+    SSKRShare,
+    Secret,
+    ShareValue,
+    ShareVersion,
+    Spec,
+    encoding::split_secret,
+    seeded_rng::SeededRandomNumberGenerator,
+};
+
+/// Generates SSKR shares for the given `Spec` and `Secret` one at a time, as
+/// they are pulled from the returned iterator.
+///
+/// The secret is split into group secrets at once, but each group's member
+/// shares are split from its group secret only when the first of them is
+/// pulled. A group secret is zeroized as soon as its group's shares have
+/// been split, and a group's member shares as soon as they have been yielded
+/// or the iterator is dropped, so that little secret material is held at any
+/// time.
+///
+/// The iterator yields `(group_index, member_index, share)` for every share,
+/// group by group, and pulled to the end yields exactly the shares that
+/// `sskr_generate_using` returns with the same random number generator.
+///
+/// # Errors
+///
+/// Returns the same errors as `sskr_generate`, before any share is yielded.
+pub fn sskr_generate_iter<'a, R>(
+    spec: &'a Spec,
+    master_secret: &Secret,
+    random_generator: &'a mut R
+) -> Result<ShareIter<'a, R>, SSKRError>
+where
+    R: RandomNumberGenerator
+{
+    let version = ShareVersion::V0;
+    spec.validate_for_version(version)?;
+
+    // assign a random identifier
+    let mut identifier = [0u8; 2];
+    random_generator.fill_random_data(&mut identifier);
+    let identifier = u16::from_be_bytes(identifier) as u32;
+
+    let group_secrets = split_secret(version, spec.group_threshold(), spec.group_count(), master_secret.data(), random_generator)?
+        .into_iter()
+        .map(GroupSecret::new)
+        .collect::<Vec<_>>();
+
+    Ok(ShareIter {
+        spec,
+        version,
+        identifier,
+        random_generator,
+        group_secrets: group_secrets.into_iter().enumerate(),
+        members: None,
+    })
+}
+
+/// The shares of a split, generated as they are pulled, as returned by
+/// [`sskr_generate_iter`].
+pub struct ShareIter<'a, R> {
+    spec: &'a Spec,
+    version: ShareVersion,
+    identifier: u32,
+    random_generator: &'a mut R,
+    group_secrets: std::iter::Enumerate<std::vec::IntoIter<GroupSecret>>,
+    /// The group whose member shares are being yielded, with those not yet
+    /// yielded.
+    members: Option<(usize, std::iter::Enumerate<std::vec::IntoIter<ShareValue>>)>,
+}
+
+impl<R> Iterator for ShareIter<'_, R>
+where
+    R: RandomNumberGenerator
+{
+    type Item = (usize, usize, Vec<u8>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_share().map(|(group_index, member_index, share)| (group_index, member_index, share.serialize()))
+    }
+}
+
+impl<R> ShareIter<'_, R>
+where
+    R: RandomNumberGenerator
+{
+    /// Returns the random number generator the shares are split with.
+    pub(crate) fn random_generator(&mut self) -> &mut R {
+        self.random_generator
+    }
+
+    /// Returns the next share, splitting the next group if needed.
+    pub(crate) fn next_share(&mut self) -> Option<(usize, usize, SSKRShare)> {
+        loop {
+            if let Some((group_index, members)) = &mut self.members {
+                if let Some((member_index, value)) = members.next() {
+                    let group = &self.spec.groups()[*group_index];
+                    let share = SSKRShare::new(
+                        self.identifier,
+                        *group_index,
+                        self.spec.group_threshold(),
+                        self.spec.group_count(),
+                        member_index,
+                        group.member_threshold(),
+                        value,
+                    ).with_version(self.version);
+                    return Some((*group_index, member_index, share));
+                }
+            }
+            // The group secret is dropped, and so zeroized, once its member
+            // shares have been split.
+            let (group_index, group_secret) = self.group_secrets.next()?;
+            let group = &self.spec.groups()[group_index];
+            let mut group_rng = SeededRandomNumberGenerator::from_rng(self.random_generator);
+            let member_shares = split_secret(
+                self.version,
+                group.member_threshold(),
+                group.member_count(),
+                group_secret.data(),
+                &mut group_rng,
+            ).expect("the spec was validated before the secret was split");
+            let member_shares: Vec<ShareValue> = member_shares.into_iter().map(ShareValue::new).collect();
+            self.members = Some((group_index, member_shares.into_iter().enumerate()));
+        }
+    }
+}
+
+impl<R> std::fmt::Debug for ShareIter<'_, R> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ShareIter")
+            .field("identifier", &self.identifier)
+            .field("groups_remaining", &self.group_secrets.len())
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{GroupSpec, sskr_combine, sskr_generate_using};
+    use hex_literal::hex;
+
+    #[test]
+    fn test_generate_iter() {
+        let secret = Secret::new(hex!("204188bfa6b440a1bdfd6753ff55a8241e07af5c5be943db917e3efabc184b1a")).unwrap();
+        let spec = Spec::new(2, vec![GroupSpec::new(2, 3).unwrap(), GroupSpec::new(1, 1).unwrap(), GroupSpec::new(3, 5).unwrap()]).unwrap();
+        let eager = sskr_generate_using(&spec, &secret, &mut bc_rand::make_fake_random_number_generator()).unwrap();
+
+        let mut rng = bc_rand::make_fake_random_number_generator();
+        let lazy: Vec<(usize, usize, Vec<u8>)> = sskr_generate_iter(&spec, &secret, &mut rng).unwrap().collect();
+        let expected: Vec<(usize, usize, Vec<u8>)> = eager
+            .iter()
+            .enumerate()
+            .flat_map(|(group_index, group)| {
+                group.iter().enumerate().map(move |(member_index, share)| (group_index, member_index, share.clone()))
+            })
+            .collect();
+        assert_eq!(lazy, expected);
+
+        // Only the groups pulled are split.
+        let mut rng = bc_rand::make_fake_random_number_generator();
+        let mut shares = sskr_generate_iter(&spec, &secret, &mut rng).unwrap();
+        let first: Vec<Vec<u8>> = shares.by_ref().take(4).map(|(_, _, share)| share).collect();
+        assert_eq!(format!("{shares:?}"), format!("ShareIter {{ identifier: {}, groups_remaining: 1, .. }}", u16::from_be_bytes([first[0][0], first[0][1]])));
+        drop(shares);
+        assert_eq!(sskr_combine(&first[1..]).unwrap(), secret);
+
+        let mut rng = bc_rand::make_fake_random_number_generator();
+        assert!(sskr_generate_iter(&spec, &Secret::new([0; 32]).unwrap(), &mut rng).unwrap().count() == 9);
+        let spec = Spec::new(1, vec![GroupSpec::new(1, 1).unwrap(); 17]);
+        assert!(spec.is_err());
+    }
+}
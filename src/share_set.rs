@@ -0,0 +1,198 @@
+use std::ops::Index;
+
+use bc_rand::RandomNumberGenerator;
+
+use crate::{SSKRError, Secret, ShareBytes, Spec, sskr_generate_share_bytes_using};
+
+/// Generates SSKR shares for the given `Spec` and `Secret`, returning them as
+/// a `ShareSet`.
+///
+/// The shares are identical to those returned by `sskr_generate`.
+#[cfg(feature = "default-rng")]
+pub fn sskr_generate_share_set(spec: &Spec, master_secret: &Secret) -> Result<ShareSet, SSKRError> {
+    let mut rng = bc_rand::SecureRandomNumberGenerator;
+    sskr_generate_share_set_using(spec, master_secret, &mut rng)
+}
+
+/// Generates SSKR shares for the given `Spec` and `Secret` using the provided
+/// random number generator, returning them as a `ShareSet`.
+///
+/// The shares are identical to those returned by `sskr_generate_using` with
+/// the same random number generator.
+pub fn sskr_generate_share_set_using(
+    spec: &Spec,
+    master_secret: &Secret,
+    random_generator: &mut impl RandomNumberGenerator
+) -> Result<ShareSet, SSKRError> {
+    let groups = sskr_generate_share_bytes_using(spec, master_secret, random_generator)?;
+    Ok(ShareSet::from_groups(groups))
+}
+
+/// The shares of a split, by group.
+///
+/// The shares are held in one list, group by group and, within each group,
+/// in member order, as `sskr_generate` nests them. A share is addressed by
+/// its group and member index, either with [`get`](Self::get) or by indexing
+/// with a `(group, member)` pair.
+///
+/// A `ShareSet` can be combined directly, as
+/// `sskr_combine(share_set.as_flat())` or `sskr_combine_iter(&share_set)`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ShareSet {
+    shares: Vec<ShareBytes>,
+    /// The position in `shares` of the first share of each group, followed by
+    /// the number of shares.
+    group_starts: Vec<usize>,
+}
+
+impl ShareSet {
+    fn from_groups(groups: Vec<Vec<ShareBytes>>) -> Self {
+        let mut group_starts = Vec::with_capacity(groups.len() + 1);
+        group_starts.push(0);
+        let mut shares = Vec::new();
+        for group in groups {
+            shares.extend(group);
+            group_starts.push(shares.len());
+        }
+        Self { shares, group_starts }
+    }
+
+    /// Returns the number of groups.
+    pub fn group_count(&self) -> usize {
+        self.group_starts.len() - 1
+    }
+
+    /// Returns the total number of shares, in all groups.
+    pub fn len(&self) -> usize {
+        self.shares.len()
+    }
+
+    /// Returns `true` if there are no shares, which a generated set never
+    /// is.
+    pub fn is_empty(&self) -> bool {
+        self.shares.is_empty()
+    }
+
+    /// Returns the shares of group `group`, in member order.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `group` is not less than `group_count()`.
+    pub fn group(&self, group: usize) -> &[ShareBytes] {
+        assert!(group < self.group_count(), "group index {group} out of range for {} groups", self.group_count());
+        &self.shares[self.group_starts[group]..self.group_starts[group + 1]]
+    }
+
+    /// Returns the share of member `member` of group `group`, or `None` if
+    /// there is no such group or member.
+    pub fn get(&self, group: usize, member: usize) -> Option<&ShareBytes> {
+        if group >= self.group_count() {
+            return None;
+        }
+        self.group(group).get(member)
+    }
+
+    /// Returns an iterator over the groups, each as a slice of its shares.
+    pub fn groups(&self) -> impl ExactSizeIterator<Item = &[ShareBytes]> + '_ {
+        (0..self.group_count()).map(|group| self.group(group))
+    }
+
+    /// Returns all the shares, group by group.
+    pub fn as_flat(&self) -> &[ShareBytes] {
+        &self.shares
+    }
+
+    /// Returns an iterator over all the shares, group by group.
+    pub fn iter_flat(&self) -> std::slice::Iter<'_, ShareBytes> {
+        self.shares.iter()
+    }
+
+    /// Consumes the set, returning all the shares, group by group.
+    pub fn into_flat(self) -> Vec<ShareBytes> {
+        self.shares
+    }
+
+    /// Consumes the set, returning the serialized shares nested as
+    /// `sskr_generate` returns them.
+    pub fn into_nested(self) -> Vec<Vec<Vec<u8>>> {
+        let mut shares = self.shares.into_iter();
+        self.group_starts
+            .windows(2)
+            .map(|bounds| shares.by_ref().take(bounds[1] - bounds[0]).map(ShareBytes::into_bytes).collect())
+            .collect()
+    }
+}
+
+impl Index<(usize, usize)> for ShareSet {
+    type Output = ShareBytes;
+
+    /// Returns the share of member `member` of group `group`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there is no such group or member.
+    fn index(&self, (group, member): (usize, usize)) -> &ShareBytes {
+        &self.group(group)[member]
+    }
+}
+
+impl<'a> IntoIterator for &'a ShareSet {
+    type Item = &'a ShareBytes;
+    type IntoIter = std::slice::Iter<'a, ShareBytes>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_flat()
+    }
+}
+
+impl From<ShareSet> for Vec<Vec<Vec<u8>>> {
+    fn from(share_set: ShareSet) -> Self {
+        share_set.into_nested()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{GroupSpec, sskr_combine, sskr_combine_iter, test_support};
+    
+
+    #[test]
+    fn test_share_set() {
+        let secret = test_support::secret();
+        let spec = Spec::new(2, vec![GroupSpec::new(2, 3).unwrap(), GroupSpec::new(1, 1).unwrap(), GroupSpec::new(2, 2).unwrap()]).unwrap();
+        let share_set = sskr_generate_share_set_using(&spec, &secret, &mut bc_rand::make_fake_random_number_generator()).unwrap();
+        let nested = test_support::generate(&spec);
+        assert_eq!(share_set.group_count(), 3);
+        assert_eq!(share_set.len(), 6);
+        assert_eq!(share_set.groups().map(<[ShareBytes]>::len).collect::<Vec<_>>(), [3, 1, 2]);
+        assert_eq!(share_set.group(2)[1].data(), &nested[2][1][..]);
+        assert_eq!(share_set.get(0, 2).unwrap().data(), &nested[0][2][..]);
+        assert_eq!(share_set[(1, 0)].data(), &nested[1][0][..]);
+
+        // Flattening is group-major, member-minor.
+        let flat: Vec<&[u8]> = share_set.iter_flat().map(ShareBytes::data).collect();
+        assert_eq!(flat, nested.iter().flatten().map(Vec::as_slice).collect::<Vec<_>>());
+        assert_eq!(share_set.clone().into_flat().into_iter().map(ShareBytes::into_bytes).collect::<Vec<_>>(), nested.concat());
+        assert_eq!(Vec::<Vec<Vec<u8>>>::from(share_set.clone()), nested);
+
+        assert_eq!(sskr_combine(share_set.as_flat()).unwrap(), secret);
+        assert_eq!(sskr_combine_iter(&share_set).unwrap(), secret);
+        assert_eq!(sskr_combine_iter(share_set.group(0).iter().chain(share_set.group(1))).unwrap(), secret);
+
+        let share_set = sskr_generate_share_set_using(&Spec::new(1, vec![GroupSpec::new(2, 3).unwrap()]).unwrap(), &secret, &mut bc_rand::SecureRandomNumberGenerator).unwrap();
+        assert_eq!(sskr_combine(&share_set.group(0)[1..]).unwrap(), secret);
+    }
+
+    #[test]
+    fn test_share_set_out_of_range() {
+        let spec = Spec::new(2, vec![GroupSpec::new(2, 3).unwrap(), GroupSpec::new(1, 1).unwrap(), GroupSpec::new(2, 2).unwrap()]).unwrap();
+        let share_set = sskr_generate_share_set_using(&spec, &test_support::secret(), &mut bc_rand::make_fake_random_number_generator()).unwrap();
+        assert!(share_set.get(1, 1).is_none());
+        assert!(share_set.get(3, 0).is_none());
+        assert!(share_set.get(usize::MAX, 0).is_none());
+        assert!(std::panic::catch_unwind(|| share_set.group(3).len()).is_err());
+        assert!(std::panic::catch_unwind(|| share_set[(1, 1)].len()).is_err());
+        assert!(std::panic::catch_unwind(|| share_set[(3, 0)].len()).is_err());
+    }
+}
@@ -0,0 +1,184 @@
+use std::convert::Infallible;
+
+use bc_rand::RandomNumberGenerator;
+use zeroize::Zeroize;
+
+use crate::{SSKRError, Secret, Spec, encoding::serialize_share_into, sskr_generate_iter};
+
+/// A destination for shares as they are generated by
+/// [`sskr_generate_to_sink`].
+pub trait ShareSink {
+    /// The error the sink fails with.
+    type Error;
+
+    /// Accepts the serialized share for member `member` of group `group`.
+    ///
+    /// `share` is only valid for the duration of the call and is zeroized
+    /// after it returns; a sink that needs the share afterwards must copy it.
+    fn accept(&mut self, group: usize, member: usize, share: &[u8]) -> Result<(), Self::Error>;
+}
+
+impl<S> ShareSink for &mut S
+where
+    S: ShareSink + ?Sized
+{
+    type Error = S::Error;
+
+    fn accept(&mut self, group: usize, member: usize, share: &[u8]) -> Result<(), Self::Error> {
+        (**self).accept(group, member, share)
+    }
+}
+
+/// A `ShareSink` that collects the shares grouped as `sskr_generate` returns
+/// them.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct VecSink {
+    shares: Vec<Vec<Vec<u8>>>,
+}
+
+impl VecSink {
+    /// Creates an empty `VecSink`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the shares collected so far, grouped by group.
+    pub fn shares(&self) -> &[Vec<Vec<u8>>] {
+        &self.shares
+    }
+
+    /// Returns the shares collected, grouped by group.
+    pub fn into_shares(self) -> Vec<Vec<Vec<u8>>> {
+        self.shares
+    }
+}
+
+impl ShareSink for VecSink {
+    type Error = Infallible;
+
+    fn accept(&mut self, group: usize, _member: usize, share: &[u8]) -> Result<(), Self::Error> {
+        if self.shares.len() <= group {
+            self.shares.resize_with(group + 1, Vec::new);
+        }
+        self.shares[group].push(share.to_vec());
+        Ok(())
+    }
+}
+
+/// The error returned by [`sskr_generate_to_sink`].
+#[derive(Debug, thiserror::Error)]
+pub enum SinkError<E> {
+    #[error(transparent)]
+    Sskr(#[from] SSKRError),
+
+    #[error("the share sink failed: {0}")]
+    Sink(E),
+}
+
+/// Generates SSKR shares for the given `Spec` and `Secret`, handing each to
+/// `sink` as it is generated instead of returning them.
+///
+/// Shares are passed to the sink group by group, in the order `sskr_generate`
+/// returns them, and with the same random number generator they are the same
+/// shares. Each share is serialized into a single scratch buffer, which is
+/// zeroized after the sink has accepted it, and each group's shares are split
+/// only once the previous group's have been accepted.
+///
+/// # Example
+///
+/// Writing each share to a `std::io::Write` as a line of hex:
+///
+/// ```
+/// use std::io::Write;
+/// use sskr::{GroupSpec, Secret, ShareSink, Spec, sskr_generate_to_sink};
+///
+/// struct HexLines<W>(W);
+///
+/// impl<W: Write> ShareSink for HexLines<W> {
+///     type Error = std::io::Error;
+///
+///     fn accept(&mut self, group: usize, member: usize, share: &[u8]) -> std::io::Result<()> {
+///         write!(self.0, "{group}/{member} ")?;
+///         for byte in share {
+///             write!(self.0, "{byte:02x}")?;
+///         }
+///         writeln!(self.0)
+///     }
+/// }
+///
+/// let secret = Secret::new(b"my secret belongs to me.").unwrap();
+/// let spec = Spec::new(1, vec![GroupSpec::new(2, 3).unwrap()]).unwrap();
+/// let mut sink = HexLines(Vec::new());
+/// sskr_generate_to_sink(&spec, &secret, &mut bc_rand::SecureRandomNumberGenerator, &mut sink).unwrap();
+/// let output = String::from_utf8(sink.0).unwrap();
+/// assert_eq!(output.lines().count(), 3);
+/// assert!(output.starts_with("0/0 "));
+/// ```
+///
+/// # Errors
+///
+/// Returns `SinkError::Sskr` with the same errors as `sskr_generate`, before
+/// any share is passed to the sink. Returns `SinkError::Sink` with the sink's
+/// error as soon as the sink fails, generating no further shares.
+pub fn sskr_generate_to_sink<R, S>(
+    spec: &Spec,
+    master_secret: &Secret,
+    random_generator: &mut R,
+    mut sink: S
+) -> Result<(), SinkError<S::Error>>
+where
+    R: RandomNumberGenerator,
+    S: ShareSink
+{
+    let mut shares = sskr_generate_iter(spec, master_secret, random_generator)?;
+    let mut scratch = Vec::new();
+    while let Some((group_index, member_index, share)) = shares.next_share() {
+        serialize_share_into(&share, &mut scratch);
+        let accepted = sink.accept(group_index, member_index, &scratch);
+        scratch.zeroize();
+        accepted.map_err(SinkError::Sink)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{GroupSpec, sskr_generate_using, test_support};
+    
+
+    struct FailingSink {
+        accepted: usize,
+        limit: usize,
+    }
+
+    impl ShareSink for FailingSink {
+        type Error = String;
+
+        fn accept(&mut self, group: usize, member: usize, _share: &[u8]) -> Result<(), Self::Error> {
+            if self.accepted == self.limit {
+                return Err(format!("full at {group}/{member}"));
+            }
+            self.accepted += 1;
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_generate_to_sink() {
+        let secret = test_support::secret();
+        let spec = Spec::new(2, vec![GroupSpec::new(2, 3).unwrap(), GroupSpec::new(1, 1).unwrap(), GroupSpec::new(2, 2).unwrap()]).unwrap();
+        let expected = sskr_generate_using(&spec, &secret, &mut bc_rand::make_fake_random_number_generator()).unwrap();
+
+        let mut sink = VecSink::new();
+        sskr_generate_to_sink(&spec, &secret, &mut bc_rand::make_fake_random_number_generator(), &mut sink).unwrap();
+        assert_eq!(sink.shares(), expected.as_slice());
+        assert_eq!(sink.into_shares(), expected);
+
+        let mut sink = FailingSink { accepted: 0, limit: 4 };
+        let error = sskr_generate_to_sink(&spec, &secret, &mut bc_rand::make_fake_random_number_generator(), &mut sink).unwrap_err();
+        assert!(matches!(&error, SinkError::Sink(message) if message == "full at 2/0"));
+        assert_eq!(error.to_string(), "the share sink failed: full at 2/0");
+        assert_eq!(sink.accepted, 4);
+    }
+}
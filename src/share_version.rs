@@ -0,0 +1,147 @@
+use crate::{
+    SSKRError,
+    PASSPHRASE_SHARE_MAGIC,
+    SHARE_ENVELOPE_MAGIC,
+    SHARE_VERSION_ESCAPE,
+    METADATA_SIZE_BYTES,
+    EXTENDED_METADATA_SIZE_BYTES,
+    WIDE_IDENTIFIER_METADATA_SIZE_BYTES,
+    FINGERPRINTED_METADATA_SIZE_BYTES,
+    MAX_SHARE_COUNT,
+    MAX_GROUPS_COUNT,
+    MAX_EXTENDED_SHARE_COUNT,
+    MAX_EXTENDED_GROUPS_COUNT,
+};
+
+/// The version of the SSKR share format.
+///
+/// The four bits of share metadata that the original format reserves are
+/// zero in every share it produces. A share in any other format sets them to
+/// `SHARE_VERSION_ESCAPE` and gives its version in the byte that follows the
+/// standard metadata, which is where the formats first differ. A decoder that
+/// meets a version it does not recognize reports
+/// `SSKRError::UnsupportedShareVersion` instead of misreading the rest of the
+/// share, so a future format can be introduced without being mistaken for a
+/// corrupt one, and without taking a value of the reserved bits from
+/// applications: every value other than zero, the escape, and the 14 and 15
+/// that mark a share protected by a passphrase and a `ShareEnvelope` is
+/// application data for `sskr_generate_with_app_nibble`, and will stay so.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[non_exhaustive]
+pub enum ShareVersion {
+    /// The format described in BCR-2020-011.
+    #[default]
+    V0,
+    /// The extended format, which widens the group and member fields to a
+    /// byte each so that a split can have up to `MAX_EXTENDED_GROUPS_COUNT`
+    /// groups of up to `MAX_EXTENDED_SHARE_COUNT` members. Its metadata is
+    /// `EXTENDED_METADATA_SIZE_BYTES` long.
+    V1,
+    /// The wide-identifier format, which is the standard format with a 32-bit
+    /// identifier in place of the 16-bit one, making it far less likely that
+    /// shares from independent splits are mistaken for one another. Its
+    /// metadata is `WIDE_IDENTIFIER_METADATA_SIZE_BYTES` long.
+    V2,
+    /// The fingerprinted format, which is the standard format with a
+    /// fingerprint of the master secret, `FINGERPRINT_SIZE_BYTES` long,
+    /// following the metadata, so that a recovered secret can be checked
+    /// against it. The fingerprint is that of `FingerprintAlg::Sha256Trunc4`.
+    /// Its metadata, with the fingerprint, is
+    /// `FINGERPRINTED_METADATA_SIZE_BYTES` long.
+    V3,
+}
+
+impl ShareVersion {
+    /// Returns the value that marks this version in the share metadata: the
+    /// version byte that follows `SHARE_VERSION_ESCAPE`, or zero for the
+    /// standard format, which has neither.
+    pub fn value(&self) -> u8 {
+        match self {
+            ShareVersion::V0 => 0,
+            ShareVersion::V1 => 1,
+            ShareVersion::V2 => 2,
+            ShareVersion::V3 => 3,
+        }
+    }
+
+    /// Returns the number of bytes in the identifier of a split in this
+    /// format.
+    pub fn identifier_size_bytes(&self) -> usize {
+        match self {
+            ShareVersion::V0 | ShareVersion::V1 | ShareVersion::V3 => 2,
+            ShareVersion::V2 => 4,
+        }
+    }
+
+    /// Checks that `identifier` fits in the identifier of a split in this
+    /// format, failing with `SSKRError::IdentifierInvalid` if it does not.
+    pub(crate) fn check_identifier(&self, identifier: u32) -> Result<(), SSKRError> {
+        let bits = self.identifier_size_bytes() * 8;
+        if bits < 32 && identifier >> bits != 0 {
+            return Err(SSKRError::IdentifierInvalid { identifier, version: *self });
+        }
+        Ok(())
+    }
+
+    /// Returns the number of bytes of metadata that precede the value of a
+    /// share in this format.
+    pub fn metadata_size_bytes(&self) -> usize {
+        match self {
+            ShareVersion::V0 => METADATA_SIZE_BYTES,
+            ShareVersion::V1 => EXTENDED_METADATA_SIZE_BYTES,
+            ShareVersion::V2 => WIDE_IDENTIFIER_METADATA_SIZE_BYTES,
+            ShareVersion::V3 => FINGERPRINTED_METADATA_SIZE_BYTES,
+        }
+    }
+
+    /// Returns the maximum number of member shares in a group in this format.
+    pub fn max_share_count(&self) -> usize {
+        match self {
+            ShareVersion::V0 | ShareVersion::V2 | ShareVersion::V3 => MAX_SHARE_COUNT,
+            ShareVersion::V1 => MAX_EXTENDED_SHARE_COUNT,
+        }
+    }
+
+    /// Returns the maximum number of groups in a split in this format.
+    pub fn max_groups_count(&self) -> usize {
+        match self {
+            ShareVersion::V0 | ShareVersion::V2 | ShareVersion::V3 => MAX_GROUPS_COUNT,
+            ShareVersion::V1 => MAX_EXTENDED_GROUPS_COUNT,
+        }
+    }
+
+    /// Returns the maximum number of shares in a single split in this format.
+    pub fn max_total_share_count(&self) -> usize {
+        self.max_groups_count() * self.max_share_count()
+    }
+
+    /// Returns `true` if `nibble` marks a share format other than the standard
+    /// one, a passphrase-protected share or a `ShareEnvelope`, so that it
+    /// cannot carry application data.
+    pub(crate) fn is_assigned_nibble(nibble: u8) -> bool {
+        nibble == SHARE_VERSION_ESCAPE
+            || nibble == PASSPHRASE_SHARE_MAGIC[4] >> 4
+            || nibble == SHARE_ENVELOPE_MAGIC[4] >> 4
+    }
+}
+
+impl TryFrom<u8> for ShareVersion {
+    type Error = SSKRError;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(ShareVersion::V0),
+            1 => Ok(ShareVersion::V1),
+            2 => Ok(ShareVersion::V2),
+            3 => Ok(ShareVersion::V3),
+            _ => Err(SSKRError::UnsupportedShareVersion { share_index: None, version: value }),
+        }
+    }
+}
+
+impl From<ShareVersion> for u8 {
+    fn from(version: ShareVersion) -> Self {
+        version.value()
+    }
+}
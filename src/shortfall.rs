@@ -0,0 +1,187 @@
+use crate::{CollectorStatus, SSKRError, ShareCollector};
+
+/// Reports how many more shares are needed to recover the secret split into
+/// `shares`, and from which groups.
+///
+/// Only the share metadata is read; no recovery is attempted, so a corrupt
+/// share value is not detected. A share that appears more than once is
+/// counted once.
+///
+/// # Errors
+///
+/// Returns `SSKRError::SharesEmpty` if `shares` is empty, or any error
+/// `ShareCollector::add_share` returns for a share that is malformed or
+/// belongs to a different split than the first.
+pub fn shares_needed<T>(shares: &[T]) -> Result<RecoveryShortfall, SSKRError>
+where
+    T: AsRef<[u8]>
+{
+    if shares.is_empty() {
+        return Err(SSKRError::SharesEmpty);
+    }
+    let mut collector = ShareCollector::new();
+    for share in shares {
+        let share = share.as_ref();
+        if collector.shares().iter().any(|collected| collected.data() == share) {
+            continue;
+        }
+        collector.add_share(share)?;
+    }
+    Ok(collector.status().shortfall().unwrap())
+}
+
+/// What is missing from a set of shares before the secret can be recovered,
+/// as reported by [`shares_needed`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct RecoveryShortfall {
+    groups_needed: usize,
+    groups: Vec<GroupShortfall>,
+}
+
+impl RecoveryShortfall {
+    /// Returns the number of additional groups that must reach their member
+    /// threshold to meet the group threshold.
+    pub fn groups_needed(&self) -> usize {
+        self.groups_needed
+    }
+
+    /// Returns the shortfall of every group of the split, in order of group
+    /// index, including those no shares were provided for.
+    pub fn groups(&self) -> &[GroupShortfall] {
+        &self.groups
+    }
+
+    /// Returns `true` if nothing is missing.
+    pub fn is_satisfied(&self) -> bool {
+        self.groups_needed == 0
+    }
+}
+
+/// What is missing from one group, as reported by [`shares_needed`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct GroupShortfall {
+    group_index: usize,
+    member_threshold: Option<usize>,
+    shares_held: usize,
+    shares_needed: Option<usize>,
+}
+
+impl GroupShortfall {
+    /// Returns the index of the group.
+    pub fn group_index(&self) -> usize {
+        self.group_index
+    }
+
+    /// Returns the member threshold of the group, or `None` if no shares of
+    /// the group were provided, so it is unknown.
+    pub fn member_threshold(&self) -> Option<usize> {
+        self.member_threshold
+    }
+
+    /// Returns the number of distinct member shares provided for the group.
+    pub fn shares_held(&self) -> usize {
+        self.shares_held
+    }
+
+    /// Returns the number of additional member shares the group needs to
+    /// reach its threshold, which is zero for a complete group.
+    ///
+    /// Returns `None` for a group no shares were provided for: it needs at
+    /// least one share, which will reveal its threshold.
+    pub fn shares_needed(&self) -> Option<usize> {
+        self.shares_needed
+    }
+
+    /// Returns `true` if the group has reached its member threshold.
+    pub fn is_complete(&self) -> bool {
+        self.shares_needed == Some(0)
+    }
+}
+
+impl CollectorStatus {
+    /// Returns what is missing before the secret can be recovered, or `None`
+    /// if no shares have been collected.
+    pub fn shortfall(&self) -> Option<RecoveryShortfall> {
+        let group_count = self.group_count()?;
+        let groups = (0..group_count)
+            .map(|group_index| {
+                match self.groups().iter().find(|group| group.group_index() == group_index) {
+                    Some(group) => GroupShortfall {
+                        group_index,
+                        member_threshold: Some(group.member_threshold()),
+                        shares_held: group.shares_held(),
+                        shares_needed: Some(group.member_threshold().saturating_sub(group.shares_held())),
+                    },
+                    None => GroupShortfall { group_index, member_threshold: None, shares_held: 0, shares_needed: None },
+                }
+            })
+            .collect();
+        Some(RecoveryShortfall { groups_needed: self.groups_needed()?, groups })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{GroupSpec, Spec, test_support};
+    
+
+    fn summary(shortfall: &RecoveryShortfall) -> Vec<(Option<usize>, usize, Option<usize>)> {
+        shortfall.groups().iter().map(|group| (group.member_threshold(), group.shares_held(), group.shares_needed())).collect()
+    }
+
+    #[test]
+    fn test_single_group_shortfall() {
+        let shares = test_support::generate(&Spec::new(1, vec![GroupSpec::new(3, 5).unwrap()]).unwrap());
+
+        let shortfall = shares_needed(&shares[0][..1]).unwrap();
+        assert_eq!(shortfall.groups_needed(), 1);
+        assert_eq!(summary(&shortfall), [(Some(3), 1, Some(2))]);
+        assert!(!shortfall.is_satisfied());
+
+        // A repeated share counts once.
+        let shortfall = shares_needed(&[&shares[0][4], &shares[0][1], &shares[0][4]]).unwrap();
+        assert_eq!(summary(&shortfall), [(Some(3), 2, Some(1))]);
+
+        let shortfall = shares_needed(&shares[0]).unwrap();
+        assert_eq!(shortfall.groups_needed(), 0);
+        assert_eq!(summary(&shortfall), [(Some(3), 5, Some(0))]);
+        assert!(shortfall.is_satisfied() && shortfall.groups()[0].is_complete());
+
+        assert!(matches!(shares_needed::<Vec<u8>>(&[]), Err(SSKRError::SharesEmpty)));
+        assert!(shares_needed(&[&shares[0][0][..4]]).is_err());
+    }
+
+    #[test]
+    fn test_multi_group_shortfall() {
+        let shares = test_support::generate(&Spec::new(3, vec![
+            GroupSpec::new(2, 3).unwrap(),
+            GroupSpec::new(3, 5).unwrap(),
+            GroupSpec::new(1, 1).unwrap(),
+            GroupSpec::new(4, 4).unwrap(),
+        ]).unwrap());
+
+        let shortfall = shares_needed(&[&shares[1][0], &shares[1][2], &shares[2][0]]).unwrap();
+        assert_eq!(shortfall.groups_needed(), 2);
+        assert_eq!(summary(&shortfall), [
+            (None, 0, None),
+            (Some(3), 2, Some(1)),
+            (Some(1), 1, Some(0)),
+            (None, 0, None),
+        ]);
+
+        let shortfall = shares_needed(&[&shares[1][0], &shares[1][2], &shares[2][0], &shares[0][1], &shares[0][0]]).unwrap();
+        assert_eq!(shortfall.groups_needed(), 1);
+        assert_eq!(summary(&shortfall)[0], (Some(2), 2, Some(0)));
+
+        let shortfall = shares_needed(&[&shares[1][0], &shares[1][2], &shares[2][0], &shares[0][1], &shares[0][0], &shares[1][4]]).unwrap();
+        assert!(shortfall.is_satisfied());
+        assert_eq!(summary(&shortfall)[3], (None, 0, None));
+
+        // Shares of another split are an error.
+        let other = test_support::generate(&Spec::new(1, vec![GroupSpec::new(2, 3).unwrap()]).unwrap());
+        assert!(shares_needed(&[&shares[0][0], &other[0][0]]).is_err());
+    }
+}
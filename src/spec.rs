@@ -1,9 +1,8 @@
-use bc_shamir::MAX_SHARE_COUNT;
-
-use crate::SSKRError;
+use crate::{SSKRError, Secret, ShareVersion, HolderLayout, PolicyWarning, RequiredMembers, SplitPlan, encoding::{ReservedBits, deserialize_shares, group_shares}};
 
 /// A specification for an SSKR split.
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct Spec {
     group_threshold: usize,
     groups: Vec<GroupSpec>,
@@ -25,13 +24,29 @@ impl Spec {
     /// is greater than the number of groups, or if the number of groups is
     /// greater than the maximum share count.
     pub fn new(group_threshold: usize, groups: Vec<GroupSpec>) -> Result<Self, SSKRError> {
-        if group_threshold == 0 {
-            return Err(SSKRError::GroupThresholdInvalid);
-        }
-        if group_threshold > groups.len() {
-            return Err(SSKRError::GroupThresholdInvalid);
+        Self::new_with_version(group_threshold, groups, ShareVersion::V0)
+    }
+
+    /// Creates a new `Spec` instance for a split in the given share format.
+    ///
+    /// This is `new` with the bounds of `version` in place of the standard
+    /// ones, so an extended-format (`ShareVersion::V1`) spec may have up to
+    /// `MAX_EXTENDED_GROUPS_COUNT` groups.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the group threshold is zero, if the group threshold
+    /// is greater than the number of groups, or if the number of groups is
+    /// greater than `version` allows.
+    pub fn new_with_version(
+        group_threshold: usize,
+        groups: Vec<GroupSpec>,
+        version: ShareVersion
+    ) -> Result<Self, SSKRError> {
+        if group_threshold == 0 || group_threshold > groups.len() {
+            return Err(SSKRError::GroupThresholdInvalid { threshold: group_threshold, group_count: groups.len() });
         }
-        if groups.len() > MAX_SHARE_COUNT {
+        if groups.len() > version.max_groups_count() {
             return Err(SSKRError::GroupCountInvalid);
         }
         Ok(Self {
@@ -59,13 +74,403 @@ impl Spec {
     pub fn share_count(&self) -> usize {
         self.groups.iter().map(|g| g.member_count()).sum()
     }
+
+    /// Returns the shape of the shares `sskr_generate` would give for this
+    /// spec and a secret of `secret_len` bytes, without generating them.
+    ///
+    /// # Errors
+    ///
+    /// Returns the errors `Secret::new` gives for a secret of `secret_len`
+    /// bytes, and `sskr_generate`'s errors for a spec that does not fit the
+    /// standard share format.
+    pub fn plan(&self, secret_len: usize) -> Result<SplitPlan, SSKRError> {
+        Secret::validate_len(secret_len)?;
+        self.validate_for_version(ShareVersion::V0)?;
+        let shares_per_group = self.groups.iter().map(GroupSpec::member_count).collect();
+        Ok(SplitPlan::new(secret_len, secret_len + ShareVersion::V0.metadata_size_bytes(), shares_per_group))
+    }
+
+    /// Creates the spec of a split in which `required_count` members of
+    /// `group` must take part in every recovery.
+    ///
+    /// SSKR cannot require particular members of a group, so the requirement
+    /// is expanded into two groups, both of which are needed: a group of the
+    /// required members, all of whom must take part, and a group of the
+    /// others, of whom enough must take part to make up the member threshold
+    /// of `group`. A 3-of-5 group with one required member becomes a 1-of-1
+    /// group and a 2-of-4 group, with a group threshold of two. The returned
+    /// [`RequiredMembers`] maps the members of `group` to the shares of the
+    /// expanded split; any weights of `group` are not carried over.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SSKRError::RequiredCountInvalid` unless `required_count` is
+    /// at least one and less than the member threshold of `group`.
+    pub fn with_required_member(group: GroupSpec, required_count: usize) -> Result<(Self, RequiredMembers), SSKRError> {
+        let member_threshold = group.member_threshold();
+        if required_count == 0 || required_count >= member_threshold {
+            return Err(SSKRError::RequiredCountInvalid { required_count, member_threshold });
+        }
+        let required = GroupSpec::new(required_count, required_count)?;
+        let others = GroupSpec::new(member_threshold - required_count, group.member_count() - required_count)?;
+        let spec = Self::new(2, vec![required, others])?;
+        Ok((spec, RequiredMembers::new(required_count, group.member_count())))
+    }
+
+    /// Creates the spec of a split that the owner can recover alone, or a
+    /// quorum of the owner's social circle can recover without the owner.
+    ///
+    /// The split has two groups and a group threshold of one. The first, at
+    /// [`OWNER_GROUP_INDEX`](Self::OWNER_GROUP_INDEX), has `owner_shares`
+    /// shares, any one of which recovers the secret, so that the owner can
+    /// keep copies in several places. The second is `social`, whose member
+    /// threshold must be at least two: a group of friends any one of whom can
+    /// recover the secret alone is rarely what is meant.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SSKRError::MemberCountInvalid` if `owner_shares` is zero or
+    /// more than a group can hold, and `SSKRError::SocialThresholdInvalid` if
+    /// the member threshold of `social` is one.
+    pub fn owner_plus_social(owner_shares: usize, social: GroupSpec) -> Result<Self, SSKRError> {
+        if social.member_threshold() < 2 {
+            return Err(SSKRError::SocialThresholdInvalid { threshold: social.member_threshold() });
+        }
+        let owner = GroupSpec::new(1, owner_shares)?;
+        Self::new(1, vec![owner, social])
+    }
+
+    /// The index of the owner's group in a spec created by
+    /// [`owner_plus_social`](Self::owner_plus_social).
+    pub const OWNER_GROUP_INDEX: usize = 0;
+
+    /// Returns the index of the owner's group if this spec has the shape of
+    /// one created by [`owner_plus_social`](Self::owner_plus_social), or
+    /// `None` otherwise.
+    pub fn owner_group_index(&self) -> Option<usize> {
+        match self.groups.as_slice() {
+            [owner, social] if self.group_threshold == 1 && owner.member_threshold() == 1 && social.member_threshold() >= 2 => {
+                Some(Self::OWNER_GROUP_INDEX)
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns a plain English description of who can recover the secret of
+    /// a split with this spec, suitable for showing to a user before they
+    /// generate it. Group numbers are one-based. The wording is stable, for
+    /// example:
+    ///
+    /// ```text
+    /// Recoverable with 2 of the 3 groups: group 1 needs 2 of its 3 shares,
+    /// group 2 needs 1 of its 1 share, and group 3 needs 3 of its 5 shares.
+    /// ```
+    ///
+    /// A spec with the shape of one created by
+    /// [`owner_plus_social`](Self::owner_plus_social) is described in those
+    /// terms:
+    ///
+    /// ```text
+    /// Recoverable by the owner alone with any 1 of their 2 shares, or
+    /// without the owner by 3 of the 5 social shares together.
+    /// ```
+    pub fn describe(&self) -> String {
+        let plural = |n: usize, word: &str| {
+            if n == 1 { format!("{} {}", n, word) } else { format!("{} {}s", n, word) }
+        };
+        if let Some(owner_index) = self.owner_group_index() {
+            let owner = &self.groups[owner_index];
+            let social = &self.groups[1 - owner_index];
+            return format!(
+                "Recoverable by the owner alone with any 1 of their {}, or without the owner by {} of the {} together.",
+                plural(owner.member_count(), "share"),
+                social.member_threshold(),
+                plural(social.member_count(), "social share"),
+            );
+        }
+        let groups = self.groups
+            .iter()
+            .enumerate()
+            .map(|(group_index, group)| {
+                format!("group {} needs {} of its {}", group_index + 1, group.member_threshold(), plural(group.member_count(), "share"))
+            })
+            .collect::<Vec<_>>();
+        let groups = match groups.as_slice() {
+            [group] => group.clone(),
+            [rest @ .., last] if rest.len() == 1 => format!("{} and {}", rest[0], last),
+            [rest @ .., last] => format!("{}, and {}", rest.join(", "), last),
+            [] => String::new(),
+        };
+        format!("Recoverable with {} of the {}: {}.", self.group_threshold, plural(self.group_count(), "group"), groups)
+    }
+
+    /// Returns the properties of this spec that are valid but risky, such as
+    /// a group any one of whose members can recover it, or a share whose loss
+    /// alone loses the secret.
+    ///
+    /// The warnings are for showing to a user before shares are generated;
+    /// they never stop a split. A warning about the spec as a whole comes
+    /// before those about its groups, which are in order of group index, and
+    /// the loss of the secret to a single lost share comes last.
+    pub fn warnings(&self) -> Vec<PolicyWarning> {
+        let mut warnings = Vec::new();
+        if self.group_threshold == 1 && self.group_count() > 1 {
+            warnings.push(PolicyWarning::SingleGroupRecoversSecret);
+        }
+        for (group_index, group) in self.groups.iter().enumerate() {
+            if group.member_threshold() == 1 && group.member_count() > 1 {
+                warnings.push(PolicyWarning::SingleMemberRecoversGroup { group_index });
+            }
+            if group.member_threshold() == group.member_count() {
+                warnings.push(PolicyWarning::GroupHasNoLossTolerance { group_index });
+            }
+        }
+        // A lost share can only cost its own group, which is fatal only if
+        // every group is needed.
+        let fragile_group = self.groups.iter().any(|group| group.member_threshold() == group.member_count());
+        if fragile_group && self.group_threshold == self.group_count() {
+            warnings.push(PolicyWarning::SpecHasNoLossTolerance);
+        }
+        warnings
+    }
+
+    /// Reconstructs as much of the spec of a split as can be learned from
+    /// `shares`, without attempting any recovery.
+    ///
+    /// Every share records the group threshold and group count of its split
+    /// and the member threshold of its group, so these are known for every
+    /// group a share is provided for. The member counts are never known, as
+    /// shares do not record them.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SSKRError::SharesEmpty` if `shares` is empty, an error if a
+    /// share is malformed, and the same mismatch errors as `sskr_combine` if
+    /// the shares disagree about their split.
+    pub fn from_shares<T>(shares: &[T]) -> Result<PartialSpec, SSKRError>
+    where
+        T: AsRef<[u8]>
+    {
+        let sskr_shares = deserialize_shares(shares, ReservedBits::Version)?;
+        let grouped = group_shares(&sskr_shares)?;
+        let mut member_thresholds = vec![None; sskr_shares[0].group_count()];
+        for group in &grouped.groups {
+            member_thresholds[group.group_index] = Some(group.member_threshold);
+        }
+        Ok(PartialSpec { version: grouped.version, group_threshold: grouped.group_threshold, member_thresholds })
+    }
+
+    /// Parses a split specification from a string.
+    ///
+    /// A spec is written as its group threshold and a bracketed list of its
+    /// groups, each written as `GroupSpec::parse` reads it, such as
+    /// `2 of [2-of-3, 3-of-5]`. A spec of a single group can be written as
+    /// that group alone: `2-of-3` is `1 of [2-of-3]`. Whitespace is allowed
+    /// around the group threshold, `of`, the brackets and the commas, but not
+    /// within a group. A spec is displayed in this form, so its string parses
+    /// back to it.
+    ///
+    /// The counts are checked against the bounds of the extended format
+    /// (`ShareVersion::V1`), the widest of any format, so that the string of
+    /// a spec made with `new_with_version` parses back too. A spec that does
+    /// not fit the standard format is rejected when shares are generated in
+    /// it.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SSKRError::SpecParseFailed`, with what was expected and the
+    /// byte offset at which it was not found, if the string does not follow
+    /// this grammar, and otherwise the errors of `Spec::new_with_version` and
+    /// `GroupSpec::new_with_version` for the counts it gives.
+    pub fn parse(s: &str) -> Result<Self, SSKRError> {
+        let mut parser = SpecParser { source: s, position: 0 };
+        let spec = if parser.peek_group() {
+            Self::new(1, vec![parser.group()?])?
+        } else {
+            let threshold = parser.number()?;
+            parser.keyword("of", "\"of\"")?;
+            parser.punctuation('[', "\"[\"")?;
+            let mut groups = vec![parser.group()?];
+            while parser.try_punctuation(',') {
+                groups.push(parser.group()?);
+            }
+            parser.punctuation(']', "\",\" or \"]\"")?;
+            Self::new_with_version(threshold, groups, ShareVersion::V1)?
+        };
+        parser.end()?;
+        Ok(spec)
+    }
+
+    /// Checks that every count in this spec can be encoded in `version`.
+    pub(crate) fn validate_for_version(&self, version: ShareVersion) -> Result<(), SSKRError> {
+        if self.group_count() > version.max_groups_count() {
+            return Err(SSKRError::GroupCountInvalid);
+        }
+        if let Some(g) = self.groups.iter().find(|g| g.member_count() > version.max_share_count()) {
+            return Err(SSKRError::MemberCountInvalid { count: g.member_count(), max: version.max_share_count() });
+        }
+        Ok(())
+    }
+}
+
+impl std::str::FromStr for Spec {
+    type Err = SSKRError;
+
+    /// Parses a spec as [`Spec::parse`] does.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+/// Writes the spec in the form `Spec::parse` reads, such as
+/// `2 of [2-of-3, 3-of-5]`, or just the group, such as `2-of-3`, for a spec
+/// of a single group.
+impl std::fmt::Display for Spec {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let [group] = self.groups.as_slice() {
+            return write!(f, "{group}");
+        }
+        write!(f, "{} of [", self.group_threshold)?;
+        for (group_index, group) in self.groups.iter().enumerate() {
+            if group_index > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{group}")?;
+        }
+        write!(f, "]")
+    }
+}
+
+/// Reads the parts of a spec string in turn, skipping the whitespace before
+/// each, and reports the byte offset of the first part that is not what the
+/// grammar expects.
+struct SpecParser<'a> {
+    source: &'a str,
+    position: usize,
+}
+
+impl SpecParser<'_> {
+    fn rest(&mut self) -> &str {
+        let rest = &self.source[self.position..];
+        self.position += rest.len() - rest.trim_start().len();
+        &self.source[self.position..]
+    }
+
+    fn error(&self, expected: &'static str) -> SSKRError {
+        SSKRError::SpecParseFailed { position: self.position, expected }
+    }
+
+    fn number(&mut self) -> Result<usize, SSKRError> {
+        let rest = self.rest();
+        let len = rest.find(|c: char| !c.is_ascii_digit()).unwrap_or(rest.len());
+        let number = rest[..len].parse::<usize>().map_err(|_| self.error("a number"))?;
+        self.position += len;
+        Ok(number)
+    }
+
+    fn try_punctuation(&mut self, c: char) -> bool {
+        let found = self.rest().starts_with(c);
+        if found {
+            self.position += c.len_utf8();
+        }
+        found
+    }
+
+    fn punctuation(&mut self, c: char, expected: &'static str) -> Result<(), SSKRError> {
+        if self.try_punctuation(c) { Ok(()) } else { Err(self.error(expected)) }
+    }
+
+    fn keyword(&mut self, keyword: &str, expected: &'static str) -> Result<(), SSKRError> {
+        if !self.rest().starts_with(keyword) {
+            return Err(self.error(expected));
+        }
+        self.position += keyword.len();
+        Ok(())
+    }
+
+    /// Returns the part starting at the current position, which runs to the
+    /// next whitespace, comma or bracket.
+    fn part(&mut self) -> &str {
+        let rest = self.rest();
+        let len = rest.find(|c: char| c.is_whitespace() || matches!(c, ',' | '[' | ']')).unwrap_or(rest.len());
+        &rest[..len]
+    }
+
+    fn peek_group(&mut self) -> bool {
+        self.part().contains('-')
+    }
+
+    /// Reads a group, as `GroupSpec::parse` reads it, with the bounds of the
+    /// extended format.
+    fn group(&mut self) -> Result<GroupSpec, SSKRError> {
+        let part = self.part();
+        let len = part.len();
+        let group = match GroupSpec::parse_with_version(part, ShareVersion::V1) {
+            Err(SSKRError::GroupSpecInvalid) => return Err(self.error("a group such as \"2-of-3\"")),
+            result => result?,
+        };
+        self.position += len;
+        Ok(group)
+    }
+
+    fn end(&mut self) -> Result<(), SSKRError> {
+        if self.rest().is_empty() { Ok(()) } else { Err(self.error("the end of the spec")) }
+    }
+}
+
+/// The part of the spec of a split that can be learned from its shares, as
+/// returned by [`Spec::from_shares`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct PartialSpec {
+    version: ShareVersion,
+    group_threshold: usize,
+    member_thresholds: Vec<Option<usize>>,
+}
+
+impl PartialSpec {
+    /// Returns the format of the shares.
+    pub fn version(&self) -> ShareVersion {
+        self.version
+    }
+
+    /// Returns the group threshold.
+    pub fn group_threshold(&self) -> usize {
+        self.group_threshold
+    }
+
+    /// Returns the number of groups.
+    pub fn group_count(&self) -> usize {
+        self.member_thresholds.len()
+    }
+
+    /// Returns the member threshold of each group, in order of group index,
+    /// or `None` for a group no shares were provided for.
+    pub fn member_thresholds(&self) -> &[Option<usize>] {
+        &self.member_thresholds
+    }
+
+    /// Returns the member threshold of the group at `group_index`, or `None`
+    /// if no shares were provided for it or there is no such group.
+    pub fn member_threshold(&self, group_index: usize) -> Option<usize> {
+        self.member_thresholds.get(group_index).copied().flatten()
+    }
+
+    /// Returns `true` if a share was provided for every group, so that every
+    /// threshold is known.
+    pub fn is_complete(&self) -> bool {
+        self.member_thresholds.iter().all(Option::is_some)
+    }
 }
 
 /// A specification for a group of shares within an SSKR split.
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct GroupSpec {
     member_threshold: usize,
     member_count: usize,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Vec::is_empty"))]
+    weights: Vec<usize>,
 }
 
 impl GroupSpec {
@@ -82,18 +487,61 @@ impl GroupSpec {
     ///
     /// Returns an error if the member count is zero, if the member count is
     /// greater than the maximum share count, or if the member threshold is
-    /// greater than the member count.
+    /// zero or greater than the member count.
     pub fn new(member_threshold: usize, member_count: usize) -> Result<Self, SSKRError> {
-        if member_count == 0 {
-            return Err(SSKRError::MemberCountInvalid);
+        Self::new_with_version(member_threshold, member_count, ShareVersion::V0)
+    }
+
+    /// Creates a new `GroupSpec` instance for a split in the given share
+    /// format.
+    ///
+    /// This is `new` with the bounds of `version` in place of the standard
+    /// ones, so an extended-format (`ShareVersion::V1`) group may have up to
+    /// `MAX_EXTENDED_SHARE_COUNT` members.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the member count is zero, if the member count is
+    /// greater than `version` allows, or if the member threshold is zero or
+    /// greater than the member count.
+    pub fn new_with_version(
+        member_threshold: usize,
+        member_count: usize,
+        version: ShareVersion
+    ) -> Result<Self, SSKRError> {
+        if member_count == 0 || member_count > version.max_share_count() {
+            return Err(SSKRError::MemberCountInvalid { count: member_count, max: version.max_share_count() });
         }
-        if member_count > MAX_SHARE_COUNT {
-            return Err(SSKRError::MemberCountInvalid);
+        if member_threshold == 0 || member_threshold > member_count {
+            return Err(SSKRError::MemberThresholdInvalid { threshold: member_threshold, count: member_count });
         }
-        if member_threshold > member_count {
-            return Err(SSKRError::MemberThresholdInvalid);
+        Ok(Self { member_threshold, member_count, weights: Vec::new() })
+    }
+
+    /// Creates a new `GroupSpec` instance whose member shares are held by
+    /// holders of the given weights.
+    ///
+    /// A holder of weight `n` is given `n` shares, which count as `n` votes
+    /// towards `member_threshold`. The member count is the total weight, and
+    /// each holder's shares have consecutive member indexes, in the order of
+    /// `weights`, as [`holder_layout`](Self::holder_layout) describes. The
+    /// shares themselves are ordinary shares; the weights are not recorded in
+    /// them.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SSKRError::HolderWeightInvalid` if a weight is zero, or is
+    /// the member threshold or more so that its holder could recover the
+    /// group alone, and otherwise the same errors as `new` for the member
+    /// threshold and the total weight.
+    pub fn with_weights(member_threshold: usize, weights: &[usize]) -> Result<Self, SSKRError> {
+        let member_count = weights.iter().try_fold(0usize, |total, &weight| total.checked_add(weight)).unwrap_or(usize::MAX);
+        let mut group = Self::new(member_threshold, member_count)?;
+        if let Some((holder_index, &weight)) = weights.iter().enumerate().find(|&(_, &weight)| weight == 0 || weight >= member_threshold) {
+            return Err(SSKRError::HolderWeightInvalid { holder_index, weight, member_threshold });
         }
-        Ok(Self { member_threshold, member_count })
+        group.weights = weights.to_vec();
+        Ok(group)
     }
 
     /// Returns the member share threshold for this group.
@@ -106,8 +554,34 @@ impl GroupSpec {
         self.member_count
     }
 
-    /// Parses a group specification from a string.
+    /// Returns the weights of the group's holders, or `None` if each member
+    /// share has its own holder.
+    pub fn weights(&self) -> Option<&[usize]> {
+        (!self.weights.is_empty()).then_some(self.weights.as_slice())
+    }
+
+    /// Returns which member shares of this group go to which holder.
+    pub fn holder_layout(&self) -> HolderLayout {
+        match self.weights() {
+            Some(weights) => HolderLayout::from_weights(weights),
+            None => HolderLayout::from_weights(&vec![1; self.member_count]),
+        }
+    }
+
+    /// Parses a group specification written `M-of-N`, such as `2-of-3`,
+    /// with no whitespace.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SSKRError::GroupSpecInvalid` if the string is not of this
+    /// form, and otherwise the errors of `GroupSpec::new` for the counts it
+    /// gives.
     pub fn parse(s: &str) -> Result<Self, SSKRError> {
+        Self::parse_with_version(s, ShareVersion::V0)
+    }
+
+    /// Parses a group as `parse` does, with the bounds of `version`.
+    fn parse_with_version(s: &str, version: ShareVersion) -> Result<Self, SSKRError> {
         let parts: Vec<&str> = s.split('-').collect();
         if parts.len() != 3 {
             return Err(SSKRError::GroupSpecInvalid);
@@ -117,7 +591,7 @@ impl GroupSpec {
             return Err(SSKRError::GroupSpecInvalid);
         }
         let member_count = parts[2].parse::<usize>().map_err(|_| SSKRError::GroupSpecInvalid)?;
-        Self::new(member_threshold, member_count)
+        Self::new_with_version(member_threshold, member_count, version)
     }
 }
 
@@ -132,3 +606,107 @@ impl std::fmt::Display for GroupSpec {
         write!(f, "{}-of-{}", self.member_threshold, self.member_count)
     }
 }
+
+/// A property of a split that the shares passed to
+/// [`sskr_combine_with_spec`](crate::sskr_combine_with_spec) are checked
+/// against, as reported by `SSKRError::SpecMismatch`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize), serde(rename_all = "snake_case"))]
+pub enum SpecField {
+    /// The number of groups in the split.
+    GroupCount,
+    /// The number of groups needed to recover the secret.
+    GroupThreshold,
+    /// The number of member shares needed to recover a group.
+    MemberThreshold { group_index: usize },
+}
+
+impl std::fmt::Display for SpecField {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SpecField::GroupCount => write!(f, "group count"),
+            SpecField::GroupThreshold => write!(f, "group threshold"),
+            SpecField::MemberThreshold { group_index } => write!(f, "member threshold of group {group_index}"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MAX_EXTENDED_GROUPS_COUNT;
+
+    #[test]
+    fn test_parse_spec() {
+        let spec = Spec::parse("2 of [2-of-3, 3-of-5]").unwrap();
+        assert_eq!(spec, Spec::new(2, vec![GroupSpec::new(2, 3).unwrap(), GroupSpec::new(3, 5).unwrap()]).unwrap());
+        assert_eq!("  2of[ 2-of-3 ,3-of-5 ]\n".parse::<Spec>().unwrap(), spec);
+        assert_eq!("2-of-3".parse::<Spec>().unwrap(), Spec::new(1, vec![GroupSpec::new(2, 3).unwrap()]).unwrap());
+        assert_eq!(Spec::parse("1 of [1-of-1]").unwrap(), Spec::new(1, vec![GroupSpec::default()]).unwrap());
+
+        let groups = vec!["1-of-16"; 16].join(", ");
+        let spec = Spec::parse(&format!("16 of [{groups}]")).unwrap();
+        assert_eq!((spec.group_threshold(), spec.group_count(), spec.share_count()), (16, 16, 256));
+
+        // Counts beyond the standard format parse with the extended bounds.
+        let spec = Spec::parse(&format!("1 of [{groups}, 1-of-20]")).unwrap();
+        assert_eq!((spec.group_count(), spec.groups()[16].member_count()), (17, 20));
+        assert!(matches!(spec.validate_for_version(ShareVersion::V0), Err(SSKRError::GroupCountInvalid)));
+        let groups = vec!["1-of-1"; MAX_EXTENDED_GROUPS_COUNT + 1].join(", ");
+        assert!(matches!(Spec::parse(&format!("1 of [{groups}]")), Err(SSKRError::GroupCountInvalid)));
+        assert!(matches!(Spec::parse("1-of-255"), Err(SSKRError::MemberCountInvalid { count: 255, max: 254 })));
+    }
+
+    #[test]
+    fn test_display_spec() {
+        let cases = [
+            (Spec::new(2, vec![GroupSpec::new(2, 3).unwrap(), GroupSpec::new(3, 5).unwrap()]).unwrap(), "2 of [2-of-3, 3-of-5]"),
+            (Spec::new(1, vec![GroupSpec::new(2, 3).unwrap()]).unwrap(), "2-of-3"),
+            (Spec::new(1, vec![GroupSpec::default()]).unwrap(), "1-of-1"),
+            (Spec::new(1, vec![GroupSpec::new(1, 2).unwrap(), GroupSpec::new(2, 2).unwrap()]).unwrap(), "1 of [1-of-2, 2-of-2]"),
+            (
+                Spec::new(16, vec![GroupSpec::new(16, 16).unwrap(); 16]).unwrap(),
+                "16 of [16-of-16, 16-of-16, 16-of-16, 16-of-16, 16-of-16, 16-of-16, 16-of-16, 16-of-16, \
+                 16-of-16, 16-of-16, 16-of-16, 16-of-16, 16-of-16, 16-of-16, 16-of-16, 16-of-16]",
+            ),
+            (
+                Spec::new_with_version(2, vec![GroupSpec::new_with_version(3, 20, ShareVersion::V1).unwrap(); 17], ShareVersion::V1).unwrap(),
+                "2 of [3-of-20, 3-of-20, 3-of-20, 3-of-20, 3-of-20, 3-of-20, 3-of-20, 3-of-20, 3-of-20, \
+                 3-of-20, 3-of-20, 3-of-20, 3-of-20, 3-of-20, 3-of-20, 3-of-20, 3-of-20]",
+            ),
+        ];
+        for (spec, expected) in cases {
+            assert_eq!(spec.to_string(), expected);
+            assert_eq!(Spec::parse(expected).unwrap(), spec);
+        }
+    }
+
+    #[test]
+    fn test_parse_spec_errors() {
+        let error = |s: &str| match Spec::parse(s) {
+            Err(SSKRError::SpecParseFailed { position, expected }) => (position, expected),
+            other => panic!("{s:?} parsed as {other:?}"),
+        };
+        assert_eq!(error(""), (0, "a number"));
+        assert_eq!(error("two of [2-of-3]"), (0, "a number"));
+        assert_eq!(error("2 [2-of-3]"), (2, "\"of\""));
+        assert_eq!(error("2 of 2-of-3"), (5, "\"[\""));
+        assert_eq!(error("2 of [2-of-3 3-of-5]"), (13, "\",\" or \"]\""));
+        assert_eq!(error("2 of [2-of-3, ]"), (14, "a group such as \"2-of-3\""));
+        assert_eq!(error("2 of [2-3]"), (6, "a group such as \"2-of-3\""));
+        assert_eq!(error("2-of-3 extra"), (7, "the end of the spec"));
+        assert_eq!(error("99999999999999999999999-of-3"), (0, "a group such as \"2-of-3\""));
+
+        // Groups are read as `GroupSpec::parse` reads them, so whitespace
+        // within a group is rejected by both.
+        assert!(matches!(GroupSpec::parse("2 - of - 3"), Err(SSKRError::GroupSpecInvalid)));
+        assert_eq!(error("2 of [2 - of - 3]"), (6, "a group such as \"2-of-3\""));
+        assert_eq!(error("2 - of - 3"), (2, "\"of\""));
+        assert_eq!(SSKRError::SpecParseFailed { position: 2, expected: "\"[\"" }.to_string(), "Invalid split specification: expected \"[\" at position 2");
+
+        // A well-formed spec must still be a valid one.
+        assert!(matches!(Spec::parse("3 of [2-of-3, 3-of-5]"), Err(SSKRError::GroupThresholdInvalid { threshold: 3, group_count: 2 })));
+        assert!(matches!(Spec::parse("0 of [2-of-3]"), Err(SSKRError::GroupThresholdInvalid { threshold: 0, group_count: 1 })));
+        assert!(matches!(Spec::parse("1 of [4-of-3]"), Err(SSKRError::MemberThresholdInvalid { threshold: 4, count: 3 })));
+    }
+}
@@ -0,0 +1,193 @@
+use std::fmt;
+
+use crate::{GroupSpec, SSKRError, ShareVersion, Spec};
+
+/// A builder for a [`Spec`], which takes its groups first and its group
+/// threshold by name, and reports every problem with them at once.
+///
+/// ```
+/// # use sskr::{GroupSpec, Spec, SpecBuilder};
+/// let spec = SpecBuilder::new()
+///     .named_group("family", 2, 3)
+///     .named_group("friends", 3, 5)
+///     .group_threshold(1)
+///     .build()
+///     .unwrap();
+/// assert_eq!(spec, Spec::new(1, vec![GroupSpec::new(2, 3).unwrap(), GroupSpec::new(3, 5).unwrap()]).unwrap());
+/// ```
+///
+/// Nothing is checked until [`build`](Self::build). Group names are only
+/// used to label the problems it reports; they are not part of the spec, and
+/// are not recorded in the shares.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SpecBuilder {
+    group_threshold: Option<usize>,
+    groups: Vec<(Option<String>, usize, usize)>,
+}
+
+impl SpecBuilder {
+    /// Creates a builder with no groups and no group threshold.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns this builder with a group of `member_count` members, of whom
+    /// `member_threshold` are needed to recover it, added after the others.
+    pub fn group(mut self, member_threshold: usize, member_count: usize) -> Self {
+        self.groups.push((None, member_threshold, member_count));
+        self
+    }
+
+    /// Returns this builder with a group added as [`group`](Self::group)
+    /// adds it, labelled `name` in the problems `build` reports.
+    pub fn named_group(mut self, name: impl Into<String>, member_threshold: usize, member_count: usize) -> Self {
+        self.groups.push((Some(name.into()), member_threshold, member_count));
+        self
+    }
+
+    /// Returns this builder with the number of groups needed to recover the
+    /// secret set to `group_threshold`. If it is never set, every group is
+    /// needed.
+    pub fn group_threshold(mut self, group_threshold: usize) -> Self {
+        self.group_threshold = Some(group_threshold);
+        self
+    }
+
+    /// Builds the spec, checking every group and the group threshold.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SSKRError::SpecBuildFailed` with a [`SpecProblem`] for each
+    /// of the errors `GroupSpec::new` gives for a group, in order of group
+    /// index, followed by those `Spec::new` gives for the group threshold and
+    /// group count.
+    pub fn build(&self) -> Result<Spec, SSKRError> {
+        let mut problems = Vec::new();
+        let mut groups = Vec::with_capacity(self.groups.len());
+        for (group_index, (name, member_threshold, member_count)) in self.groups.iter().enumerate() {
+            match GroupSpec::new(*member_threshold, *member_count) {
+                Ok(group) => groups.push(group),
+                Err(error) => problems.push(SpecProblem { group_index: Some(group_index), group_name: name.clone(), error }),
+            }
+        }
+
+        let group_count = self.groups.len();
+        let group_threshold = self.group_threshold.unwrap_or(group_count);
+        if group_threshold == 0 || group_threshold > group_count {
+            let error = SSKRError::GroupThresholdInvalid { threshold: group_threshold, group_count };
+            problems.push(SpecProblem { group_index: None, group_name: None, error });
+        }
+        if group_count > ShareVersion::V0.max_groups_count() {
+            problems.push(SpecProblem { group_index: None, group_name: None, error: SSKRError::GroupCountInvalid });
+        }
+
+        if !problems.is_empty() {
+            return Err(SSKRError::SpecBuildFailed { problems });
+        }
+        Spec::new(group_threshold, groups)
+    }
+}
+
+/// One of the problems with a spec that [`SpecBuilder::build`] reports in
+/// `SSKRError::SpecBuildFailed`.
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct SpecProblem {
+    group_index: Option<usize>,
+    group_name: Option<String>,
+    error: SSKRError,
+}
+
+impl SpecProblem {
+    /// Returns the index of the group with the problem, or `None` if the
+    /// problem is with the spec as a whole.
+    pub fn group_index(&self) -> Option<usize> {
+        self.group_index
+    }
+
+    /// Returns the name of the group with the problem, if it was added with
+    /// [`SpecBuilder::named_group`].
+    pub fn group_name(&self) -> Option<&str> {
+        self.group_name.as_deref()
+    }
+
+    /// Returns the error the problem would be reported as on its own.
+    pub fn error(&self) -> &SSKRError {
+        &self.error
+    }
+}
+
+/// Names the group, if any, by its name or one-based number, followed by the
+/// error.
+impl fmt::Display for SpecProblem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (&self.group_name, self.group_index) {
+            (Some(name), _) => write!(f, "group \"{}\": {}", name, self.error),
+            (None, Some(group_index)) => write!(f, "group {}: {}", group_index + 1, self.error),
+            (None, None) => write!(f, "{}", self.error),
+        }
+    }
+}
+
+/// Joins the descriptions of `problems` for the message of
+/// `SSKRError::SpecBuildFailed`.
+pub(crate) fn describe_problems(problems: &[SpecProblem]) -> String {
+    problems.iter().map(ToString::to_string).collect::<Vec<_>>().join("; ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_build_spec() {
+        let spec = SpecBuilder::new().group(2, 3).group(3, 5).group_threshold(1).build().unwrap();
+        assert_eq!(spec.to_string(), "1 of [2-of-3, 3-of-5]");
+        // Without a group threshold, every group is needed.
+        let spec = SpecBuilder::new().group(2, 3).named_group("friends", 3, 5).build().unwrap();
+        assert_eq!(spec.to_string(), "2 of [2-of-3, 3-of-5]");
+        let spec = SpecBuilder::new().group(1, 1).build().unwrap();
+        assert_eq!(spec.to_string(), "1-of-1");
+    }
+
+    #[test]
+    fn test_build_reports_every_problem() {
+        let builder = SpecBuilder::new()
+            .named_group("family", 4, 3)
+            .group(2, 3)
+            .group(1, 17)
+            .group_threshold(4);
+        let Err(SSKRError::SpecBuildFailed { problems }) = builder.build() else {
+            panic!("expected SpecBuildFailed");
+        };
+        assert_eq!(problems.len(), 3);
+        assert_eq!((problems[0].group_index(), problems[0].group_name()), (Some(0), Some("family")));
+        assert!(matches!(problems[0].error(), SSKRError::MemberThresholdInvalid { threshold: 4, count: 3 }));
+        assert_eq!((problems[1].group_index(), problems[1].group_name()), (Some(2), None));
+        assert!(matches!(problems[1].error(), SSKRError::MemberCountInvalid { count: 17, max: 16 }));
+        assert_eq!(problems[2].group_index(), None);
+        assert!(matches!(problems[2].error(), SSKRError::GroupThresholdInvalid { threshold: 4, group_count: 3 }));
+
+        let error = builder.build().unwrap_err();
+        assert_eq!(error.code(), "spec_build_failed");
+        assert_eq!(
+            error.to_string(),
+            format!(
+                "Invalid split specification: group \"family\": {}; group 3: {}; {}",
+                problems[0].error(),
+                problems[1].error(),
+                problems[2].error()
+            )
+        );
+
+        let Err(SSKRError::SpecBuildFailed { problems }) = SpecBuilder::new().build() else {
+            panic!("expected SpecBuildFailed");
+        };
+        assert!(matches!(problems[..], [SpecProblem { error: SSKRError::GroupThresholdInvalid { threshold: 0, group_count: 0 }, .. }]));
+
+        let Err(SSKRError::SpecBuildFailed { problems }) = (0..17).fold(SpecBuilder::new(), |builder, _| builder.group(1, 1)).build() else {
+            panic!("expected SpecBuildFailed");
+        };
+        assert!(matches!(problems[..], [SpecProblem { error: SSKRError::GroupCountInvalid, .. }]));
+    }
+}
@@ -0,0 +1,190 @@
+use bc_rand::RandomNumberGenerator;
+
+use crate::{
+    GroupSecret,
+    GroupSpec,
+    SSKRError,
+    SSKRShare,
+    Secret,
+    ShareValue,
+    ShareVersion,
+    Spec,
+    SplitMetadata,
+    encoding::split_secret,
+    seeded_rng::SeededRandomNumberGenerator,
+};
+
+/// The identifier and parameters of a split whose member shares are produced
+/// group by group with [`sskr_split_group_members`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct SplitHeader {
+    identifier: u32,
+    metadata: SplitMetadata,
+}
+
+impl SplitHeader {
+    /// Creates the header of the split with the given identifier and
+    /// metadata, such as one recorded from [`sskr_split_groups`].
+    ///
+    /// # Errors
+    ///
+    /// Returns `SSKRError::IdentifierInvalid` if `identifier` does not fit in
+    /// the identifier field of the metadata's format.
+    pub fn new(identifier: u32, metadata: SplitMetadata) -> Result<Self, SSKRError> {
+        metadata.version().check_identifier(identifier)?;
+        Ok(Self { identifier, metadata })
+    }
+
+    /// Returns the identifier of the split.
+    pub fn identifier(&self) -> u32 {
+        self.identifier
+    }
+
+    /// Returns the metadata of the split.
+    pub fn metadata(&self) -> &SplitMetadata {
+        &self.metadata
+    }
+}
+
+/// Performs the first stage of a two-stage split: splits `master_secret` into
+/// the group secrets of `spec`, fixing the split's identifier and metadata.
+///
+/// Each group secret can later be split into its group's member shares with
+/// [`sskr_split_group_members`], independently and with a different random
+/// number generator. The resulting shares are standard-format shares that
+/// combine with `sskr_combine` exactly as those from `sskr_generate` do.
+/// Performing both stages in order with one random number generator gives
+/// the shares `sskr_generate_using` returns with it.
+///
+/// Every group secret must be protected as carefully as the master secret
+/// until it has been split.
+///
+/// # Errors
+///
+/// Returns the same errors as `sskr_generate`.
+pub fn sskr_split_groups<R>(
+    spec: &Spec,
+    master_secret: &Secret,
+    random_generator: &mut R
+) -> Result<(SplitHeader, Vec<GroupSecret>), SSKRError>
+where
+    R: RandomNumberGenerator
+{
+    let version = ShareVersion::V0;
+    spec.validate_for_version(version)?;
+
+    // assign a random identifier
+    let mut identifier = [0u8; 2];
+    random_generator.fill_random_data(&mut identifier);
+    let identifier = u16::from_be_bytes(identifier) as u32;
+
+    let group_secrets = split_secret(version, spec.group_threshold(), spec.group_count(), master_secret.data(), random_generator)?
+        .into_iter()
+        .map(GroupSecret::new)
+        .collect();
+    let header = SplitHeader { identifier, metadata: SplitMetadata::from_spec(spec, version)? };
+    Ok((header, group_secrets))
+}
+
+/// Performs the second stage of a two-stage split for one group: splits
+/// `group_secret` into the serialized member shares of group `group_index`
+/// of the split described by `header`, as `group_spec` specifies.
+///
+/// # Errors
+///
+/// Returns `SSKRError::GroupSecretIndexInvalid` if `group_index` is not a
+/// group of the split, `SSKRError::MemberCountInvalid` if `group_spec` has
+/// more members than the split's format allows, and `SSKRError::ShamirError`
+/// if `group_secret` cannot be split.
+pub fn sskr_split_group_members<R>(
+    header: &SplitHeader,
+    group_index: usize,
+    group_spec: &GroupSpec,
+    group_secret: &GroupSecret,
+    random_generator: &mut R
+) -> Result<Vec<Vec<u8>>, SSKRError>
+where
+    R: RandomNumberGenerator
+{
+    let metadata = header.metadata();
+    let version = metadata.version();
+    if group_index >= metadata.group_count() {
+        return Err(SSKRError::GroupSecretIndexInvalid { group_index, group_count: metadata.group_count() });
+    }
+    if group_spec.member_count() > version.max_share_count() {
+        return Err(SSKRError::MemberCountInvalid { count: group_spec.member_count(), max: version.max_share_count() });
+    }
+
+    let mut group_rng = SeededRandomNumberGenerator::from_rng(random_generator);
+    let member_secrets = split_secret(version, group_spec.member_threshold(), group_spec.member_count(), group_secret.data(), &mut group_rng)?;
+    let shares = member_secrets
+        .into_iter()
+        .enumerate()
+        .map(|(member_index, member_secret)| {
+            SSKRShare::new(
+                header.identifier(),
+                group_index,
+                metadata.group_threshold(),
+                metadata.group_count(),
+                member_index,
+                group_spec.member_threshold(),
+                ShareValue::new(member_secret),
+            ).with_version(version).serialize()
+        })
+        .collect();
+    Ok(shares)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{sskr_combine, sskr_generate_using, test_support};
+    use bc_rand::RandomNumberGenerator;
+    
+
+    #[test]
+    fn test_two_stage_split() {
+        let secret = test_support::secret();
+        let spec = Spec::new(2, vec![GroupSpec::new(2, 3).unwrap(), GroupSpec::new(3, 5).unwrap()]).unwrap();
+
+        // Both stages with one generator give the shares of a single split.
+        let mut rng = bc_rand::make_fake_random_number_generator();
+        let (header, group_secrets) = sskr_split_groups(&spec, &secret, &mut rng).unwrap();
+        let shares: Vec<Vec<Vec<u8>>> = spec.groups().iter().zip(&group_secrets).enumerate()
+            .map(|(group_index, (group_spec, group_secret))| {
+                sskr_split_group_members(&header, group_index, group_spec, group_secret, &mut rng).unwrap()
+            })
+            .collect();
+        assert_eq!(shares, sskr_generate_using(&spec, &secret, &mut bc_rand::make_fake_random_number_generator()).unwrap());
+
+        // Each group split later with its own generator.
+        let (header, group_secrets) = sskr_split_groups(&spec, &secret, &mut bc_rand::SecureRandomNumberGenerator).unwrap();
+        let mut first_rng = bc_rand::make_fake_random_number_generator();
+        let first = sskr_split_group_members(&header, 0, &spec.groups()[0], &group_secrets[0], &mut first_rng).unwrap();
+        let mut second_rng = bc_rand::make_fake_random_number_generator();
+        second_rng.random_data(5);
+        let second = sskr_split_group_members(&header, 1, &spec.groups()[1], &group_secrets[1], &mut second_rng).unwrap();
+        assert_eq!(sskr_combine(&[&first[2], &second[4], &first[0], &second[1], &second[2]]).unwrap(), secret);
+
+        assert!(matches!(
+            sskr_split_group_members(&header, 2, &spec.groups()[0], &group_secrets[0], &mut second_rng),
+            Err(SSKRError::GroupSecretIndexInvalid { group_index: 2, group_count: 2 })
+        ));
+        let wide = GroupSpec::new_with_version(1, 17, ShareVersion::V1).unwrap();
+        assert!(matches!(
+            sskr_split_group_members(&header, 0, &wide, &group_secrets[0], &mut second_rng),
+            Err(SSKRError::MemberCountInvalid { count: 17, max: 16 })
+        ));
+    }
+
+    #[test]
+    fn test_split_header() {
+        let metadata = SplitMetadata::new(ShareVersion::V0, 1, 2).unwrap();
+        let header = SplitHeader::new(0xabcd, metadata).unwrap();
+        assert_eq!((header.identifier(), header.metadata()), (0xabcd, &metadata));
+        assert!(matches!(SplitHeader::new(0x1_0000, metadata), Err(SSKRError::IdentifierInvalid { .. })));
+        let metadata = SplitMetadata::new(ShareVersion::V2, 1, 2).unwrap();
+        assert!(SplitHeader::new(u32::MAX, metadata).is_ok());
+    }
+}
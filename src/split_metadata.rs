@@ -0,0 +1,101 @@
+use crate::{SSKRError, Spec, ShareVersion, encoding::deserialize_lone_share};
+
+/// The parameters of a split needed to recover its master secret from group
+/// secrets, as taken by
+/// [`sskr_combine_from_group_secrets`](crate::sskr_combine_from_group_secrets).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct SplitMetadata {
+    version: ShareVersion,
+    group_threshold: usize,
+    group_count: usize,
+}
+
+impl SplitMetadata {
+    /// Creates the metadata of a split of `group_count` groups in the format
+    /// `version`, of which `group_threshold` are needed to recover the
+    /// secret.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SSKRError::GroupCountInvalid` if `group_count` is zero or
+    /// more than `version` allows, and `SSKRError::GroupThresholdInvalid` if
+    /// `group_threshold` is zero or more than `group_count`.
+    pub fn new(version: ShareVersion, group_threshold: usize, group_count: usize) -> Result<Self, SSKRError> {
+        if group_count == 0 || group_count > version.max_groups_count() {
+            return Err(SSKRError::GroupCountInvalid);
+        }
+        if group_threshold == 0 || group_threshold > group_count {
+            return Err(SSKRError::GroupThresholdInvalid { threshold: group_threshold, group_count });
+        }
+        Ok(Self { version, group_threshold, group_count })
+    }
+
+    /// Creates the metadata of the split described by `spec`, in the format
+    /// `version`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SSKRError::GroupCountInvalid` if `spec` has more groups than
+    /// `version` allows.
+    pub fn from_spec(spec: &Spec, version: ShareVersion) -> Result<Self, SSKRError> {
+        Self::new(version, spec.group_threshold(), spec.group_count())
+    }
+
+    /// Reads the metadata of the split that the serialized share `share`
+    /// belongs to. Any share of the split will do.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `share` is not a well-formed SSKR share.
+    pub fn from_share(share: &[u8]) -> Result<Self, SSKRError> {
+        let share = deserialize_lone_share(share)?;
+        Ok(Self {
+            version: share.version(),
+            group_threshold: share.group_threshold(),
+            group_count: share.group_count(),
+        })
+    }
+
+    /// Returns the format of the split's shares.
+    pub fn version(&self) -> ShareVersion {
+        self.version
+    }
+
+    /// Returns the number of groups needed to recover the secret.
+    pub fn group_threshold(&self) -> usize {
+        self.group_threshold
+    }
+
+    /// Returns the number of groups in the split.
+    pub fn group_count(&self) -> usize {
+        self.group_count
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{GroupSpec, Secret, sskr_generate_using};
+
+    #[test]
+    fn test_split_metadata() {
+        let spec = Spec::new(2, vec![GroupSpec::new(2, 3).unwrap(), GroupSpec::new(1, 1).unwrap(), GroupSpec::new(1, 1).unwrap()]).unwrap();
+        let metadata = SplitMetadata::from_spec(&spec, ShareVersion::V0).unwrap();
+        assert_eq!((metadata.version(), metadata.group_threshold(), metadata.group_count()), (ShareVersion::V0, 2, 3));
+
+        let secret = Secret::new([0x5a; 16]).unwrap();
+        let shares = sskr_generate_using(&spec, &secret, &mut bc_rand::make_fake_random_number_generator()).unwrap();
+        assert_eq!(SplitMetadata::from_share(&shares[1][0]).unwrap(), metadata);
+        assert!(SplitMetadata::from_share(&shares[1][0][..4]).is_err());
+
+        assert!(matches!(SplitMetadata::new(ShareVersion::V0, 1, 0), Err(SSKRError::GroupCountInvalid)));
+        assert!(matches!(SplitMetadata::new(ShareVersion::V0, 1, 17), Err(SSKRError::GroupCountInvalid)));
+        assert!(SplitMetadata::new(ShareVersion::V1, 1, 17).is_ok());
+        assert!(matches!(
+            SplitMetadata::new(ShareVersion::V0, 4, 3),
+            Err(SSKRError::GroupThresholdInvalid { threshold: 4, group_count: 3 })
+        ));
+        assert!(SplitMetadata::new(ShareVersion::V0, 0, 3).is_err());
+    }
+}
@@ -0,0 +1,120 @@
+/// The shape of the shares `sskr_generate` would give for a spec and a
+/// secret length, as returned by [`Spec::plan`](crate::Spec::plan).
+///
+/// Shares are numbered in flat order, as `sskr_generate` returns them once
+/// its groups are concatenated: all of the first group's members in member
+/// order, then all of the second's, and so on.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct SplitPlan {
+    secret_len: usize,
+    share_size_bytes: usize,
+    shares_per_group: Vec<usize>,
+    group_starts: Vec<usize>,
+}
+
+impl SplitPlan {
+    pub(crate) fn new(secret_len: usize, share_size_bytes: usize, shares_per_group: Vec<usize>) -> Self {
+        let mut start = 0;
+        let group_starts = shares_per_group
+            .iter()
+            .map(|&count| {
+                let group_start = start;
+                start += count;
+                group_start
+            })
+            .collect();
+        Self { secret_len, share_size_bytes, shares_per_group, group_starts }
+    }
+
+    /// Returns the length of the secret the plan was made for.
+    pub fn secret_len(&self) -> usize {
+        self.secret_len
+    }
+
+    /// Returns the size of each serialized share: its metadata header and a
+    /// value as long as the secret.
+    pub fn share_size_bytes(&self) -> usize {
+        self.share_size_bytes
+    }
+
+    /// Returns the number of shares across all groups.
+    pub fn total_shares(&self) -> usize {
+        self.shares_per_group.iter().sum()
+    }
+
+    /// Returns the number of groups.
+    pub fn group_count(&self) -> usize {
+        self.shares_per_group.len()
+    }
+
+    /// Returns the number of shares in each group.
+    pub fn shares_per_group(&self) -> &[usize] {
+        &self.shares_per_group
+    }
+
+    /// Returns the flat index of the share at `member_index` of the group at
+    /// `group_index`, or `None` if there is no such share.
+    pub fn flat_index(&self, group_index: usize, member_index: usize) -> Option<usize> {
+        let &count = self.shares_per_group.get(group_index)?;
+        (member_index < count).then(|| self.group_starts[group_index] + member_index)
+    }
+
+    /// Returns the group index and member index of the share at
+    /// `flat_index`, or `None` if there is no such share.
+    pub fn position(&self, flat_index: usize) -> Option<(usize, usize)> {
+        if flat_index >= self.total_shares() {
+            return None;
+        }
+        // Empty groups can't occur, so the last group starting at or before
+        // the index holds it.
+        let group_index = self.group_starts.partition_point(|&start| start <= flat_index) - 1;
+        Some((group_index, flat_index - self.group_starts[group_index]))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{GroupSpec, SSKRError, Secret, Spec, METADATA_SIZE_BYTES, sskr_generate_using};
+
+    #[test]
+    fn test_plan() {
+        let spec = Spec::new(2, vec![GroupSpec::new(2, 3).unwrap(), GroupSpec::new(1, 1).unwrap(), GroupSpec::new(3, 5).unwrap()]).unwrap();
+        for secret_len in [16, 32] {
+            let plan = spec.plan(secret_len).unwrap();
+            assert_eq!(plan.secret_len(), secret_len);
+            assert_eq!(plan.share_size_bytes(), secret_len + METADATA_SIZE_BYTES);
+            assert_eq!((plan.total_shares(), plan.group_count(), plan.shares_per_group()), (9, 3, &[3, 1, 5][..]));
+
+            let secret = Secret::new(vec![0x5a; secret_len]).unwrap();
+            let shares = sskr_generate_using(&spec, &secret, &mut bc_rand::make_fake_random_number_generator()).unwrap();
+            assert_eq!(shares.iter().map(Vec::len).collect::<Vec<_>>(), plan.shares_per_group());
+            let flat = shares.concat();
+            assert_eq!(flat.len(), plan.total_shares());
+            assert!(flat.iter().all(|share| share.len() == plan.share_size_bytes()));
+            for (group_index, group) in shares.iter().enumerate() {
+                for (member_index, share) in group.iter().enumerate() {
+                    let flat_index = plan.flat_index(group_index, member_index).unwrap();
+                    assert_eq!(&flat[flat_index], share);
+                    assert_eq!(plan.position(flat_index), Some((group_index, member_index)));
+                }
+            }
+        }
+
+        let plan = spec.plan(16).unwrap();
+        assert_eq!(plan.flat_index(0, 3), None);
+        assert_eq!(plan.flat_index(3, 0), None);
+        assert_eq!(plan.position(9), None);
+    }
+
+    #[test]
+    fn test_plan_errors() {
+        let spec = Spec::new(1, vec![GroupSpec::new(2, 3).unwrap()]).unwrap();
+        assert!(matches!(spec.plan(17), Err(SSKRError::SecretLengthNotEven { len: 17 })));
+        assert!(matches!(spec.plan(14), Err(SSKRError::SecretTooShort { len: 14, .. })));
+        assert!(matches!(spec.plan(34), Err(SSKRError::SecretTooLong { len: 34, .. })));
+
+        let extended = Spec::new_with_version(1, vec![GroupSpec::new(1, 1).unwrap(); 17], crate::ShareVersion::V1).unwrap();
+        assert!(matches!(extended.plan(16), Err(SSKRError::GroupCountInvalid)));
+    }
+}
@@ -0,0 +1,258 @@
+use bc_crypto::hash::crc32_data;
+
+use crate::{
+    MIN_SERIALIZE_SIZE_BYTES,
+    SSKRError,
+    Secret,
+    ShareBytes,
+    checkwords::BYTEWORDS,
+    sskr_combine,
+};
+
+/// The CBOR tags that mark a byte string as an SSKR share: the registered tag
+/// and the older one it replaced.
+const SSKR_CBOR_TAGS: [[u8; 3]; 2] = [[0xd9, 0x9d, 0x75], [0xd9, 0x01, 0x35]];
+
+/// A text encoding of a serialized share, as recognized by
+/// [`sskr_combine_strings`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize), serde(rename_all = "snake_case"))]
+pub enum ShareEncoding {
+    /// The share bytes as hex, in either case.
+    Hex,
+    /// A single-part `ur:sskr/` UR.
+    Ur,
+    /// Bytewords in the standard, URI or minimal style.
+    Bytewords,
+}
+
+impl std::fmt::Display for ShareEncoding {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ShareEncoding::Hex => write!(f, "hex"),
+            ShareEncoding::Ur => write!(f, "a UR"),
+            ShareEncoding::Bytewords => write!(f, "Bytewords"),
+        }
+    }
+}
+
+/// Combines shares given as strings, detecting the encoding of each.
+///
+/// Each input is trimmed of surrounding whitespace and then decoded as:
+///
+/// - a UR, if it starts with `ur:` in any case. Only single-part `ur:sskr/`
+///   URs are accepted.
+/// - hex, if it is entirely hex digits and long enough to hold a share. An
+///   input that looks like hex but does not decode to a share is then tried
+///   as Bytewords, since minimal Bytewords can consist only of hex digits.
+/// - Bytewords otherwise, in the standard (space-separated), URI
+///   (hyphen-separated) or minimal style. The Bytewords checksum must match.
+///
+/// Bytewords may hold the share bytes or, as tools that also write URs
+/// produce, the share as a CBOR byte string with or without its SSKR tag.
+/// Encodings may be mixed freely within one call. The decoded shares are
+/// combined as `sskr_combine` combines them, and a share index in an error
+/// is the position of the input.
+///
+/// # Errors
+///
+/// Returns `SSKRError::ShareStringInvalid` for the first input that cannot
+/// be decoded, listing the encodings tried, and otherwise the same errors as
+/// `sskr_combine`.
+pub fn sskr_combine_strings(inputs: &[&str]) -> Result<Secret, SSKRError> {
+    let shares = inputs
+        .iter()
+        .enumerate()
+        .map(|(input_index, input)| decode_share_string(input, input_index))
+        .collect::<Result<Vec<_>, _>>()?;
+    sskr_combine(&shares)
+}
+
+/// Decodes one input of [`sskr_combine_strings`].
+fn decode_share_string(input: &str, input_index: usize) -> Result<ShareBytes, SSKRError> {
+    let input = input.trim();
+    let attempts: &[ShareEncoding] = if has_ur_prefix(input) {
+        &[ShareEncoding::Ur]
+    } else if looks_like_hex(input) {
+        &[ShareEncoding::Hex, ShareEncoding::Bytewords]
+    } else {
+        &[ShareEncoding::Bytewords]
+    };
+    attempts
+        .iter()
+        .find_map(|encoding| {
+            let data = match encoding {
+                ShareEncoding::Hex => hex::decode(input).ok()?,
+                ShareEncoding::Ur => decode_ur(input)?,
+                ShareEncoding::Bytewords => {
+                    let data = decode_bytewords(input)?;
+                    match cbor_share_bytes(&data) {
+                        Some(share) => share.to_vec(),
+                        None => data,
+                    }
+                }
+            };
+            ShareBytes::new(data).ok()
+        })
+        .ok_or_else(|| SSKRError::ShareStringInvalid { input_index, attempted: attempts.to_vec() })
+}
+
+/// Returns the encodings tried for an input, as `SSKRError::ShareStringInvalid`
+/// displays them.
+pub(crate) fn describe_attempts(attempted: &[ShareEncoding]) -> String {
+    attempted.iter().map(ShareEncoding::to_string).collect::<Vec<_>>().join(" or ")
+}
+
+fn has_ur_prefix(input: &str) -> bool {
+    input.get(..3).is_some_and(|prefix| prefix.eq_ignore_ascii_case("ur:"))
+}
+
+fn looks_like_hex(input: &str) -> bool {
+    input.len() >= 2 * MIN_SERIALIZE_SIZE_BYTES
+        && input.len().is_multiple_of(2)
+        && input.bytes().all(|byte| byte.is_ascii_hexdigit())
+}
+
+/// Decodes a single-part `ur:sskr/` UR into the share it holds.
+fn decode_ur(input: &str) -> Option<Vec<u8>> {
+    let input = input.to_ascii_lowercase();
+    let body = input.strip_prefix("ur:sskr/")?;
+    if body.contains('/') {
+        return None;
+    }
+    let cbor = decode_bytewords(body)?;
+    cbor_share_bytes(&cbor).map(<[u8]>::to_vec)
+}
+
+/// Decodes Bytewords in any style, checking and removing the trailing
+/// checksum.
+fn decode_bytewords(input: &str) -> Option<Vec<u8>> {
+    let input = input.to_ascii_lowercase();
+    let bytes = if input.contains(|c: char| c == '-' || c.is_whitespace()) {
+        input
+            .split(|c: char| c == '-' || c.is_whitespace())
+            .filter(|word| !word.is_empty())
+            .map(|word| BYTEWORDS.iter().position(|&w| w == word).map(|byte| byte as u8))
+            .collect::<Option<Vec<u8>>>()?
+    } else {
+        if !input.is_ascii() || !input.len().is_multiple_of(2) {
+            return None;
+        }
+        input
+            .as_bytes()
+            .chunks_exact(2)
+            .map(|pair| {
+                BYTEWORDS
+                    .iter()
+                    .position(|w| w.as_bytes()[0] == pair[0] && w.as_bytes()[3] == pair[1])
+                    .map(|byte| byte as u8)
+            })
+            .collect::<Option<Vec<u8>>>()?
+    };
+    let (data, checksum) = bytes.split_at(bytes.len().checked_sub(4)?);
+    (crc32_data(data) == checksum).then(|| data.to_vec())
+}
+
+/// Returns the contents of `cbor` if it is exactly one CBOR byte string,
+/// optionally tagged as an SSKR share.
+fn cbor_share_bytes(cbor: &[u8]) -> Option<&[u8]> {
+    let cbor = SSKR_CBOR_TAGS
+        .iter()
+        .find_map(|tag| cbor.strip_prefix(tag.as_slice()))
+        .unwrap_or(cbor);
+    let (&head, rest) = cbor.split_first()?;
+    let (len, rest) = match head {
+        0x40..=0x57 => ((head - 0x40) as usize, rest),
+        0x58 => (*rest.first()? as usize, &rest[1..]),
+        0x59 => (u16::from_be_bytes([*rest.first()?, *rest.get(1)?]) as usize, &rest[2..]),
+        _ => return None,
+    };
+    (rest.len() == len).then_some(rest)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{GroupSpec, Spec, sskr_generate_using, test_support};
+    
+
+    fn bytewords(data: &[u8], separator: &str) -> String {
+        let mut data = data.to_vec();
+        data.extend_from_slice(&crc32_data(&data));
+        data.iter().map(|&byte| BYTEWORDS[byte as usize]).collect::<Vec<_>>().join(separator)
+    }
+
+    fn minimal_bytewords(data: &[u8]) -> String {
+        bytewords(data, " ").split(' ').map(|word| format!("{}{}", &word[..1], &word[3..])).collect()
+    }
+
+    fn cbor(share: &[u8], tag: Option<[u8; 3]>) -> Vec<u8> {
+        let mut cbor = tag.map(Vec::from).unwrap_or_default();
+        cbor.extend_from_slice(&[0x58, share.len() as u8]);
+        cbor.extend_from_slice(share);
+        cbor
+    }
+
+    #[test]
+    fn test_combine_strings() {
+        let secret = test_support::secret();
+        let spec = Spec::new(2, vec![GroupSpec::new(2, 3).unwrap(), GroupSpec::new(2, 2).unwrap()]).unwrap();
+        let shares = sskr_generate_using(&spec, &secret, &mut bc_rand::make_fake_random_number_generator()).unwrap();
+
+        let hex_share = format!("  {}\n", hex::encode_upper(&shares[0][0]));
+        let words_share = bytewords(&cbor(&shares[0][2], Some(SSKR_CBOR_TAGS[0])), " ").to_uppercase();
+        let ur_share = format!("UR:SSKR/{}", minimal_bytewords(&cbor(&shares[1][0], None)).to_uppercase());
+        let uri_share = bytewords(&shares[1][1], "-");
+        let inputs = [hex_share.as_str(), &words_share, &ur_share, &uri_share];
+        assert_eq!(sskr_combine_strings(&inputs).unwrap(), secret);
+
+        let minimal_share = minimal_bytewords(&cbor(&shares[1][1], Some(SSKR_CBOR_TAGS[1])));
+        let inputs = [hex_share.as_str(), &words_share, &ur_share, &minimal_share];
+        assert_eq!(sskr_combine_strings(&inputs).unwrap(), secret);
+
+        // Combining still reports missing shares by input position.
+        assert!(matches!(
+            sskr_combine_strings(&[&hex_share, &ur_share, &uri_share]),
+            Err(SSKRError::NotEnoughMemberShares { group_index: 0, .. })
+        ));
+    }
+
+    #[test]
+    fn test_combine_strings_errors() {
+        let secret = test_support::secret();
+        let spec = Spec::new(1, vec![GroupSpec::new(2, 3).unwrap()]).unwrap();
+        let shares = sskr_generate_using(&spec, &secret, &mut bc_rand::make_fake_random_number_generator()).unwrap();
+        let hex_share = hex::encode(&shares[0][0]);
+
+        // A corrupted checksum.
+        let mut words = bytewords(&shares[0][1], " ");
+        words.replace_range(..4, if words.starts_with("able") { "acid" } else { "able" });
+        let error = sskr_combine_strings(&[&hex_share, &words]).unwrap_err();
+        assert!(matches!(
+            &error,
+            SSKRError::ShareStringInvalid { input_index: 1, attempted } if attempted == &[ShareEncoding::Bytewords]
+        ));
+        assert_eq!(error.to_string(), "SSKR share string at index 1 could not be decoded as Bytewords");
+
+        // Hex of a share whose group threshold exceeds its group count.
+        let bad_hex = format!("{}10{}", &hex_share[..4], &hex_share[6..]);
+        let error = sskr_combine_strings(&[&bad_hex]).unwrap_err();
+        assert!(matches!(
+            &error,
+            SSKRError::ShareStringInvalid { input_index: 0, attempted }
+                if attempted == &[ShareEncoding::Hex, ShareEncoding::Bytewords]
+        ));
+        assert_eq!(error.to_string(), "SSKR share string at index 0 could not be decoded as hex or Bytewords");
+
+        // Only single-part SSKR URs are understood.
+        let body = minimal_bytewords(&cbor(&shares[0][1], None));
+        for ur in [format!("ur:crypto-seed/{body}"), format!("ur:sskr/1-2/{body}"), format!("ur:sskr/{}", &body[2..])] {
+            assert!(matches!(
+                sskr_combine_strings(&[&hex_share, &ur]),
+                Err(SSKRError::ShareStringInvalid { input_index: 1, ref attempted }) if attempted == &[ShareEncoding::Ur]
+            ));
+        }
+        assert!(matches!(sskr_combine_strings(&["  "]), Err(SSKRError::ShareStringInvalid { input_index: 0, .. })));
+        assert!(matches!(sskr_combine_strings(&[]), Err(SSKRError::SharesEmpty)));
+    }
+}
@@ -0,0 +1,148 @@
+use std::sync::{Mutex, MutexGuard, PoisonError};
+
+use zeroize::Zeroizing;
+
+use crate::{CollectorStatus, SSKRError, Secret, ShareCollector, sskr_combine};
+
+/// A `ShareCollector` that can be shared between threads.
+///
+/// Every method takes `&self`, so a single collector can be kept in an `Arc`
+/// and used from any number of threads. Each call is atomic: if two threads
+/// race to add the same member's share, one adds it and the other gets
+/// `SSKRError::DuplicateMemberIndex`.
+///
+/// A `ShareCollector` is never left half-updated, so a thread that panics
+/// while using the collector does not poison it for the others.
+#[derive(Debug, Default)]
+pub struct SyncShareCollector {
+    collector: Mutex<ShareCollector>,
+}
+
+impl SyncShareCollector {
+    /// Creates a new, empty `SyncShareCollector`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Locks the collector, recovering it if another thread panicked while
+    /// holding the lock.
+    pub(crate) fn lock(&self) -> MutexGuard<'_, ShareCollector> {
+        self.collector.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Adds a serialized share, returning the updated status.
+    ///
+    /// # Errors
+    ///
+    /// Returns any error `ShareCollector::add_share` returns, leaving the
+    /// collector unchanged.
+    pub fn add_share(&self, share: &[u8]) -> Result<CollectorStatus, SSKRError> {
+        self.lock().add_share(share)
+    }
+
+    /// Removes the share of the given member, returning its bytes, or `None`
+    /// if the collector doesn't hold it.
+    pub fn remove_share(&self, group_index: usize, member_index: usize) -> Option<Vec<u8>> {
+        self.lock().remove_share(group_index, member_index)
+    }
+
+    /// Returns the progress of the recovery.
+    pub fn status(&self) -> CollectorStatus {
+        self.lock().status()
+    }
+
+    /// Combines the shares collected so far into a `Secret`, leaving them in
+    /// the collector.
+    ///
+    /// The lock is held only while the shares are copied, not during the
+    /// recovery itself. The copies are zeroized once it is done.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the collected shares do not meet the quorum, or if
+    /// any of them is corrupt.
+    pub fn try_combine(&self) -> Result<Secret, SSKRError> {
+        let shares: Zeroizing<Vec<Vec<u8>>> = Zeroizing::new(self.lock().shares().iter().map(|share| share.as_ref().to_vec()).collect());
+        sskr_combine(&shares)
+    }
+
+    /// Consumes this collector, returning the `ShareCollector` it wraps.
+    pub fn into_inner(self) -> ShareCollector {
+        self.collector.into_inner().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl From<ShareCollector> for SyncShareCollector {
+    fn from(collector: ShareCollector) -> Self {
+        Self { collector: Mutex::new(collector) }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{sync::Arc, thread};
+
+    use super::*;
+    use crate::{GroupSpec, Spec, sskr_generate_using, test_support};
+    
+
+    fn is_send_sync<T: Send + Sync>() {}
+
+    #[test]
+    fn test_concurrent_add_share() {
+        is_send_sync::<SyncShareCollector>();
+
+        let secret = test_support::secret();
+        let spec = Spec::new(2, vec![
+            GroupSpec::new(3, 5).unwrap(),
+            GroupSpec::new(4, 7).unwrap(),
+            GroupSpec::new(2, 2).unwrap(),
+        ]).unwrap();
+        let shares = sskr_generate_using(&spec, &secret, &mut bc_rand::make_fake_random_number_generator()).unwrap();
+        let all_shares: Arc<Vec<Vec<u8>>> = Arc::new(shares.into_iter().flatten().collect());
+        let collector = Arc::new(SyncShareCollector::new());
+        assert!(collector.try_combine().is_err());
+
+        // Every thread tries to add every share, starting at a different one.
+        let threads: Vec<_> = (0..8)
+            .map(|thread_index| {
+                let collector = collector.clone();
+                let all_shares = all_shares.clone();
+                thread::spawn(move || {
+                    let mut added = 0;
+                    for i in 0..all_shares.len() {
+                        let share = &all_shares[(i + thread_index * 3) % all_shares.len()];
+                        match collector.add_share(share) {
+                            Ok(_) => added += 1,
+                            Err(SSKRError::DuplicateMemberIndex) => {}
+                            Err(error) => panic!("unexpected error: {error}"),
+                        }
+                        let _ = collector.status();
+                    }
+                    added
+                })
+            })
+            .collect();
+        let added: usize = threads.into_iter().map(|thread| thread.join().unwrap()).sum();
+
+        // Each share was added exactly once.
+        assert_eq!(added, all_shares.len());
+        let status = collector.status();
+        assert_eq!(status.share_count(), all_shares.len());
+        assert_eq!(status.complete_group_count(), 3);
+        assert_eq!(collector.try_combine().unwrap(), secret);
+        assert_eq!(collector.status().share_count(), all_shares.len());
+
+        // A panic while the lock is held doesn't poison the collector.
+        let panicking = collector.clone();
+        assert!(thread::spawn(move || {
+            let _guard = panicking.lock();
+            panic!("holding the lock");
+        }).join().is_err());
+        assert!(collector.remove_share(2, 1).is_some());
+        assert_eq!(collector.try_combine().unwrap(), secret);
+
+        let collector = Arc::into_inner(collector).unwrap().into_inner();
+        assert_eq!(collector.len(), all_shares.len() - 1);
+    }
+}
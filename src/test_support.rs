@@ -0,0 +1,86 @@
+//! Fixtures shared by the tests of every module.
+
+use bc_rand::RandomNumberGenerator;
+use hex_literal::hex;
+
+use crate::{GenerateOptions, GroupSpec, Secret, ShareBytes, ShareCopy, ShareVersion, Spec, sskr_generate_share_bytes_using, sskr_generate_with_options_using, sskr_generate_with_version_using};
+
+/// Returns the secret the tests split, unless they need one of another
+/// length.
+pub(crate) fn secret() -> Secret {
+    Secret::new(hex!("0ff784df000c4380a5ed683f7e6e3dcf")).unwrap()
+}
+
+/// Returns `2 of [2-of-3, 3-of-5, 1-of-1]`, a spec whose groups each need a
+/// different number of shares.
+pub(crate) fn spec() -> Spec {
+    Spec::new(2, vec![
+        GroupSpec::new(2, 3).unwrap(),
+        GroupSpec::new(3, 5).unwrap(),
+        GroupSpec::new(1, 1).unwrap(),
+    ]).unwrap()
+}
+
+/// Splits [`secret`] with `spec`, drawing from the fake random number
+/// generator.
+pub(crate) fn generate(spec: &Spec) -> Vec<Vec<Vec<u8>>> {
+    generate_using(spec, &mut bc_rand::make_fake_random_number_generator())
+}
+
+/// Splits [`secret`] with `spec`, drawing from `rng`.
+pub(crate) fn generate_using(spec: &Spec, rng: &mut impl RandomNumberGenerator) -> Vec<Vec<Vec<u8>>> {
+    generate_with_version_using(spec, ShareVersion::V0, rng)
+}
+
+/// Splits [`secret`] with `spec` into shares of format `version`, drawing
+/// from `rng`.
+pub(crate) fn generate_with_version_using(
+    spec: &Spec,
+    version: ShareVersion,
+    rng: &mut impl RandomNumberGenerator
+) -> Vec<Vec<Vec<u8>>> {
+    sskr_generate_with_version_using(spec, &secret(), version, rng).unwrap()
+}
+
+/// Splits [`secret`] with `spec` into `ShareBytes`, drawing from the fake
+/// random number generator.
+pub(crate) fn generate_share_bytes(spec: &Spec) -> Vec<Vec<ShareBytes>> {
+    sskr_generate_share_bytes_using(spec, &secret(), &mut bc_rand::make_fake_random_number_generator()).unwrap()
+}
+
+/// Splits [`secret`] with `spec` and `options`, drawing from the fake random
+/// number generator, and returns the shares without their copy indexes.
+pub(crate) fn generate_with_options(spec: &Spec, options: &GenerateOptions) -> Vec<Vec<Vec<u8>>> {
+    sskr_generate_with_options_using(spec, &secret(), options, &mut bc_rand::make_fake_random_number_generator())
+        .unwrap()
+        .into_iter()
+        .map(|group| group.into_iter().map(ShareCopy::into_share).collect())
+        .collect()
+}
+
+/// A generator that returns zeros, and so the same identifier, every time.
+pub(crate) struct ZeroRandomNumberGenerator;
+
+impl rand_core::RngCore for ZeroRandomNumberGenerator {
+    fn next_u32(&mut self) -> u32 {
+        0
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        0
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        dest.fill(0);
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand_core::Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
+
+// Testing purposes only!
+impl rand_core::CryptoRng for ZeroRandomNumberGenerator {}
+
+impl RandomNumberGenerator for ZeroRandomNumberGenerator {}
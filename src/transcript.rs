@@ -0,0 +1,262 @@
+use bc_crypto::hash::sha256;
+use bc_rand::RandomNumberGenerator;
+
+use crate::{
+    SSKRError,
+    Secret,
+    ShareVersion,
+    Spec,
+    encoding::deserialize_share,
+    sskr_generate_detailed_using,
+};
+
+/// The serialized shares of a split, grouped as `sskr_generate` groups them.
+pub(crate) type GroupedShareBytes = Vec<Vec<Vec<u8>>>;
+
+/// Generates SSKR shares for the given `Spec` and `Secret`, along with a
+/// transcript of the generation that holds no secret material.
+///
+/// The shares are identical to those returned by `sskr_generate`.
+/// `timestamp` is recorded in the transcript as given; the crate does not
+/// read the clock.
+///
+/// # Errors
+///
+/// Returns the same errors as `sskr_generate`.
+#[cfg(feature = "default-rng")]
+pub fn sskr_generate_with_transcript(
+    spec: &Spec,
+    master_secret: &Secret,
+    timestamp: u64
+) -> Result<(GroupedShareBytes, Transcript), SSKRError> {
+    let mut rng = bc_rand::SecureRandomNumberGenerator;
+    sskr_generate_with_transcript_using(spec, master_secret, timestamp, &mut rng)
+}
+
+/// Generates SSKR shares for the given `Spec` and `Secret` using the provided
+/// random number generator, along with a transcript of the generation that
+/// holds no secret material.
+///
+/// The shares are identical to those returned by `sskr_generate_using` with
+/// the same random number generator.
+///
+/// # Errors
+///
+/// Returns the same errors as `sskr_generate`.
+pub fn sskr_generate_with_transcript_using(
+    spec: &Spec,
+    master_secret: &Secret,
+    timestamp: u64,
+    random_generator: &mut impl RandomNumberGenerator
+) -> Result<(GroupedShareBytes, Transcript), SSKRError> {
+    let generated = sskr_generate_detailed_using(spec, master_secret, random_generator)?;
+    let shares = generated
+        .shares()
+        .iter()
+        .flatten()
+        .enumerate()
+        .map(|(share_index, bytes)| {
+            let share = deserialize_share(bytes, share_index)?;
+            Ok(ShareRecord {
+                version: share.version(),
+                group_index: share.group_index(),
+                member_index: share.member_index(),
+                member_threshold: share.member_threshold(),
+                len: share.value().len(),
+                digest: sha256(bytes),
+            })
+        })
+        .collect::<Result<Vec<_>, SSKRError>>()?;
+    let transcript = Transcript {
+        identifier: generated.identifier(),
+        spec: generated.spec().clone(),
+        timestamp,
+        share_counts: generated.shares().iter().map(Vec::len).collect(),
+        shares,
+    };
+    Ok((generated.into_shares(), transcript))
+}
+
+/// A record of a split generated by [`sskr_generate_with_transcript`], for
+/// auditing.
+///
+/// The transcript holds the identifier and spec of the split, and the
+/// metadata and SHA-256 digest of every share. It never holds a share
+/// value, the secret, or anything derived from them other than the digests
+/// of the serialized shares, so it can be kept where the shares cannot.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Transcript {
+    identifier: u32,
+    spec: Spec,
+    timestamp: u64,
+    share_counts: Vec<usize>,
+    shares: Vec<ShareRecord>,
+}
+
+impl Transcript {
+    /// Returns the identifier of the split.
+    pub fn identifier(&self) -> u32 {
+        self.identifier
+    }
+
+    /// Returns the spec of the split.
+    pub fn spec(&self) -> &Spec {
+        &self.spec
+    }
+
+    /// Returns the timestamp given when the split was generated.
+    pub fn timestamp(&self) -> u64 {
+        self.timestamp
+    }
+
+    /// Returns the number of shares generated for each group.
+    pub fn share_counts(&self) -> &[usize] {
+        &self.share_counts
+    }
+
+    /// Returns a record of each share, in the order they were generated.
+    pub fn shares(&self) -> &[ShareRecord] {
+        &self.shares
+    }
+
+    /// Checks that every one of `shares` is a share recorded in this
+    /// transcript.
+    ///
+    /// Any subset of the shares may be presented, in any order, so that the
+    /// holders of some shares can confirm theirs without the others.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SSKRError::ShareNotInTranscript` for the first share whose
+    /// digest is not recorded.
+    pub fn verify<T>(&self, shares: &[T]) -> Result<(), SSKRError>
+    where
+        T: AsRef<[u8]>
+    {
+        for (share_index, share) in shares.iter().enumerate() {
+            let digest = sha256(share.as_ref());
+            if !self.shares.iter().any(|record| record.digest == digest) {
+                return Err(SSKRError::ShareNotInTranscript { share_index });
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The record of one share in a [`Transcript`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct ShareRecord {
+    version: ShareVersion,
+    group_index: usize,
+    member_index: usize,
+    member_threshold: usize,
+    len: usize,
+    #[cfg_attr(feature = "serde", serde(serialize_with = "serialize_digest"))]
+    digest: [u8; 32],
+}
+
+impl ShareRecord {
+    /// Returns the format of the share.
+    pub fn version(&self) -> ShareVersion {
+        self.version
+    }
+
+    /// Returns the index of the share's group.
+    pub fn group_index(&self) -> usize {
+        self.group_index
+    }
+
+    /// Returns the index of the share within its group.
+    pub fn member_index(&self) -> usize {
+        self.member_index
+    }
+
+    /// Returns the member threshold of the share's group.
+    pub fn member_threshold(&self) -> usize {
+        self.member_threshold
+    }
+
+    /// Returns the length of the share's value, which is that of the secret.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if the share's value is empty, which it never is.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the SHA-256 digest of the serialized share.
+    pub fn digest(&self) -> &[u8; 32] {
+        &self.digest
+    }
+}
+
+/// Serializes a digest as lowercase hex.
+#[cfg(feature = "serde")]
+fn serialize_digest<S>(digest: &[u8; 32], serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: serde::Serializer,
+{
+    serializer.collect_str(&hex::encode(digest))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{GroupSpec, test_support};
+    
+
+    #[test]
+    fn test_transcript() {
+        let secret = test_support::secret();
+        let spec = Spec::new(2, vec![GroupSpec::new(2, 3).unwrap(), GroupSpec::new(1, 2).unwrap()]).unwrap();
+        let mut rng = bc_rand::make_fake_random_number_generator();
+        let (shares, transcript) = sskr_generate_with_transcript_using(&spec, &secret, 1_700_000_000, &mut rng).unwrap();
+        assert_eq!(shares, test_support::generate(&spec));
+        assert_eq!(transcript.identifier(), u16::from_be_bytes([shares[0][0][0], shares[0][0][1]]) as u32);
+        assert_eq!(transcript.spec(), &spec);
+        assert_eq!(transcript.timestamp(), 1_700_000_000);
+        assert_eq!(transcript.share_counts(), [3, 2]);
+
+        let records: Vec<(usize, usize, usize, usize)> = transcript
+            .shares()
+            .iter()
+            .map(|record| (record.group_index(), record.member_index(), record.member_threshold(), record.len()))
+            .collect();
+        assert_eq!(records, [(0, 0, 2, 16), (0, 1, 2, 16), (0, 2, 2, 16), (1, 0, 1, 16), (1, 1, 1, 16)]);
+        assert_eq!(transcript.shares()[3].digest(), &sha256(&shares[1][0]));
+
+        transcript.verify(&[&shares[1][1], &shares[0][0]]).unwrap();
+        transcript.verify(&shares.concat()).unwrap();
+        let mut tampered = shares[0][2].clone();
+        tampered[10] ^= 0x01;
+        assert!(matches!(
+            transcript.verify(&[&shares[0][0], &tampered]),
+            Err(SSKRError::ShareNotInTranscript { share_index: 1 })
+        ));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_transcript_json() {
+        let secret = test_support::secret();
+        let spec = Spec::new(2, vec![GroupSpec::new(2, 3).unwrap(), GroupSpec::new(1, 2).unwrap()]).unwrap();
+        let mut rng = bc_rand::make_fake_random_number_generator();
+        let (shares, transcript) = sskr_generate_with_transcript_using(&spec, &secret, 1_700_000_000, &mut rng).unwrap();
+        let json = serde_json::to_string(&transcript).unwrap();
+        let prefix = r#","spec":{"group_threshold":2,"groups":[{"member_threshold":2,"member_count":3},"#;
+        assert!(json.starts_with(&format!(r#"{{"identifier":{}{prefix}"#, transcript.identifier())));
+        assert!(json.contains(&hex::encode(sha256(&shares[0][1]))));
+
+        // Neither the secret nor any share value appears, in hex or as bytes.
+        assert!(!json.contains(&hex::encode(secret.data())));
+        for share in shares.iter().flatten() {
+            let value = &share[5..];
+            assert!(!json.contains(&hex::encode(value)));
+            assert!(!json.contains(&serde_json::to_string(value).unwrap()));
+        }
+    }
+}